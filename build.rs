@@ -0,0 +1,48 @@
+//! Generates the terms of every s, p, d and f sublevel, see `src/tables.rs`
+
+use std::{env, fmt::Write, fs, path::Path};
+
+#[path = "src/multiplicities.rs"]
+mod multiplicities;
+
+/// Largest L of the tabulated sublevels
+const MAX_L: u8 = 3;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/multiplicities.rs");
+    let mut out = String::new();
+    writeln!(
+        out,
+        "/// Terms of every sublevel with L at most {MAX_L}, indexed by L and the number of electrons"
+    )?;
+    writeln!(
+        out,
+        "static TERM_TABLES: [&[&[TermType]]; {}] = [",
+        MAX_L + 1
+    )?;
+    for l in 0..=MAX_L {
+        writeln!(out, "    &[")?;
+        for electrons in 0..=2 * (2 * l + 1) {
+            let parity = if l * electrons % 2 == 1 {
+                "Odd"
+            } else {
+                "Even"
+            };
+            write!(out, "        &[")?;
+            let multiplicities = multiplicities::term_multiplicities::<u128>(l, electrons)
+                .ok_or("Small sublevels can't overflow")?;
+            for (momentum, doubled_spin, count) in multiplicities {
+                for _ in 0..count {
+                    write!(out, "term({momentum}, {doubled_spin}, Parity::{parity}), ")?;
+                }
+            }
+            writeln!(out, "],")?;
+        }
+        writeln!(out, "    ],")?;
+    }
+    writeln!(out, "];")?;
+    let path = Path::new(&env::var("OUT_DIR")?).join("term_tables.rs");
+    fs::write(path, out)?;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+#ifndef CHITOSE_H
+#define CHITOSE_H
+
+/* Generated by cbindgen from src/ffi.rs, don't edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Terms of a configuration, created by [`chitose_terms_new`] and destroyed by [`chitose_terms_free`]
+ */
+typedef struct ChitoseTerms ChitoseTerms;
+
+/**
+ * LS term
+ */
+typedef struct ChitoseTerm {
+  /**
+   * Orbital momentum L
+   */
+  uint32_t momentum;
+  /**
+   * Spin S, doubled
+   */
+  uint32_t doubled_spin;
+  /**
+   * 1 for the odd terms, 0 for the even ones
+   */
+  int odd;
+} ChitoseTerm;
+
+/**
+ * Level of an LS term
+ */
+typedef struct ChitoseLevel {
+  struct ChitoseTerm term;
+  /**
+   * Total momentum J, doubled
+   */
+  uint32_t doubled_j;
+} ChitoseLevel;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Message of the last failure on the calling thread, or null if nothing failed yet
+ *
+ * The message is owned by the library, and stays valid until the next failure on the same thread
+ */
+const char *chitose_last_error(void);
+
+/**
+ * Terms of the configuration, like "2p2 3d1", in spectroscopic order and with repetitions, or null if the configuration is invalid or its momenta don't fit into the fields
+ *
+ * # Safety
+ *
+ * `configuration` must be null or a NUL-terminated string
+ */
+struct ChitoseTerms *chitose_terms_new(const char *configuration);
+
+/**
+ * Number of the terms
+ *
+ * # Safety
+ *
+ * `terms` must be created by [`chitose_terms_new`] and not destroyed yet
+ */
+size_t chitose_terms_len(const struct ChitoseTerms *terms);
+
+/**
+ * The terms themselves, [`chitose_terms_len`] of them, valid until the terms are destroyed
+ *
+ * # Safety
+ *
+ * `terms` must be created by [`chitose_terms_new`] and not destroyed yet
+ */
+const struct ChitoseTerm *chitose_terms_data(const struct ChitoseTerms *terms);
+
+/**
+ * Destroys the terms; null is ignored
+ *
+ * # Safety
+ *
+ * `terms` must be null, or created by [`chitose_terms_new`] and not destroyed yet
+ */
+void chitose_terms_free(struct ChitoseTerms *terms);
+
+/**
+ * Writes the ground level of the configuration by Hund's rules to `level`; returns 0 on success, and -1 if the configuration is invalid or its momenta don't fit into the fields
+ *
+ * # Safety
+ *
+ * `configuration` must be null or a NUL-terminated string, and `level` must be valid for writes
+ */
+int chitose_ground_level(const char *configuration, struct ChitoseLevel *level);
+
+/**
+ * Clebsch–Gordan coefficient ⟨j1 m1 j2 m2 | j m⟩, all of the momenta doubled; 0 for the forbidden ones, and NaN for the ones too large to compute
+ */
+double chitose_clebsch_gordan(int j1, int m1, int j2, int m2, int j, int m);
+
+/**
+ * Wigner 3j symbol (j1 j2 j3; m1 m2 m3), all of the momenta doubled; 0 for the forbidden ones, and NaN for the ones too large to compute
+ */
+double chitose_wigner_3j(int j1, int j2, int j3, int m1, int m2, int m3);
+
+/**
+ * Wigner 6j symbol {j1 j2 j3; j4 j5 j6}, all of the momenta doubled; 0 for the forbidden ones, and NaN for the ones too large to compute
+ */
+double chitose_wigner_6j(int j1, int j2, int j3, int j4, int j5, int j6);
+
+/**
+ * Wigner 9j symbol with the rows `{j[0] j[1] j[2]; j[3] j[4] j[5]; j[6] j[7] j[8]}`, all of the momenta doubled; 0 for the forbidden ones, and NaN for the ones too large to compute
+ *
+ * # Safety
+ *
+ * `j` must point to 9 integers
+ */
+double chitose_wigner_9j(const int *j);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CHITOSE_H */
@@ -0,0 +1,261 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use chitose::Configuration;
+
+use crate::{
+    error::CliError,
+    report::{self, Counts, Report},
+    terms::{self, Input},
+    Format, Output,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// File with a configuration, element or ion per line, like "2p3" or "Fe2+"; empty lines and lines starting with '#' are skipped
+    #[arg(required_unless_present = "stdin", conflicts_with = "stdin")]
+    file: Option<PathBuf>,
+    /// If set, reads the input from the standard input instead of a file
+    #[arg(long, default_value_t = false)]
+    stdin: bool,
+    /// Number of lines derived at once, on threads of their own; every available core by default. The output is in the order of the input regardless. Derivations printing as they go (-vvv, --stats and --trace) are always done one at a time
+    #[arg(short, long)]
+    jobs: Option<NonZeroUsize>,
+    #[command(flatten)]
+    pub options: terms::Options,
+}
+
+/// Result of a single line of the input
+#[cfg_attr(not(feature = "json"), allow(dead_code))]
+struct Entry {
+    line: usize,
+    input: String,
+    result: Result<(Configuration, Report), String>,
+}
+
+pub fn run(mut args: Args, output: &Output) -> Result<(), CliError> {
+    if args.options.count_only() {
+        output.reject_nist("Counts")?;
+    }
+    let reader: Box<dyn BufRead> = match &args.file {
+        Some(path) => Box::new(BufReader::new(
+            std::fs::File::open(path).map_err(|error| CliError::Open(path.clone(), error))?,
+        )),
+        None => Box::new(io::stdin().lock()),
+    };
+    let mut lines = Vec::new();
+    for (line, text) in reader.lines().enumerate() {
+        let text = text?;
+        let input = text.trim();
+        if input.is_empty() || input.starts_with('#') {
+            continue;
+        }
+        lines.push((line + 1, input.to_string()));
+    }
+    let jobs = if args.options.prints_derivation() {
+        1
+    } else {
+        args.jobs
+            .or_else(|| std::thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+            .min(lines.len())
+    };
+    // bars of the concurrent derivations would overwrite each other
+    args.options.hide_progress |= jobs > 1;
+    let entries = derive_all(lines, jobs, &args.options, output);
+    for entry in &entries {
+        if let Err(error) = &entry.result {
+            let message = format!("line {}: {}: {error}", entry.line, entry.input);
+            eprintln!("{}", output.stderr_style().error(message));
+        }
+    }
+    let mut out = output.writer()?;
+    write(&entries, &args, output, &mut out)?;
+    out.flush()?;
+    match entries.iter().filter(|entry| entry.result.is_err()).count() {
+        0 => Ok(()),
+        failed => Err(CliError::Computation(format!(
+            "{failed} of {} lines failed",
+            entries.len()
+        ))),
+    }
+}
+
+/// Derives the lines on `jobs` threads, each taking the next line not taken yet, and returns them in the original order
+fn derive_all(
+    lines: Vec<(usize, String)>,
+    jobs: usize,
+    options: &terms::Options,
+    output: &Output,
+) -> Vec<Entry> {
+    let next = AtomicUsize::new(0);
+    let mut entries = std::thread::scope(|scope| {
+        let workers = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut entries = Vec::new();
+                    while let Some((line, input)) = lines.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        entries.push(Entry {
+                            line: *line,
+                            input: input.clone(),
+                            result: derive(input, options, output),
+                        });
+                    }
+                    entries
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect::<Vec<_>>()
+    });
+    entries.sort_by_key(|entry| entry.line);
+    entries
+}
+
+fn derive(
+    input: &str,
+    options: &terms::Options,
+    output: &Output,
+) -> Result<(Configuration, Report), String> {
+    input
+        .parse::<Input>()
+        .and_then(|parsed| {
+            parsed
+                .resolve(None, output.notation())
+                .map_err(|error| error.to_string())
+        })
+        .and_then(|(configuration, species)| {
+            terms::report(&configuration, species, options, output)
+                .map(|report| (configuration, report))
+                .map_err(|error| CliError::from(error).to_string())
+        })
+}
+
+/// Reports of all of the lines together; failed lines are only reported to stderr, except for JSON
+fn write(entries: &[Entry], args: &Args, output: &Output, out: &mut impl Write) -> io::Result<()> {
+    let levels = args.options.levels();
+    let reports = entries
+        .iter()
+        .filter_map(|entry| Some((entry, entry.result.as_ref().ok()?)));
+    match output.format() {
+        Format::Text => {
+            for (entry, (configuration, report)) in reports {
+                let header = format!("=== {} ===", entry.input);
+                writeln!(out, "{}", output.out_style().dim(header))?;
+                terms::write(report, configuration, &args.options, output, out)?;
+                writeln!(out)?;
+            }
+        }
+        Format::Csv if args.options.count_only() => {
+            let mut header = vec!["input".to_string()];
+            header.extend(Counts::csv_header());
+            report::csv_row(out, &header)?;
+            for (entry, (_, report)) in reports {
+                let mut row = report.counts().csv_row();
+                row.insert(0, entry.input.clone());
+                report::csv_row(out, &row)?;
+            }
+        }
+        Format::Csv => {
+            let mut header = vec!["input".to_string()];
+            header.extend(Report::csv_header(levels));
+            report::csv_row(out, &header)?;
+            for (entry, (_, report)) in reports {
+                for row in report.csv_rows(levels) {
+                    let mut row = row;
+                    row.insert(0, entry.input.clone());
+                    report::csv_row(out, &row)?;
+                }
+            }
+        }
+        Format::Html if args.options.count_only() => {
+            let mut header = vec!["input".to_string()];
+            header.extend(Counts::csv_header());
+            let rows = reports
+                .map(|(entry, (_, report))| {
+                    let mut row = report.counts().csv_row();
+                    row.insert(0, report::html_escape(&entry.input));
+                    row
+                })
+                .collect::<Vec<_>>();
+            report::html_table(out, &header, &rows)?;
+        }
+        // a single table, with the terms (or levels) of every input; pages of their own are made by `chitose terms`
+        Format::Html => {
+            let mut header = vec!["input".to_string()];
+            header.extend(Report::csv_header(levels));
+            let mut rows = Vec::new();
+            for (entry, (_, report)) in reports {
+                for mut row in report.csv_rows(levels) {
+                    row.insert(0, report::html_escape(&entry.input));
+                    rows.push(row);
+                }
+            }
+            report::html_table(out, &header, &rows)?;
+        }
+        // the input is prepended to every record
+        Format::Records => {
+            for (entry, (_, report)) in reports {
+                let records = if args.options.count_only() {
+                    vec![report.counts().record()]
+                } else {
+                    report.records()
+                };
+                for mut record in records {
+                    record.insert(0, entry.input.clone());
+                    report::record(out, &record)?;
+                }
+            }
+        }
+        Format::Latex | Format::Typst | Format::Nist => {
+            for (_, (configuration, report)) in reports {
+                terms::write(report, configuration, &args.options, output, out)?;
+            }
+        }
+        Format::Markdown => {
+            for (entry, (configuration, report)) in reports {
+                writeln!(out, "### {}\n", entry.input)?;
+                terms::write(report, configuration, &args.options, output, out)?;
+                writeln!(out)?;
+            }
+        }
+        #[cfg(feature = "json")]
+        Format::Json => {
+            let entries = entries
+                .iter()
+                .map(|entry| {
+                    let mut value = serde_json::json!({
+                        "line": entry.line,
+                        "input": entry.input,
+                    });
+                    match &entry.result {
+                        Ok((_, report)) if args.options.count_only() => {
+                            value["counts"] = serde_json::to_value(report.counts())?
+                        }
+                        Ok((_, report)) => value["report"] = serde_json::to_value(report)?,
+                        Err(error) => value["error"] = error.clone().into(),
+                    }
+                    Ok(value)
+                })
+                .collect::<Result<Vec<_>, serde_json::Error>>()
+                .expect("Report is serializable");
+            writeln!(
+                out,
+                "{}",
+                serde_json::to_string_pretty(&entries).expect("Report is serializable")
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! Orbital box diagrams of the Hund's-rules ground state, or of a given microstate
+
+use std::io::Write;
+
+use chitose::{Microstate, OrbitalBoxes, SpinOrbital};
+use clap::ValueEnum;
+
+use crate::{error::CliError, terms::Input, Format, Output};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Configuration, like "2p3" or "1s2 2s2 2p3", or an element or ion
+    input: Input,
+    /// Draws only the open subshells
+    #[arg(long)]
+    open: bool,
+    /// Occupied spin-orbitals of the only open subshell, like "1+ 0+ -1-", drawn instead of the Hund's-rules ground state
+    #[arg(long, allow_hyphen_values = true)]
+    microstate: Option<String>,
+    /// Draws the boxes as an SVG picture
+    #[arg(long)]
+    svg: bool,
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    let (configuration, _) = args.input.resolve(None, output.notation())?;
+    let boxes = match &args.microstate {
+        Some(microstate) => given_microstate(&configuration, microstate)?,
+        None if args.open => configuration.open_subshells().hund_boxes()?,
+        None => configuration.hund_boxes()?,
+    };
+    let text = match output.format() {
+        _ if args.svg => boxes.to_svg(),
+        Format::Text => boxes.to_unicode(),
+        #[cfg(feature = "json")]
+        Format::Json => {
+            serde_json::to_string_pretty(&boxes).expect("Boxes are serializable") + "\n"
+        }
+        format => {
+            return Err(CliError::Usage(format!(
+                "Orbital boxes can't be written as {}",
+                format
+                    .to_possible_value()
+                    .expect("Formats are not skipped")
+                    .get_name()
+            )))
+        }
+    };
+    let mut out = output.writer()?;
+    write!(out, "{text}")?;
+    Ok(out.flush()?)
+}
+
+fn given_microstate(
+    configuration: &chitose::Configuration,
+    microstate: &str,
+) -> Result<OrbitalBoxes, CliError> {
+    let open = configuration.open_subshells();
+    let [sublevel] = open.sublevels() else {
+        return Err(CliError::Usage(
+            "--microstate needs a configuration with a single open subshell".to_string(),
+        ));
+    };
+    let orbitals = microstate
+        .split([' ', ','])
+        .filter(|orbital| !orbital.is_empty())
+        .map(str::parse::<SpinOrbital>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| CliError::Usage(error.to_string()))?;
+    let count = orbitals.len();
+    let microstate = Microstate::from_spin_orbitals(sublevel.level_type(), orbitals)
+        .filter(|microstate| microstate.electrons() as usize == count)
+        .ok_or_else(|| {
+            CliError::Usage(format!(
+                "\"{microstate}\" is not a microstate of the {sublevel} sublevel"
+            ))
+        })?;
+    OrbitalBoxes::new(sublevel.clone(), microstate).ok_or_else(|| {
+        CliError::Usage(format!(
+            "The {sublevel} sublevel has {} electrons, while the microstate has {count}",
+            sublevel.electrons()
+        ))
+    })
+}
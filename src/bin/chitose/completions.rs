@@ -0,0 +1,83 @@
+//! Shell completion scripts, generated by `clap_complete` from the definition of the command line
+
+use std::io::Write;
+
+use chitose::{Element, Notation, SubLevelType};
+use clap::{builder::PossibleValuesParser, Arg, Command, CommandFactory};
+use clap_complete::Shell;
+
+use crate::{error::CliError, Cli, Output};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Shell to generate the completion script for
+    #[arg(value_enum)]
+    shell: Shell,
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    let mut command = suggesting(Cli::command());
+    let name = command.get_name().to_string();
+    let mut out = output.writer()?;
+    clap_complete::generate(args.shell, &mut command, name, &mut out);
+    Ok(out.flush()?)
+}
+
+/// Element symbols and subshells, like `3d`, suggested for the configurations and ions
+fn inputs() -> Vec<String> {
+    let subshells = (1..=7u8)
+        .flat_map(|n| (0..n.min(4)).map(move |l| format!("{n}{}", SubLevelType::new(l).unwrap())));
+    Element::all()
+        .iter()
+        .map(|element| element.symbol.to_string())
+        .chain(subshells)
+        .collect()
+}
+
+/// Tells the values of the options parsed with `FromStr` to clap, which only completes the listed ones
+fn suggesting(command: Command) -> Command {
+    command.mut_args(suggest).mut_subcommands(suggesting)
+}
+
+fn suggest(arg: Arg) -> Arg {
+    let values = match arg.get_id().as_str() {
+        "notation" => Notation::ALL
+            .map(|notation| notation.name().to_string())
+            .to_vec(),
+        "energy_unit" => ["J", "eV", "cm-1", "MHz"].map(String::from).to_vec(),
+        "input" => inputs(),
+        _ => return arg,
+    };
+    arg.value_parser(PossibleValuesParser::new(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+    use clap_complete::Shell;
+
+    use crate::{completions::suggesting, Cli};
+
+    fn script(shell: Shell) -> String {
+        let mut script = Vec::new();
+        clap_complete::generate(
+            shell,
+            &mut suggesting(Cli::command()),
+            "chitose",
+            &mut script,
+        );
+        String::from_utf8(script).expect("Should be ok")
+    }
+
+    #[test]
+    fn suggested_values() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let script = script(shell);
+            assert!(script.contains("completions"), "{shell}");
+            assert!(script.contains("unicode"), "{shell}");
+        }
+        let bash = script(Shell::Bash);
+        assert!(bash.contains("Fe"));
+        assert!(bash.contains("3d"));
+    }
+}
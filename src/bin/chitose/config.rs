@@ -0,0 +1,133 @@
+//! Defaults of the options, read from `~/.config/chitose/config.toml`
+//!
+//! The keys are the names of the long flags, with the values they take, like
+//!
+//! ```toml
+//! notation = "unicode"
+//! format = "text"
+//! color = "always"
+//! verbose = 1
+//! coupling = "jj"
+//! ```
+
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use chitose::Notation;
+use clap::{ColorChoice, ValueEnum};
+use serde::{de, Deserialize, Deserializer};
+
+use crate::{error::CliError, terms::Coupling, Format};
+
+/// Defaults from the configuration file, each overridden by the corresponding flag
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default, deserialize_with = "parsed")]
+    pub notation: Option<Notation>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pub format: Option<Format>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pub color: Option<ColorChoice>,
+    #[serde(default)]
+    pub verbose: Option<u8>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pub coupling: Option<Coupling>,
+    /// Keys of no option, reported and ignored
+    #[serde(flatten)]
+    unknown: toml::Table,
+}
+
+/// Value parsed from a string, like the notations
+fn parsed<'de, D: Deserializer<'de>, T: FromStr<Err = impl Display>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(de::Error::custom)
+}
+
+/// Value of the options with a fixed set of values, named as on the command line
+fn value_enum<'de, D: Deserializer<'de>, T: ValueEnum>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    T::from_str(&value, true)
+        .map(Some)
+        .map_err(de::Error::custom)
+}
+
+/// `$XDG_CONFIG_HOME/chitose/config.toml`, or `~/.config/chitose/config.toml`
+fn default_path() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(std::env::var_os("HOME")?).join(".config")))?;
+    Some(config.join("chitose").join("config.toml"))
+}
+
+impl Config {
+    /// Reads the given file, or the default one if it exists
+    pub fn load(path: Option<&Path>) -> Result<Self, CliError> {
+        let (path, text) = match path {
+            Some(path) => (
+                path.to_path_buf(),
+                fs::read_to_string(path).map_err(|error| CliError::Open(path.into(), error))?,
+            ),
+            None => match default_path() {
+                Some(path) => match fs::read_to_string(&path) {
+                    Ok(text) => (path, text),
+                    Err(_) => return Ok(Self::default()),
+                },
+                None => return Ok(Self::default()),
+            },
+        };
+        let config = Self::parse(&text)
+            .map_err(|error| CliError::Usage(format!("{}: {error}", path.display())))?;
+        for key in config.unknown.keys() {
+            eprintln!("Unknown key {key:?} in the configuration file is ignored");
+        }
+        Ok(config)
+    }
+
+    fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chitose::Notation;
+    use clap::ColorChoice;
+
+    use crate::{config::Config, terms::Coupling, Format};
+
+    #[test]
+    fn parse() {
+        let config = Config::parse(
+            "# defaults\nnotation = \"unicode\"\nformat = \"markdown\" # tables\n\ncolor = \"never\"\nverbose = 2\ncoupling = \"jj\"\n",
+        )
+        .expect("Should be ok");
+        assert!(matches!(config.notation, Some(Notation::Unicode)));
+        assert_eq!(config.format, Some(Format::Markdown));
+        assert_eq!(config.color, Some(ColorChoice::Never));
+        assert_eq!(config.verbose, Some(2));
+        assert_eq!(config.coupling, Some(Coupling::Jj));
+        assert!(config.unknown.is_empty());
+
+        let config = Config::parse("theme = \"dark\"\n[terms]\nverbose = 1").expect("Should be ok");
+        assert_eq!(config.verbose, None);
+        assert_eq!(
+            config.unknown.keys().collect::<Vec<_>>(),
+            ["terms", "theme"]
+        );
+
+        let error = Config::parse("\nverbose = \"lots\"").unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+        assert!(Config::parse("format = \"tables\"").is_err());
+        assert!(Config::parse("notation = \"runes\"").is_err());
+        assert!(Config::parse("format = \"text").is_err());
+    }
+}
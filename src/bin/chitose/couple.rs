@@ -0,0 +1,175 @@
+//! Coupling of terms of non-equivalent groups of electrons, with the parents of every resulting term
+
+use std::{io::Write, str::FromStr};
+
+use chitose::{
+    couple_terms, Configuration, ConfigurationParseError, Notated, TermParseError, TermType,
+};
+
+use crate::{error::CliError, report, table::Table, Format, Output};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Terms, like "3P" or "2D*", or configurations, like "3d2" or "4p", coupled in the given order
+    #[arg(num_args = 2.., required = true)]
+    parts: Vec<Part>,
+}
+
+/// Group of electrons, given by its term or by its configuration (then every term of it is coupled)
+#[derive(Debug, Clone)]
+enum Part {
+    Term(TermType),
+    Configuration(Configuration),
+}
+
+/// Term symbols have uppercase letters, unlike the configurations
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.chars().any(|c| c.is_ascii_uppercase()) {
+            s.parse()
+                .map(Self::Term)
+                .map_err(|error: TermParseError| error.to_string())
+        } else {
+            s.parse()
+                .map(Self::Configuration)
+                .map_err(|error: ConfigurationParseError| error.to_string())
+        }
+    }
+}
+
+impl Part {
+    fn terms(&self) -> Result<Vec<TermType>, CliError> {
+        Ok(match self {
+            Self::Term(term) => vec![term.clone()],
+            Self::Configuration(configuration) => configuration.ls_terms()?,
+        })
+    }
+}
+
+/// Resulting term, along with its coupling scheme like `(^3P ^2D) ^4F`
+#[derive(Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+struct Coupled {
+    #[cfg_attr(feature = "json", serde(skip))]
+    term_type: TermType,
+    term: String,
+    /// Parents of the term: the previously coupled term (with its own parents) and the added one
+    parents: String,
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    output.reject_nist("Coupled terms")?;
+    let notation = output.notation();
+    let mut parts = args.parts.iter();
+    let first = parts.next().expect("At least two parts are required");
+    let mut coupled = first
+        .terms()?
+        .into_iter()
+        .map(|term| Coupled {
+            term: term.notated(notation).to_string(),
+            term_type: term,
+            parents: String::new(),
+        })
+        .collect::<Vec<_>>();
+    for part in parts {
+        let terms = part.terms()?;
+        coupled = coupled
+            .iter()
+            .flat_map(|previous| {
+                // terms coupled earlier are written along with their parents
+                let left = if previous.parents.is_empty() {
+                    previous.term.clone()
+                } else {
+                    format!("({}) {}", previous.parents, previous.term)
+                };
+                couple_terms(std::slice::from_ref(&previous.term_type), &terms)
+                    .into_iter()
+                    .map(move |coupled| Coupled {
+                        term: coupled.term.notated(notation).to_string(),
+                        parents: format!("{left} {}", coupled.right.notated(notation)),
+                        term_type: coupled.term,
+                    })
+            })
+            .collect();
+    }
+    // stable, so the terms with the same symbol stay in the order of the parents
+    coupled.sort_by(|left, right| left.term_type.cmp(&right.term_type));
+
+    let mut out = output.writer()?;
+    let header = ["term", "parents"].map(String::from);
+    let cells = |coupled: &Coupled| [coupled.term.clone(), coupled.parents.clone()];
+    match output.format() {
+        Format::Text => {
+            writeln!(out, "Found terms:")?;
+            let mut table = Table::new(&["Term", "Parents"]);
+            for coupled in &coupled {
+                table.push(cells(coupled).to_vec());
+            }
+            table.write(output.out_style(), &mut out)?;
+        }
+        Format::Csv => {
+            report::csv_row(&mut out, &header)?;
+            for coupled in &coupled {
+                report::csv_row(&mut out, &cells(coupled))?;
+            }
+        }
+        Format::Records => {
+            for coupled in &coupled {
+                let [term, parents] = cells(coupled);
+                report::record(&mut out, &["term".to_string(), term, parents])?;
+            }
+        }
+        Format::Latex => {
+            writeln!(out, "\\begin{{tabular}}{{ll}}")?;
+            writeln!(out, "\\hline")?;
+            writeln!(out, "Term & Parents \\\\")?;
+            writeln!(out, "\\hline")?;
+            for coupled in &coupled {
+                // spaces are dropped in the math mode
+                let parents = coupled.parents.replace(' ', "\\ ");
+                writeln!(out, "${}$ & ${parents}$ \\\\", coupled.term)?;
+            }
+            writeln!(out, "\\hline")?;
+            writeln!(out, "\\end{{tabular}}")?;
+        }
+        Format::Typst => {
+            let rows = coupled
+                .iter()
+                .map(|coupled| {
+                    // spaces are dropped in the math mode
+                    let parents = coupled.parents.replace(' ', " space ");
+                    vec![
+                        report::typst_math(&coupled.term),
+                        report::typst_math(parents),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            let header = ["Term", "Parents"].map(report::typst_text);
+            report::typst_table(&mut out, &header, &rows)?;
+        }
+        Format::Html => {
+            let rows = coupled
+                .iter()
+                .map(|coupled| cells(coupled).to_vec())
+                .collect::<Vec<_>>();
+            report::html_table(&mut out, &["Term", "Parents"].map(String::from), &rows)?;
+        }
+        Format::Markdown => {
+            report::markdown_row(&mut out, &["Term", "Parents"].map(String::from))?;
+            writeln!(out, "|---|---|")?;
+            for coupled in &coupled {
+                report::markdown_row(&mut out, &cells(coupled))?;
+            }
+        }
+        Format::Nist => unreachable!("Rejected by Output::reject_nist"),
+        #[cfg(feature = "json")]
+        Format::Json => writeln!(
+            out,
+            "{}",
+            serde_json::to_string_pretty(&coupled).expect("Terms are serializable")
+        )?,
+    }
+    Ok(out.flush()?)
+}
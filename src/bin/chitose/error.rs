@@ -0,0 +1,60 @@
+use std::{io, path::PathBuf};
+
+use chitose::{DerivationError, IonError, LevelError};
+use thiserror::Error;
+
+/// Error of a subcommand, reported by `main` along with the corresponding exit code
+#[derive(Debug, Error)]
+pub enum CliError {
+    /// Invalid input, like too many electrons for the subshell
+    #[error("{0}")]
+    Usage(String),
+    /// Input is valid, but the derivation failed
+    #[error("{0}")]
+    Computation(String),
+    #[error("Can't open {}: {1}", .0.display())]
+    Open(PathBuf, io::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl CliError {
+    /// 2 for the invalid input (same as the argument parsing errors), 1 for the failed computations and 74 (`EX_IOERR`) for the I/O errors
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Usage(_) => 2,
+            Self::Computation(_) => 1,
+            Self::Open(..) | Self::Io(_) => 74,
+        }
+    }
+}
+
+impl From<LevelError> for CliError {
+    fn from(error: LevelError) -> Self {
+        Self::Usage(error.to_string())
+    }
+}
+
+impl From<IonError> for CliError {
+    fn from(error: IonError) -> Self {
+        Self::Usage(error.to_string())
+    }
+}
+
+impl From<DerivationError> for CliError {
+    fn from(error: DerivationError) -> Self {
+        match error {
+            DerivationError::Log(error) => Self::Io(error),
+            error @ (DerivationError::MicrostateLimit { .. } | DerivationError::MemoryLimit { .. }) => {
+                Self::Computation(format!(
+                    "{error}; --count-only or --analytic derive the terms without enumerating the microstates"
+                ))
+            }
+            error @ (DerivationError::TermLimit { .. }
+            | DerivationError::CouplingMemoryLimit { .. }) => Self::Computation(format!(
+                "{error}; --count-only counts the terms without listing them"
+            )),
+            error => Self::Computation(error.to_string()),
+        }
+    }
+}
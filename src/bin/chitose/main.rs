@@ -0,0 +1,270 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, IsTerminal, Write},
+    path::PathBuf,
+};
+
+use chitose::Notation;
+use clap::{ColorChoice, Parser, Subcommand, ValueEnum};
+use config::Config;
+use error::CliError;
+use style::Style;
+
+mod batch;
+mod boxes;
+mod completions;
+mod config;
+mod couple;
+mod error;
+mod periodic;
+mod progress;
+mod repl;
+mod report;
+#[cfg(feature = "serve")]
+mod serve;
+mod solution;
+mod stats;
+mod stream;
+mod style;
+mod symbols;
+mod table;
+mod terms;
+#[cfg(feature = "json")]
+mod trace;
+mod transitions;
+mod tree;
+mod tui;
+mod zeeman;
+
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Derivation of atomic terms and levels",
+    after_help = "Exit codes: 0 on success, 1 if a computation failed, 2 for invalid input, 74 for I/O errors"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    #[command(flatten)]
+    output: Output,
+}
+
+/// Options shared by all of the subcommands
+#[derive(Debug, Clone, clap::Args)]
+struct Output {
+    /// Notation of the printed terms and configurations: ascii (^3P_2), unicode (³P₂), html, latex, typst or mathml; applies to every output format (defaults to the one suitable for the format)
+    #[arg(long, global = true)]
+    notation: Option<Notation>,
+    /// Format of the output (inferred from the extension of the output file, text by default)
+    #[arg(long, global = true, value_enum)]
+    format: Option<Format>,
+    /// File to write the results to, instead of the standard output; the verbose derivation log still goes to stderr
+    #[arg(short, long, global = true)]
+    output: Option<PathBuf>,
+    /// When to color the human-readable output (NO_COLOR environment variable disables the automatic coloring) [default: auto]
+    #[arg(long, global = true, value_enum)]
+    color: Option<ColorChoice>,
+    /// Configuration file with the defaults of the options, instead of ~/.config/chitose/config.toml
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+}
+
+impl Output {
+    /// Fills the options not given on the command line from the configuration file
+    fn configure(&mut self, config: &Config) {
+        self.notation = self.notation.or(config.notation);
+        self.format = self.format.or(config.format);
+        self.color = self.color.or(config.color);
+    }
+
+    /// Explicitly requested format, or the one matching the extension of the output file: .json, .csv, .tex, .typ, .md, .html or .tsv
+    fn format(&self) -> Format {
+        self.format
+            .or_else(|| {
+                let extension = self.output.as_ref()?.extension()?.to_str()?;
+                match extension.to_ascii_lowercase().as_str() {
+                    #[cfg(feature = "json")]
+                    "json" => Some(Format::Json),
+                    "csv" => Some(Format::Csv),
+                    "tex" => Some(Format::Latex),
+                    "typ" => Some(Format::Typst),
+                    "md" | "markdown" => Some(Format::Markdown),
+                    "html" | "htm" => Some(Format::Html),
+                    "tsv" => Some(Format::Records),
+                    _ => None,
+                }
+            })
+            .unwrap_or(Format::Text)
+    }
+
+    /// Destination of the results: the output file, or the standard output
+    fn writer(&self) -> Result<Box<dyn Write>, CliError> {
+        Ok(match &self.output {
+            Some(path) => Box::new(BufWriter::new(
+                File::create(path).map_err(|error| CliError::Open(path.clone(), error))?,
+            )),
+            None => Box::new(io::stdout()),
+        })
+    }
+
+    /// Notation of the terms, LaTeX tables get LaTeX notation by default, and Markdown tables get Unicode one
+    fn notation(&self) -> Notation {
+        self.notation.unwrap_or(match self.format() {
+            Format::Latex => Notation::Latex,
+            Format::Typst => Notation::Typst,
+            Format::Html => Notation::Html,
+            Format::Markdown => Notation::Unicode,
+            _ => Notation::Ascii,
+        })
+    }
+
+    /// NIST level list is only written by `chitose terms` and `chitose batch`, and only for the levels themselves
+    fn reject_nist(&self, what: &str) -> Result<(), CliError> {
+        if self.format() == Format::Nist {
+            return Err(CliError::Usage(format!(
+                "{what} can't be written as a NIST level list"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Colors of the text, automatically enabled only on a terminal
+    fn style(&self, terminal: bool) -> Style {
+        let enabled = match self.color.unwrap_or(ColorChoice::Auto) {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                terminal && std::env::var_os("NO_COLOR").is_none_or(|no| no.is_empty())
+            }
+        };
+        Style { enabled }
+    }
+
+    /// Colors of the results, only the text format is colored
+    fn out_style(&self) -> Style {
+        let style = self.style(self.output.is_none() && io::stdout().is_terminal());
+        Style {
+            enabled: style.enabled && self.format() == Format::Text,
+        }
+    }
+
+    fn stderr_style(&self) -> Style {
+        self.style(io::stderr().is_terminal())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Human-readable text
+    Text,
+    /// Comma-separated values, one row per term (or per level with --levels)
+    Csv,
+    /// LaTeX tabular, one row per term (or per level with --levels)
+    Latex,
+    /// Typst table, one row per term (or per level with --levels)
+    Typst,
+    /// Markdown table, one row per term (or per level with --levels)
+    Markdown,
+    /// Standalone HTML page with the terms, levels and microstates of a configuration; HTML table for the other commands
+    Html,
+    /// Tab-separated records for line-oriented tools, each starting with its kind, like `term` or `level`
+    Records,
+    /// Level list in the layout of the NIST Atomic Spectra Database (configuration, term, J, g, and the energy with --zeta), for `chitose terms` and `chitose batch`
+    Nist,
+    /// Structured JSON document
+    #[cfg(feature = "json")]
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Derives LS-coupling terms of a sublevel or a configuration
+    Terms(Box<terms::Args>),
+    /// Derives terms of every configuration, element or ion listed in a file, one per line
+    Batch(batch::Args),
+    /// Interactive prompt deriving terms of the entered configurations, and levels of the entered terms
+    Repl(repl::Args),
+    /// Steps through the extraction of the terms from the table of microstates
+    Tui(tui::Args),
+    /// Prints ground configurations and levels of the neutral atoms
+    Periodic(periodic::Args),
+    /// Writes the whole derivation of the terms as a LaTeX (or Typst, with --format typst) worked solution, or draws it as SVG: single-electron states, microstates, the ML–MS table and the elimination steps
+    Solution(solution::Args),
+    /// Couples terms or configurations of non-equivalent electrons, printing the resulting terms along with their parents
+    Couple(couple::Args),
+    /// Draws the orbital boxes of the Hund's-rules ground state (or of a given microstate) with ↑/↓ arrows, as terminal art or SVG
+    Boxes(boxes::Args),
+    /// Writes the coupling genealogy of the terms as a Graphviz DOT graph: terms of every open subshell, the intermediate terms and the final ones
+    Tree(tree::Args),
+    /// Lists electric-dipole lines between the levels of two configurations, terms or levels
+    Transitions(transitions::Args),
+    /// Follows the magnetic sublevels of a term or a level from zero to a strong field, from the anomalous Zeeman to the Paschen–Back effect; --format html draws an interactive chart
+    Zeeman(zeeman::Args),
+    /// Prints the Clebsch–Gordan coefficient ⟨j1 m1 j2 m2 | j m⟩, exactly and as a decimal
+    Cg(symbols::CgArgs),
+    /// Prints the Wigner 3j symbol
+    #[command(name = "3j")]
+    ThreeJ(symbols::ThreeJArgs),
+    /// Prints the Wigner 6j symbol
+    #[command(name = "6j")]
+    SixJ(symbols::SixJArgs),
+    /// Prints the Wigner 9j symbol, as a decimal
+    #[command(name = "9j")]
+    NineJ(symbols::NineJArgs),
+    /// Runs an HTTP server answering /terms, /ground and /cg requests with JSON
+    #[cfg(feature = "serve")]
+    Serve(serve::Args),
+    /// Prints the completion script for a shell
+    Completions(completions::Args),
+}
+
+pub fn main() {
+    // bugs are reported without a backtrace, as the users can't do anything about them
+    std::panic::set_hook(Box::new(|info| {
+        eprintln!("chitose: internal error: {info}");
+        eprintln!("This is a bug, please report it");
+    }));
+    let mut cli = Cli::parse();
+    let result = Config::load(cli.output.config.as_deref()).and_then(|config| {
+        cli.output.configure(&config);
+        match cli.command {
+            Command::Terms(mut args) => {
+                args.options.configure(&config);
+                terms::run(*args, &cli.output)
+            }
+            Command::Batch(mut args) => {
+                args.options.configure(&config);
+                batch::run(args, &cli.output)
+            }
+            Command::Repl(mut args) => {
+                args.options.configure(&config);
+                repl::run(args, &cli.output)
+            }
+            Command::Tui(args) => tui::run(args, &cli.output),
+            Command::Periodic(args) => periodic::run(args, &cli.output),
+            Command::Solution(args) => solution::run(args, &cli.output),
+            Command::Couple(args) => couple::run(args, &cli.output),
+            Command::Boxes(args) => boxes::run(args, &cli.output),
+            Command::Tree(args) => tree::run(args, &cli.output),
+            Command::Transitions(args) => transitions::run(args, &cli.output),
+            Command::Zeeman(args) => zeeman::run(args, &cli.output),
+            Command::Cg(args) => symbols::cg(args, &cli.output),
+            Command::ThreeJ(args) => symbols::three_j(args, &cli.output),
+            Command::SixJ(args) => symbols::six_j(args, &cli.output),
+            Command::NineJ(args) => symbols::nine_j(args, &cli.output),
+            #[cfg(feature = "serve")]
+            Command::Serve(args) => serve::run(args, &cli.output),
+            Command::Completions(args) => completions::run(args, &cli.output),
+        }
+    });
+    match result {
+        Ok(()) => {}
+        // reader of the output has quit, like `head`
+        Err(CliError::Io(error)) if error.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(error) => {
+            let message = format!("error: {error}");
+            eprintln!("{}", cli.output.stderr_style().error(message));
+            std::process::exit(error.exit_code());
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use std::io::{self, Write};
+
+use chitose::{Configuration, Element, Notated, Notation};
+
+use crate::{error::CliError, report, Format, Output};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// First atomic number of the table
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=118))]
+    from: u8,
+    /// Last atomic number of the table
+    #[arg(long, default_value_t = 118, value_parser = clap::value_parser!(u8).range(1..=118))]
+    to: u8,
+}
+
+/// Ground state of a neutral atom
+#[derive(Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+struct Row {
+    #[cfg_attr(feature = "json", serde(rename = "Z"))]
+    z: u8,
+    symbol: &'static str,
+    name: &'static str,
+    configuration: String,
+    /// Ground level given by Hund's rules
+    ground: String,
+    #[cfg_attr(feature = "json", serde(rename = "J"))]
+    j: chitose::HalfInt,
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    output.reject_nist("Periodic table")?;
+    let rows = rows(args.from, args.to, output.notation());
+    let mut out = output.writer()?;
+    write(&rows, output, &mut out)?;
+    Ok(out.flush()?)
+}
+
+fn rows(from: u8, to: u8, notation: Notation) -> Vec<Row> {
+    Element::all()
+        .iter()
+        .filter(|element| (from..=to).contains(&element.number))
+        .map(|element| {
+            let ground = element.configuration().ground_term();
+            Row {
+                z: element.number,
+                symbol: element.symbol,
+                name: element.name,
+                configuration: configuration(element, notation),
+                ground: ground.notated(notation).to_string(),
+                j: ground.j(),
+            }
+        })
+        .collect()
+}
+
+/// Configuration with the noble gas core, like `[Ar] 3d^6 4s^2`
+fn configuration(element: &Element, notation: Notation) -> String {
+    let text = element.configuration_str();
+    let (core, valence) = match text.split_once(' ') {
+        Some((core, valence)) if core.starts_with('[') => (Some(core), valence),
+        _ => (None, text),
+    };
+    let valence = valence
+        .parse::<Configuration>()
+        .expect("Configurations of the table are valid")
+        .notated(notation)
+        .to_string();
+    match core {
+        Some(core) => format!("{core} {valence}"),
+        None => valence,
+    }
+}
+
+fn write(rows: &[Row], output: &Output, out: &mut impl Write) -> io::Result<()> {
+    let header = ["Z", "symbol", "name", "configuration", "ground", "J"].map(String::from);
+    let cells = |row: &Row| {
+        [
+            row.z.to_string(),
+            row.symbol.to_string(),
+            row.name.to_string(),
+            row.configuration.clone(),
+            row.ground.clone(),
+            row.j.to_string(),
+        ]
+    };
+    match output.format() {
+        Format::Text => {
+            let style = output.out_style();
+            for row in rows {
+                writeln!(
+                    out,
+                    "{:>3} {:<2} {:<13} {:<26} {}",
+                    row.z,
+                    row.symbol,
+                    row.name,
+                    row.configuration,
+                    style.term(&row.ground)
+                )?;
+            }
+        }
+        Format::Csv => {
+            report::csv_row(out, &header)?;
+            for row in rows {
+                report::csv_row(out, &cells(row))?;
+            }
+        }
+        Format::Records => {
+            for row in rows {
+                let mut record = cells(row).to_vec();
+                record.insert(0, "element".to_string());
+                report::record(out, &record)?;
+            }
+        }
+        Format::Latex => {
+            writeln!(out, "\\begin{{tabular}}{{rllll}}")?;
+            writeln!(out, "\\hline")?;
+            writeln!(
+                out,
+                "$Z$ & Symbol & Name & Configuration & Ground level \\\\"
+            )?;
+            writeln!(out, "\\hline")?;
+            for row in rows {
+                // the core stays out of the math mode
+                let configuration = match row.configuration.split_once(' ') {
+                    Some((core, valence)) if core.starts_with('[') => {
+                        format!("{core} ${valence}$")
+                    }
+                    _ => format!("${}$", row.configuration),
+                };
+                writeln!(
+                    out,
+                    "{} & {} & {} & {configuration} & ${}$ \\\\",
+                    row.z, row.symbol, row.name, row.ground
+                )?;
+            }
+            writeln!(out, "\\hline")?;
+            writeln!(out, "\\end{{tabular}}")?;
+        }
+        Format::Typst => {
+            let rows = rows
+                .iter()
+                .map(|row| {
+                    // the core stays out of the math mode
+                    let configuration = match row.configuration.split_once(' ') {
+                        Some((core, valence)) if core.starts_with('[') => {
+                            format!("[{} ${valence}$]", report::typst_escape(core))
+                        }
+                        _ => report::typst_math(&row.configuration),
+                    };
+                    vec![
+                        report::typst_text(&row.z.to_string()),
+                        report::typst_text(row.symbol),
+                        report::typst_text(row.name),
+                        configuration,
+                        report::typst_math(&row.ground),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            let header = [
+                report::typst_math("Z"),
+                report::typst_text("Symbol"),
+                report::typst_text("Name"),
+                report::typst_text("Configuration"),
+                report::typst_text("Ground level"),
+            ];
+            report::typst_table(out, &header, &rows)?;
+        }
+        Format::Html => {
+            let rows = rows
+                .iter()
+                .map(|row| cells(row)[..5].to_vec())
+                .collect::<Vec<_>>();
+            report::html_table(out, &header[..5], &rows)?;
+        }
+        Format::Markdown => {
+            report::markdown_row(out, &header[..5])?;
+            writeln!(out, "|{}", "---|".repeat(5))?;
+            for row in rows {
+                report::markdown_row(out, &cells(row)[..5])?;
+            }
+        }
+        Format::Nist => unreachable!("Rejected by Output::reject_nist"),
+        #[cfg(feature = "json")]
+        Format::Json => writeln!(
+            out,
+            "{}",
+            serde_json::to_string_pretty(rows).expect("Table is serializable")
+        )?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chitose::Notation;
+
+    use crate::periodic::rows;
+
+    /// Ground levels of the NIST Atomic Spectra Database by atomic number, up to Rf, the last element it gives one for
+    const NIST_GROUND_LEVELS: [(&str, &str); 104] = [
+        ("H", "^2S_1/2"),
+        ("He", "^1S_0"),
+        ("Li", "^2S_1/2"),
+        ("Be", "^1S_0"),
+        ("B", "^2P*_1/2"),
+        ("C", "^3P_0"),
+        ("N", "^4S*_3/2"),
+        ("O", "^3P_2"),
+        ("F", "^2P*_3/2"),
+        ("Ne", "^1S_0"),
+        ("Na", "^2S_1/2"),
+        ("Mg", "^1S_0"),
+        ("Al", "^2P*_1/2"),
+        ("Si", "^3P_0"),
+        ("P", "^4S*_3/2"),
+        ("S", "^3P_2"),
+        ("Cl", "^2P*_3/2"),
+        ("Ar", "^1S_0"),
+        ("K", "^2S_1/2"),
+        ("Ca", "^1S_0"),
+        ("Sc", "^2D_3/2"),
+        ("Ti", "^3F_2"),
+        ("V", "^4F_3/2"),
+        ("Cr", "^7S_3"),
+        ("Mn", "^6S_5/2"),
+        ("Fe", "^5D_4"),
+        ("Co", "^4F_9/2"),
+        ("Ni", "^3F_4"),
+        ("Cu", "^2S_1/2"),
+        ("Zn", "^1S_0"),
+        ("Ga", "^2P*_1/2"),
+        ("Ge", "^3P_0"),
+        ("As", "^4S*_3/2"),
+        ("Se", "^3P_2"),
+        ("Br", "^2P*_3/2"),
+        ("Kr", "^1S_0"),
+        ("Rb", "^2S_1/2"),
+        ("Sr", "^1S_0"),
+        ("Y", "^2D_3/2"),
+        ("Zr", "^3F_2"),
+        ("Nb", "^6D_1/2"),
+        ("Mo", "^7S_3"),
+        ("Tc", "^6S_5/2"),
+        ("Ru", "^5F_5"),
+        ("Rh", "^4F_9/2"),
+        ("Pd", "^1S_0"),
+        ("Ag", "^2S_1/2"),
+        ("Cd", "^1S_0"),
+        ("In", "^2P*_1/2"),
+        ("Sn", "^3P_0"),
+        ("Sb", "^4S*_3/2"),
+        ("Te", "^3P_2"),
+        ("I", "^2P*_3/2"),
+        ("Xe", "^1S_0"),
+        ("Cs", "^2S_1/2"),
+        ("Ba", "^1S_0"),
+        ("La", "^2D_3/2"),
+        ("Ce", "^1G*_4"),
+        ("Pr", "^4I*_9/2"),
+        ("Nd", "^5I_4"),
+        ("Pm", "^6H*_5/2"),
+        ("Sm", "^7F_0"),
+        ("Eu", "^8S*_7/2"),
+        ("Gd", "^9D*_2"),
+        ("Tb", "^6H*_15/2"),
+        ("Dy", "^5I_8"),
+        ("Ho", "^4I*_15/2"),
+        ("Er", "^3H_6"),
+        ("Tm", "^2F*_7/2"),
+        ("Yb", "^1S_0"),
+        ("Lu", "^2D_3/2"),
+        ("Hf", "^3F_2"),
+        ("Ta", "^4F_3/2"),
+        ("W", "^5D_0"),
+        ("Re", "^6S_5/2"),
+        ("Os", "^5D_4"),
+        ("Ir", "^4F_9/2"),
+        ("Pt", "^3D_3"),
+        ("Au", "^2S_1/2"),
+        ("Hg", "^1S_0"),
+        ("Tl", "^2P*_1/2"),
+        ("Pb", "^3P_0"),
+        ("Bi", "^4S*_3/2"),
+        ("Po", "^3P_2"),
+        ("At", "^2P*_3/2"),
+        ("Rn", "^1S_0"),
+        ("Fr", "^2S_1/2"),
+        ("Ra", "^1S_0"),
+        ("Ac", "^2D_3/2"),
+        ("Th", "^3F_2"),
+        ("Pa", "^4K_11/2"),
+        ("U", "^5L*_6"),
+        ("Np", "^6L_11/2"),
+        ("Pu", "^7F_0"),
+        ("Am", "^8S*_7/2"),
+        ("Cm", "^9D*_2"),
+        ("Bk", "^6H*_15/2"),
+        ("Cf", "^5I_8"),
+        ("Es", "^4I*_15/2"),
+        ("Fm", "^3H_6"),
+        ("Md", "^2F*_7/2"),
+        ("No", "^1S_0"),
+        ("Lr", "^2P*_1/2"),
+        ("Rf", "^3F_2"),
+    ];
+
+    /// Elements whose NIST ground level is not the one of Hund's rules, with the latter
+    const HUND_EXCEPTIONS: [(&str, &str); 1] = [("Ce", "^3H*_4")];
+
+    #[test]
+    fn nist_ground_levels() {
+        let rows = rows(1, 118, Notation::Ascii);
+        for (row, (symbol, nist)) in rows.iter().zip(NIST_GROUND_LEVELS) {
+            assert_eq!(row.symbol, symbol);
+            match HUND_EXCEPTIONS
+                .iter()
+                .find(|(exception, _)| *exception == symbol)
+            {
+                Some((_, hund)) => {
+                    assert_eq!(row.ground, *hund, "{symbol}");
+                    assert_ne!(row.ground, nist, "{symbol} is not an exception");
+                }
+                None => assert_eq!(row.ground, nist, "{symbol}"),
+            }
+        }
+    }
+}
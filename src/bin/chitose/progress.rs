@@ -0,0 +1,90 @@
+//! Progress bar of the microstate enumeration, drawn on stderr by `indicatif`
+
+use std::{
+    io::{self, IsTerminal},
+    time::{Duration, Instant},
+};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Quick enumerations finish without showing the bar
+const DELAY: Duration = Duration::from_millis(200);
+/// Checking the clock on every update is too slow, so it is checked about this often
+const CHECK: Duration = Duration::from_millis(10);
+const TEMPLATE: &str = "[{bar:30}] {pos}/{len} microstates ({percent}%)";
+
+#[derive(Debug)]
+pub struct Progress {
+    enabled: bool,
+    started: Instant,
+    /// Shown once the enumeration takes longer than [`DELAY`]
+    bar: Option<ProgressBar>,
+    checked: Instant,
+    /// Updates between the checks of the clock, doubled or halved to keep them [`CHECK`] apart
+    stride: u32,
+    /// Updates left until the next check of the clock
+    skipped: u32,
+}
+
+impl Progress {
+    /// Progress bar, suppressed unless both stdout and stderr are terminals
+    pub fn new() -> Self {
+        Self {
+            enabled: io::stdout().is_terminal() && io::stderr().is_terminal(),
+            started: Instant::now(),
+            bar: None,
+            checked: Instant::now(),
+            stride: 1,
+            skipped: 0,
+        }
+    }
+
+    /// Progress bar that is never drawn
+    pub fn hidden() -> Self {
+        Self {
+            enabled: false,
+            ..Self::new()
+        }
+    }
+
+    /// Moves the bar, which `indicatif` redraws at its own rate
+    pub fn update(&mut self, processed: u128, total: u128) {
+        if !self.enabled {
+            return;
+        }
+        if self.skipped > 0 {
+            self.skipped -= 1;
+            return;
+        }
+        let now = Instant::now();
+        self.stride = if now - self.checked < CHECK {
+            self.stride.saturating_mul(2)
+        } else {
+            (self.stride / 2).max(1)
+        };
+        self.skipped = self.stride - 1;
+        self.checked = now;
+        if now - self.started < DELAY {
+            return;
+        }
+        let bar = self.bar.get_or_insert_with(|| {
+            let style = ProgressStyle::with_template(TEMPLATE)
+                .expect("Template is valid")
+                .progress_chars("#.");
+            ProgressBar::new(saturating(total)).with_style(style)
+        });
+        bar.set_position(saturating(processed));
+    }
+
+    /// Erases the bar, if it was drawn
+    pub fn finish(&mut self) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// Enumerations beyond `u64::MAX` microstates would never finish anyway
+fn saturating(count: u128) -> u64 {
+    u64::try_from(count).unwrap_or(u64::MAX)
+}
@@ -0,0 +1,176 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use chitose::{Element, Notated, TermType};
+use itertools::Itertools;
+
+use crate::{
+    error::CliError,
+    terms::{self, Input},
+    Output,
+};
+
+const HELP: &str = "\
+Enter a configuration (\"2p3\", \"[Ar] 3d5 4s1\"), an element or ion (\"Fe\", \"Fe2+\") or a term (\"^3P\").
+Commands:
+  :help             this message
+  :history          lists the previous inputs
+  !!, !N            repeats the last or the N-th input
+  :complete PREFIX  lists elements starting with the prefix
+  :quit             exits (as does Ctrl-D)";
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// File the inputs are appended to, and loaded from on start (defaults to ~/.chitose_history)
+    #[arg(long)]
+    history: Option<PathBuf>,
+    /// If set, the history is not loaded nor saved
+    #[arg(long, default_value_t = false, conflicts_with = "history")]
+    no_history: bool,
+    #[command(flatten)]
+    pub options: terms::Options,
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    let history_path = if args.no_history {
+        None
+    } else {
+        args.history.clone().or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".chitose_history"))
+        })
+    };
+    let mut history = history_path
+        .as_ref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|history| history.lines().map(String::from).collect_vec())
+        .unwrap_or_default();
+    let mut history_file =
+        history_path.and_then(|path| OpenOptions::new().create(true).append(true).open(path).ok());
+    let mut out = output.writer()?;
+    println!("chitose {}, type :help for help", env!("CARGO_PKG_VERSION"));
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        print!("chitose> ");
+        io::stdout().flush()?;
+        let Some(Ok(line)) = lines.next() else {
+            println!();
+            break;
+        };
+        let mut line = line.trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        // history recall
+        if let Some(index) = line.strip_prefix('!') {
+            let recalled = if index == "!" {
+                history.last()
+            } else {
+                index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| history.get(index.wrapping_sub(1)))
+            };
+            match recalled {
+                Some(recalled) => {
+                    println!("{recalled}");
+                    line = recalled.clone();
+                }
+                None => {
+                    let message = format!("No such input in the history: {line}");
+                    eprintln!("{}", output.stderr_style().error(message));
+                    continue;
+                }
+            }
+        }
+        match line.split_once(' ').unwrap_or((&line, "")) {
+            (":quit" | ":q" | ":exit", _) => break,
+            (":help" | ":h", _) => {
+                println!("{HELP}");
+                continue;
+            }
+            (":history", _) => {
+                for (index, input) in history.iter().enumerate() {
+                    println!("{:>5}  {input}", index + 1);
+                }
+                continue;
+            }
+            (":complete", prefix) => {
+                println!("{}", complete(prefix.trim()).join(" "));
+                continue;
+            }
+            (command, _) if command.starts_with(':') => {
+                let message = format!("Unknown command {command}, type :help for help");
+                eprintln!("{}", output.stderr_style().error(message));
+                continue;
+            }
+            _ => {}
+        }
+        if history.last() != Some(&line) {
+            if let Some(file) = &mut history_file {
+                // losing the history is not worth interrupting the session
+                let _ = writeln!(file, "{line}");
+            }
+            history.push(line.clone());
+        }
+        if let Err(error) = evaluate(&line, &args.options, output, &mut out) {
+            eprintln!("{}", output.stderr_style().error(error));
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Element symbols and names starting with the prefix, case-insensitively
+fn complete(prefix: &str) -> Vec<&'static str> {
+    let prefix = prefix.to_lowercase();
+    Element::all()
+        .iter()
+        .flat_map(|element| [element.symbol, element.name])
+        .filter(|candidate| candidate.to_lowercase().starts_with(&prefix))
+        .collect()
+}
+
+/// Derives the terms of a configuration or an ion, or the levels of a term
+fn evaluate(
+    line: &str,
+    options: &terms::Options,
+    output: &Output,
+    out: &mut impl Write,
+) -> Result<(), String> {
+    let notation = output.notation();
+    let input = match line.parse::<Input>() {
+        Ok(input) => input,
+        Err(error) => {
+            let Ok(term) = line.parse::<TermType>() else {
+                return Err(error);
+            };
+            writeln!(
+                out,
+                "{}: L = {}, S = {}, {} parity, {} states",
+                term.notated(notation),
+                term.l(),
+                term.s(),
+                term.parity(),
+                term.degeneracy()
+            )
+            .map_err(|error| error.to_string())?;
+            for level in term.levels() {
+                match level.lande_g_exact() {
+                    Some(g) => writeln!(out, "  {}: g = {g}", level.notated(notation)),
+                    None => writeln!(out, "  {}: g is undefined", level.notated(notation)),
+                }
+                .map_err(|error| error.to_string())?;
+            }
+            return Ok(());
+        }
+    };
+    let (configuration, species) = input
+        .resolve(None, notation)
+        .map_err(|error| error.to_string())?;
+    let report = terms::full_report(&configuration, species, options, output)
+        .map_err(|error| error.to_string())?;
+    terms::write(&report, &configuration, options, output, out).map_err(|error| error.to_string())
+}
@@ -0,0 +1,736 @@
+//! Results of the `terms` subcommand, independent of the output format
+
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use chitose::{
+    Configuration, EnergyUnit, HalfInt, IntermediateLevel, Level, MicrostateGrid, Notated,
+    Notation, Parity, TermEnergy, TermMomentum, TermType, WorkedSolution, ZeemanSublevel,
+};
+use itertools::Itertools;
+use num_bigint::BigUint;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[cfg_attr(not(feature = "json"), allow(dead_code))]
+pub struct Report {
+    /// Element or ion the configuration belongs to
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub species: Option<String>,
+    pub configuration: String,
+    /// Holes of the open subshells, if they are more than half filled or the holes are requested
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub hole_equivalent: Option<String>,
+    /// Configurations of jj coupling, with `--coupling jj`
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub jj_levels: Option<Vec<JjLevels>>,
+    /// Number of microstates, if it fits into `u128`
+    pub microstates: Option<u128>,
+    pub terms: Vec<TermReport>,
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub microstate_assignments: Option<Vec<Assignment>>,
+    /// ML–MS tables of the open subshells
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub grids: Option<Vec<MicrostateGrid>>,
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub term_energies: Option<Vec<TermEnergy>>,
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub intermediate_levels: Option<Vec<IntermediateLevel>>,
+    /// Written instead of the rest with `--count-only`
+    #[cfg_attr(feature = "json", serde(skip))]
+    pub counts: Option<Counts>,
+    /// Derivation the HTML page is made of, with `--format html`
+    #[cfg_attr(feature = "json", serde(skip))]
+    pub solution: Option<WorkedSolution>,
+}
+
+/// Configuration of jj coupling, along with its J values and the numbers of their occurrences
+#[derive(Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct JjLevels {
+    pub configuration: String,
+    pub levels: Vec<(HalfInt, usize)>,
+}
+
+/// Distinct term, along with the number of its occurrences
+#[derive(Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct TermReport {
+    pub term: String,
+    pub multiplicity: usize,
+    #[cfg_attr(feature = "json", serde(rename = "L"))]
+    pub l: usize,
+    #[cfg_attr(feature = "json", serde(rename = "S"))]
+    pub s: HalfInt,
+    pub parity: Parity,
+    pub count: usize,
+    /// Number of states of a single occurrence of the term
+    pub degeneracy: usize,
+    pub levels: Vec<LevelReport>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct LevelReport {
+    pub level: String,
+    #[cfg_attr(feature = "json", serde(rename = "J"))]
+    pub j: HalfInt,
+    pub degeneracy: usize,
+    /// Landé g-factor, undefined for J = 0
+    pub g: Option<f64>,
+    /// Same g-factor as an exact fraction, like "3/2"
+    pub g_exact: Option<String>,
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub zeeman: Option<Vec<ZeemanSublevel>>,
+}
+
+/// Microstates assigned to a term by the derivation
+#[derive(Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct Assignment {
+    pub term: String,
+    pub microstates: Vec<String>,
+}
+
+impl TermReport {
+    pub fn new(
+        term: &TermType,
+        count: usize,
+        notation: Notation,
+        zeeman: impl Fn(&Level) -> Option<Vec<ZeemanSublevel>>,
+    ) -> Self {
+        Self {
+            term: term.notated(notation).to_string(),
+            multiplicity: term.multiplicity(),
+            l: term.l(),
+            s: term.s(),
+            parity: term.parity(),
+            count,
+            degeneracy: term.degeneracy(),
+            levels: term
+                .levels()
+                .iter()
+                .map(|level| LevelReport {
+                    level: level.notated(notation).to_string(),
+                    j: level.j(),
+                    degeneracy: level.degeneracy(),
+                    g: level.lande_g(),
+                    g_exact: level.lande_g_exact().map(|g| g.to_string()),
+                    zeeman: zeeman(level),
+                })
+                .collect_vec(),
+        }
+    }
+}
+
+/// Quotes the CSV field if needed
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn csv_row(out: &mut impl Write, fields: &[String]) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        fields.iter().map(|field| csv_field(field)).join(",")
+    )
+}
+
+impl Report {
+    /// Writes one row per term, or one row per level of every term
+    pub fn write_csv(&self, levels: bool, out: &mut impl Write) -> io::Result<()> {
+        csv_row(out, &Self::csv_header(levels))?;
+        for row in self.csv_rows(levels) {
+            csv_row(out, &row)?;
+        }
+        Ok(())
+    }
+
+    pub fn csv_header(levels: bool) -> Vec<String> {
+        let mut header = vec!["term", "multiplicity", "L", "S", "parity", "count"];
+        if levels {
+            header.extend(["level", "J", "degeneracy", "g"]);
+        } else {
+            header.push("degeneracy");
+        }
+        header.into_iter().map(String::from).collect()
+    }
+
+    pub fn csv_rows(&self, levels: bool) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        for term in &self.terms {
+            let common = [
+                term.term.clone(),
+                term.multiplicity.to_string(),
+                term.l.to_string(),
+                term.s.to_string(),
+                term.parity.to_string(),
+                term.count.to_string(),
+            ];
+            if !levels {
+                let mut row = common.to_vec();
+                row.push(term.degeneracy.to_string());
+                rows.push(row);
+                continue;
+            }
+            for level in &term.levels {
+                let mut row = common.to_vec();
+                row.extend([
+                    level.level.clone(),
+                    level.j.to_string(),
+                    level.degeneracy.to_string(),
+                    level.g.map(|g| g.to_string()).unwrap_or_default(),
+                ]);
+                rows.push(row);
+            }
+        }
+        rows
+    }
+}
+
+impl Report {
+    /// Writes a LaTeX tabular with one row per term, or one row per level of every term. Terms are expected to be in the LaTeX notation
+    pub fn write_latex(&self, levels: bool, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "% Terms of {}", self.configuration)?;
+        if levels {
+            writeln!(out, "\\begin{{tabular}}{{lccc}}")?;
+            writeln!(out, "\\hline")?;
+            writeln!(out, "Level & $J$ & Degeneracy & $g_J$ \\\\")?;
+        } else {
+            writeln!(out, "\\begin{{tabular}}{{lccccc}}")?;
+            writeln!(out, "\\hline")?;
+            writeln!(out, "Term & $S$ & $L$ & Parity & Count & Degeneracy \\\\")?;
+        }
+        writeln!(out, "\\hline")?;
+        for term in &self.terms {
+            if !levels {
+                writeln!(
+                    out,
+                    "${}$ & ${}$ & {} & {} & {} & {} \\\\",
+                    term.term, term.s, term.l, term.parity, term.count, term.degeneracy
+                )?;
+                continue;
+            }
+            for level in &term.levels {
+                let g =
+                    level
+                        .g_exact
+                        .as_ref()
+                        .map_or("---".to_string(), |g| match g.split_once('/') {
+                            Some((numerator, denominator)) => {
+                                format!("$\\frac{{{numerator}}}{{{denominator}}}$")
+                            }
+                            None => format!("${g}$"),
+                        });
+                writeln!(
+                    out,
+                    "${}$ & ${}$ & {} & {g} \\\\",
+                    level.level, level.j, level.degeneracy
+                )?;
+            }
+        }
+        writeln!(out, "\\hline")?;
+        writeln!(out, "\\end{{tabular}}")
+    }
+}
+
+/// Text cell of a Typst table, with the markup characters escaped
+pub fn typst_text(text: &str) -> String {
+    format!("[{}]", typst_escape(text))
+}
+
+/// Escapes the characters having special meaning in Typst markup
+pub fn typst_escape(text: &str) -> String {
+    text.chars().fold(String::new(), |mut escaped, c| {
+        if matches!(
+            c,
+            '\\' | '#' | '[' | ']' | '*' | '_' | '$' | '@' | '<' | '`' | '~'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        escaped
+    })
+}
+
+/// Math cell of a Typst table, like the terms in the Typst notation
+pub fn typst_math(math: impl Display) -> String {
+    format!("[${math}$]")
+}
+
+/// Writes a Typst table; the cells are content blocks, see [`typst_text`] and [`typst_math`]
+pub fn typst_table(
+    out: &mut impl Write,
+    header: &[String],
+    rows: &[Vec<String>],
+) -> io::Result<()> {
+    writeln!(out, "#table(")?;
+    writeln!(out, "  columns: {},", header.len())?;
+    writeln!(out, "  table.header({}),", header.join(", "))?;
+    for row in rows {
+        writeln!(out, "  {},", row.join(", "))?;
+    }
+    writeln!(out, ")")
+}
+
+impl Report {
+    /// Writes a Typst table with one row per term, or one row per level of every term. Terms are expected to be in the Typst notation
+    pub fn write_typst(&self, levels: bool, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "// Terms of {}", self.configuration)?;
+        let header = if levels {
+            vec![
+                typst_text("Level"),
+                typst_math("J"),
+                typst_text("Degeneracy"),
+                typst_math("g_J"),
+            ]
+        } else {
+            vec![
+                typst_text("Term"),
+                typst_math("S"),
+                typst_math("L"),
+                typst_text("Parity"),
+                typst_text("Count"),
+                typst_text("Degeneracy"),
+            ]
+        };
+        let mut rows = Vec::new();
+        for term in &self.terms {
+            if !levels {
+                rows.push(vec![
+                    typst_math(&term.term),
+                    typst_math(term.s),
+                    typst_text(&term.l.to_string()),
+                    typst_text(&term.parity.to_string()),
+                    typst_text(&term.count.to_string()),
+                    typst_text(&term.degeneracy.to_string()),
+                ]);
+                continue;
+            }
+            for level in &term.levels {
+                rows.push(vec![
+                    typst_math(&level.level),
+                    typst_math(level.j),
+                    typst_text(&level.degeneracy.to_string()),
+                    // Typst math makes a fraction of `3/2` by itself
+                    level.g_exact.as_ref().map_or(typst_text("—"), typst_math),
+                ]);
+            }
+        }
+        typst_table(out, &header, &rows)
+    }
+}
+
+/// Escapes the characters having special meaning in Markdown tables
+fn markdown_cell(cell: &str) -> String {
+    cell.chars().fold(String::new(), |mut escaped, c| {
+        if matches!(c, '|' | '*' | '_' | '\\' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        escaped
+    })
+}
+
+pub fn markdown_row(out: &mut impl Write, cells: &[String]) -> io::Result<()> {
+    writeln!(
+        out,
+        "| {} |",
+        cells.iter().map(|cell| markdown_cell(cell)).join(" | ")
+    )
+}
+
+impl Report {
+    /// Writes a Markdown table with one row per term, or one row per level of every term
+    pub fn write_markdown(&self, levels: bool, out: &mut impl Write) -> io::Result<()> {
+        let header = if levels {
+            vec!["Level", "J", "Degeneracy", "g_J"]
+        } else {
+            vec!["Term", "S", "L", "Parity", "Count", "Degeneracy"]
+        };
+        markdown_row(
+            out,
+            &header.iter().map(|cell| cell.to_string()).collect_vec(),
+        )?;
+        writeln!(out, "|{}", "---|".repeat(header.len()))?;
+        for term in &self.terms {
+            if !levels {
+                markdown_row(
+                    out,
+                    &[
+                        term.term.clone(),
+                        term.s.to_string(),
+                        term.l.to_string(),
+                        term.parity.to_string(),
+                        term.count.to_string(),
+                        term.degeneracy.to_string(),
+                    ],
+                )?;
+                continue;
+            }
+            for level in &term.levels {
+                markdown_row(
+                    out,
+                    &[
+                        level.level.clone(),
+                        level.j.to_string(),
+                        level.degeneracy.to_string(),
+                        level.g_exact.clone().unwrap_or("—".to_string()),
+                    ],
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Escapes the characters having special meaning in HTML
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Writes an HTML table; the cells are HTML already, like the terms in the HTML notation, see [`html_escape`]
+pub fn html_table(out: &mut impl Write, header: &[String], rows: &[Vec<String>]) -> io::Result<()> {
+    writeln!(out, "<table>")?;
+    let header = header.iter().map(|cell| format!("<th>{cell}</th>"));
+    writeln!(out, "<tr>{}</tr>", header.format(""))?;
+    for row in rows {
+        let row = row.iter().map(|cell| format!("<td>{cell}</td>"));
+        writeln!(out, "<tr>{}</tr>", row.format(""))?;
+    }
+    writeln!(out, "</table>")
+}
+
+/// Numbers of the states, terms and levels of the report, without the terms themselves
+///
+/// Numbers are of arbitrary precision; JSON has them as numbers if they fit into `u128`, and as strings otherwise
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct Counts {
+    #[cfg_attr(feature = "json", serde(serialize_with = "serialize_count"))]
+    pub microstates: BigUint,
+    /// Number of terms, repeated ones counted as many times as they occur
+    #[cfg_attr(feature = "json", serde(serialize_with = "serialize_count"))]
+    pub terms: BigUint,
+    pub distinct_terms: usize,
+    /// Number of levels, repeated ones counted as many times as they occur
+    #[cfg_attr(feature = "json", serde(serialize_with = "serialize_count"))]
+    pub levels: BigUint,
+    /// Total degeneracy of the terms, should be equal to the number of microstates
+    #[cfg_attr(feature = "json", serde(serialize_with = "serialize_count"))]
+    pub states: BigUint,
+}
+
+#[cfg(feature = "json")]
+fn serialize_count<S: serde::Serializer>(
+    count: &BigUint,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match u128::try_from(count) {
+        Ok(count) => serializer.serialize_u128(count),
+        Err(_) => serializer.collect_str(count),
+    }
+}
+
+impl Report {
+    /// Counts derived for `--count-only`
+    pub fn counts(&self) -> &Counts {
+        self.counts
+            .as_ref()
+            .expect("Counts are derived with --count-only")
+    }
+}
+
+impl Counts {
+    /// Counts of the distinct terms, each occurring the given number of times
+    pub fn new(microstates: BigUint, terms: &[(TermType, BigUint)]) -> Self {
+        Self {
+            microstates,
+            terms: terms.iter().map(|(_, count)| count).sum(),
+            distinct_terms: terms.len(),
+            levels: terms
+                .iter()
+                .map(|(term, count)| count * term.levels().len())
+                .sum(),
+            states: terms
+                .iter()
+                .map(|(term, count)| count * term.degeneracy())
+                .sum(),
+        }
+    }
+
+    /// Whether the terms contain every microstate
+    pub fn is_consistent(&self) -> bool {
+        self.microstates == self.states
+    }
+
+    pub fn csv_header() -> Vec<String> {
+        ["microstates", "terms", "distinct_terms", "levels", "states"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    pub fn csv_row(&self) -> Vec<String> {
+        vec![
+            self.microstates.to_string(),
+            self.terms.to_string(),
+            self.distinct_terms.to_string(),
+            self.levels.to_string(),
+            self.states.to_string(),
+        ]
+    }
+
+    pub fn write_text(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "Microstates: {}", self.microstates)?;
+        writeln!(
+            out,
+            "Terms: {} ({} distinct)",
+            self.terms, self.distinct_terms
+        )?;
+        writeln!(out, "Levels: {}", self.levels)?;
+        if self.is_consistent() {
+            writeln!(out, "Degeneracy check: ok")
+        } else {
+            writeln!(
+                out,
+                "Degeneracy check: failed, terms contain {} states",
+                self.states
+            )
+        }
+    }
+
+    pub fn write_latex(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "\\begin{{tabular}}{{ccccc}}")?;
+        writeln!(out, "\\hline")?;
+        writeln!(
+            out,
+            "Microstates & Terms & Distinct terms & Levels & States \\\\"
+        )?;
+        writeln!(out, "\\hline")?;
+        writeln!(out, "{} \\\\", self.csv_row().join(" & "))?;
+        writeln!(out, "\\hline")?;
+        writeln!(out, "\\end{{tabular}}")
+    }
+
+    pub fn write_typst(&self, out: &mut impl Write) -> io::Result<()> {
+        let header = ["Microstates", "Terms", "Distinct terms", "Levels", "States"];
+        let row = self
+            .csv_row()
+            .iter()
+            .map(|cell| typst_text(cell))
+            .collect_vec();
+        typst_table(out, &header.map(typst_text), &[row])
+    }
+
+    pub fn write_html(&self, out: &mut impl Write) -> io::Result<()> {
+        let header = ["Microstates", "Terms", "Distinct terms", "Levels", "States"];
+        html_table(out, &header.map(String::from), &[self.csv_row()])
+    }
+
+    pub fn write_markdown(&self, out: &mut impl Write) -> io::Result<()> {
+        let header = ["Microstates", "Terms", "Distinct terms", "Levels", "States"];
+        markdown_row(out, &header.map(String::from))?;
+        writeln!(out, "|{}", "---|".repeat(header.len()))?;
+        markdown_row(out, &self.csv_row())
+    }
+}
+
+/// Writes a tab-separated record; tabs and line breaks inside the fields are replaced with spaces
+pub fn record(out: &mut impl Write, fields: &[String]) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        fields
+            .iter()
+            .map(|field| field.replace(['\t', '\n', '\r'], " "))
+            .join("\t")
+    )
+}
+
+impl Report {
+    /// Records of every term, each followed by the records of its levels:
+    ///
+    /// - `term  symbol  multiplicity  L  S  parity  count  degeneracy`
+    /// - `level  symbol  term  J  degeneracy  g`, with an empty g if it's undefined
+    pub fn records(&self) -> Vec<Vec<String>> {
+        let mut records = Vec::new();
+        for term in &self.terms {
+            records.push(vec![
+                "term".to_string(),
+                term.term.clone(),
+                term.multiplicity.to_string(),
+                term.l.to_string(),
+                term.s.to_string(),
+                term.parity.to_string(),
+                term.count.to_string(),
+                term.degeneracy.to_string(),
+            ]);
+            for level in &term.levels {
+                records.push(vec![
+                    "level".to_string(),
+                    level.level.clone(),
+                    term.term.clone(),
+                    level.j.to_string(),
+                    level.degeneracy.to_string(),
+                    level.g_exact.clone().unwrap_or_default(),
+                ]);
+            }
+        }
+        records
+    }
+}
+
+/// Configuration the way NIST labels it: subshells outside of the noble gas core joined with dots, a single electron having no count, like `3d6.4s2` or `2p5.3s`
+pub fn nist_configuration(configuration: &Configuration) -> String {
+    configuration
+        .valence()
+        .sublevels()
+        .iter()
+        .map(|sublevel| {
+            let n = sublevel.n().map(|n| n.to_string()).unwrap_or_default();
+            let electrons = match sublevel.electrons() {
+                1 => String::new(),
+                electrons => electrons.to_string(),
+            };
+            format!("{n}{}{electrons}", sublevel.level_type())
+        })
+        .join(".")
+}
+
+/// Term the way NIST labels it, like `3P` or `4S*`
+fn nist_term(multiplicity: usize, l: usize, parity: Parity) -> String {
+    let odd = if parity == Parity::Odd { "*" } else { "" };
+    format!("{multiplicity}{}{odd}", TermMomentum(l))
+}
+
+impl Report {
+    /// Level list in the layout of the NIST Atomic Spectra Database, to be diffed against the downloaded tables. Configuration and term are only printed on the first level of a term, and the terms are separated by empty rows
+    ///
+    /// Levels of the intermediate coupling are listed by energy, relative to the lowest one, and labeled with their leading terms; LS-coupling levels have no energies, and repeated terms are listed the corresponding number of times
+    pub fn write_nist(
+        &self,
+        configuration: &Configuration,
+        unit: EnergyUnit,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        let g_text = |g: Option<f64>| g.map(|g| format!("{g:.5}")).unwrap_or_default();
+        // whether the row starts a term, and its term, J, g and energy
+        let rows: Vec<(bool, [String; 4])> = match &self.intermediate_levels {
+            Some(levels) => {
+                let lowest = levels.first().map_or(0.0, |level| level.energy);
+                let mut previous = None;
+                levels
+                    .iter()
+                    .map(|level| {
+                        let term = level.leading().term();
+                        let first = previous != Some(term);
+                        previous = Some(term);
+                        (
+                            first,
+                            [
+                                nist_term(term.multiplicity(), term.l(), term.parity()),
+                                level.j.to_string(),
+                                g_text(level.lande_g()),
+                                format!("{:.3}", level.energy - lowest),
+                            ],
+                        )
+                    })
+                    .collect()
+            }
+            None => self
+                .terms
+                .iter()
+                .flat_map(|term| std::iter::repeat_n(term, term.count))
+                .flat_map(|term| {
+                    term.levels.iter().enumerate().map(|(index, level)| {
+                        (
+                            index == 0,
+                            [
+                                nist_term(term.multiplicity, term.l, term.parity),
+                                level.j.to_string(),
+                                g_text(level.g),
+                                String::new(),
+                            ],
+                        )
+                    })
+                })
+                .collect(),
+        };
+        let header = [
+            "Configuration".to_string(),
+            "Term".to_string(),
+            "J".to_string(),
+            "g".to_string(),
+            format!("Level ({unit})"),
+        ];
+        let configuration = nist_configuration(configuration);
+        let mut widths = header.clone().map(|title| title.chars().count());
+        widths[0] = widths[0].max(configuration.len());
+        for (_, row) in &rows {
+            for (width, cell) in widths[1..].iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let line = |out: &mut dyn Write, cells: &[&str]| {
+            let line = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .join(" | ");
+            writeln!(out, "{}", line.trim_end())
+        };
+        let total = widths.iter().sum::<usize>() + 3 * (widths.len() - 1);
+        writeln!(out, "{}", "-".repeat(total))?;
+        line(out, &header.each_ref().map(String::as_str))?;
+        writeln!(
+            out,
+            "{}",
+            widths.iter().map(|width| "-".repeat(*width)).join("-|-")
+        )?;
+        for (index, (first, [term, j, g, energy])) in rows.iter().enumerate() {
+            if *first && index != 0 {
+                line(out, &["", "", "", "", ""])?;
+            }
+            let (label, term) = if *first {
+                (configuration.as_str(), term.as_str())
+            } else {
+                ("", "")
+            };
+            line(out, &[label, term, j, g, energy])?;
+        }
+        writeln!(out, "{}", "-".repeat(total))
+    }
+}
+
+impl Counts {
+    /// `counts  microstates  terms  distinct_terms  levels  states`
+    pub fn record(&self) -> Vec<String> {
+        let mut record = self.csv_row();
+        record.insert(0, "counts".to_string());
+        record
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chitose::Configuration;
+
+    use crate::report::nist_configuration;
+
+    #[test]
+    fn nist_labels() {
+        let label =
+            |s: &str| nist_configuration(&s.parse::<Configuration>().expect("Should be ok"));
+        assert_eq!(label("[Ar] 3d6 4s2"), "3d6.4s2");
+        assert_eq!(label("1s2 2s2 2p5 3s1"), "2s2.2p5.3s");
+    }
+}
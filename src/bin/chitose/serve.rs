@@ -0,0 +1,431 @@
+//! HTTP server answering with JSON, for the web front-ends and other languages
+//!
+//! Endpoints (all `GET`):
+//! - `/terms?config=3d5` or `/terms?element=Fe2%2B`: same report as `chitose --format json terms`, `levels=true` lists every level; configurations exceeding `--max-microstates`, `--max-memory` or `--max-terms` are answered with 422, and the ones taking longer than `--max-seconds` to derive with 503
+//! - `/ground?element=Fe` or `/ground?config=3d6`: ground level by Hund's rules
+//! - `/cg?j1=1&j2=1/2&m1=1&m2=-1/2&j=3/2&m=1/2`: Clebsch–Gordan coefficient; momenta larger than `--max-momentum` are answered with 422
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{self, AtomicBool},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use chitose::{
+    clebsch_gordan, clebsch_gordan_exact, momenta_in_range, DerivationError, HalfInt, Notated,
+};
+use clap::{ColorChoice, FromArgMatches};
+use serde_json::{json, Value};
+
+use crate::{
+    error::CliError,
+    stats,
+    terms::{self, Input, Options},
+    Format, Output,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: String,
+    /// Number of requests answered at once, the rest wait for a free worker (defaults to the number of cores)
+    #[arg(long)]
+    workers: Option<NonZeroUsize>,
+    /// Seconds to receive a request and to send the answer in, before the connection is dropped
+    #[arg(long, default_value_t = 10)]
+    timeout: u64,
+    /// Most microstates to enumerate for a single subshell of a /terms request
+    #[arg(long, default_value_t = 1_000_000)]
+    max_microstates: u128,
+    /// Most memory for the terms coupled from the ones of the subshells of a /terms request, like "512M" or "2G"
+    #[arg(long, default_value = "256M", value_parser = stats::parse_bytes)]
+    max_memory: u128,
+    /// Most terms coupled from the terms of the subshells of a /terms request
+    #[arg(long, default_value_t = 1_000_000)]
+    max_terms: u128,
+    /// Seconds a /terms request may take to derive, before the derivation is cancelled
+    #[arg(long, default_value_t = 30)]
+    max_seconds: u64,
+    /// Largest momentum (or projection) of a /cg request, as the coefficients take time quadratic in the momenta; at most 10000
+    #[arg(long, default_value = "1000")]
+    max_momentum: HalfInt,
+}
+
+/// Longest request (the request line and the headers) read
+const MAX_REQUEST: u64 = 16 * 1024;
+
+/// Failed request, answered with `{"error": ...}`
+struct HttpError(u16, String);
+
+impl From<CliError> for HttpError {
+    fn from(error: CliError) -> Self {
+        Self(400, error.to_string())
+    }
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    let listener = TcpListener::bind(&args.bind)?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+    let output = Output {
+        format: Some(Format::Json),
+        output: None,
+        color: Some(ColorChoice::Never),
+        ..output.clone()
+    };
+    let workers = args
+        .workers
+        .or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get);
+    // connections wait in the queue while every worker is busy, and in the backlog of the listener once it's full too
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(workers);
+    let receiver = Mutex::new(receiver);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let stream = receiver
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .recv();
+                let Ok(stream) = stream else {
+                    break;
+                };
+                // a panicking request drops its connection, but keeps the worker
+                match panic::catch_unwind(AssertUnwindSafe(|| handle(stream, &args, &output))) {
+                    Ok(Ok(())) => {}
+                    Ok(Err(error)) => eprintln!("Can't answer a request: {error}"),
+                    Err(_) => eprintln!("Can't answer a request: the derivation panicked"),
+                }
+            });
+        }
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => sender.send(stream).expect("Workers never stop first"),
+                Err(error) => eprintln!("Can't accept a connection: {error}"),
+            }
+        }
+        drop(sender);
+    });
+    Ok(())
+}
+
+/// Reads from the stream, failing with [`ErrorKind::TimedOut`] once the deadline passes, however slowly the bytes come
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+fn handle(mut stream: TcpStream, args: &Args, output: &Output) -> std::io::Result<()> {
+    let timeout = Duration::from_secs(args.timeout);
+    stream.set_write_timeout(Some(timeout))?;
+    let mut request = String::new();
+    let mut reader = BufReader::new(
+        Deadline {
+            stream: &stream,
+            deadline: Instant::now() + timeout,
+        }
+        .take(MAX_REQUEST),
+    );
+    reader.read_line(&mut request)?;
+    // headers are not needed
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+    let (status, body) = match answer(&request, args, output) {
+        Ok(body) => (200, body),
+        Err(HttpError(status, error)) => (status, json!({ "error": error })),
+    };
+    let body = serde_json::to_string_pretty(&body).expect("Answer is serializable");
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        422 => "Unprocessable Content",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+/// Answers the request line, like `GET /terms?config=3d5 HTTP/1.1`
+fn answer(request: &str, args: &Args, output: &Output) -> Result<Value, HttpError> {
+    let mut words = request.split_whitespace();
+    let (Some(method), Some(target)) = (words.next(), words.next()) else {
+        return Err(HttpError(400, "Malformed request".to_string()));
+    };
+    if method != "GET" {
+        return Err(HttpError(405, format!("Method {method} is not allowed")));
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = parse_query(query);
+    let param = |name: &str| {
+        query
+            .get(name)
+            .map(String::as_str)
+            .ok_or_else(|| HttpError(400, format!("Parameter {name:?} is required")))
+    };
+    let input = || -> Result<Input, HttpError> {
+        let text = param("config").or_else(|_| param("element"))?;
+        text.parse().map_err(|error| HttpError(400, error))
+    };
+    let notation = output.notation();
+    match path {
+        "/terms" => {
+            let (configuration, species) = input()?.resolve(None, notation)?;
+            let [max_microstates, max_memory, max_terms] =
+                [args.max_microstates, args.max_memory, args.max_terms].map(|max| max.to_string());
+            let mut arguments = vec![
+                "terms",
+                "--max-microstates",
+                &max_microstates,
+                "--max-memory",
+                &max_memory,
+                "--max-terms",
+                &max_terms,
+            ];
+            if query.get("levels").is_some_and(|levels| levels == "true") {
+                arguments.push("--levels");
+            }
+            let mut options = options(&arguments)?;
+            // the workers would draw their bars over each other
+            options.hide_progress = true;
+            let cancel = Arc::new(AtomicBool::new(false));
+            options.cancel = Some(cancel.clone());
+            let (done, finished) = mpsc::channel::<()>();
+            let report = thread::scope(|scope| {
+                let cancel = &cancel;
+                scope.spawn(move || {
+                    let limit = Duration::from_secs(args.max_seconds);
+                    if finished.recv_timeout(limit) == Err(RecvTimeoutError::Timeout) {
+                        cancel.store(true, atomic::Ordering::Relaxed);
+                    }
+                });
+                let report = terms::report(&configuration, species, &options, output);
+                drop(done);
+                report
+            })
+            .map_err(|error| match error {
+                DerivationError::MicrostateLimit { .. }
+                | DerivationError::MemoryLimit { .. }
+                | DerivationError::TermLimit { .. }
+                | DerivationError::CouplingMemoryLimit { .. } => HttpError(422, error.to_string()),
+                DerivationError::Cancelled => HttpError(
+                    503,
+                    format!("Derivation takes longer than {} seconds", args.max_seconds),
+                ),
+                error => CliError::from(error).into(),
+            })?;
+            Ok(serde_json::to_value(report).expect("Report is serializable"))
+        }
+        "/ground" => {
+            let (configuration, species) = input()?.resolve(None, notation)?;
+            let ground = configuration.open_subshells().ground_term();
+            Ok(json!({
+                "species": species,
+                "configuration": configuration.notated(notation).to_string(),
+                "ground": ground.notated(notation).to_string(),
+                "J": ground.j(),
+            }))
+        }
+        "/cg" => {
+            let value = |name: &str| -> Result<HalfInt, HttpError> {
+                param(name)?
+                    .parse()
+                    .map_err(|error| HttpError(400, format!("{name}: {error}")))
+            };
+            let (j1, j2, m1, m2, j, m) = (
+                value("j1")?,
+                value("j2")?,
+                value("m1")?,
+                value("m2")?,
+                value("j")?,
+                value("m")?,
+            );
+            let momenta = [j1, j2, m1, m2, j, m];
+            let max_momentum = args.max_momentum;
+            // bounded by `MAX_MOMENTUM` first, so that `-max_momentum` is safe to compare with
+            if !momenta_in_range(&momenta)
+                || momenta
+                    .iter()
+                    .any(|x| *x < -max_momentum || *x > max_momentum)
+            {
+                return Err(HttpError(
+                    422,
+                    format!("Momenta should be at most {max_momentum} to compute the coefficient"),
+                ));
+            }
+            Ok(json!({
+                "exact": clebsch_gordan_exact((j1, m1), (j2, m2), (j, m)).map(|exact| exact.to_string()),
+                "value": clebsch_gordan((j1, m1), (j2, m2), (j, m)),
+            }))
+        }
+        _ => Err(HttpError(404, format!("Unknown endpoint {path}"))),
+    }
+}
+
+/// Options of the `terms` subcommand, parsed from the given arguments
+fn options(arguments: &[&str]) -> Result<Options, HttpError> {
+    let command = <Options as clap::Args>::augment_args(clap::Command::new("terms"));
+    command
+        .try_get_matches_from(arguments)
+        .and_then(|matches| Options::from_arg_matches(&matches))
+        .map_err(|error| HttpError(400, error.to_string()))
+}
+
+/// Parameters of the query string, like `config=%5BAr%5D+3d5`
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+        .collect()
+}
+
+/// Decodes `+` and `%XX` escapes
+fn decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => match rest
+                .get(..2)
+                .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+            {
+                Some(decoded) => {
+                    bytes.push(decoded);
+                    rest = &rest[2..];
+                }
+                None => bytes.push(byte),
+            },
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{ColorChoice, FromArgMatches};
+
+    use crate::{
+        serve::{answer, parse_query, Args, HttpError},
+        Format, Output,
+    };
+
+    /// Arguments of `chitose serve` with the given flags, and the defaults for the rest
+    fn args(flags: &[&str]) -> Args {
+        let command = <Args as clap::Args>::augment_args(clap::Command::new("serve"));
+        let matches = command.get_matches_from(["serve"].iter().chain(flags));
+        Args::from_arg_matches(&matches).expect("Should be ok")
+    }
+
+    fn output() -> Output {
+        Output {
+            notation: None,
+            format: Some(Format::Json),
+            output: None,
+            color: Some(ColorChoice::Never),
+            config: None,
+        }
+    }
+
+    #[test]
+    fn query() {
+        let query = parse_query("config=%5BAr%5D+3d5&levels=true&empty");
+        assert_eq!(query["config"], "[Ar] 3d5");
+        assert_eq!(query["levels"], "true");
+        assert_eq!(query["empty"], "");
+        // malformed escapes are kept
+        assert_eq!(parse_query("element=Fe%2")["element"], "Fe%2");
+    }
+
+    #[test]
+    fn microstate_limit() {
+        // 8568 microstates
+        let request = "GET /terms?config=5g5 HTTP/1.1";
+        assert!(matches!(
+            answer(request, &args(&["--max-microstates", "1000"]), &output()),
+            Err(HttpError(422, _))
+        ));
+        assert!(answer(request, &args(&["--max-microstates", "10000"]), &output()).is_ok());
+    }
+
+    #[test]
+    fn coupling_limits() {
+        // about 10^9 terms, which would take the server down with it
+        let request = "GET /terms?config=5g9+6h11 HTTP/1.1";
+        assert!(matches!(
+            answer(request, &args(&[]), &output()),
+            Err(HttpError(422, _))
+        ));
+        // 4p3 4d1: 18 terms
+        let request = "GET /terms?config=4p3+4d1 HTTP/1.1";
+        let few = args(&["--max-terms", "10", "--max-memory", "1G"]);
+        assert!(matches!(
+            answer(request, &few, &output()),
+            Err(HttpError(422, _))
+        ));
+        let small = args(&["--max-memory", "64"]);
+        assert!(matches!(
+            answer(request, &small, &output()),
+            Err(HttpError(422, _))
+        ));
+        assert!(answer(request, &args(&[]), &output()).is_ok());
+    }
+
+    #[test]
+    fn deadline() {
+        // 2118760 microstates, which take far longer than no time at all to enumerate
+        let request = "GET /terms?config=(L%3D12)5 HTTP/1.1";
+        let args = args(&["--max-microstates", "10000000", "--max-seconds", "0"]);
+        assert!(matches!(
+            answer(request, &args, &output()),
+            Err(HttpError(503, _))
+        ));
+    }
+
+    #[test]
+    fn momentum_limit() {
+        let request = |j: &str| format!("GET /cg?j1={j}&j2={j}&m1=0&m2=0&j=0&m=0 HTTP/1.1");
+        // 1000 by default
+        let args = args(&[]);
+        assert!(answer(&request("1000"), &args, &output()).is_ok());
+        for j in ["1001", "100000000", "-1073741823"] {
+            assert!(matches!(
+                answer(&request(j), &args, &output()),
+                Err(HttpError(422, _))
+            ));
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Worked solution of the term derivation, to be included into homework solutions or lecture notes
+
+use std::{fs, io::Write, path::PathBuf};
+
+use chitose::WorkedSolution;
+use clap::ValueEnum;
+
+use crate::{error::CliError, terms::Input, Format, Output};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Configuration, like "2p2" or "1s2 2s2 2p2", or an element or ion
+    input: Input,
+    /// Draws the ML–MS table as every term is extracted from it, as a single SVG picture
+    #[arg(long)]
+    svg: bool,
+    /// Writes every step of the extraction as a separate SVG picture into the directory, named like `frame-001.svg`
+    #[arg(long, value_name = "DIR", conflicts_with = "svg")]
+    frames: Option<PathBuf>,
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    let (configuration, _) = args.input.resolve(None, output.notation())?;
+    let solution = WorkedSolution::new(&configuration)?;
+    if let Some(directory) = args.frames {
+        fs::create_dir_all(&directory).map_err(|error| CliError::Open(directory.clone(), error))?;
+        for (index, frame) in solution.svg_frames().iter().enumerate() {
+            let path = directory.join(format!("frame-{:03}.svg", index + 1));
+            fs::write(&path, frame).map_err(|error| CliError::Open(path, error))?;
+        }
+        return Ok(());
+    }
+    let text = match output.format() {
+        _ if args.svg => solution.to_svg(),
+        Format::Text | Format::Latex => solution.to_latex(),
+        Format::Typst => solution.to_typst(),
+        format => {
+            return Err(CliError::Usage(format!(
+                "Worked solution can't be written as {}",
+                format
+                    .to_possible_value()
+                    .expect("Formats are not skipped")
+                    .get_name()
+            )))
+        }
+    };
+    let mut out = output.writer()?;
+    write!(out, "{text}")?;
+    Ok(out.flush()?)
+}
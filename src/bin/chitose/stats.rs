@@ -0,0 +1,218 @@
+//! Statistics of the term derivation, printed to stderr by `--stats`
+
+use std::{
+    io::{self, Write},
+    mem::size_of,
+    time::{Duration, Instant},
+};
+
+use chitose::{tabulated_terms, Configuration, HalfInt, SubLevel};
+use itertools::Itertools;
+
+use crate::style::Style;
+
+/// How the terms are derived
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Only the ground level, by Hund's rules
+    Hund,
+    /// Both the microstate enumeration and the term extraction work with the hole equivalent of every subshell
+    Enumeration,
+    /// Numbers of microstates with each ML and MS are counted without the microstates themselves
+    Analytic,
+    /// Terms of l^n from the terms of l^(n-1), without the microstates
+    Recursive,
+}
+
+impl Backend {
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Hund => "Hund's rules (no enumeration)",
+            Self::Enumeration => {
+                "s, p, d and f tables, microstate enumeration otherwise (hole-equivalent subshells, ML/MS count table)"
+            }
+            Self::Analytic => {
+                "s, p, d and f tables, generating function otherwise (ML/MS counts without enumeration)"
+            }
+            Self::Recursive => {
+                "s, p, d and f tables, fractional parentage otherwise (terms of l^n from l^(n-1))"
+            }
+        }
+    }
+}
+
+/// Width of the names, so that the estimate and the timings are aligned
+const WIDTH: usize = 22;
+
+/// Estimate printed before the derivation, so that the hopeless ones can be interrupted right away
+pub fn print_estimate(configuration: &Configuration, backend: Backend, style: Style) {
+    let open = configuration.open_subshells();
+    // terms of the s, p, d and f subshells are looked up instead
+    let derived = open
+        .sublevels()
+        .iter()
+        .filter(|sublevel| tabulated_terms(sublevel).is_none())
+        .collect_vec();
+    let tables = derived.iter().copied().map(table_size);
+    let enumerated = derived
+        .iter()
+        .copied()
+        .map(|sublevel| {
+            let sublevel = sublevel
+                .hole_equivalent()
+                .unwrap_or_else(|| sublevel.clone());
+            sublevel.microstate_count()
+        })
+        .try_fold(0u128, |sum, count| sum.checked_add(count?));
+    print(
+        style,
+        &[
+            ("Configuration", configuration.to_string()),
+            (
+                "Microstates",
+                configuration.microstate_count_big().to_string(),
+            ),
+            (
+                "Enumerated microstates",
+                if backend == Backend::Enumeration {
+                    optional(enumerated)
+                } else {
+                    "none".to_string()
+                },
+            ),
+            (
+                "Peak memory estimate",
+                bytes(tables.max().unwrap_or_default()),
+            ),
+            ("Backend", backend.describe().to_string()),
+        ],
+    );
+}
+
+/// Measures the time spent enumerating microstates, by the progress callbacks of the derivation
+#[derive(Debug)]
+pub struct Timer {
+    started: Instant,
+    subshell_started: Instant,
+    enumeration: Option<Duration>,
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            subshell_started: Instant::now(),
+            enumeration: None,
+        }
+    }
+
+    /// Progress callback of the derivation
+    pub fn update(&mut self, processed: u128, total: u128) {
+        if processed == 1 {
+            self.subshell_started = Instant::now();
+        }
+        if processed == total {
+            *self.enumeration.get_or_insert_default() += self.subshell_started.elapsed();
+        }
+    }
+
+    /// Prints the times, once the terms are derived
+    pub fn print(&self, style: Style) {
+        let total = self.started.elapsed();
+        let mut rows = vec![];
+        // without the callbacks (like with the derivation log) only the total is known
+        if let Some(enumeration) = self.enumeration {
+            rows.push(("Enumeration time", format!("{enumeration:.2?}")));
+            rows.push((
+                "Term extraction time",
+                format!("{:.2?}", total.saturating_sub(enumeration)),
+            ));
+        }
+        rows.push(("Total time", format!("{total:.2?}")));
+        print(style, &rows);
+    }
+}
+
+fn print(style: Style, rows: &[(&str, String)]) {
+    let mut stderr = io::stderr().lock();
+    for (name, value) in rows {
+        let _ = writeln!(stderr, "{}", style.dim(format!("{name:<WIDTH$}  {value}")));
+    }
+}
+
+fn optional(count: Option<u128>) -> String {
+    count.map_or_else(|| "more than 2^128".to_string(), |count| count.to_string())
+}
+
+/// Rough size of the ML/MS count table of the subshell, in bytes
+fn table_size(sublevel: &SubLevel) -> u128 {
+    let sublevel = sublevel
+        .hole_equivalent()
+        .unwrap_or_else(|| sublevel.clone());
+    let l = u128::from(sublevel.level_type().l());
+    let electrons = u128::from(sublevel.electrons());
+    // electrons fill the orbitals with the greatest ML in pairs
+    let max_ml = (0..electrons).map(|i| l - i / 2).sum::<u128>();
+    let cells = (2 * max_ml + 1) * (electrons + 1);
+    // B-tree nodes are about half full
+    let cell = 2 * (size_of::<HalfInt>() + size_of::<Vec<()>>()) as u128;
+    let single_states = 2 * (2 * l + 1) * size_of::<(i8, HalfInt)>() as u128;
+    cells * cell + single_states
+}
+
+fn bytes(bytes: u128) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Number of bytes like "4096", "512K" or "2GiB", the suffixes being binary like the ones [`bytes`] prints
+pub fn parse_bytes(text: &str) -> Result<u128, String> {
+    let digits = text.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let scale = match text[digits.len()..].to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KIB" => 1 << 10,
+        "M" | "MIB" => 1 << 20,
+        "G" | "GIB" => 1 << 30,
+        "T" | "TIB" => 1 << 40,
+        suffix => return Err(format!("Unknown size suffix \"{suffix}\"")),
+    };
+    digits
+        .parse::<u128>()
+        .map_err(|error| error.to_string())?
+        .checked_mul(scale)
+        .ok_or_else(|| "Size doesn't fit into 128 bits".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use chitose::SubLevel;
+
+    use crate::stats::{parse_bytes, table_size};
+
+    #[test]
+    fn table() {
+        let d2: SubLevel = "d2".parse().expect("Should be ok");
+        let d8: SubLevel = "d8".parse().expect("Should be ok");
+        assert_eq!(table_size(&d2), table_size(&d8));
+        assert!(table_size(&d2) < table_size(&"d5".parse().expect("Should be ok")));
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_bytes("4096"), Ok(4096));
+        assert_eq!(parse_bytes("512K"), Ok(512 * 1024));
+        assert_eq!(parse_bytes("2GiB"), Ok(2 << 30));
+        assert!(parse_bytes("2X").is_err());
+        assert!(parse_bytes("GiB").is_err());
+    }
+}
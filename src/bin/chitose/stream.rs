@@ -0,0 +1,169 @@
+//! `chitose terms -`: a result line for every line of the standard input, flushed right away, so that chitose can serve as a coprocess
+
+use std::io::{self, BufRead, Write};
+
+use itertools::Itertools;
+
+use crate::{
+    error::CliError,
+    report::{self, Report},
+    terms::{self, Input, Options},
+    Format, Output,
+};
+
+/// Reads the inputs from stdin until its end; `charge` and `holes` apply to every line, like for a single input
+pub fn run(
+    charge: Option<i8>,
+    holes: Option<u8>,
+    options: &Options,
+    output: &Output,
+) -> Result<(), CliError> {
+    output.reject_nist("Summaries of the standard input")?;
+    let mut out = output.writer()?;
+    let (mut total, mut failed) = (0, 0);
+    for (line, text) in io::stdin().lock().lines().enumerate() {
+        let text = text?;
+        let input = text.trim();
+        // every line gets an answer, so that the reader stays in sync
+        if input.is_empty() || input.starts_with('#') {
+            writeln!(out)?;
+            out.flush()?;
+            continue;
+        }
+        total += 1;
+        let result = derive(input, charge, holes, options, output);
+        if result.is_err() {
+            failed += 1;
+        }
+        write(line + 1, input, &result, options, output, &mut out)?;
+        out.flush()?;
+    }
+    match failed {
+        0 => Ok(()),
+        failed => Err(CliError::Computation(format!(
+            "{failed} of {total} lines failed"
+        ))),
+    }
+}
+
+fn derive(
+    input: &str,
+    charge: Option<i8>,
+    holes: Option<u8>,
+    options: &Options,
+    output: &Output,
+) -> Result<Report, String> {
+    let parsed = input.parse::<Input>()?;
+    let (configuration, species) = parsed
+        .resolve(charge, output.notation())
+        .map_err(|error| error.to_string())?;
+    let configuration = match holes {
+        Some(holes) => {
+            terms::with_holes(&configuration, holes).map_err(|error| error.to_string())?
+        }
+        None => configuration,
+    };
+    terms::report(&configuration, species, options, output).map_err(|error| error.to_string())
+}
+
+/// Terms (or levels) of the report in a single line, like `^2P, ^2D ×2, ^2F`
+fn summary(report: &Report, options: &Options, output: &Output) -> String {
+    if options.count_only() {
+        let counts = report.counts();
+        return format!(
+            "{} microstates, {} terms ({} distinct), {} levels",
+            counts.microstates, counts.terms, counts.distinct_terms, counts.levels
+        );
+    }
+    let times = |count: usize| match (count, output.format()) {
+        (1, _) => String::new(),
+        (count, Format::Latex) => format!(" \\times {count}"),
+        (count, Format::Typst) => format!(" times {count}"),
+        (count, _) => format!(" ×{count}"),
+    };
+    let items = if options.levels() {
+        report
+            .terms
+            .iter()
+            .flat_map(|term| {
+                let times = times(term.count);
+                term.levels
+                    .iter()
+                    .map(move |level| format!("{}{times}", level.level))
+            })
+            .collect_vec()
+    } else {
+        report
+            .terms
+            .iter()
+            .map(|term| format!("{}{}", term.term, times(term.count)))
+            .collect_vec()
+    };
+    items.join(", ")
+}
+
+/// Single line with the result of the input, `line` is only reported in JSON
+#[cfg_attr(not(feature = "json"), allow(unused_variables))]
+fn write(
+    line: usize,
+    input: &str,
+    result: &Result<Report, String>,
+    options: &Options,
+    output: &Output,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let summary = match result {
+        Ok(report) => summary(report, options, output),
+        Err(error) => format!("error: {error}"),
+    };
+    match output.format() {
+        Format::Text => {
+            let style = output.out_style();
+            match result {
+                Ok(_) => writeln!(out, "{input}: {}", style.term(summary)),
+                Err(_) => writeln!(out, "{input}: {}", style.error(summary)),
+            }
+        }
+        Format::Csv => report::csv_row(out, &[input.to_string(), summary]),
+        Format::Records => report::record(out, &[input.to_string(), summary]),
+        Format::Latex => match result {
+            Ok(_) => writeln!(out, "{input} & ${summary}$ \\\\"),
+            Err(_) => writeln!(out, "{input} & {summary} \\\\"),
+        },
+        Format::Markdown => report::markdown_row(out, &[input.to_string(), summary]),
+        // rows of a table, which is up to the reader to open and close
+        Format::Html => writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td></tr>",
+            report::html_escape(input),
+            match result {
+                Ok(_) => summary,
+                Err(_) => report::html_escape(&summary),
+            }
+        ),
+        Format::Typst => {
+            let summary = match result {
+                Ok(_) => report::typst_math(summary),
+                Err(_) => report::typst_text(&summary),
+            };
+            writeln!(out, "  {}, {summary},", report::typst_text(input))
+        }
+        Format::Nist => unreachable!("Rejected by Output::reject_nist"),
+        // same entries as the ones of `chitose batch`, one per line
+        #[cfg(feature = "json")]
+        Format::Json => {
+            let mut value = serde_json::json!({ "line": line, "input": input });
+            match result {
+                Ok(report) if options.count_only() => {
+                    value["counts"] =
+                        serde_json::to_value(report.counts()).expect("Counts are serializable")
+                }
+                Ok(report) => {
+                    value["report"] = serde_json::to_value(report).expect("Report is serializable")
+                }
+                Err(error) => value["error"] = error.clone().into(),
+            }
+            writeln!(out, "{value}")
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! ANSI colors of the human-readable output
+
+use std::fmt::Display;
+
+const TERM: &str = "\x1b[1;36m";
+const WARNING: &str = "\x1b[33m";
+const ERROR: &str = "\x1b[1;31m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Colors the text, if enabled
+#[derive(Debug, Clone, Copy)]
+pub struct Style {
+    pub enabled: bool,
+}
+
+impl Style {
+    fn paint(&self, color: &str, text: impl Display) -> String {
+        if self.enabled {
+            format!("{color}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Term and level symbols
+    pub fn term(&self, text: impl Display) -> String {
+        self.paint(TERM, text)
+    }
+
+    pub fn warning(&self, text: impl Display) -> String {
+        self.paint(WARNING, text)
+    }
+
+    pub fn error(&self, text: impl Display) -> String {
+        self.paint(ERROR, text)
+    }
+
+    /// Secondary output, like the steps of the derivation
+    pub fn dim(&self, text: impl Display) -> String {
+        self.paint(DIM, text)
+    }
+
+    /// Escape code starting the dimmed text, for the output written by the library
+    pub fn dim_start(&self) -> &'static str {
+        if self.enabled {
+            DIM
+        } else {
+            ""
+        }
+    }
+
+    pub fn reset(&self) -> &'static str {
+        if self.enabled {
+            RESET
+        } else {
+            ""
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Desk calculator of the angular momentum coupling coefficients: Clebsch–Gordan coefficients, 3j, 6j and 9j symbols
+
+use std::io::Write;
+
+use chitose::{
+    clebsch_gordan, clebsch_gordan_exact, momenta_in_9j_range, momenta_in_range, wigner_3j,
+    wigner_3j_exact, wigner_6j, wigner_6j_exact, wigner_9j, HalfInt, SqrtRational, MAX_9J_MOMENTUM,
+    MAX_MOMENTUM,
+};
+use itertools::Itertools;
+use num_rational::BigRational;
+use num_traits::{One, Signed};
+
+use crate::{error::CliError, report, Format, Output};
+
+#[derive(Debug, clap::Args)]
+pub struct CgArgs {
+    /// Coupled momenta, their projections, and the total momentum with its projection, like `1 1/2 1 -1/2 3/2 1/2` for ⟨1 1; 1/2 -1/2 | 3/2 1/2⟩
+    #[arg(num_args = 6, required = true, allow_hyphen_values = true, value_names = ["j1", "j2", "m1", "m2", "j", "m"])]
+    values: Vec<HalfInt>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ThreeJArgs {
+    /// Top row of the symbol (momenta), then the bottom row (projections)
+    #[arg(num_args = 6, required = true, allow_hyphen_values = true, value_names = ["j1", "j2", "j3", "m1", "m2", "m3"])]
+    values: Vec<HalfInt>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct SixJArgs {
+    /// Top row of the symbol, then the bottom row
+    #[arg(num_args = 6, required = true, value_names = ["j1", "j2", "j3", "j4", "j5", "j6"])]
+    values: Vec<HalfInt>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct NineJArgs {
+    /// Rows of the symbol, one after another
+    #[arg(num_args = 9, required = true, value_names = ["j11", "j12", "j13", "j21", "j22", "j23", "j31", "j32", "j33"])]
+    values: Vec<HalfInt>,
+}
+
+/// Value of a coefficient, along with its arguments
+#[derive(Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[cfg_attr(not(feature = "json"), allow(dead_code))]
+struct Coefficient {
+    symbol: &'static str,
+    arguments: Vec<HalfInt>,
+    /// Exact value, unless it's only computed numerically (like the 9j symbols, and the coefficients of momenta above [`chitose::MAX_EXACT_MOMENTUM`])
+    exact: Option<String>,
+    value: f64,
+    /// Symbol with its arguments, in the text, LaTeX and Typst notation
+    #[cfg_attr(feature = "json", serde(skip))]
+    label: (String, String, String),
+    #[cfg_attr(feature = "json", serde(skip))]
+    exact_latex: Option<String>,
+    /// Integers don't need the decimal value
+    #[cfg_attr(feature = "json", serde(skip))]
+    integer: bool,
+}
+
+/// Angular momenta can't be negative
+fn check_momenta(momenta: impl IntoIterator<Item = HalfInt>) -> Result<(), CliError> {
+    match momenta.into_iter().find(|j| *j < HalfInt::ZERO) {
+        Some(j) => Err(CliError::Usage(format!(
+            "Angular momentum {j} should be non-negative"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Coefficients of large momenta take too long to compute, so the arguments should be at most `max` (as checked by `in_range`)
+fn check_range(
+    values: &[HalfInt],
+    in_range: fn(&[HalfInt]) -> bool,
+    max: HalfInt,
+) -> Result<(), CliError> {
+    if in_range(values) {
+        Ok(())
+    } else {
+        Err(CliError::Usage(format!(
+            "Momenta are too large to compute the coefficient, they should be at most {max}"
+        )))
+    }
+}
+
+/// Projections should be one of -j, -j+1, ..., j
+fn check_projections(pairs: impl IntoIterator<Item = (HalfInt, HalfInt)>) -> Result<(), CliError> {
+    for (j, m) in pairs {
+        if m.abs() > j || (j - m).doubled() % 2 != 0 {
+            return Err(CliError::Usage(format!(
+                "{m} is not a projection of angular momentum {j}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn cg(args: CgArgs, output: &Output) -> Result<(), CliError> {
+    let [j1, j2, m1, m2, j, m] = values(&args.values);
+    check_range(&args.values, momenta_in_range, MAX_MOMENTUM)?;
+    check_momenta([j1, j2, j])?;
+    check_projections([(j1, m1), (j2, m2), (j, m)])?;
+    let text = format!("<{j1} {m1}; {j2} {m2} | {j} {m}>");
+    let latex = format!(
+        "\\langle {} {}; {} {} | {} {} \\rangle",
+        latex(j1),
+        latex(m1),
+        latex(j2),
+        latex(m2),
+        latex(j),
+        latex(m)
+    );
+    // Typst math makes fractions of the slashes by itself
+    let typst = format!("angle.l {j1} {m1}; {j2} {m2} | {j} {m} angle.r");
+    let exact = clebsch_gordan_exact((j1, m1), (j2, m2), (j, m));
+    let value = clebsch_gordan((j1, m1), (j2, m2), (j, m));
+    write(
+        coefficient("cg", &args.values, (text, latex, typst), exact, value),
+        output,
+    )
+}
+
+pub fn three_j(args: ThreeJArgs, output: &Output) -> Result<(), CliError> {
+    let [j1, j2, j3, m1, m2, m3] = values(&args.values);
+    check_range(&args.values, momenta_in_range, MAX_MOMENTUM)?;
+    check_momenta([j1, j2, j3])?;
+    check_projections([(j1, m1), (j2, m2), (j3, m3)])?;
+    let label = matrix(&args.values, 3, ("(", ")"), "pmatrix", "\"(\"");
+    let exact = wigner_3j_exact((j1, m1), (j2, m2), (j3, m3));
+    let value = wigner_3j((j1, m1), (j2, m2), (j3, m3));
+    write(coefficient("3j", &args.values, label, exact, value), output)
+}
+
+pub fn six_j(args: SixJArgs, output: &Output) -> Result<(), CliError> {
+    let [a, b, c, d, e, f] = values(&args.values);
+    check_range(&args.values, momenta_in_range, MAX_MOMENTUM)?;
+    check_momenta([a, b, c, d, e, f])?;
+    let label = matrix(&args.values, 3, ("{", "}"), "Bmatrix", "\"{\"");
+    let exact = wigner_6j_exact([a, b, c], [d, e, f]);
+    let value = wigner_6j([a, b, c], [d, e, f]);
+    write(coefficient("6j", &args.values, label, exact, value), output)
+}
+
+pub fn nine_j(args: NineJArgs, output: &Output) -> Result<(), CliError> {
+    let [a, b, c, d, e, f, g, h, i] = values(&args.values);
+    check_range(&args.values, momenta_in_9j_range, MAX_9J_MOMENTUM)?;
+    check_momenta([a, b, c, d, e, f, g, h, i])?;
+    let label = matrix(&args.values, 3, ("{", "}"), "Bmatrix", "\"{\"");
+    let value = wigner_9j([[a, b, c], [d, e, f], [g, h, i]]);
+    write(coefficient("9j", &args.values, label, None, value), output)
+}
+
+/// Arguments as an array, their number is checked by clap
+fn values<const N: usize>(values: &[HalfInt]) -> [HalfInt; N] {
+    values.try_into().expect("Number of the values is checked")
+}
+
+fn coefficient(
+    symbol: &'static str,
+    arguments: &[HalfInt],
+    label: (String, String, String),
+    exact: Option<SqrtRational>,
+    value: f64,
+) -> Coefficient {
+    Coefficient {
+        symbol,
+        arguments: arguments.to_vec(),
+        exact: exact.as_ref().map(ToString::to_string),
+        exact_latex: exact.as_ref().map(latex_value),
+        integer: exact
+            .and_then(|exact| exact.to_rational())
+            .is_some_and(|rational| rational.is_integer()),
+        value,
+        label,
+    }
+}
+
+/// Symbol with the given rows, like `{1 2 3; 1 1 1}` and its LaTeX and Typst counterparts
+fn matrix(
+    values: &[HalfInt],
+    columns: usize,
+    (open, close): (&str, &str),
+    environment: &str,
+    delimiter: &str,
+) -> (String, String, String) {
+    let text = values
+        .chunks(columns)
+        .map(|row| row.iter().join(" "))
+        .join("; ");
+    let latex = values
+        .chunks(columns)
+        .map(|row| row.iter().map(|value| latex(*value)).join(" & "))
+        .join(" \\\\ ");
+    let typst = values
+        .chunks(columns)
+        .map(|row| row.iter().join(", "))
+        .join("; ");
+    (
+        format!("{open}{text}{close}"),
+        format!("\\begin{{{environment}}} {latex} \\end{{{environment}}}"),
+        format!("mat(delim: {delimiter}, {typst})"),
+    )
+}
+
+/// Half-integers as `\frac{3}{2}`
+fn latex(value: HalfInt) -> String {
+    if value.doubled() % 2 == 0 {
+        value.to_string()
+    } else {
+        let sign = if value < HalfInt::ZERO { "-" } else { "" };
+        format!("{sign}\\frac{{{}}}{{2}}", value.doubled().abs())
+    }
+}
+
+fn latex_rational(value: &BigRational) -> String {
+    if value.denom().is_one() {
+        value.numer().to_string()
+    } else {
+        let sign = if value.is_negative() { "-" } else { "" };
+        format!(
+            "{sign}\\frac{{{}}}{{{}}}",
+            value.numer().abs(),
+            value.denom()
+        )
+    }
+}
+
+/// Rational numbers as fractions, and the rest as square roots of them
+fn latex_value(value: &SqrtRational) -> String {
+    match value.to_rational() {
+        Some(rational) => latex_rational(&rational),
+        None => {
+            let sign = if value.is_negative() { "-" } else { "" };
+            format!(
+                "{sign}\\sqrt{{{}}}",
+                latex_rational(&value.signed_square().abs())
+            )
+        }
+    }
+}
+
+fn write(coefficient: Coefficient, output: &Output) -> Result<(), CliError> {
+    output.reject_nist("Coefficients")?;
+    let mut out = output.writer()?;
+    let (text, latex, typst) = &coefficient.label;
+    let value = coefficient.value;
+    let exact = coefficient.exact.as_deref();
+    match output.format() {
+        Format::Text => match exact {
+            Some(exact) if coefficient.integer => writeln!(out, "{text} = {exact}")?,
+            Some(exact) => writeln!(out, "{text} = {exact} ≈ {value:.10}")?,
+            None => writeln!(out, "{text} ≈ {value:.10}")?,
+        },
+        Format::Csv => {
+            report::csv_row(&mut out, &["symbol", "exact", "value"].map(String::from))?;
+            report::csv_row(
+                &mut out,
+                &[
+                    text.clone(),
+                    exact.unwrap_or_default().to_string(),
+                    value.to_string(),
+                ],
+            )?;
+        }
+        Format::Records => report::record(
+            &mut out,
+            &[
+                coefficient.symbol.to_string(),
+                text.clone(),
+                exact.unwrap_or_default().to_string(),
+                value.to_string(),
+            ],
+        )?,
+        Format::Latex => match &coefficient.exact_latex {
+            Some(exact) if coefficient.integer => writeln!(out, "\\[ {latex} = {exact} \\]")?,
+            Some(exact) => writeln!(out, "\\[ {latex} = {exact} \\approx {value:.10} \\]")?,
+            None => writeln!(out, "\\[ {latex} \\approx {value:.10} \\]")?,
+        },
+        // the exact values are written like `-sqrt(1/3)`, which Typst math takes as is
+        Format::Typst => match exact {
+            Some(exact) if coefficient.integer => writeln!(out, "$ {typst} = {exact} $")?,
+            Some(exact) => writeln!(out, "$ {typst} = {exact} approx {value:.10} $")?,
+            None => writeln!(out, "$ {typst} approx {value:.10} $")?,
+        },
+        Format::Html => {
+            let text = report::html_escape(text);
+            match exact {
+                Some(exact) if coefficient.integer => writeln!(out, "<p>{text} = {exact}</p>")?,
+                Some(exact) => writeln!(out, "<p>{text} = {exact} ≈ {value:.10}</p>")?,
+                None => writeln!(out, "<p>{text} ≈ {value:.10}</p>")?,
+            }
+        }
+        Format::Markdown => {
+            report::markdown_row(&mut out, &["Symbol", "Exact", "Value"].map(String::from))?;
+            writeln!(out, "|---|---|---|")?;
+            report::markdown_row(
+                &mut out,
+                &[
+                    text.clone(),
+                    exact.unwrap_or("—").to_string(),
+                    format!("{value:.10}"),
+                ],
+            )?;
+        }
+        Format::Nist => unreachable!("Rejected by Output::reject_nist"),
+        #[cfg(feature = "json")]
+        Format::Json => writeln!(
+            out,
+            "{}",
+            serde_json::to_string_pretty(&coefficient).expect("Coefficient is serializable")
+        )?,
+    }
+    Ok(out.flush()?)
+}
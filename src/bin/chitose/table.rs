@@ -0,0 +1,139 @@
+//! Aligned tables of the human-readable output
+
+use std::io::{self, Write};
+
+use comfy_table::{presets, Attribute, Cell, Color, TableComponent};
+
+use crate::style::Style;
+
+/// Width of the output, unless `COLUMNS` says otherwise
+const DEFAULT_WIDTH: usize = 80;
+/// Spaces after each column but the last one
+const GAP: u16 = 2;
+
+/// Table with the first column holding term or level symbols
+#[derive(Debug)]
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(header: &[&str]) -> Self {
+        Self {
+            header: header.iter().map(ToString::to_string).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Writes the table, wrapping the last column at `, ` separators if the lines are too wide
+    pub fn write(&self, style: Style, out: &mut impl Write) -> io::Result<()> {
+        let total = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or(DEFAULT_WIDTH);
+        writeln!(out, "{}", self.render(style, total))
+    }
+
+    fn render(&self, style: Style, total: usize) -> String {
+        let mut widths = self
+            .header
+            .iter()
+            .map(|cell| width(cell))
+            .collect::<Vec<_>>();
+        for row in &self.rows {
+            for (column, cell) in widths.iter_mut().zip(row) {
+                *column = (*column).max(width(cell));
+            }
+        }
+        let (_, rest) = widths.split_last().expect("Tables have columns");
+        let indent = rest.iter().map(|width| width + GAP as usize).sum::<usize>();
+        // the last column takes whatever is left, but at least a third of the line
+        let last = total.saturating_sub(indent).max(total / 3);
+
+        let mut table = comfy_table::Table::new();
+        table
+            .load_preset(presets::NOTHING)
+            .set_style(TableComponent::HeaderLines, '-')
+            .set_header(&self.header);
+        if style.enabled {
+            table.enforce_styling();
+        } else {
+            table.force_no_tty();
+        }
+        for row in &self.rows {
+            let (value, cells) = row.split_last().expect("Rows have cells");
+            let mut cells = cells.iter().map(Cell::new).collect::<Vec<_>>();
+            cells.push(Cell::new(wrap(value, last).join("\n")));
+            if style.enabled {
+                cells[0] = cells[0]
+                    .clone()
+                    .fg(Color::Cyan)
+                    .add_attribute(Attribute::Bold);
+            }
+            table.add_row(cells);
+        }
+        let columns = table.column_count();
+        for column in table.column_iter_mut() {
+            let after = if column.index + 1 < columns { GAP } else { 0 };
+            column.set_padding((0, after));
+        }
+        table.trim_fmt()
+    }
+}
+
+/// Number of characters, as the notations don't use wide ones
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Splits the comma-separated list into lines of at most `width` characters, where possible
+fn wrap(text: &str, width_: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for item in text.split(", ") {
+        if current.is_empty() {
+            current = item.to_string();
+        } else if width(&current) + 2 + width(item) <= width_ {
+            current += ", ";
+            current += item;
+        } else {
+            lines.push(current + ",");
+            current = item.to_string();
+        }
+    }
+    lines.push(current);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{style::Style, table::Table};
+
+    #[test]
+    fn wrapping() {
+        let mut table = Table::new(&["Term", "Microstates"]);
+        table.push(vec!["^3P".into(), "1+ 0+, 1+ -1+, 0+ -1+".into()]);
+        table.push(vec!["^1S".into(), "1+ -1-".into()]);
+        let plain = Style { enabled: false };
+        assert_eq!(
+            table.render(plain, 80),
+            "Term  Microstates\n\
+             ---------------------------\n\
+             ^3P   1+ 0+, 1+ -1+, 0+ -1+\n\
+             ^1S   1+ -1-"
+        );
+        // the microstates stay whole
+        assert_eq!(
+            table.render(plain, 20),
+            "Term  Microstates\n\
+             --------------------\n\
+             ^3P   1+ 0+, 1+ -1+,\n      0+ -1+\n\
+             ^1S   1+ -1-"
+        );
+    }
+}
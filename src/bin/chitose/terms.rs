@@ -0,0 +1,720 @@
+#[cfg(feature = "json")]
+use std::path::PathBuf;
+use std::{
+    io::Write,
+    str::FromStr,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use chitose::{
+    ee_terms_detailed_limited, hund_order, Configuration, DerivationError, DerivationPhase,
+    EnergyUnit, EnumerationLimit, Ion, LevelError, MagneticField, MicrostateGrid, Notated,
+    Notation, SubLevel, SubLevelType, WorkedSolution,
+};
+use itertools::Itertools;
+
+use crate::{
+    config::Config,
+    error::CliError,
+    progress::Progress,
+    report::{self, Assignment, Counts, JjLevels, LevelReport, Report, TermReport},
+    stats::{self, Backend, Timer},
+    stream,
+    table::Table,
+    Format, Output,
+};
+
+/// Positional input of the subcommand
+#[derive(Debug, Clone)]
+pub enum Input {
+    Configuration(Configuration),
+    Ion(Ion),
+    /// `-`, one input per line of the standard input
+    Stdin,
+}
+
+/// Element symbols start with a capital letter, `-` is the standard input, anything else is tried as a configuration first
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "-" {
+            return Ok(Self::Stdin);
+        }
+        if s.trim_start().starts_with(|c: char| c.is_ascii_uppercase()) {
+            return s.parse().map(Self::Ion).map_err(|error| error.to_string());
+        }
+        s.parse::<Configuration>()
+            .map(Self::Configuration)
+            .or_else(|error| s.parse().map(Self::Ion).map_err(|_| error.to_string()))
+    }
+}
+
+impl Input {
+    /// Configuration to derive the terms of, along with the name of the species, if an element is given. `charge` is added to the one of the ion
+    pub fn resolve(
+        &self,
+        charge: Option<i8>,
+        notation: Notation,
+    ) -> Result<(Configuration, Option<String>), CliError> {
+        match self {
+            Self::Ion(ion) => {
+                let ion = Ion::new(ion.element, ion.charge + charge.unwrap_or_default())?;
+                let species = format!("{} ({})", ion.element.name, ion.notated(notation));
+                Ok((ion.configuration(), Some(species)))
+            }
+            Self::Configuration(_) if charge.is_some() => Err(CliError::Usage(
+                "--charge can only be used with an element".to_string(),
+            )),
+            Self::Configuration(configuration) => Ok((configuration.clone(), None)),
+            Self::Stdin => Err(CliError::Usage(
+                "\"-\" (the standard input) is only accepted by `chitose terms`".to_string(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Electron configuration, like "2p3", "1s2 2s2 2p3" or "[Ar] 3d5 4s1", or a chemical element or ion, like "Fe", "Fe2+" or "Fe III" (terms of the open subshells of its ground configuration are derived); "-" reads one of them per line of the standard input, printing a result line for each
+    #[arg(conflicts_with_all = ["orbital", "electrons", "configuration"])]
+    input: Option<Input>,
+    /// Charge of the ion, added to the one of the element
+    #[arg(long, requires = "input", allow_negative_numbers = true)]
+    charge: Option<i8>,
+    /// Type of a sublevel (0 for s, 1 for p, etc), used instead of the configuration
+    #[arg(short = 'l', required_unless_present_any = ["configuration", "input"])]
+    orbital: Option<u8>,
+    /// Number of electrons on the sublevel specified by -l
+    #[arg(short = 'n', required_unless_present_any = ["configuration", "input", "holes"])]
+    electrons: Option<u8>,
+    /// Number of holes instead of electrons in the last subshell of the configuration, or in the one specified by -l, like `chitose terms d --holes 2`; both electron and hole pictures are printed
+    #[arg(long, conflicts_with = "electrons")]
+    holes: Option<u8>,
+    /// Electron configuration, same as the positional one
+    #[arg(short, conflicts_with_all = ["orbital", "electrons"])]
+    configuration: Option<Configuration>,
+    #[command(flatten)]
+    pub options: Options,
+}
+
+/// Coupling scheme the states of the configuration are listed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Coupling {
+    /// LS terms
+    #[default]
+    Ls,
+    /// jj configurations, with the J values of each
+    Jj,
+}
+
+/// Options of the derivation and of the printed report
+#[derive(Debug, clap::Args)]
+pub struct Options {
+    /// Verbosity of the text output: -v adds the number of occurrences and the levels of every term, -vv the microstates assigned to the terms and the ML–MS tables, -vvv the full derivation trace
+    #[arg(short, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// If set, lists every level of the terms separately, along with its degeneracy
+    #[arg(long, default_value_t = false)]
+    levels: bool,
+    /// If set, sorts the terms and their levels in the order of energy expected from Hund's rules
+    #[arg(long, default_value_t = false)]
+    hund_order: bool,
+    /// If set, prints only the ground level given by Hund's rules, without enumerating the microstates
+    #[arg(long, default_value_t = false)]
+    ground: bool,
+    /// If set, prints microstates assigned to every term (for a single open subshell), implied by -vv
+    #[arg(long, default_value_t = false)]
+    microstates: bool,
+    /// If set, prints the ML–MS table of the microstates of every open subshell (also in LaTeX), implied by -vv; with --microstates its cells list the microstates instead of counting them
+    #[arg(long, default_value_t = false)]
+    grid: bool,
+    /// If set, prints fine-structure levels of every term along with their Landé g-factors, as exact fractions
+    #[arg(short = 'g', long = "g-factors", default_value_t = false)]
+    lande: bool,
+    /// Coupling scheme of the listed states: LS terms, or jj configurations with their J values (in the text and JSON output) [default: ls]
+    #[arg(long, value_enum)]
+    coupling: Option<Coupling>,
+    /// If set, prints Zeeman splitting of every level in a weak magnetic field, like "0.5T" or "1000G"
+    #[arg(short = 'B', long)]
+    field: Option<MagneticField>,
+    /// Unit of the printed energies: J, eV, cm-1 or MHz
+    #[arg(long, default_value = "cm-1")]
+    energy_unit: EnergyUnit,
+    /// If set, prints electrostatic energies of the terms in terms of the Slater integrals F_k and G_k
+    #[arg(long, default_value_t = false)]
+    slater: bool,
+    /// Spin-orbit parameter ζ; if set, prints levels in the intermediate coupling, in the same units
+    #[arg(long)]
+    zeta: Option<f64>,
+    /// Slater integrals F_0,F_2,... for the intermediate coupling, in the Condon–Shortley normalization
+    #[arg(long, value_delimiter = ',', requires = "zeta")]
+    slater_integrals: Vec<f64>,
+    /// If set, prints only the numbers of microstates, terms and levels, and checks that the terms contain every microstate
+    #[arg(long, default_value_t = false, conflicts_with = "ground")]
+    count_only: bool,
+    /// If set, counts the microstates with each ML and MS combinatorially instead of enumerating them, which is instant even for the huge subshells; always done for `--count-only`, but not with the derivation log or trace
+    #[arg(long, default_value_t = false, conflicts_with = "ground")]
+    analytic: bool,
+    /// If set, builds the terms of every subshell from the terms of one electron less by the coefficients of fractional parentage instead of enumerating microstates, but not with the derivation log or trace
+    #[arg(long, default_value_t = false, conflicts_with_all = ["ground", "analytic"])]
+    recursive: bool,
+    /// If set, prints the number of microstates, the memory estimate and the algorithm to stderr before the derivation, and its timings after it
+    #[arg(long, default_value_t = false)]
+    stats: bool,
+    /// Most microstates to enumerate for a single subshell; larger ones fail right away instead of running for hours
+    #[arg(long)]
+    max_microstates: Option<u128>,
+    /// Most memory for the microstates kept to assign them to the terms (--microstates, --grid, -vv and beyond, --trace), and for the terms coupled from the ones of the subshells, like "512M" or "2G"
+    #[arg(long, value_parser = stats::parse_bytes)]
+    max_memory: Option<u128>,
+    /// Most terms coupled from the terms of the subshells; configurations with more fail right away instead of running out of memory
+    #[arg(long)]
+    max_terms: Option<u128>,
+    /// File to write the whole derivation to as JSON lines, one event per line ("-" for the standard output)
+    #[cfg(feature = "json")]
+    #[arg(long, conflicts_with = "ground")]
+    trace: Option<PathBuf>,
+    /// Whether the hole picture is printed regardless of the verbosity, set by `--holes`
+    #[arg(skip)]
+    show_holes: bool,
+    /// Whether the progress bar is suppressed, set by `chitose batch` when deriving several configurations at once
+    #[arg(skip)]
+    pub hide_progress: bool,
+    /// Token failing the derivation with [`DerivationError::Cancelled`] once set, by `chitose serve` when a request takes too long
+    #[arg(skip)]
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+pub fn run(mut args: Args, output: &Output) -> Result<(), CliError> {
+    let notation = output.notation();
+    let input = args.input.or(args.configuration.map(Input::Configuration));
+    if let Some(Input::Stdin) = input {
+        args.options.show_holes |= args.holes.is_some();
+        return stream::run(args.charge, args.holes, &args.options, output);
+    }
+    let (configuration, species) = match input {
+        Some(input) => input.resolve(args.charge, notation)?,
+        None => {
+            let orbital = args
+                .orbital
+                .expect("-l is required without a configuration");
+            let level_type = SubLevelType::new(orbital)?;
+            let electrons = args.electrons.unwrap_or(level_type.max_electrons());
+            (
+                Configuration::new([SubLevel::new(level_type, electrons)?]),
+                None,
+            )
+        }
+    };
+    let configuration = match args.holes {
+        Some(holes) => with_holes(&configuration, holes)?,
+        None => configuration,
+    };
+    args.options.show_holes |= args.holes.is_some();
+    if args.options.count_only() {
+        output.reject_nist("Counts")?;
+    }
+    let report = full_report(&configuration, species, &args.options, output)?;
+    let mut out = output.writer()?;
+    write(&report, &configuration, &args.options, output, &mut out)?;
+    Ok(out.flush()?)
+}
+
+/// Replaces the electrons of the last subshell with the given number of holes
+pub fn with_holes(configuration: &Configuration, holes: u8) -> Result<Configuration, LevelError> {
+    let mut sublevels = configuration.sublevels().to_vec();
+    if let Some(last) = sublevels.last_mut() {
+        let holes = SubLevel::from_holes(last.level_type().clone(), holes)?;
+        *last = match last.n() {
+            Some(n) => SubLevel::with_principal(n, holes.level_type().clone(), holes.electrons())?,
+            None => holes,
+        };
+    }
+    Ok(Configuration::new(sublevels))
+}
+
+impl Options {
+    /// Fills the options not given on the command line from the configuration file
+    pub fn configure(&mut self, config: &Config) {
+        if self.verbose == 0 {
+            self.verbose = config.verbose.unwrap_or_default();
+        }
+        self.coupling = self.coupling.or(config.coupling);
+    }
+
+    pub fn coupling(&self) -> Coupling {
+        self.coupling.unwrap_or_default()
+    }
+    pub fn count_only(&self) -> bool {
+        self.count_only
+    }
+
+    /// Whether the derivation prints to stderr (or the trace file) as it goes, so that several of them at once would interleave
+    pub fn prints_derivation(&self) -> bool {
+        #[cfg(feature = "json")]
+        let traced = self.trace.is_some();
+        #[cfg(not(feature = "json"))]
+        let traced = false;
+        self.verbose >= 3 || self.stats || traced
+    }
+
+    /// How the terms are derived; the derivation log and trace need the microstates themselves
+    pub fn backend(&self) -> Backend {
+        #[cfg(feature = "json")]
+        let traced = self.trace.is_some();
+        #[cfg(not(feature = "json"))]
+        let traced = false;
+        if self.ground {
+            Backend::Hund
+        } else if self.recursive && self.verbose < 3 && !traced {
+            Backend::Recursive
+        } else if (self.analytic || self.count_only) && self.verbose < 3 && !traced {
+            Backend::Analytic
+        } else {
+            Backend::Enumeration
+        }
+    }
+
+    /// Limit of the enumeration set by `--max-microstates`, `--max-memory` and `--max-terms`
+    pub fn limit(&self) -> EnumerationLimit {
+        EnumerationLimit {
+            microstates: self.max_microstates,
+            memory: self.max_memory,
+            terms: self.max_terms,
+        }
+    }
+
+    /// Whether the tabular output lists levels instead of terms
+    pub fn levels(&self) -> bool {
+        self.levels || self.ground
+    }
+}
+
+/// Derives the terms of the configuration, and everything else requested by the options
+pub fn report(
+    configuration: &Configuration,
+    species: Option<String>,
+    args: &Options,
+    output: &Output,
+) -> Result<Report, DerivationError> {
+    let notation = output.notation();
+    let limit = args.limit();
+    let open = configuration.clone().with_limit(limit).open_subshells();
+    if args.stats {
+        stats::print_estimate(configuration, args.backend(), output.stderr_style());
+    }
+    let mut timer = Timer::new();
+    // derivation log goes to stderr, unless it's a human-readable output to the terminal
+    #[cfg(feature = "json")]
+    let traced = args
+        .trace
+        .as_deref()
+        .map(|path| crate::trace::derive(&open, path));
+    #[cfg(not(feature = "json"))]
+    let traced = None;
+    let mut terms = if args.ground {
+        Ok(Vec::new())
+    } else if args.backend() == Backend::Analytic {
+        if args.count_only {
+            // counted below without listing the terms, which may not even fit into memory
+            Ok(Vec::new())
+        } else {
+            open.ls_terms_analytic()
+        }
+    } else if args.backend() == Backend::Recursive {
+        open.ls_terms_recursive()
+    } else if let Some(terms) = traced {
+        terms
+    } else if let Some(cancel) = &args.cancel {
+        // set by the server, which draws no progress bar
+        open.ls_terms_cancellable(cancel)
+    } else if args.verbose < 3 {
+        let mut progress = if args.hide_progress {
+            Progress::hidden()
+        } else {
+            Progress::new()
+        };
+        let terms = open.ls_terms_progress(|derivation| {
+            // extraction takes a fraction of the enumeration time, the bar and the timer only follow the latter
+            if derivation.phase == DerivationPhase::Enumeration {
+                timer.update(derivation.processed, derivation.total);
+                progress.update(derivation.processed, derivation.total)
+            }
+        });
+        progress.finish();
+        terms
+    } else if output.format() == Format::Text && output.output.is_none() {
+        let style = output.out_style();
+        let mut stdout = std::io::stdout().lock();
+        // failures to write the log are reported by the derivation itself
+        let _ = write!(stdout, "{}", style.dim_start());
+        let terms = open.ls_terms_log(&mut stdout);
+        let _ = write!(stdout, "{}", style.reset());
+        terms
+    } else {
+        // stderr is unbuffered, while the log comes in small pieces
+        let mut stderr = std::io::BufWriter::new(std::io::stderr().lock());
+        let terms = open.ls_terms_log(&mut stderr);
+        let _ = stderr.flush();
+        terms
+    }?;
+    if args.stats && !args.ground {
+        timer.print(output.stderr_style());
+    }
+    if args.hund_order {
+        hund_order(&mut terms);
+    }
+    let counts = args.count_only.then(|| {
+        let terms = if args.backend() == Backend::Analytic {
+            open.ls_term_counts()
+        } else {
+            terms
+                .iter()
+                .sorted()
+                .dedup_with_count()
+                .map(|(count, term)| (term.clone(), count.into()))
+                .collect()
+        };
+        Counts::new(configuration.microstate_count_big(), &terms)
+    });
+    let zeeman = |level: &chitose::Level| {
+        args.field
+            .map(|field| level.zeeman(field, args.energy_unit))
+    };
+    let microstate_assignments = match open.sublevels() {
+        [sublevel] if args.microstates || args.verbose >= 2 => Some(
+            ee_terms_detailed_limited(sublevel.clone(), limit)?
+                .into_iter()
+                .map(|(term, microstates)| Assignment {
+                    term: term.notated(notation).to_string(),
+                    microstates: microstates.iter().map(ToString::to_string).collect(),
+                })
+                .collect(),
+        ),
+        _ => None,
+    };
+    let grids = (args.grid || args.verbose >= 2)
+        .then(|| {
+            open.sublevels()
+                .iter()
+                .map(|sublevel| {
+                    limit
+                        .check(sublevel, true)
+                        .and_then(|()| MicrostateGrid::new(sublevel.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?;
+    let mut terms = terms
+        .iter()
+        .dedup_with_count()
+        .map(|(count, term)| TermReport::new(term, count, notation, zeeman))
+        .collect_vec();
+    if args.hund_order && open.has_inverted_multiplets() {
+        terms.iter_mut().for_each(|term| term.levels.reverse());
+    }
+    if args.ground {
+        let ground = open.ground_term();
+        let mut term = TermReport::new(ground.term(), 1, notation, zeeman);
+        term.levels.retain(|level| level.j == ground.j());
+        terms.push(term);
+    }
+    // every open subshell is replaced by its holes
+    let hole_equivalent = (args.show_holes || open.has_inverted_multiplets()).then(|| {
+        let holes = open.sublevels().iter().map(|sublevel| {
+            let level_type = sublevel.level_type().clone();
+            match sublevel.n() {
+                Some(n) => SubLevel::with_principal(n, level_type, sublevel.holes()),
+                None => SubLevel::new(level_type, sublevel.holes()),
+            }
+            .expect("Number of holes is valid")
+        });
+        Configuration::new(holes).notated(notation).to_string()
+    });
+    Ok(Report {
+        species,
+        configuration: configuration.notated(notation).to_string(),
+        hole_equivalent,
+        jj_levels: (args.coupling() == Coupling::Jj).then(|| {
+            open.relativistic_split()
+                .iter()
+                .map(|configuration| JjLevels {
+                    configuration: configuration.notated(notation).to_string(),
+                    levels: configuration.total_js(),
+                })
+                .collect()
+        }),
+        microstates: configuration.microstate_count(),
+        terms,
+        counts,
+        microstate_assignments,
+        grids,
+        solution: None,
+        term_energies: args
+            .slater
+            .then(|| configuration.slater_energies())
+            .transpose()
+            .unwrap_or_else(|error| {
+                eprintln!("{}", output.stderr_style().warning(error));
+                None
+            }),
+        intermediate_levels: args
+            .zeta
+            .map(|zeta| configuration.intermediate_coupling(&args.slater_integrals, zeta))
+            .transpose()
+            .unwrap_or_else(|error| {
+                eprintln!("{}", output.stderr_style().warning(error));
+                None
+            }),
+    })
+}
+
+/// Same as [`report`], along with the derivation the page is made of with `--format html`; the reports given to [`write`] are made by it
+pub fn full_report(
+    configuration: &Configuration,
+    species: Option<String>,
+    args: &Options,
+    output: &Output,
+) -> Result<Report, DerivationError> {
+    let mut report = report(configuration, species, args, output)?;
+    if output.format() == Format::Html && !args.count_only {
+        // the page shows every step of the derivation, and not only the terms
+        let limited = configuration.clone().with_limit(args.limit());
+        report.solution = Some(WorkedSolution::new(&limited)?);
+    }
+    Ok(report)
+}
+
+/// Writes the report in the requested format
+pub fn write(
+    report: &Report,
+    configuration: &Configuration,
+    args: &Options,
+    output: &Output,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    if args.count_only {
+        return write_counts(report.counts(), output, out);
+    }
+    match output.format() {
+        Format::Text => print_text(report, args, &configuration.open_subshells(), output, out),
+        Format::Csv => report.write_csv(args.levels(), out),
+        Format::Latex => {
+            report.write_latex(args.levels(), out)?;
+            for grid in report.grids.iter().flatten() {
+                writeln!(out, "\n% ML-MS table of {}", grid.sublevel())?;
+                write!(out, "{}", grid.to_latex(args.microstates))?;
+            }
+            Ok(())
+        }
+        Format::Typst => report.write_typst(args.levels(), out),
+        Format::Html => match &report.solution {
+            Some(solution) => write!(out, "{}", solution.to_html()),
+            None => unreachable!("Derived by full_report for the HTML output"),
+        },
+        Format::Markdown => report.write_markdown(args.levels(), out),
+        Format::Nist => report.write_nist(configuration, args.energy_unit, out),
+        Format::Records => report
+            .records()
+            .iter()
+            .try_for_each(|record| report::record(out, record)),
+        #[cfg(feature = "json")]
+        Format::Json => writeln!(
+            out,
+            "{}",
+            serde_json::to_string_pretty(report).expect("Report is serializable")
+        ),
+    }
+}
+
+fn write_counts(counts: &Counts, output: &Output, out: &mut impl Write) -> std::io::Result<()> {
+    match output.format() {
+        Format::Text => counts.write_text(out),
+        Format::Csv => {
+            report::csv_row(out, &Counts::csv_header())?;
+            report::csv_row(out, &counts.csv_row())
+        }
+        Format::Latex => counts.write_latex(out),
+        Format::Typst => counts.write_typst(out),
+        Format::Html => counts.write_html(out),
+        Format::Markdown => counts.write_markdown(out),
+        Format::Records => report::record(out, &counts.record()),
+        Format::Nist => unreachable!("Rejected by Output::reject_nist"),
+        #[cfg(feature = "json")]
+        Format::Json => writeln!(
+            out,
+            "{}",
+            serde_json::to_string_pretty(counts).expect("Counts are serializable")
+        ),
+    }
+}
+
+/// Landé g-factor as a fraction, along with its decimal value
+fn g_text(level: &LevelReport) -> String {
+    match (&level.g_exact, level.g) {
+        (Some(exact), Some(g)) if exact.contains('/') => format!("g = {exact} ({g:.4})"),
+        (Some(exact), _) => format!("g = {exact}"),
+        _ => "g is undefined".to_string(),
+    }
+}
+
+fn print_text(
+    report: &Report,
+    args: &Options,
+    open: &Configuration,
+    output: &Output,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let notation = output.notation();
+    let style = output.out_style();
+    if let Some(species) = &report.species {
+        let open = if open.sublevels().is_empty() {
+            "none".to_string()
+        } else {
+            open.notated(notation).to_string()
+        };
+        writeln!(
+            out,
+            "{species}: {}, open subshells: {open}",
+            report.configuration
+        )?;
+    }
+    if let Some(holes) = &report.hole_equivalent {
+        if args.show_holes || args.verbose >= 1 {
+            let electrons = open.notated(notation);
+            writeln!(out, "Electron picture: {electrons}")?;
+            writeln!(out, "Hole picture: {holes} (holes)")?;
+        }
+    }
+    if args.ground {
+        let level = &report.terms[0].levels[0];
+        if args.lande {
+            let g = g_text(level);
+            writeln!(out, "Ground level: {}, {g}", style.term(&level.level))?;
+        } else {
+            writeln!(out, "Ground level: {}", style.term(&level.level))?;
+        }
+        return Ok(());
+    }
+    if let Some(jj_levels) = &report.jj_levels {
+        writeln!(out, "\nFound jj levels:")?;
+        let mut table = Table::new(&["Configuration", "J"]);
+        for configuration in jj_levels {
+            let js = configuration
+                .levels
+                .iter()
+                .map(|(j, count)| match count {
+                    1 => j.to_string(),
+                    count => format!("{j} (x{count})"),
+                })
+                .join(", ");
+            table.push(vec![configuration.configuration.clone(), js]);
+        }
+        table.write(style, out)?;
+    } else if args.levels {
+        writeln!(out, "\nFound levels:")?;
+        let mut header = vec!["Level", "J", "Degeneracy", "Count"];
+        if args.lande {
+            header.push("g");
+        }
+        let mut table = Table::new(&header);
+        for term in &report.terms {
+            for level in &term.levels {
+                let mut row = vec![
+                    level.level.clone(),
+                    level.j.to_string(),
+                    level.degeneracy.to_string(),
+                    term.count.to_string(),
+                ];
+                if args.lande {
+                    row.push(g_text(level));
+                }
+                table.push(row);
+            }
+        }
+        table.write(style, out)?;
+    } else {
+        writeln!(out, "\nFound terms:")?;
+        let mut table = Table::new(&["Term", "S", "L", "Parity", "Count", "Degeneracy", "J"]);
+        for term in &report.terms {
+            table.push(vec![
+                term.term.clone(),
+                term.s.to_string(),
+                term.l.to_string(),
+                term.parity.to_string(),
+                term.count.to_string(),
+                term.degeneracy.to_string(),
+                term.levels.iter().map(|level| level.j).join(", "),
+            ]);
+        }
+        table.write(style, out)?;
+        if args.lande {
+            writeln!(out, "\nLandé g-factors:")?;
+            let mut table = Table::new(&["Level", "g"]);
+            for level in report.terms.iter().flat_map(|term| &term.levels) {
+                table.push(vec![level.level.clone(), g_text(level)]);
+            }
+            table.write(style, out)?;
+        }
+    }
+    if args.field.is_some() {
+        writeln!(out, "\nZeeman sublevels:")?;
+        let unit = format!("Energy, {}", args.energy_unit);
+        let mut table = Table::new(&["Level", "M_J", &unit]);
+        for level in report.terms.iter().flat_map(|term| &term.levels) {
+            for sublevel in level.zeeman.iter().flatten() {
+                table.push(vec![
+                    level.level.clone(),
+                    sublevel.mj.to_string(),
+                    format!("{:+.6e}", sublevel.energy),
+                ]);
+            }
+        }
+        table.write(style, out)?;
+    }
+    if let Some(assignments) = &report.microstate_assignments {
+        writeln!(out, "\nMicrostates:")?;
+        let mut table = Table::new(&["Term", "Microstates"]);
+        for assignment in assignments {
+            table.push(vec![
+                assignment.term.clone(),
+                assignment.microstates.join(", "),
+            ]);
+        }
+        table.write(style, out)?;
+    }
+    for grid in report.grids.iter().flatten() {
+        writeln!(
+            out,
+            "\nM_L–M_S table of {}:",
+            grid.sublevel().notated(notation)
+        )?;
+        let mut header = vec!["M_L \\ M_S".to_string()];
+        header.extend(grid.mss().iter().map(ToString::to_string));
+        let mut table = Table::new(&header.iter().map(String::as_str).collect_vec());
+        for (ml, cells) in grid.rows() {
+            let mut row = vec![ml.to_string()];
+            row.extend(cells.iter().map(|cell| match cell.len() {
+                0 => String::new(),
+                _ if args.microstates => cell.iter().join(", "),
+                count => count.to_string(),
+            }));
+            table.push(row);
+        }
+        table.write(style, out)?;
+    }
+    if let Some(energies) = &report.term_energies {
+        writeln!(out, "\nTerm energies:")?;
+        for energy in energies {
+            writeln!(out, "{}", energy.notated(notation))?;
+        }
+    }
+    if let Some(levels) = &report.intermediate_levels {
+        writeln!(out, "\nIntermediate coupling levels:")?;
+        for level in levels {
+            writeln!(out, "{}", level.notated(notation))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Derivation written as JSON lines, one [`DerivationEvent`] per line, for the tools replaying or grading it
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use chitose::{Configuration, DerivationError, DerivationEvent, TermType};
+
+/// Derives the terms of the configuration, writing every event to the file (or to stdout for `-`)
+pub fn derive(
+    configuration: &Configuration,
+    path: &Path,
+) -> Result<Vec<TermType>, DerivationError> {
+    let context =
+        |error: io::Error| io::Error::new(error.kind(), format!("{}: {error}", path.display()));
+    let mut out: Box<dyn Write> = if path == Path::new("-") {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(BufWriter::new(File::create(path).map_err(context)?))
+    };
+    let mut error = None;
+    let terms = configuration.ls_terms_observed(&mut |event: &DerivationEvent| {
+        if error.is_none() {
+            let line = serde_json::to_string(event).expect("Events are serializable");
+            error = writeln!(out, "{line}").err();
+        }
+    })?;
+    match error {
+        Some(error) => Err(context(error).into()),
+        None => {
+            out.flush().map_err(context)?;
+            Ok(terms)
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! Electric-dipole lines between the levels of two configurations, terms or levels
+
+use std::{io::Write, str::FromStr};
+
+use chitose::{
+    multiplet_strengths, violations, Configuration, ConfigurationParseError, HalfInt, Level,
+    Multipole, Notated, RuleViolation, TermParseError, TermType,
+};
+use itertools::Itertools;
+
+use crate::{error::CliError, report, table::Table, Format, Output};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Upper configuration ("2p 3s"), term ("3P*") or level ("3P*_1")
+    upper: Side,
+    /// Lower configuration, term or level
+    lower: Side,
+    /// If set, prints relative strengths of the lines within their LS multiplets
+    #[arg(short, long, default_value_t = false)]
+    intensities: bool,
+    /// If set, also lists the lines only forbidden in pure LS coupling, like the intercombination ones
+    #[arg(long, default_value_t = false)]
+    intercombination: bool,
+}
+
+/// Levels on one side of the transitions
+#[derive(Debug, Clone)]
+enum Side {
+    Configuration(Configuration),
+    Term(TermType),
+    Level(Level),
+}
+
+/// Term and level symbols have uppercase letters, unlike the configurations
+impl FromStr for Side {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.chars().any(|c| c.is_ascii_uppercase()) {
+            return s
+                .parse()
+                .map(Self::Configuration)
+                .map_err(|error: ConfigurationParseError| error.to_string());
+        }
+        match s.parse() {
+            Ok(level) => Ok(Self::Level(level)),
+            Err(_) => s
+                .parse()
+                .map(Self::Term)
+                .map_err(|error: TermParseError| error.to_string()),
+        }
+    }
+}
+
+impl Side {
+    fn levels(&self) -> Result<Vec<Level>, CliError> {
+        Ok(match self {
+            Self::Configuration(configuration) => configuration
+                .ls_terms()?
+                .into_iter()
+                .dedup()
+                .flat_map(|term| term.levels())
+                .collect(),
+            Self::Term(term) => term.levels(),
+            Self::Level(level) => vec![level.clone()],
+        })
+    }
+}
+
+/// Line between two levels
+#[derive(Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+struct Line {
+    upper: String,
+    lower: String,
+    #[cfg_attr(feature = "json", serde(rename = "dJ"))]
+    delta_j: HalfInt,
+    #[cfg_attr(feature = "json", serde(rename = "dL"))]
+    delta_l: i32,
+    #[cfg_attr(feature = "json", serde(rename = "dS"))]
+    delta_s: HalfInt,
+    /// LS-coupling rules broken by the line, empty for the allowed ones
+    broken: Vec<String>,
+    /// Relative strength of the line in its multiplet
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    strength: Option<f64>,
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    output.reject_nist("Transitions")?;
+    let notation = output.notation();
+    let uppers = args.upper.levels()?;
+    let lowers = args.lower.levels()?;
+    let momentum = |level: &Level| level.term().l() as i32;
+    let lines = uppers
+        .iter()
+        .cartesian_product(&lowers)
+        .filter_map(|(upper, lower)| {
+            let broken = violations(Multipole::E1, upper, lower);
+            // parity and J rules hold in any coupling
+            if broken.iter().any(RuleViolation::is_strict)
+                || !(broken.is_empty() || args.intercombination)
+            {
+                return None;
+            }
+            let strength = args.intensities.then(|| {
+                multiplet_strengths(upper.term(), lower.term())
+                    .into_iter()
+                    .find(|line| line.upper.j() == upper.j() && line.lower.j() == lower.j())
+                    .map_or(0.0, |line| line.strength)
+            });
+            Some(Line {
+                upper: upper.notated(notation).to_string(),
+                lower: lower.notated(notation).to_string(),
+                delta_j: upper.j() - lower.j(),
+                delta_l: momentum(upper) - momentum(lower),
+                delta_s: upper.term().s() - lower.term().s(),
+                broken: broken.iter().map(ToString::to_string).collect(),
+                strength,
+            })
+        })
+        .collect_vec();
+
+    let mut out = output.writer()?;
+    let mut header = vec!["upper", "lower", "dJ", "dL", "dS", "broken"];
+    if args.intensities {
+        header.push("strength");
+    }
+    let cells = |line: &Line| {
+        let mut cells = vec![
+            line.upper.clone(),
+            line.lower.clone(),
+            line.delta_j.to_string(),
+            line.delta_l.to_string(),
+            line.delta_s.to_string(),
+            line.broken.join("; "),
+        ];
+        cells.extend(line.strength.map(|strength| format!("{strength:.4}")));
+        cells
+    };
+    match output.format() {
+        Format::Text => {
+            if lines.is_empty() {
+                writeln!(out, "No E1 lines")?;
+                return Ok(out.flush()?);
+            }
+            writeln!(out, "E1 lines (parity changes, ΔJ = 0, ±1, not 0 → 0):")?;
+            let mut text_header = vec!["Upper", "Lower", "ΔJ", "ΔL", "ΔS"];
+            if args.intensities {
+                text_header.push("Strength");
+            }
+            text_header.push("LS coupling");
+            let mut table = Table::new(&text_header);
+            for line in &lines {
+                let mut row = cells(line);
+                let broken = row.remove(5);
+                row.push(if broken.is_empty() {
+                    "allowed".to_string()
+                } else {
+                    broken
+                });
+                table.push(row);
+            }
+            table.write(output.out_style(), &mut out)?;
+        }
+        Format::Csv => {
+            report::csv_row(
+                &mut out,
+                &header.iter().map(ToString::to_string).collect_vec(),
+            )?;
+            for line in &lines {
+                report::csv_row(&mut out, &cells(line))?;
+            }
+        }
+        Format::Records => {
+            for line in &lines {
+                let mut record = cells(line);
+                record.insert(0, "line".to_string());
+                report::record(&mut out, &record)?;
+            }
+        }
+        Format::Latex => {
+            let columns = if args.intensities { "llcccc" } else { "llccc" };
+            writeln!(out, "\\begin{{tabular}}{{{columns}}}")?;
+            writeln!(out, "\\hline")?;
+            write!(
+                out,
+                "Upper & Lower & $\\Delta J$ & $\\Delta L$ & $\\Delta S$"
+            )?;
+            if args.intensities {
+                write!(out, " & Strength")?;
+            }
+            writeln!(out, " \\\\")?;
+            writeln!(out, "\\hline")?;
+            for line in &lines {
+                write!(
+                    out,
+                    "${}$ & ${}$ & ${}$ & ${}$ & ${}$",
+                    line.upper, line.lower, line.delta_j, line.delta_l, line.delta_s
+                )?;
+                if let Some(strength) = line.strength {
+                    write!(out, " & {strength:.4}")?;
+                }
+                writeln!(out, " \\\\")?;
+            }
+            writeln!(out, "\\hline")?;
+            writeln!(out, "\\end{{tabular}}")?;
+        }
+        Format::Typst => {
+            let mut typst_header = vec![
+                report::typst_text("Upper"),
+                report::typst_text("Lower"),
+                report::typst_math("Delta J"),
+                report::typst_math("Delta L"),
+                report::typst_math("Delta S"),
+            ];
+            if args.intensities {
+                typst_header.push(report::typst_text("Strength"));
+            }
+            let rows = lines
+                .iter()
+                .map(|line| {
+                    let mut row = vec![
+                        report::typst_math(&line.upper),
+                        report::typst_math(&line.lower),
+                        report::typst_math(line.delta_j),
+                        report::typst_math(line.delta_l),
+                        report::typst_math(line.delta_s),
+                    ];
+                    row.extend(
+                        line.strength
+                            .map(|strength| report::typst_text(&format!("{strength:.4}"))),
+                    );
+                    row
+                })
+                .collect_vec();
+            report::typst_table(&mut out, &typst_header, &rows)?;
+        }
+        Format::Html => {
+            let mut html_header = vec!["Upper", "Lower", "ΔJ", "ΔL", "ΔS", "Broken LS rules"];
+            if args.intensities {
+                html_header.push("Strength");
+            }
+            let rows = lines
+                .iter()
+                .map(|line| {
+                    let mut row = cells(line);
+                    row[5] = report::html_escape(&row[5]);
+                    row
+                })
+                .collect_vec();
+            report::html_table(
+                &mut out,
+                &html_header.iter().map(ToString::to_string).collect_vec(),
+                &rows,
+            )?;
+        }
+        Format::Markdown => {
+            let mut markdown_header = vec!["Upper", "Lower", "ΔJ", "ΔL", "ΔS", "Broken LS rules"];
+            if args.intensities {
+                markdown_header.push("Strength");
+            }
+            report::markdown_row(
+                &mut out,
+                &markdown_header
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect_vec(),
+            )?;
+            writeln!(out, "|{}", "---|".repeat(markdown_header.len()))?;
+            for line in &lines {
+                report::markdown_row(&mut out, &cells(line))?;
+            }
+        }
+        Format::Nist => unreachable!("Rejected by Output::reject_nist"),
+        #[cfg(feature = "json")]
+        Format::Json => writeln!(
+            out,
+            "{}",
+            serde_json::to_string_pretty(&lines).expect("Lines are serializable")
+        )?,
+    }
+    Ok(out.flush()?)
+}
@@ -0,0 +1,37 @@
+//! Coupling genealogy of the terms of a configuration, as a Graphviz DOT graph
+
+use std::io::Write;
+
+use chitose::Notation;
+use clap::ValueEnum;
+
+use crate::{error::CliError, terms::Input, Format, Output};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Configuration with several open subshells, like "3d2 4p" or "4f7 5d1 6s2", or an element or ion
+    input: Input,
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    let (configuration, _) = args.input.resolve(None, output.notation())?;
+    let tree = configuration.coupling_tree()?;
+    let text = match output.format() {
+        // ASCII labels are hard to read in a picture, so Unicode is the default one
+        Format::Text => tree.to_dot(output.notation.unwrap_or(Notation::Unicode)),
+        #[cfg(feature = "json")]
+        Format::Json => serde_json::to_string_pretty(&tree).expect("Tree is serializable") + "\n",
+        format => {
+            return Err(CliError::Usage(format!(
+                "Coupling tree can't be written as {}",
+                format
+                    .to_possible_value()
+                    .expect("Formats are not skipped")
+                    .get_name()
+            )))
+        }
+    };
+    let mut out = output.writer()?;
+    write!(out, "{text}")?;
+    Ok(out.flush()?)
+}
@@ -0,0 +1,253 @@
+//! Step-by-step view of the term extraction from the ML–MS table of microstates, drawn with ratatui on the alternate screen
+
+use std::{
+    collections::BTreeMap,
+    io::{self, IsTerminal},
+};
+
+use chitose::{ee_terms_detailed_limited, HalfInt, Microstate, Notated, Notation, TermType};
+use itertools::Itertools;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Cell, List, ListState, Paragraph, Row, Table, Wrap},
+    DefaultTerminal, Frame,
+};
+
+use crate::{error::CliError, terms::Input, Output};
+
+/// Width of the columns of the ML–MS table
+const CELL: u16 = 6;
+const KEYS: &str = "→/n/Enter next  ←/p previous  Home/End first/last  q/Esc quit";
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Configuration with a single open subshell, like "3d3", or an element or ion
+    input: Input,
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    if !(io::stdin().is_terminal() && io::stdout().is_terminal()) {
+        return Err(CliError::Usage(
+            "The step-by-step view needs a terminal, `chitose terms -vvv` prints the extraction as text"
+                .to_string(),
+        ));
+    }
+    let notation = output.notation();
+    let (configuration, _) = args.input.resolve(None, notation)?;
+    let open = configuration.open_subshells();
+    let [sublevel] = open.sublevels() else {
+        return Err(CliError::Usage(format!(
+            "Exactly one open subshell is expected, found {}",
+            open.sublevels().len()
+        )));
+    };
+    let mut steps = ee_terms_detailed_limited(sublevel.clone(), configuration.limit())?;
+    // terms are extracted starting with the greatest L, and then the greatest S
+    steps.sort_by_key(|(term, _)| std::cmp::Reverse((term.l(), term.s())));
+
+    let title = if configuration.sublevels().len() == 1 {
+        sublevel.notated(notation).to_string()
+    } else {
+        format!(
+            "{} ({})",
+            sublevel.notated(notation),
+            configuration.notated(notation)
+        )
+    };
+    let mut browser = Browser::new(title, steps, notation);
+    Ok(ratatui::run(|terminal| browser.browse(terminal))?)
+}
+
+/// Terms in the order of the extraction with their microstates, and the step shown
+struct Browser {
+    title: String,
+    steps: Vec<(TermType, Vec<Microstate>)>,
+    notation: Notation,
+    /// Rows and columns of the table, from the greatest ML and MS
+    mls: Vec<i32>,
+    mss: Vec<HalfInt>,
+    /// Index of the term extracted next, or the number of terms after the last one
+    step: usize,
+}
+
+impl Browser {
+    fn new(title: String, steps: Vec<(TermType, Vec<Microstate>)>, notation: Notation) -> Self {
+        let states = steps.iter().flat_map(|(_, states)| states);
+        let mls = states
+            .clone()
+            .map(Microstate::ml)
+            .unique()
+            .sorted()
+            .rev()
+            .collect();
+        let mss = states.map(Microstate::ms).unique().sorted().rev().collect();
+        Self {
+            title,
+            steps,
+            notation,
+            mls,
+            mss,
+            step: 0,
+        }
+    }
+
+    fn browse(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Moves between the steps, returning `false` to quit
+    fn key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Right | KeyCode::Char('n') | KeyCode::Enter | KeyCode::Char(' ') => {
+                self.step = (self.step + 1).min(self.steps.len())
+            }
+            KeyCode::Left | KeyCode::Char('p') | KeyCode::Backspace => {
+                self.step = self.step.saturating_sub(1)
+            }
+            KeyCode::Home => self.step = 0,
+            KeyCode::End => self.step = self.steps.len(),
+            _ => {}
+        }
+        true
+    }
+
+    /// Microstates not extracted before the step at each ML and MS, with the ones of the current term
+    fn remaining(&self) -> BTreeMap<(i32, HalfInt), (usize, usize)> {
+        let mut table: BTreeMap<_, (usize, usize)> = BTreeMap::new();
+        for (index, (_, states)) in self.steps.iter().enumerate().skip(self.step) {
+            for state in states {
+                let (remaining, current) = table.entry((state.ml(), state.ms())).or_default();
+                *remaining += 1;
+                if index == self.step {
+                    *current += 1;
+                }
+            }
+        }
+        table
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, details, keys] = Layout::vertical([
+            Constraint::Min(self.mls.len() as u16 + 3),
+            Constraint::Length(6),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [grid, terms] =
+            Layout::horizontal([Constraint::Fill(2), Constraint::Fill(1)]).areas(main);
+
+        frame.render_widget(self.table(), grid);
+        let mut state = ListState::default().with_selected(Some(self.step));
+        frame.render_stateful_widget(self.terms(), terms, &mut state);
+        frame.render_widget(self.details(), details);
+        frame.render_widget(Line::from(KEYS).style(Modifier::DIM), keys);
+    }
+
+    /// ML–MS table with the terms extracted before the step removed, and the microstates of the current term highlighted
+    fn table(&self) -> Table<'_> {
+        let remaining = self.remaining();
+        let header = std::iter::once("ML\\MS".to_string())
+            .chain(self.mss.iter().map(ToString::to_string))
+            .map(|text| Cell::from(Line::from(text).right_aligned()));
+        let rows = self.mls.iter().map(|ml| {
+            let cells = self.mss.iter().map(|ms| match remaining.get(&(*ml, *ms)) {
+                Some((remaining, 0)) => {
+                    Cell::from(Line::from(remaining.to_string()).right_aligned())
+                }
+                Some((remaining, _)) => {
+                    Cell::from(Line::from(remaining.to_string()).right_aligned())
+                        .style(Modifier::REVERSED | Modifier::BOLD)
+                }
+                None => Cell::from(Line::from(".").right_aligned()).style(Modifier::DIM),
+            });
+            Row::new(
+                std::iter::once(Cell::from(Line::from(ml.to_string()).right_aligned()))
+                    .chain(cells),
+            )
+        });
+        Table::new(rows, vec![Constraint::Length(CELL); self.mss.len() + 1])
+            .header(Row::new(header).style(Modifier::BOLD))
+            .block(Block::bordered().title(format!(" ML–MS table of {} ", self.title)))
+    }
+
+    /// Terms extracted before the step dimmed, the current one highlighted, and the rest plain
+    fn terms(&self) -> List<'_> {
+        let items = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(index, (term, states))| {
+                let line = Line::from(format!(
+                    "{}: {} microstates",
+                    term.notated(self.notation),
+                    states.len()
+                ));
+                if index < self.step {
+                    line.style(Modifier::DIM)
+                } else {
+                    line
+                }
+            });
+        List::new(items)
+            .block(Block::bordered().title(format!(" Terms {}/{} ", self.step, self.steps.len())))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED | Modifier::BOLD))
+    }
+
+    fn details(&self) -> Paragraph<'_> {
+        let text = match self.steps.get(self.step) {
+            Some((term, states)) => Line::from(vec![
+                Span::styled(format!("{}: ", term.notated(self.notation)), Modifier::BOLD),
+                Span::raw(states.iter().map(ToString::to_string).join(", ")),
+            ]),
+            None => Line::from(format!(
+                "All of the {} terms are extracted",
+                self.steps.len()
+            )),
+        };
+        Paragraph::new(text)
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered().title(" Microstates of the term "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chitose::{ee_terms_detailed, HalfInt, Notation};
+    use ratatui::crossterm::event::KeyCode;
+
+    use crate::tui::Browser;
+
+    #[test]
+    fn steps() {
+        let mut steps = ee_terms_detailed("2p2".parse().unwrap()).expect("Should be ok");
+        steps.sort_by_key(|(term, _)| std::cmp::Reverse((term.l(), term.s())));
+        let mut browser = Browser::new("2p2".to_string(), steps, Notation::Ascii);
+        assert_eq!(browser.mls, [2, 1, 0, -1, -2]);
+        assert_eq!(browser.mss.len(), 3);
+        // ^1D takes one of the three microstates with ML = MS = 0
+        let zero = (0, HalfInt::ZERO);
+        assert_eq!(browser.remaining()[&zero], (3, 1));
+        assert!(browser.key(KeyCode::Right));
+        assert_eq!(browser.remaining()[&zero], (2, 1));
+        assert!(!browser.remaining().contains_key(&(2, HalfInt::ZERO)));
+        browser.key(KeyCode::End);
+        browser.key(KeyCode::Char('n'));
+        assert_eq!(browser.step, 3);
+        assert!(browser.remaining().is_empty());
+        browser.key(KeyCode::Home);
+        browser.key(KeyCode::Left);
+        assert_eq!(browser.step, 0);
+        assert!(!browser.key(KeyCode::Char('q')));
+    }
+}
@@ -0,0 +1,118 @@
+//! Energies of the magnetic sublevels of a term or a level versus the field, from the anomalous Zeeman effect to the Paschen–Back one
+
+use std::{io::Write, str::FromStr};
+
+use chitose::{EnergyUnit, Level, MagneticField, Notated, TermParseError, TermType};
+use clap::ValueEnum;
+
+use crate::{error::CliError, report, table::Table, Format, Output};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Term ("2P") or level ("2P_3/2"); a level gets only the sublevels starting from it
+    symbol: Symbol,
+    /// Spin-orbit constant A of the term, in the energy unit; negative for the inverted multiplets
+    #[arg(short = 'A', long, allow_negative_numbers = true)]
+    spin_orbit: f64,
+    /// Largest field of the chart, like "2T" or "5000G"
+    #[arg(short = 'B', long)]
+    max_field: MagneticField,
+    /// Number of field steps from zero to the largest field
+    #[arg(long, default_value_t = 100)]
+    steps: usize,
+    /// Unit of the spin-orbit constant and of the energies: J, eV, cm-1 or MHz
+    #[arg(long, default_value = "cm-1")]
+    energy_unit: EnergyUnit,
+}
+
+#[derive(Debug, Clone)]
+enum Symbol {
+    Term(TermType),
+    Level(Level),
+}
+
+impl FromStr for Symbol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(level) => Ok(Self::Level(level)),
+            Err(_) => s
+                .parse()
+                .map(Self::Term)
+                .map_err(|error: TermParseError| error.to_string()),
+        }
+    }
+}
+
+pub fn run(args: Args, output: &Output) -> Result<(), CliError> {
+    let fan = match &args.symbol {
+        Symbol::Term(term) => term.zeeman_fan(
+            args.spin_orbit,
+            args.max_field,
+            args.steps,
+            args.energy_unit,
+        ),
+        Symbol::Level(level) => level.zeeman_fan(
+            args.spin_orbit,
+            args.max_field,
+            args.steps,
+            args.energy_unit,
+        ),
+    };
+    let mut out = output.writer()?;
+    match output.format() {
+        // energies without the field and in the largest one; the whole chart is in the other formats
+        Format::Text => {
+            let unit = fan.unit;
+            let zero = format!("E(0), {unit}");
+            let max = format!("E({}), {unit}", args.max_field);
+            let mut table = Table::new(&["Level", "M", &zero, &max]);
+            for curve in &fan.curves {
+                let level = Level::new(fan.term.clone(), curve.j).expect("Curves start at levels");
+                table.push(vec![
+                    level.notated(output.notation()).to_string(),
+                    curve.m.to_string(),
+                    format!("{:.4}", curve.energies.first().copied().unwrap_or_default()),
+                    format!("{:.4}", curve.energies.last().copied().unwrap_or_default()),
+                ]);
+            }
+            table.write(output.out_style(), &mut out)?;
+        }
+        // a row per field, a column per sublevel
+        Format::Csv => {
+            let mut header = vec!["B, T".to_string()];
+            header.extend(fan.curves.iter().map(|curve| {
+                let level = Level::new(fan.term.clone(), curve.j).expect("Curves start at levels");
+                format!("{} M={}", level.notated(output.notation()), curve.m)
+            }));
+            report::csv_row(&mut out, &header)?;
+            for (index, field) in fan.fields.iter().enumerate() {
+                let mut row = vec![field.as_tesla().to_string()];
+                row.extend(
+                    fan.curves
+                        .iter()
+                        .map(|curve| curve.energies[index].to_string()),
+                );
+                report::csv_row(&mut out, &row)?;
+            }
+        }
+        Format::Html => write!(out, "{}", fan.to_html())?,
+        #[cfg(feature = "json")]
+        Format::Json => writeln!(
+            out,
+            "{}",
+            serde_json::to_string_pretty(&fan).expect("Chart is serializable")
+        )?,
+        format => {
+            return Err(CliError::Usage(format!(
+                "Zeeman chart can't be written as {}",
+                format
+                    .to_possible_value()
+                    .expect("Formats are not skipped")
+                    .get_name()
+            )))
+        }
+    }
+    Ok(out.flush()?)
+}
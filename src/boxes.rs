@@ -0,0 +1,227 @@
+//! Orbital box diagrams: a box for every orbital of a subshell, holding an ↑ or ↓ arrow for each of its electrons
+
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::{
+    svg::write_svg, Configuration, DerivationError, HalfInt, Microstate, Notated, Notation,
+    SpinOrbital, SubLevel,
+};
+
+/// Side of an orbital box
+const CELL: usize = 36;
+/// Space between the subshells
+const GAP: usize = 24;
+/// Room for the ml and the subshell labels
+const BOTTOM: usize = 52;
+const PADDING: usize = 16;
+
+const STYLE: &str = "text { font-family: sans-serif; font-size: 15px; text-anchor: middle; dominant-baseline: central; } \
+.arrow { font-size: 22px; } \
+.label { fill: #555; font-size: 12px; } \
+rect { fill: #fff; stroke: #333; }";
+
+/// Orbital boxes of one or several subshells, each in a given microstate; orbitals go from the greatest ml to the lowest one
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OrbitalBoxes {
+    subshells: Vec<(SubLevel, Microstate)>,
+}
+
+impl OrbitalBoxes {
+    /// Boxes of a single subshell in the given microstate
+    ///
+    /// Returns `None` if the microstate belongs to a different sublevel, or has a different number of electrons
+    pub fn new(sublevel: SubLevel, microstate: Microstate) -> Option<Self> {
+        (microstate.l() == sublevel.level_type().l()
+            && microstate.electrons() == u32::from(sublevel.electrons()))
+        .then(|| Self {
+            subshells: vec![(sublevel, microstate)],
+        })
+    }
+
+    pub fn subshells(&self) -> &[(SubLevel, Microstate)] {
+        &self.subshells
+    }
+
+    /// Terminal art with box-drawing characters, subshells side by side, with the ml of every box and the subshell under them
+    pub fn to_unicode(&self) -> String {
+        let blocks = self
+            .subshells
+            .iter()
+            .map(|(sublevel, microstate)| unicode_block(sublevel, microstate))
+            .collect_vec();
+        let mut out = String::new();
+        for line in 0..5 {
+            let line = blocks.iter().map(|block| &block[line]).join("   ");
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// SVG picture of the boxes, subshells side by side
+    pub fn to_svg(&self) -> String {
+        let widths = self
+            .subshells
+            .iter()
+            .map(|(sublevel, _)| (2 * usize::from(sublevel.level_type().l()) + 1) * CELL)
+            .collect_vec();
+        let width = 2 * PADDING + widths.iter().sum::<usize>() + GAP * (widths.len().max(1) - 1);
+        let height = 2 * PADDING + CELL + BOTTOM;
+        let mut out = String::new();
+        write_svg(width, height, STYLE, &mut out, |out| {
+            let mut x = PADDING;
+            for ((sublevel, microstate), width) in self.subshells.iter().zip(&widths) {
+                write_svg_subshell(sublevel, microstate, x, out)?;
+                x += width + GAP;
+            }
+            Ok(())
+        })
+        .expect("Writing to a string can't fail");
+        out
+    }
+}
+
+impl Configuration {
+    /// Orbital boxes of every subshell of the configuration in the state given by Hund's rules, see [`SubLevel::hund_microstate`]
+    pub fn hund_boxes(&self) -> Result<OrbitalBoxes, DerivationError> {
+        let subshells = self
+            .sublevels()
+            .iter()
+            .map(|sublevel| Ok((sublevel.clone(), sublevel.hund_microstate()?)))
+            .collect::<Result<_, DerivationError>>()?;
+        Ok(OrbitalBoxes { subshells })
+    }
+}
+
+/// Orbitals of the sublevel from the greatest ml down, with the arrows of their electrons
+fn orbitals(microstate: &Microstate) -> impl Iterator<Item = (i8, &'static str)> + '_ {
+    let l = microstate.l() as i8;
+    (-l..=l).rev().map(|ml| {
+        let occupied = |ms| microstate.is_occupied(SpinOrbital { ml, ms });
+        let arrows = match (occupied(HalfInt::HALF), occupied(-HalfInt::HALF)) {
+            (true, true) => "↑↓",
+            (true, false) => "↑",
+            (false, true) => "↓",
+            (false, false) => "",
+        };
+        (ml, arrows)
+    })
+}
+
+fn ml_label(ml: i8) -> String {
+    match ml {
+        0 => "0".to_string(),
+        ml => format!("{ml:+}"),
+    }
+}
+
+/// Lines of the boxes of a single subshell, all of the same width
+fn unicode_block(sublevel: &SubLevel, microstate: &Microstate) -> [String; 5] {
+    let orbitals = orbitals(microstate).collect_vec();
+    let cell = orbitals
+        .iter()
+        .map(|(ml, _)| ml_label(*ml).len())
+        .max()
+        .unwrap_or_default()
+        .max(2);
+    let border = |left, middle, right| {
+        format!(
+            "{left}{}{right}",
+            orbitals.iter().map(|_| "─".repeat(cell)).join(middle)
+        )
+    };
+    let arrows = orbitals
+        .iter()
+        .map(|(_, arrows)| format!("{arrows:^cell$}"))
+        .join("│");
+    let mls = orbitals
+        .iter()
+        .map(|(ml, _)| format!("{:>cell$}", ml_label(*ml)))
+        .join(" ");
+    let width = orbitals.len() * (cell + 1) + 1;
+    let name = sublevel.notated(Notation::Unicode).to_string();
+    [
+        border("┌", "┬", "┐"),
+        format!("│{arrows}│"),
+        border("└", "┴", "┘"),
+        format!(" {mls} "),
+        format!("{name:^width$}"),
+    ]
+}
+
+fn write_svg_subshell(
+    sublevel: &SubLevel,
+    microstate: &Microstate,
+    x: usize,
+    out: &mut String,
+) -> std::fmt::Result {
+    let y = PADDING;
+    let mut orbitals_width = 0;
+    for (index, (ml, arrows)) in orbitals(microstate).enumerate() {
+        let x = x + index * CELL;
+        let center = x + CELL / 2;
+        writeln!(
+            out,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{CELL}\" height=\"{CELL}\"/>"
+        )?;
+        if !arrows.is_empty() {
+            writeln!(
+                out,
+                "<text class=\"arrow\" x=\"{center}\" y=\"{}\">{arrows}</text>",
+                y + CELL / 2
+            )?;
+        }
+        writeln!(
+            out,
+            "<text class=\"label\" x=\"{center}\" y=\"{}\">{}</text>",
+            y + CELL + 12,
+            ml_label(ml)
+        )?;
+        orbitals_width += CELL;
+    }
+    writeln!(
+        out,
+        "<text x=\"{}\" y=\"{}\">{}</text>",
+        x + orbitals_width / 2,
+        y + CELL + 36,
+        sublevel.notated(Notation::Html)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Configuration, Microstate, OrbitalBoxes, SpinOrbital, SubLevel};
+
+    #[test]
+    fn hund_boxes() {
+        let configuration: Configuration = "2s2 2p3".parse().expect("Should be ok");
+        let boxes = configuration.hund_boxes().expect("Should be ok");
+        assert_eq!(
+            boxes.to_unicode(),
+            "\
+┌──┐   ┌──┬──┬──┐
+│↑↓│   │↑ │↑ │↑ │
+└──┘   └──┴──┴──┘
+  0     +1  0 -1
+2s²       2p³
+"
+        );
+        let svg = boxes.to_svg();
+        assert_eq!(svg.matches("<rect").count(), 4);
+        assert_eq!(svg.matches(">↑<").count(), 3);
+    }
+
+    #[test]
+    fn microstate() {
+        let sublevel: SubLevel = "2p2".parse().expect("Should be ok");
+        let orbitals = ["1+", "-1-"].map(|s| s.parse::<SpinOrbital>().expect("Should be ok"));
+        let microstate = Microstate::from_spin_orbitals(sublevel.level_type(), orbitals).unwrap();
+        let boxes = OrbitalBoxes::new(sublevel, microstate).expect("Should be ok");
+        assert!(boxes.to_unicode().contains("│↑ │  │↓ │"));
+        let p3: SubLevel = "2p3".parse().expect("Should be ok");
+        assert!(OrbitalBoxes::new(p3, microstate).is_none());
+    }
+}
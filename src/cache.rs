@@ -0,0 +1,113 @@
+//! Terms of equivalent electrons remembered for every sublevel they were derived for, so that coupling many configurations derives each subshell only once
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, OnceLock, PoisonError},
+};
+
+use crate::{tabulated_terms, DerivationError, SubLevel, TermType};
+
+/// Sorted terms, keyed by L and the number of electrons of the [hole equivalent](SubLevel::hole_equivalent), so that g^2 and g^16 share an entry
+type TermTables = HashMap<(u8, u8), Vec<TermType>>;
+
+/// Terms of the sublevels derived so far. The library keeps a [global](TermCache::global) one, and the tests may own theirs
+#[derive(Debug)]
+pub(crate) struct TermCache(OnceLock<Mutex<TermTables>>);
+
+static TERMS: TermCache = TermCache::new();
+
+impl TermCache {
+    pub(crate) const fn new() -> Self {
+        Self(OnceLock::new())
+    }
+
+    /// Cache of [`ee_terms`](crate::ee_terms) and the others, see [`clear_term_cache`]
+    pub(crate) fn global() -> &'static Self {
+        &TERMS
+    }
+
+    fn tables(&self) -> MutexGuard<'_, TermTables> {
+        self.0
+            .get_or_init(Default::default)
+            .lock()
+            // the map is never left half-updated
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Terms of the sublevel from the tables or the cache, or from `derive` (which are then cached) if there are none yet
+    ///
+    /// The lock is not held while deriving, so concurrent derivations of the same sublevel may both run
+    pub(crate) fn terms(
+        &self,
+        l: &SubLevel,
+        derive: impl FnOnce() -> Result<Vec<TermType>, DerivationError>,
+    ) -> Result<Vec<TermType>, DerivationError> {
+        if let Some(terms) = tabulated_terms(l) {
+            return Ok(terms.to_vec());
+        }
+        let key = key(l);
+        if let Some(terms) = self.tables().get(&key) {
+            return Ok(terms.clone());
+        }
+        let derived = derive()?;
+        self.tables().insert(key, derived.clone());
+        Ok(derived)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.tables().len()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.tables().clear();
+    }
+}
+
+fn key(l: &SubLevel) -> (u8, u8) {
+    (l.tp.0, l.placed_electrons())
+}
+
+/// Same as [`TermCache::terms`] of the [global](TermCache::global) cache
+pub(crate) fn cached_terms(
+    l: &SubLevel,
+    derive: impl FnOnce() -> Result<Vec<TermType>, DerivationError>,
+) -> Result<Vec<TermType>, DerivationError> {
+    TermCache::global().terms(l, derive)
+}
+
+/// Number of sublevels with cached terms, see [`clear_term_cache`]
+pub fn cached_term_tables() -> usize {
+    TermCache::global().len()
+}
+
+/// Forgets the terms of every sublevel but the [tabulated](tabulated_terms) ones, which [`ee_terms`](crate::ee_terms), [`Configuration::ls_terms`](crate::Configuration::ls_terms), [`ee_terms_progress`](crate::ee_terms_progress), [`ee_terms_analytic`](crate::ee_terms_analytic) and [`ee_terms_recursive`](crate::ee_terms_recursive) remember for the rest of the program
+///
+/// Terms of the largest sublevels take megabytes, so a long-running program may want to free them
+pub fn clear_term_cache() {
+    TermCache::global().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{cache::TermCache, ee_terms_iter, SubLevel};
+
+    #[test]
+    fn hole_symmetry() {
+        let cache = TermCache::new();
+        // s, p, d and f sublevels are tabulated instead
+        let g2: SubLevel = "5g2".parse().expect("Should be ok");
+        let terms = cache
+            .terms(&g2, || Ok(ee_terms_iter(g2.clone()).sorted().collect()))
+            .expect("Should be ok");
+        assert_eq!(cache.len(), 1);
+        let g16: SubLevel = "6g16".parse().expect("Should be ok");
+        let cached = cache
+            .terms(&g16, || panic!("Terms of g^16 should be cached by g^2"))
+            .expect("Should be ok");
+        assert_eq!(cached, terms);
+        cache.clear();
+        assert_eq!(cache.len(), 0);
+    }
+}
@@ -0,0 +1,150 @@
+//! Terms from the numbers of microstates with each ML and MS, counted combinatorially instead of enumerating the microstates
+
+use std::collections::BTreeMap;
+
+use num_bigint::BigUint;
+
+use crate::{
+    cache::cached_terms,
+    couple_terms,
+    multiplicities::{term_multiplicities, Count},
+    tables::term,
+    Configuration, DerivationError, Parity, SubLevel, TermType,
+};
+
+impl Count for BigUint {
+    fn sum_of_products(pairs: impl Iterator<Item = (u128, u128)>) -> Option<Self> {
+        // 64-bit limbs of the sum, each accumulated in 128 bits, so that only the result is allocated
+        let mut limbs = [0u128; 4];
+        for (a, b) in pairs {
+            let (a_low, a_high) = (a & u128::from(u64::MAX), a >> 64);
+            let (b_low, b_high) = (b & u128::from(u64::MAX), b >> 64);
+            for (limb, product) in [
+                (0, a_low * b_low),
+                (1, a_low * b_high),
+                (1, a_high * b_low),
+                (2, a_high * b_high),
+            ] {
+                limbs[limb] += product & u128::from(u64::MAX);
+                limbs[limb + 1] += product >> 64;
+            }
+        }
+        Some(
+            limbs
+                .iter()
+                .rev()
+                .fold(BigUint::default(), |sum, &limb| (sum << 64u32) + limb),
+        )
+    }
+    fn checked_sum(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+    fn difference(&self, other: &Self) -> Self {
+        self - other
+    }
+}
+
+/// Same as [`ee_terms`](crate::ee_terms), but counts the microstates with each ML and MS combinatorially instead of enumerating them
+///
+/// The number of terms with given L and S is N(L, S) - N(L+1, S) - N(L, S+1) + N(L+1, S+1), where N(ML, MS) is the number of microstates. This takes a fraction of a second even for the sublevels with billions of microstates, and works for any L
+pub fn ee_terms_analytic(l: SubLevel) -> Result<Vec<TermType>, DerivationError> {
+    cached_terms(&l, || terms_from_census(&l))
+}
+
+pub(crate) fn terms_from_census(l: &SubLevel) -> Result<Vec<TermType>, DerivationError> {
+    let overflow = || DerivationError::Overflow(l.clone());
+    let mut terms = Vec::new();
+    for (momentum, doubled_spin, count) in
+        term_multiplicities::<u128>(l.tp.0, l.electrons).ok_or_else(overflow)?
+    {
+        let count = usize::try_from(count).map_err(|_| overflow())?;
+        terms.extend(std::iter::repeat_n(
+            term(momentum, doubled_spin, l.parity()),
+            count,
+        ));
+    }
+    Ok(terms)
+}
+
+/// Distinct terms of the sublevel in spectroscopic order, each with the number of its occurrences, counted like [`ee_terms_analytic`] does, but in arbitrary precision
+///
+/// Terms are not repeated, so this never fails, even for the sublevels with more terms than could ever be listed
+pub fn ee_term_counts(l: SubLevel) -> Vec<(TermType, BigUint)> {
+    term_multiplicities::<BigUint>(l.tp.0, l.electrons)
+        .expect("Arbitrary-precision sums can't overflow")
+        .into_iter()
+        .map(|(momentum, doubled_spin, count)| (term(momentum, doubled_spin, l.parity()), count))
+        .collect()
+}
+
+impl Configuration {
+    /// Distinct terms of the configuration in spectroscopic order, each with the number of its occurrences, see [`ee_term_counts`]
+    pub fn ls_term_counts(&self) -> Vec<(TermType, BigUint)> {
+        let mut terms = vec![(term(0, 0, Parity::Even), BigUint::from(1u32))];
+        for sublevel in self.sublevels() {
+            let sublevel_terms = ee_term_counts(sublevel.clone());
+            let mut coupled: BTreeMap<TermType, BigUint> = BTreeMap::new();
+            for (left, left_count) in &terms {
+                for (right, right_count) in &sublevel_terms {
+                    for term in
+                        couple_terms(std::slice::from_ref(left), std::slice::from_ref(right))
+                    {
+                        *coupled.entry(term.term).or_default() += left_count * right_count;
+                    }
+                }
+            }
+            terms = coupled.into_iter().collect();
+        }
+        terms
+    }
+
+    /// Same as [`Configuration::ls_terms`], but derives the terms of each subshell with [`ee_terms_analytic`]
+    pub fn ls_terms_analytic(&self) -> Result<Vec<TermType>, DerivationError> {
+        self.ls_terms_by(|sublevel| ee_terms_analytic(sublevel.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use num_bigint::BigUint;
+
+    use crate::{ee_terms_analytic, multiplicities::Count, Configuration, SubLevel};
+
+    #[test]
+    fn huge() {
+        // beyond the 64 single-electron states of a microstate bitmask
+        let sublevel: SubLevel = "(L=16)3".parse().expect("Should be ok");
+        let terms = ee_terms_analytic(sublevel.clone()).expect("Should be ok");
+        let states: u128 = terms.iter().map(|term| term.degeneracy() as u128).sum();
+        assert_eq!(Some(states), sublevel.microstate_count());
+    }
+
+    #[test]
+    fn counts() {
+        let configuration: Configuration = "3d3 4p1".parse().expect("Should be ok");
+        let counted = configuration
+            .ls_term_counts()
+            .into_iter()
+            .map(|(term, count)| (term, count.to_string()))
+            .collect_vec();
+        let listed = configuration
+            .ls_terms()
+            .expect("Should be ok")
+            .into_iter()
+            .dedup_with_count()
+            .map(|(count, term)| (term, count.to_string()))
+            .collect_vec();
+        assert_eq!(counted, listed);
+
+        // products of the numbers of the electrons of either spin are the first to overflow
+        let pairs = [(u128::MAX, u128::MAX), (u128::MAX, 3), (1 << 64, 1 << 64)];
+        assert_eq!(u128::sum_of_products(pairs.into_iter()), None);
+        let expected: BigUint = pairs
+            .iter()
+            .map(|&(a, b)| BigUint::from(a) * BigUint::from(b))
+            .sum();
+        assert_eq!(BigUint::sum_of_products(pairs.into_iter()), Some(expected));
+    }
+}
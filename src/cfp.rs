@@ -0,0 +1,194 @@
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{
+    coupling::clebsch_gordan,
+    slater::{self, Combination, TOLERANCE},
+    HalfInt, Microstate, SeniorityTerm, SubLevel, SubLevelType,
+};
+
+impl SubLevel {
+    /// Coefficients of fractional parentage ⟨l^(n-1) parent, l | l^n term⟩ of the term of this sublevel, for all of the parents with non-zero coefficients (in spectroscopic order)
+    ///
+    /// Coefficients are computed from the explicitly constructed states of both l^n and l^(n-1), and their squares sum up to 1. The phase convention is the one of this crate: states of seniority n are chosen to have a positive coefficient of the first Slater determinant (see [`Microstate::occupation`]), and states of lower seniority are obtained from them by adding ^1S electron pairs. Signs may therefore differ from the published tables
+    ///
+    /// Terms are distinguished by their L, S and seniority (parity is ignored), so the repeated f^n terms of the same seniority result in [`CfpError::Ambiguous`]
+    pub fn fractional_parentage(
+        &self,
+        term: &SeniorityTerm,
+    ) -> Result<Vec<(SeniorityTerm, f64)>, CfpError> {
+        let l = self.tp.l();
+        if l > Microstate::MAX_L {
+            return Err(CfpError::TooLarge(self.tp.clone()));
+        }
+        let electrons = self.electrons;
+        if electrons == 0 {
+            return Err(CfpError::NoParents);
+        }
+        let state = term_state(l, electrons, term)?;
+        let parents = SubLevel {
+            electrons: electrons - 1,
+            ..self.clone()
+        }
+        .terms_with_seniority()
+        .into_iter()
+        .dedup();
+        let (momentum, spin) = term_momenta(term);
+        let sign = if electrons % 2 == 1 { 1.0 } else { -1.0 };
+        let mut result = Vec::new();
+        for parent in parents {
+            let (parent_momentum, parent_spin) = term_momenta(&parent);
+            // both parent and the term are taken in their highest weight states, so only one electron state contributes
+            let ml = momentum - parent_momentum;
+            let ms = spin - parent_spin;
+            let coupling = clebsch_gordan(
+                (parent_momentum, parent_momentum),
+                (HalfInt::from_int(i32::from(l)), ml),
+                (momentum, momentum),
+            ) * clebsch_gordan(
+                (parent_spin, parent_spin),
+                (HalfInt::HALF, ms),
+                (spin, spin),
+            );
+            if coupling == 0.0 {
+                // parent can't be coupled with an electron to the term
+                continue;
+            }
+            let removed = slater::annihilate(
+                &state,
+                slater::index(l, ml.doubled() / 2, ms > HalfInt::ZERO),
+            );
+            let amplitude = slater::dot(&term_state(l, electrons - 1, &parent)?, &removed);
+            let cfp = sign * amplitude / coupling / f64::from(electrons).sqrt();
+            if cfp.abs() > TOLERANCE {
+                result.push((parent, cfp));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Coefficient of fractional parentage ⟨l^(n-1) parent, l | l^n term⟩, see [`SubLevel::fractional_parentage`]
+    ///
+    /// Returns zero if the parent does not contribute to the term
+    pub fn cfp(&self, parent: &SeniorityTerm, term: &SeniorityTerm) -> Result<f64, CfpError> {
+        let same = |other: &SeniorityTerm| {
+            other.seniority == parent.seniority && term_momenta(other) == term_momenta(parent)
+        };
+        let parents = self.fractional_parentage(term)?;
+        if let Some((_, cfp)) = parents.iter().find(|(other, _)| same(other)) {
+            return Ok(*cfp);
+        }
+        let parent_sublevel = SubLevel {
+            electrons: self.electrons - 1,
+            ..self.clone()
+        };
+        if parent_sublevel.terms_with_seniority().iter().any(same) {
+            Ok(0.0)
+        } else {
+            Err(CfpError::NoSuchTerm(parent.clone(), parent_sublevel))
+        }
+    }
+}
+
+fn term_momenta(term: &SeniorityTerm) -> (HalfInt, HalfInt) {
+    (
+        HalfInt::from_int(term.term.momentum.0 as i32),
+        term.term.spin,
+    )
+}
+
+/// Highest weight state of the term of l^n
+fn term_state(l: u8, electrons: u8, term: &SeniorityTerm) -> Result<Combination, CfpError> {
+    let states = slater::seniority_states(l, electrons, term.term.momentum.0, term.term.spin)
+        .into_iter()
+        .filter(|(seniority, _)| *seniority == term.seniority)
+        .map(|(_, state)| state)
+        .collect_vec();
+    match <[_; 1]>::try_from(states) {
+        Ok([state]) => Ok(state),
+        Err(states) if states.is_empty() => Err(CfpError::NoSuchTerm(
+            term.clone(),
+            SubLevel {
+                n: None,
+                tp: SubLevelType(l),
+                electrons,
+            },
+        )),
+        Err(_) => Err(CfpError::Ambiguous(term.clone())),
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CfpError {
+    #[error("There's no {0} term in the {1} configuration")]
+    NoSuchTerm(SeniorityTerm, SubLevel),
+    #[error(
+        "Term {0} occurs several times with the same seniority, so it is not uniquely defined"
+    )]
+    Ambiguous(SeniorityTerm),
+    #[error("Empty sublevel has no parents")]
+    NoParents,
+    #[error("States of the {0} sublevel can't be constructed, L should be at most {max}", max = Microstate::MAX_L)]
+    TooLarge(SubLevelType),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CfpError, SeniorityTerm, SubLevel};
+
+    fn parentage(sublevel: &str) -> Vec<(SeniorityTerm, Vec<(SeniorityTerm, f64)>)> {
+        let sublevel: SubLevel = sublevel.parse().unwrap();
+        sublevel
+            .terms_with_seniority()
+            .into_iter()
+            .map(|term| {
+                let parents = sublevel.fractional_parentage(&term).unwrap();
+                (term, parents)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn known_p3_cfps() {
+        let squares = parentage("p3")
+            .into_iter()
+            .map(|(term, parents)| {
+                let parents = parents
+                    .into_iter()
+                    .map(|(parent, cfp)| format!("{parent}:{:.4}", cfp * cfp))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("{term} <- {parents}")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            squares,
+            [
+                "^4_3S* <- ^3_2P:1.0000",
+                "^2_1P* <- ^3_2P:0.5000 ^1_0S:0.2222 ^1_2D:0.2778",
+                "^2_3D* <- ^3_2P:0.5000 ^1_2D:0.5000",
+            ]
+        );
+    }
+
+    #[test]
+    fn cfps_are_normalized() {
+        for sublevel in ["p1", "p4", "d2", "d3", "d4", "d5", "d8"] {
+            for (term, parents) in parentage(sublevel) {
+                let sum: f64 = parents.iter().map(|(_, cfp)| cfp * cfp).sum();
+                assert!((sum - 1.0).abs() < 1e-9, "{sublevel} {term}: {sum}");
+            }
+        }
+        // the two ^2D terms of d^3 are told apart by seniority
+        let d3: SubLevel = "d3".parse().unwrap();
+        let terms = d3.terms_with_seniority();
+        let d1 = &"d2".parse::<SubLevel>().unwrap().terms_with_seniority()[0];
+        assert_eq!(d1.to_string(), "^3_2P");
+        assert!(d3.cfp(d1, &terms[0]).unwrap().abs() > 0.0);
+        assert!(matches!(
+            d3.cfp(&terms[0], &terms[0]),
+            Err(CfpError::NoSuchTerm(_, _))
+        ));
+    }
+}
@@ -0,0 +1,471 @@
+use std::{fmt::Display, str::FromStr, sync::atomic::AtomicBool};
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{
+    cache::TermCache, counted_terms, logger::logln, observed_terms, DerivationError,
+    DerivationLogger, DerivationObserver, DerivationProgress, EnumerationLimit, HalfInt,
+    LogObserver, Notated, Notation, Parity, SubLevel, SubLevelParseError, TermMomentum, TermType,
+};
+
+/// Electron configuration, consisting of (possibly) several subshells
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Configuration {
+    pub(crate) sublevels: Vec<SubLevel>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    limit: EnumerationLimit,
+}
+
+impl Configuration {
+    pub fn new(sublevels: impl IntoIterator<Item = SubLevel>) -> Self {
+        Self {
+            sublevels: sublevels.into_iter().collect(),
+            limit: EnumerationLimit::default(),
+        }
+    }
+
+    /// Fails the derivations of terms enumerating more than `limit` allows with [`DerivationError::MicrostateLimit`] or [`DerivationError::MemoryLimit`], and the ones coupling more terms than it allows with [`DerivationError::TermLimit`] or [`DerivationError::CouplingMemoryLimit`]. Configurations derived from this one, like [`Configuration::open_subshells`], keep the limit
+    pub fn with_limit(mut self, limit: EnumerationLimit) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Limit set by [`Configuration::with_limit`]
+    pub fn limit(&self) -> EnumerationLimit {
+        self.limit
+    }
+
+    pub fn sublevels(&self) -> &[SubLevel] {
+        &self.sublevels
+    }
+
+    /// Partially filled subshells of the configuration. Closed subshells only contribute a ^1S term, so the open ones alone define the terms
+    pub fn open_subshells(&self) -> Configuration {
+        Self::new(
+            self.sublevels
+                .iter()
+                .filter(|sublevel| {
+                    sublevel.electrons != 0 && sublevel.electrons != sublevel.tp.max_electrons()
+                })
+                .cloned(),
+        )
+        .with_limit(self.limit)
+    }
+
+    /// Subshells outside of the largest noble gas core the configuration contains, like `3d6 4s2` for `[Ar] 3d6 4s2`, which is how configurations are labeled in the tables of levels. The outermost subshell of the core is kept if nothing else is left, like `2p6` for neon
+    pub fn valence(&self) -> Configuration {
+        let same = |a: &SubLevel, b: &SubLevel| {
+            a.n == b.n && a.tp.0 == b.tp.0 && a.electrons == b.electrons
+        };
+        let core = NOBLE_GAS_CORES
+            .iter()
+            .rev()
+            .map(|(_, core)| {
+                let mut sublevels = Vec::new();
+                parse_subshells(core, &mut sublevels).expect("Cores are valid");
+                sublevels
+            })
+            .find(|core| {
+                core.iter()
+                    .all(|subshell| self.sublevels.iter().any(|other| same(subshell, other)))
+            });
+        let Some(core) = core else {
+            return self.clone();
+        };
+        let valence = self
+            .sublevels
+            .iter()
+            .filter(|sublevel| !core.iter().any(|subshell| same(subshell, sublevel)))
+            .cloned()
+            .collect_vec();
+        if valence.is_empty() {
+            Self::new(core.last().cloned())
+        } else {
+            Self::new(valence)
+        }
+        .with_limit(self.limit)
+    }
+
+    /// Computes LS-coupling terms of the configuration.
+    ///
+    /// Terms of equivalent electrons are derived for each subshell first, and are then coupled to each other (electrons of different subshells are non-equivalent, so there's no Pauli restriction at this stage). Repeated terms are listed the corresponding number of times
+    ///
+    /// Terms of each subshell are [tabulated](crate::tabulated_terms) or cached like [`ee_terms`](crate::ee_terms) does, and the microstates are only counted by ML and MS, never kept
+    pub fn ls_terms(&self) -> Result<Vec<TermType>, DerivationError> {
+        self.ls_terms_cancellable(&AtomicBool::new(false))
+    }
+
+    /// Same as [`Configuration::ls_terms`], but calls `progress` with the progress of the derivation of each subshell in turn, see [`ee_terms_progress`](crate::ee_terms_progress)
+    pub fn ls_terms_progress(
+        &self,
+        mut progress: impl FnMut(DerivationProgress),
+    ) -> Result<Vec<TermType>, DerivationError> {
+        let cancel = AtomicBool::new(false);
+        self.ls_terms_by(|sublevel| {
+            counted_terms(
+                sublevel.clone(),
+                &mut progress,
+                &cancel,
+                self.limit,
+                TermCache::global(),
+            )
+        })
+    }
+
+    /// Same as [`Configuration::ls_terms`], but stops with [`DerivationError::Cancelled`] as soon as `cancel` is set, see [`ee_terms_cancellable`](crate::ee_terms_cancellable)
+    pub fn ls_terms_cancellable(
+        &self,
+        cancel: &AtomicBool,
+    ) -> Result<Vec<TermType>, DerivationError> {
+        self.ls_terms_in(cancel, TermCache::global())
+    }
+
+    /// Same as [`Configuration::ls_terms_cancellable`], but with the terms of the subshells cached in `cache`
+    pub(crate) fn ls_terms_in(
+        &self,
+        cancel: &AtomicBool,
+        cache: &TermCache,
+    ) -> Result<Vec<TermType>, DerivationError> {
+        self.ls_terms_by(|sublevel| {
+            counted_terms(sublevel.clone(), |_| {}, cancel, self.limit, cache)
+        })
+    }
+
+    /// Same as [`Configuration::ls_terms`], but reports the derivation of the terms of each subshell to `observer`, see [`ee_terms_observed`](crate::ee_terms_observed)
+    pub fn ls_terms_observed(
+        &self,
+        observer: &mut (impl DerivationObserver + ?Sized),
+    ) -> Result<Vec<TermType>, DerivationError> {
+        self.ls_terms_by(|sublevel| {
+            Ok(observed_terms(sublevel.clone(), observer, self.limit)?
+                .into_iter()
+                .map(|(term, _)| term)
+                .collect())
+        })
+    }
+
+    /// Same as [`Configuration::ls_terms`], but logs the derivation of the terms of each subshell, see [`ee_terms_log`](crate::ee_terms_log). A disabled logger, like [`NoLog`](crate::NoLog), only counts the microstates, like [`Configuration::ls_terms`] does
+    pub fn ls_terms_log(
+        &self,
+        log: &mut (impl DerivationLogger + ?Sized),
+    ) -> Result<Vec<TermType>, DerivationError> {
+        // there's nothing to log the microstates to, so they are only counted
+        if !log.enabled() {
+            return self.ls_terms();
+        }
+        logln!(log, "Configuration: {self}")?;
+        self.ls_terms_by(|sublevel| {
+            let mut observer = LogObserver::new(log);
+            let terms = observed_terms(sublevel.clone(), &mut observer, self.limit)?;
+            observer.finish()?;
+            Ok(terms.into_iter().map(|(term, _)| term).collect())
+        })
+    }
+
+    /// Couples the terms `sublevel_terms` gives for each subshell, in spectroscopic order
+    pub(crate) fn ls_terms_by(
+        &self,
+        mut sublevel_terms: impl FnMut(&SubLevel) -> Result<Vec<TermType>, DerivationError>,
+    ) -> Result<Vec<TermType>, DerivationError> {
+        let mut terms = vec![TermType {
+            momentum: TermMomentum(0),
+            spin: HalfInt::ZERO,
+            parity: Parity::Even,
+        }];
+        for sublevel in &self.sublevels {
+            let sublevel_terms = sublevel_terms(sublevel)?;
+            self.limit.check_coupling(&terms, &sublevel_terms)?;
+            terms = couple_terms(&terms, &sublevel_terms)
+                .into_iter()
+                .map(|coupled| coupled.term)
+                .collect();
+        }
+        terms.sort();
+        Ok(terms)
+    }
+}
+
+/// Term obtained by coupling two parent terms
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoupledTerm {
+    pub term: TermType,
+    pub left: TermType,
+    pub right: TermType,
+}
+
+/// Couples every term of the first set with every term of the second set by vector addition of both L and S, sorted in spectroscopic order (and then in order of the parents)
+///
+/// Electrons of the two sets are assumed to be non-equivalent (like 3d^2 and 4p^1), so there's no Pauli restriction. Equivalent electrons are handled by [`ee_terms`](crate::ee_terms) instead
+pub fn couple_terms(left: &[TermType], right: &[TermType]) -> Vec<CoupledTerm> {
+    let mut terms = left
+        .iter()
+        .cartesian_product(right)
+        .flat_map(|(left, right)| {
+            let (l1, l2) = (left.momentum.0, right.momentum.0);
+            let (s1, s2) = (left.spin, right.spin);
+            (l1.abs_diff(l2)..=l1 + l2)
+                .cartesian_product((s1 - s2).abs().up_to(s1 + s2))
+                .map(move |(l, spin)| CoupledTerm {
+                    term: TermType {
+                        momentum: TermMomentum(l),
+                        spin,
+                        parity: left.parity * right.parity,
+                    },
+                    left: left.clone(),
+                    right: right.clone(),
+                })
+        })
+        .collect_vec();
+    terms.sort_by(|a, b| a.term.cmp(&b.term));
+    terms
+}
+
+/// Parents are written in parentheses before the term, like `(^3F ^2P*) ^4G*`
+impl Notated for CoupledTerm {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "({} {}) {}",
+            self.left.notated(notation),
+            self.right.notated(notation),
+            self.term.notated(notation)
+        ))
+    }
+}
+
+impl Display for CoupledTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+impl Notated for Configuration {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        // spaces between the elements are dropped in MathML
+        let separator = match notation {
+            Notation::MathMl => "<mspace width=\"0.3em\"/>",
+            _ => " ",
+        };
+        f.write_str(
+            &self
+                .sublevels
+                .iter()
+                .map(|sublevel| sublevel.notated(notation))
+                .join(separator),
+        )
+    }
+}
+
+impl Display for Configuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConfigurationParseError {
+    #[error("Unknown noble gas core [{0}]")]
+    UnknownCore(String),
+    #[error("Subshell {0} is listed more than once")]
+    Duplicate(SubLevel),
+    #[error(transparent)]
+    Subshell(#[from] SubLevelParseError),
+}
+
+static NOBLE_GAS_CORES: [(&str, &str); 7] = [
+    ("He", "1s2"),
+    ("Ne", "[He] 2s2 2p6"),
+    ("Ar", "[Ne] 3s2 3p6"),
+    ("Kr", "[Ar] 3d10 4s2 4p6"),
+    ("Xe", "[Kr] 4d10 5s2 5p6"),
+    ("Rn", "[Xe] 4f14 5d10 6s2 6p6"),
+    ("Og", "[Rn] 5f14 6d10 7s2 7p6"),
+];
+
+/// Parses whitespace-separated subshells, expanding noble gas cores
+fn parse_subshells(s: &str, sublevels: &mut Vec<SubLevel>) -> Result<(), ConfigurationParseError> {
+    for token in s.split_whitespace() {
+        if let Some(core) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            let (_, core_config) = NOBLE_GAS_CORES
+                .iter()
+                .find(|(name, _)| *name == core)
+                .ok_or_else(|| ConfigurationParseError::UnknownCore(core.to_string()))?;
+            parse_subshells(core_config, sublevels)?;
+            continue;
+        }
+        let sublevel: SubLevel = token.parse()?;
+        if sublevels
+            .iter()
+            .any(|other| other.n == sublevel.n && other.tp.0 == sublevel.tp.0)
+        {
+            return Err(ConfigurationParseError::Duplicate(sublevel));
+        }
+        sublevels.push(sublevel);
+    }
+    Ok(())
+}
+
+impl FromStr for Configuration {
+    type Err = ConfigurationParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sublevels = Vec::new();
+        parse_subshells(s, &mut sublevels)?;
+        Ok(Self::new(sublevels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use crate::{
+        cache::TermCache, couple_terms, ee_terms, Configuration, ConfigurationParseError,
+        DerivationError, EnumerationLimit, LevelError, NoLog, SubLevel, SubLevelParseError,
+        SubLevelType,
+    };
+
+    #[test]
+    fn non_equivalent_p_electrons() {
+        let configuration = Configuration::new([
+            SubLevel::new(SubLevelType::new(1).unwrap(), 1).unwrap(),
+            SubLevel::new(SubLevelType::new(1).unwrap(), 1).unwrap(),
+        ]);
+        let terms = configuration.ls_terms().expect("Should be ok");
+        // 1S, 1P, 1D, 3S, 3P, 3D
+        assert_eq!(terms.len(), 6);
+    }
+
+    #[test]
+    fn parentage() {
+        let d2 = ee_terms("3d2".parse().unwrap()).unwrap();
+        let p1 = ee_terms("4p1".parse().unwrap()).unwrap();
+        let coupled = couple_terms(&d2, &p1);
+        assert_eq!(coupled.len(), 19);
+        let quartets = coupled
+            .iter()
+            .filter(|coupled| coupled.term.multiplicity() == 4)
+            .map(|coupled| coupled.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            quartets,
+            [
+                "(^3P ^2P*) ^4S*",
+                "(^3P ^2P*) ^4P*",
+                "(^3P ^2P*) ^4D*",
+                "(^3F ^2P*) ^4D*",
+                "(^3F ^2P*) ^4F*",
+                "(^3F ^2P*) ^4G*",
+            ]
+        );
+    }
+
+    #[test]
+    fn valence() {
+        let valence = |s: &str| {
+            s.parse::<Configuration>()
+                .expect("Should be ok")
+                .valence()
+                .to_string()
+        };
+        assert_eq!(valence("[Ar] 3d6 4s2"), "3d^6 4s^2");
+        assert_eq!(valence("1s2 2s2 2p2"), "2s^2 2p^2");
+        assert_eq!(valence("[Ne]"), "2p^6");
+        assert_eq!(valence("3d2"), "3d^2");
+    }
+
+    #[test]
+    fn parse() {
+        let configuration: Configuration = "[Ar] 3d5 4s1".parse().expect("Should parse");
+        assert_eq!(configuration.sublevels().len(), 7);
+        assert_eq!(
+            configuration.to_string(),
+            "1s^2 2s^2 2p^6 3s^2 3p^6 3d^5 4s^1"
+        );
+        assert!(matches!(
+            "1s2 2s2 2p^{7}".parse::<Configuration>(),
+            Err(ConfigurationParseError::Subshell(
+                SubLevelParseError::Level(LevelError::ToMuch(_))
+            ))
+        ));
+        assert!(matches!(
+            "2d1".parse::<Configuration>(),
+            Err(ConfigurationParseError::Subshell(
+                SubLevelParseError::Level(LevelError::NoSuchSublevel(2, _))
+            ))
+        ));
+        assert!(matches!(
+            "[Ne] 2p1".parse::<Configuration>(),
+            Err(ConfigurationParseError::Duplicate(SubLevel {
+                n: Some(2),
+                ..
+            }))
+        ));
+        // without n, there's no 0d subshell to name
+        let error = "d1 d2".parse::<Configuration>().expect_err("Should fail");
+        assert_eq!(error.to_string(), "Subshell d^2 is listed more than once");
+        assert!(matches!(
+            "[Ab]".parse::<Configuration>(),
+            Err(ConfigurationParseError::UnknownCore(_))
+        ));
+    }
+
+    #[test]
+    fn limit() {
+        let configuration: Configuration = "1s2 2s2 3d7".parse().expect("Should be ok");
+        assert!(configuration.ls_terms().is_ok());
+        let limit = EnumerationLimit {
+            memory: Some(1000),
+            ..EnumerationLimit::default()
+        };
+        let open = configuration.with_limit(limit).open_subshells();
+        assert_eq!(open.limit(), limit);
+        // terms of d^7 are tabulated, and 120 of its microstates are only kept for the log
+        assert!(open.ls_terms().is_ok());
+        assert!(open.ls_terms_log(&mut NoLog).is_ok());
+        assert!(matches!(
+            open.ls_terms_log(&mut Vec::new()),
+            Err(DerivationError::MemoryLimit { .. })
+        ));
+    }
+
+    #[test]
+    fn cached_subshells() {
+        let cache = TermCache::new();
+        let configuration: Configuration = "5g4 6p1".parse().expect("Should be ok");
+        let never = AtomicBool::new(false);
+        let terms = configuration
+            .ls_terms_in(&never, &cache)
+            .expect("Should be ok");
+        // p^1 is tabulated
+        assert_eq!(cache.len(), 1);
+        let g4: SubLevel = "5g4".parse().expect("Should be ok");
+        cache
+            .terms(&g4, || panic!("Terms of g^4 should be cached"))
+            .expect("Should be ok");
+        assert_eq!(
+            configuration
+                .ls_terms_in(&never, &cache)
+                .expect("Should be ok"),
+            terms
+        );
+        assert_eq!(configuration.ls_terms().expect("Should be ok"), terms);
+        // too large for the microstates to be represented, but they are only counted
+        let large: Configuration = "(L=20)2".parse().expect("Should be ok");
+        assert_eq!(
+            large.ls_terms().expect("Should be ok"),
+            ee_terms("(L=20)2".parse().unwrap()).expect("Should be ok")
+        );
+    }
+}
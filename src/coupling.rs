@@ -0,0 +1,434 @@
+use std::{cell::RefCell, fmt::Display, ops::Neg, sync::OnceLock};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::HalfInt;
+
+/// Exact number of the form ±sqrt(p/q), like most of the angular momentum coupling coefficients
+///
+/// Stored as the square with the sign of the number itself, so that `-sqrt(1/3)` is stored as `-1/3`
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct SqrtRational {
+    signed_square: BigRational,
+}
+
+impl SqrtRational {
+    pub fn zero() -> Self {
+        Self {
+            signed_square: BigRational::zero(),
+        }
+    }
+
+    /// Number equal to `sign(signed_square) * sqrt(|signed_square|)`
+    pub fn from_signed_square(signed_square: BigRational) -> Self {
+        Self { signed_square }
+    }
+
+    /// Square of the number, taken with the sign of the number
+    pub fn signed_square(&self) -> &BigRational {
+        &self.signed_square
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.signed_square.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.signed_square.is_negative()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let square = self.signed_square.abs().to_f64().unwrap_or(f64::NAN);
+        square
+            .sqrt()
+            .copysign(self.signed_square.to_f64().unwrap_or(f64::NAN))
+    }
+
+    /// The number itself, if it is rational
+    pub fn to_rational(&self) -> Option<BigRational> {
+        let square = self.signed_square.abs();
+        let numerator = square.numer().sqrt();
+        let denominator = square.denom().sqrt();
+        (&numerator * &numerator == *square.numer()
+            && &denominator * &denominator == *square.denom())
+        .then(|| {
+            let root = BigRational::new(numerator, denominator);
+            if self.is_negative() {
+                -root
+            } else {
+                root
+            }
+        })
+    }
+}
+
+/// Rational numbers are printed as they are (`-1/2`), and the rest as square roots (`-sqrt(5/18)`)
+impl Display for SqrtRational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(rational) = self.to_rational() {
+            return f.write_fmt(format_args!("{rational}"));
+        }
+        let sign = if self.is_negative() { "-" } else { "" };
+        f.write_fmt(format_args!("{sign}sqrt({})", self.signed_square.abs()))
+    }
+}
+
+impl Neg for SqrtRational {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::from_signed_square(-self.signed_square)
+    }
+}
+
+/// Largest n, such that n! still fits into `f64`
+pub(crate) const MAX_FACTORIAL: usize = 170;
+
+/// n!, as a floating point number so that it does not overflow too early (taken from a table computed once)
+pub(crate) fn factorial(n: i32) -> f64 {
+    static FACTORIALS: OnceLock<Vec<f64>> = OnceLock::new();
+    let factorials = FACTORIALS.get_or_init(|| {
+        (0..=MAX_FACTORIAL)
+            .scan(1.0, |factorial, n| {
+                if n > 0 {
+                    *factorial *= n as f64;
+                }
+                Some(*factorial)
+            })
+            .collect()
+    });
+    factorials.get(n as usize).copied().unwrap_or(f64::INFINITY)
+}
+
+/// ln(n!), from the table of [`factorial`] for small n and from the Stirling series for the rest, so that the coefficients of any momenta are computed without overflowing
+pub(crate) fn ln_factorial(n: i32) -> f64 {
+    if n as usize <= MAX_FACTORIAL {
+        return factorial(n).ln();
+    }
+    // the series is accurate to the last bit for n > 170
+    let n = f64::from(n);
+    (n + 0.5) * n.ln() - n + 0.5 * (2.0 * std::f64::consts::PI).ln() + 1.0 / (12.0 * n)
+        - 1.0 / (360.0 * n.powi(3))
+        + 1.0 / (1260.0 * n.powi(5))
+}
+
+/// Largest momentum the exact coefficients (like [`clebsch_gordan_exact`]) are computed for
+///
+/// Exact arithmetic takes time growing as the cube of the momenta, so this keeps it below a few milliseconds. Factorials of the exact coefficients are cached for every thread, and the cap also bounds the cache to a few hundred kilobytes
+pub const MAX_EXACT_MOMENTUM: HalfInt = HalfInt::from_int(200);
+
+/// Whether every momentum is at most [`MAX_EXACT_MOMENTUM`]
+pub fn momenta_exact(momenta: &[HalfInt]) -> bool {
+    momenta_within(momenta, MAX_EXACT_MOMENTUM)
+}
+
+/// Largest momentum (or projection) the floating-point coefficients (like [`clebsch_gordan`]) are computed for
+///
+/// Coefficients of large momenta are computed in logarithms, in time quadratic in the momenta, so this keeps a single coefficient below a fraction of a second. Larger ones give NaN
+pub const MAX_MOMENTUM: HalfInt = HalfInt::from_int(10_000);
+
+/// Whether every momentum is at most `max` by absolute value, checked without overflowing
+pub(crate) fn momenta_within(momenta: &[HalfInt], max: HalfInt) -> bool {
+    momenta.iter().all(|j| -max <= *j && *j <= max)
+}
+
+/// n!, computed exactly (and cached, as the same factorials are needed over and over again)
+///
+/// Only called with the momenta checked by [`momenta_exact`], so the cache never grows past (4 [`MAX_EXACT_MOMENTUM`] + 1)!
+pub(crate) fn exact_factorial(n: i32) -> BigInt {
+    thread_local! {
+        static FACTORIALS: RefCell<Vec<BigInt>> = RefCell::new(vec![BigInt::one()]);
+    }
+    FACTORIALS.with_borrow_mut(|factorials| {
+        let n = n as usize;
+        while factorials.len() <= n {
+            let next = factorials.len() * factorials.last().expect("Starts with 0!");
+            factorials.push(next);
+        }
+        factorials[n].clone()
+    })
+}
+
+/// Exact value of `sum * sqrt(squared_prefactor)`
+pub(crate) fn signed_root(sum: BigRational, squared_prefactor: BigRational) -> SqrtRational {
+    let sign = if sum.is_negative() {
+        -BigRational::one()
+    } else {
+        BigRational::one()
+    };
+    SqrtRational::from_signed_square(sign * &sum * &sum * squared_prefactor)
+}
+
+/// Checks the selection rules, and computes the range of summation index of the Racah formula
+///
+/// Every value involved in the formula is then an integer. The momenta should be at most [`MAX_MOMENTUM`] by absolute value, and the projections are checked against them before they are added up
+fn racah_range(
+    (j1, m1): (HalfInt, HalfInt),
+    (j2, m2): (HalfInt, HalfInt),
+    (j, m): (HalfInt, HalfInt),
+) -> Option<(i32, i32)> {
+    let valid = |j: HalfInt, m: HalfInt| -j <= m && m <= j && (j - m).is_integer();
+    if !valid(j1, m1)
+        || !valid(j2, m2)
+        || !valid(j, m)
+        || m1 + m2 != m
+        || j < (j1 - j2).abs()
+        || j > j1 + j2
+        || !(j1 + j2 - j).is_integer()
+    {
+        return None;
+    }
+    let from = 0.max(int(j2 - j - m1)).max(int(j1 - j + m2));
+    let to = int(j1 + j2 - j).min(int(j1 - m1)).min(int(j2 + m2));
+    Some((from, to))
+}
+
+/// Whether the floating-point coefficients of these momenta (and projections) are computed: every one of them is at most [`MAX_MOMENTUM`] by absolute value
+///
+/// The 9j symbols take longer, see [`momenta_in_9j_range`](crate::momenta_in_9j_range)
+pub fn momenta_in_range(momenta: &[HalfInt]) -> bool {
+    momenta_within(momenta, MAX_MOMENTUM)
+}
+
+pub(crate) fn int(x: HalfInt) -> i32 {
+    x.doubled() / 2
+}
+
+/// Clebsch–Gordan coefficient ⟨j1 m1 j2 m2 | j m⟩ in the Condon–Shortley phase convention, computed with the Racah formula
+///
+/// Returns zero for the coefficients forbidden by the selection rules, and NaN if some of the arguments is greater than [`MAX_MOMENTUM`] by absolute value. The momenta whose factorials don't fit into `f64` are computed in logarithms, in time quadratic in the momenta. See [`clebsch_gordan_exact`] for the exact value
+pub fn clebsch_gordan(
+    (j1, m1): (HalfInt, HalfInt),
+    (j2, m2): (HalfInt, HalfInt),
+    (j, m): (HalfInt, HalfInt),
+) -> f64 {
+    if !momenta_in_range(&[j1, m1, j2, m2, j, m]) {
+        return f64::NAN;
+    }
+    let Some((from, to)) = racah_range((j1, m1), (j2, m2), (j, m)) else {
+        return 0.0;
+    };
+    // (j1+j2+j+1)! is the largest factorial of the formula
+    if int(j1 + j2 + j) >= MAX_FACTORIAL as i32 {
+        return ln_clebsch_gordan((from, to), (j1, m1), (j2, m2), (j, m));
+    }
+    let f = |x: HalfInt| factorial(int(x));
+    let prefactor = (f64::from(j.doubled() + 1) * f(j + j1 - j2) * f(j - j1 + j2) * f(j1 + j2 - j)
+        / f(j1 + j2 + j + HalfInt::ONE))
+    .sqrt()
+        * (f(j + m) * f(j - m) * f(j1 - m1) * f(j1 + m1) * f(j2 - m2) * f(j2 + m2)).sqrt();
+    let sum: f64 = (from..=to)
+        .map(|k| {
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            let k = HalfInt::from_int(k);
+            sign / (f(k)
+                * f(j1 + j2 - j - k)
+                * f(j1 - m1 - k)
+                * f(j2 + m2 - k)
+                * f(j - j2 + m1 + k)
+                * f(j - j1 - m2 + k))
+        })
+        .sum();
+    prefactor * sum
+}
+
+/// Same as [`clebsch_gordan`], for the momenta whose factorials don't fit into `f64`: the factorials are taken in logarithms, and the sum is computed exactly by [`series_sum`], as its terms cancel out to many digits
+fn ln_clebsch_gordan(
+    (from, to): (i32, i32),
+    (j1, m1): (HalfInt, HalfInt),
+    (j2, m2): (HalfInt, HalfInt),
+    (j, m): (HalfInt, HalfInt),
+) -> f64 {
+    let f = |x: HalfInt| ln_factorial(int(x));
+    let ln_prefactor = 0.5
+        * (f64::from(j.doubled() + 1).ln() + f(j + j1 - j2) + f(j - j1 + j2) + f(j1 + j2 - j)
+            - f(j1 + j2 + j + HalfInt::ONE)
+            + f(j + m)
+            + f(j - m)
+            + f(j1 - m1)
+            + f(j1 + m1)
+            + f(j2 - m2)
+            + f(j2 + m2));
+    // factorials of the terms are k!, (a-k)!, (b-k)!, (c-k)!, (d+k)! and (e+k)!
+    let [a, b, c] = [j1 + j2 - j, j1 - m1, j2 + m2].map(|x| i128::from(int(x)));
+    let [d, e] = [j - j2 + m1, j - j1 - m2].map(|x| i128::from(int(x)));
+    let first = i128::from(from);
+    let ln_first = [first, a - first, b - first, c - first, d + first, e + first]
+        .into_iter()
+        .map(|x| -ln_factorial(x as i32))
+        .sum::<f64>();
+    let ratios = (from..to).map(i128::from).map(|k| {
+        (
+            (a - k) * (b - k) * (c - k),
+            (k + 1) * (d + k + 1) * (e + k + 1),
+        )
+    });
+    let sign = if from % 2 == 0 { 1.0 } else { -1.0 };
+    sign * series_sum(ln_prefactor + ln_first, ratios)
+}
+
+/// Sum of the alternating series exp(ln_first) (1 - p0/q0 (1 - p1/q1 (1 - ...))), whose terms are the previous ones multiplied by -p/q
+///
+/// The series is summed exactly, so that the terms cancelling out don't take the precision with them: p and q are small, and the sum of n terms takes time quadratic in n
+pub(crate) fn series_sum(
+    ln_first: f64,
+    ratios: impl DoubleEndedIterator<Item = (i128, i128)>,
+) -> f64 {
+    let (mut numerator, mut denominator) = (BigInt::one(), BigInt::one());
+    for (p, q) in ratios.rev() {
+        denominator *= q;
+        numerator = &denominator - numerator * p;
+    }
+    if numerator.is_zero() {
+        return 0.0;
+    }
+    let sign = if numerator.is_negative() { -1.0 } else { 1.0 };
+    sign * (ln_first + ln_abs(&numerator) - ln_abs(&denominator)).exp()
+}
+
+/// ln|x| of a non-zero integer, which may not fit into `f64` itself
+fn ln_abs(x: &BigInt) -> f64 {
+    let shift = x.bits().saturating_sub(f64::MANTISSA_DIGITS.into());
+    let top = (x.abs() >> shift).to_f64().expect("Fits into the mantissa");
+    top.ln() + shift as f64 * std::f64::consts::LN_2
+}
+
+/// Same as [`clebsch_gordan`], but computed exactly
+///
+/// Returns `None` if some of the momenta is greater than [`MAX_EXACT_MOMENTUM`]
+pub fn clebsch_gordan_exact(
+    (j1, m1): (HalfInt, HalfInt),
+    (j2, m2): (HalfInt, HalfInt),
+    (j, m): (HalfInt, HalfInt),
+) -> Option<SqrtRational> {
+    if !momenta_exact(&[j1, j2, j]) {
+        return None;
+    }
+    let Some((from, to)) = racah_range((j1, m1), (j2, m2), (j, m)) else {
+        return Some(SqrtRational::zero());
+    };
+    let f = |x: HalfInt| exact_factorial(int(x));
+    let squared_prefactor = BigRational::new(
+        BigInt::from(j.doubled() + 1)
+            * f(j + j1 - j2)
+            * f(j - j1 + j2)
+            * f(j1 + j2 - j)
+            * f(j + m)
+            * f(j - m)
+            * f(j1 - m1)
+            * f(j1 + m1)
+            * f(j2 - m2)
+            * f(j2 + m2),
+        f(j1 + j2 + j + HalfInt::ONE),
+    );
+    let sum: BigRational = (from..=to)
+        .map(|k| {
+            let sign = if k % 2 == 0 { 1 } else { -1 };
+            let k = HalfInt::from_int(k);
+            BigRational::new(
+                BigInt::from(sign),
+                f(k) * f(j1 + j2 - j - k)
+                    * f(j1 - m1 - k)
+                    * f(j2 + m2 - k)
+                    * f(j - j2 + m1 + k)
+                    * f(j - j1 - m2 + k),
+            )
+        })
+        .sum();
+    Some(signed_root(sum, squared_prefactor))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{clebsch_gordan, clebsch_gordan_exact, momenta_in_range, HalfInt};
+
+    #[test]
+    fn known_clebsch_gordan() {
+        let h = HalfInt::from_doubled;
+        let exact = |(j1, m1), (j2, m2), (j, m)| {
+            clebsch_gordan_exact((h(j1), h(m1)), (h(j2), h(m2)), (h(j), h(m)))
+                .expect("Momenta are small")
+                .to_string()
+        };
+        // ⟨1/2 1/2 1/2 -1/2 | 0 0⟩
+        assert_eq!(exact((1, 1), (1, -1), (0, 0)), "sqrt(1/2)");
+        assert_eq!(exact((1, -1), (1, 1), (0, 0)), "-sqrt(1/2)");
+        // ⟨1 1 1/2 -1/2 | 1/2 1/2⟩
+        assert_eq!(exact((2, 2), (1, -1), (1, 1)), "sqrt(2/3)");
+        assert_eq!(exact((2, 0), (1, 1), (1, 1)), "-sqrt(1/3)");
+        assert_eq!(exact((2, 2), (2, 2), (4, 4)), "1");
+        // ⟨2 0 2 0 | 2 0⟩
+        assert_eq!(exact((4, 0), (4, 0), (4, 0)), "-sqrt(2/7)");
+        // forbidden
+        assert_eq!(exact((2, 2), (2, 0), (4, 0)), "0");
+        assert_eq!(exact((2, 0), (2, 0), (2, 0)), "0");
+
+        // both ways agree
+        for j1 in 0..=6 {
+            for j2 in 0..=5 {
+                for j in 0..=11 {
+                    for m1 in -j1..=j1 {
+                        for m2 in -j2..=j2 {
+                            let args = ((h(j1), h(m1)), (h(j2), h(m2)), (h(j), h(m1 + m2)));
+                            let float = clebsch_gordan(args.0, args.1, args.2);
+                            let exact = clebsch_gordan_exact(args.0, args.1, args.2)
+                                .expect("Momenta are small")
+                                .to_f64();
+                            assert!((float - exact).abs() < 1e-12, "{args:?}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large_momenta() {
+        // 181! doesn't fit into f64
+        let h = |j| (HalfInt::from_int(j), HalfInt::ZERO);
+        let exact = |j1, j2, j| {
+            clebsch_gordan_exact(j1, j2, j)
+                .expect("Momenta are small")
+                .to_f64()
+        };
+        // terms of the sum cancel out to ten digits, and the ones of j = 200 to forty
+        for j in [60, 200] {
+            let value = clebsch_gordan(h(j), h(j), h(j));
+            assert!((value - exact(h(j), h(j), h(j))).abs() < 1e-12);
+        }
+        // ⟨j 0 j 0 | 0 0⟩ = (-1)^j / sqrt(2j+1), too large to compute exactly
+        let value = clebsch_gordan(h(2000), h(2000), h(0));
+        assert!((value - 1.0 / 4001f64.sqrt()).abs() < 1e-12);
+        assert!(clebsch_gordan_exact(h(2000), h(2000), h(0)).is_none());
+    }
+
+    #[test]
+    fn range() {
+        let h = HalfInt::from_int;
+        assert!(momenta_in_range(&[h(60), h(60), h(0), h(0), h(60), h(0)]));
+        assert!(!momenta_in_range(&[
+            h(1_000_000_000),
+            h(1),
+            h(0),
+            h(0),
+            h(1_000_000_000),
+            h(0)
+        ]));
+        // checked by the functions themselves, without overflowing
+        let huge = (
+            HalfInt::from_doubled(i32::MAX),
+            HalfInt::from_doubled(i32::MIN),
+        );
+        assert!(clebsch_gordan(huge, huge, huge).is_nan());
+        let large = (h(20_000), HalfInt::ZERO);
+        assert!(clebsch_gordan(large, large, large).is_nan());
+        assert!(clebsch_gordan_exact(huge, huge, huge).is_none());
+        let small = (HalfInt::ONE, HalfInt::from_doubled(i32::MIN));
+        assert_eq!(
+            clebsch_gordan_exact(small, small, small).map(|exact| exact.is_zero()),
+            Some(true)
+        );
+    }
+}
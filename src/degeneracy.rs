@@ -0,0 +1,125 @@
+use num_bigint::BigUint;
+use thiserror::Error;
+
+use crate::{Configuration, SubLevel, TermType};
+
+/// Binomial coefficient, or `None` if it does not fit into `u128`
+pub(crate) fn binomial(n: u128, k: u128) -> Option<u128> {
+    let k = k.min(n - k);
+    (0..k).try_fold(1u128, |c, i| Some(c.checked_mul(n - i)? / (i + 1)))
+}
+
+impl SubLevel {
+    /// Number of microstates (Slater determinants) of the sublevel, C(4l+2, n)
+    ///
+    /// Returns `None` if the number does not fit into `u128`
+    pub fn microstate_count(&self) -> Option<u128> {
+        binomial(self.tp.max_electrons().into(), self.electrons.into())
+    }
+
+    /// Same as [`SubLevel::microstate_count`], but of any size
+    pub fn microstate_count_big(&self) -> BigUint {
+        let n = u32::from(self.tp.max_electrons());
+        let k = u32::from(self.electrons.min(self.holes()));
+        (0..k).fold(BigUint::from(1u32), |c, i| c * (n - i) / (i + 1))
+    }
+}
+
+impl Configuration {
+    /// Number of microstates of the configuration, product of microstate numbers of every subshell
+    ///
+    /// Returns `None` if the number does not fit into `u128`
+    pub fn microstate_count(&self) -> Option<u128> {
+        self.sublevels().iter().try_fold(1u128, |count, sublevel| {
+            count.checked_mul(sublevel.microstate_count()?)
+        })
+    }
+
+    /// Same as [`Configuration::microstate_count`], but of any size
+    pub fn microstate_count_big(&self) -> BigUint {
+        self.sublevels()
+            .iter()
+            .map(SubLevel::microstate_count_big)
+            .product()
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DegeneracyMismatch {
+    #[error("Terms contain {found} states in total, while there are {expected} microstates")]
+    Terms { expected: u128, found: u128 },
+    #[error(
+        "Levels of {term} term contain {found} states in total, while the term has {expected}"
+    )]
+    Levels {
+        term: TermType,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Checks that the terms account for exactly `microstates` states, and that levels of each term account for all of its states
+pub fn check_degeneracy(microstates: u128, terms: &[TermType]) -> Result<(), DegeneracyMismatch> {
+    for term in terms {
+        let found = term.levels().iter().map(|level| level.degeneracy()).sum();
+        if found != term.degeneracy() {
+            return Err(DegeneracyMismatch::Levels {
+                term: term.clone(),
+                expected: term.degeneracy(),
+                found,
+            });
+        }
+    }
+    let found = terms.iter().map(|term| term.degeneracy() as u128).sum();
+    if found != microstates {
+        return Err(DegeneracyMismatch::Terms {
+            expected: microstates,
+            found,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{check_degeneracy, ee_terms, Configuration, SubLevel, SubLevelType};
+
+    #[test]
+    fn degeneracies_sum_up() {
+        for l in 0..=3 {
+            let tp = SubLevelType::new(l).unwrap();
+            for electrons in 0..=tp.max_electrons() {
+                let sublevel = SubLevel::new(tp.clone(), electrons).unwrap();
+                let microstates = sublevel.microstate_count().unwrap();
+                let terms = ee_terms(sublevel).unwrap();
+                check_degeneracy(microstates, &terms).expect("Should be consistent");
+            }
+        }
+
+        let configuration: Configuration = "3d2 4p1".parse().unwrap();
+        assert_eq!(configuration.microstate_count(), Some(45 * 6));
+        check_degeneracy(
+            configuration.microstate_count().unwrap(),
+            &configuration.ls_terms().unwrap(),
+        )
+        .expect("Should be consistent");
+    }
+
+    #[test]
+    fn huge_microstate_count() {
+        let sublevel = SubLevel::new(SubLevelType::new(63).unwrap(), 127).unwrap();
+        assert_eq!(sublevel.microstate_count(), None);
+        // C(254, 127)
+        assert_eq!(
+            sublevel.microstate_count_big().to_string(),
+            "1447820253728428257402917234914456316923033525201609294458588001195800784512"
+        );
+        let sublevel = SubLevel::new(SubLevelType::new(15).unwrap(), 31).unwrap();
+        assert_eq!(sublevel.microstate_count(), Some(465428353255261088));
+        assert_eq!(
+            sublevel.microstate_count_big(),
+            465428353255261088u128.into()
+        );
+    }
+}
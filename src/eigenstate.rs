@@ -0,0 +1,144 @@
+use itertools::Itertools;
+
+use crate::{
+    ee_terms_iter,
+    slater::{self, Combination},
+    DerivationError, HalfInt, Microstate, SeniorityTerm, SubLevel,
+};
+
+/// State |L S M_L M_S⟩ of a term, as a linear combination of microstates (Slater determinants)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Eigenstate {
+    pub ml: i32,
+    pub ms: HalfInt,
+    /// Microstates with non-zero coefficients, ordered by their occupation bitmasks
+    pub components: Vec<(Microstate, f64)>,
+}
+
+/// All of the (2L+1)(2S+1) states of a term
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TermEigenstates {
+    pub term: SeniorityTerm,
+    /// States ordered by descending M_L, and then by descending M_S
+    pub states: Vec<Eigenstate>,
+}
+
+impl SubLevel {
+    /// Constructs the eigenstates of L², S², L_z and S_z of every term, as linear combinations of the microstates
+    ///
+    /// Highest weight state M_L = L, M_S = S of each term is found as a state annihilated by both L+ and S+, repeated terms are told apart by seniority (see [`SubLevel::cfp`] on the phase convention). The rest of the states are then obtained with the lowering operators L- and S- in the Condon–Shortley phase convention. Terms that are repeated even with the same seniority (some of the f^n ones) get an arbitrary orthonormal basis
+    ///
+    /// Terms are listed in the same order as by [`SubLevel::terms_with_seniority`], and all of the states together form an orthonormal basis of the sublevel states
+    pub fn eigenstates(&self) -> Result<Vec<TermEigenstates>, DerivationError> {
+        let l = self.tp.l();
+        if l > Microstate::MAX_L {
+            return Err(DerivationError::TooLarge(self.tp.clone()));
+        }
+        let parity = self.parity();
+        let mut result = Vec::new();
+        for term in ee_terms_iter(self.clone()).sorted().dedup() {
+            let highest = slater::seniority_states(l, self.electrons, term.momentum.0, term.spin);
+            for (seniority, highest) in highest {
+                let mut states = Vec::new();
+                let mut orbital = Some(highest);
+                for ml in (-(term.momentum.0 as i32)..=term.momentum.0 as i32).rev() {
+                    let Some(orbital_state) = orbital else { break };
+                    let mut spin = Some(orbital_state.clone());
+                    for ms in (-term.spin)
+                        .up_to(term.spin)
+                        .collect_vec()
+                        .into_iter()
+                        .rev()
+                    {
+                        let Some(state) = spin else { break };
+                        spin = slater::normalized(slater::lower_s(l, &state));
+                        states.push(Eigenstate {
+                            ml,
+                            ms,
+                            components: components(l, state),
+                        });
+                    }
+                    orbital = slater::normalized(slater::lower_l(l, &orbital_state));
+                }
+                result.push(TermEigenstates {
+                    term: SeniorityTerm {
+                        term: term.clone().with_parity(parity),
+                        seniority,
+                    },
+                    states,
+                });
+            }
+        }
+        result.sort_by(|a, b| a.term.cmp(&b.term));
+        Ok(result)
+    }
+}
+
+fn components(l: u8, state: Combination) -> Vec<(Microstate, f64)> {
+    state
+        .into_iter()
+        .map(|(occupation, coefficient)| (Microstate { l, occupation }, coefficient))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::SubLevel;
+
+    #[test]
+    fn p2_singlet_s() {
+        let p2: SubLevel = "p2".parse().unwrap();
+        let eigenstates = p2.eigenstates().unwrap();
+        let singlet = &eigenstates[1];
+        assert_eq!(singlet.term.to_string(), "^1_0S");
+        let [state] = &singlet.states[..] else {
+            panic!("^1S has a single state")
+        };
+        let components = state
+            .components
+            .iter()
+            .map(|(microstate, coefficient)| format!("{microstate}: {coefficient:.4}"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            components,
+            ["0- 0+: 0.5774", "-1+ 1-: 0.5774", "-1- 1+: -0.5774"]
+        );
+    }
+
+    #[test]
+    fn orthonormal_basis() {
+        for sublevel in ["p3", "d2", "d3"] {
+            let sublevel: SubLevel = sublevel.parse().unwrap();
+            let states = sublevel
+                .eigenstates()
+                .unwrap()
+                .into_iter()
+                .flat_map(|term| term.states)
+                .map(|state| {
+                    state
+                        .components
+                        .into_iter()
+                        .map(|(microstate, coefficient)| (microstate.occupation(), coefficient))
+                        .collect::<HashMap<_, _>>()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(states.len() as u128, sublevel.microstate_count().unwrap());
+            for (a, left) in states.iter().enumerate() {
+                for (b, right) in states.iter().enumerate() {
+                    let product: f64 = left
+                        .iter()
+                        .filter_map(|(occupation, coefficient)| {
+                            Some(coefficient * right.get(occupation)?)
+                        })
+                        .sum();
+                    let expected = if a == b { 1.0 } else { 0.0 };
+                    assert!((product - expected).abs() < 1e-9, "{sublevel}");
+                }
+            }
+        }
+    }
+}
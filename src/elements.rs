@@ -0,0 +1,452 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{notation, Configuration, Notated, Notation, SubLevel, SubLevelType};
+
+/// Chemical element along with its ground-state configuration
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Element {
+    /// Atomic number Z
+    pub number: u8,
+    pub symbol: &'static str,
+    pub name: &'static str,
+    configuration: &'static str,
+}
+
+const fn element(
+    number: u8,
+    symbol: &'static str,
+    name: &'static str,
+    configuration: &'static str,
+) -> Element {
+    Element {
+        number,
+        symbol,
+        name,
+        configuration,
+    }
+}
+
+/// Ground-state configurations of the neutral atoms, following the NIST Atomic Spectra Database (including the exceptions to the Aufbau principle, like Cr and Cu). Configurations of the superheavy elements are predicted ones
+static ELEMENTS: &[Element] = &[
+    element(1, "H", "Hydrogen", "1s1"),
+    element(2, "He", "Helium", "1s2"),
+    element(3, "Li", "Lithium", "[He] 2s1"),
+    element(4, "Be", "Beryllium", "[He] 2s2"),
+    element(5, "B", "Boron", "[He] 2s2 2p1"),
+    element(6, "C", "Carbon", "[He] 2s2 2p2"),
+    element(7, "N", "Nitrogen", "[He] 2s2 2p3"),
+    element(8, "O", "Oxygen", "[He] 2s2 2p4"),
+    element(9, "F", "Fluorine", "[He] 2s2 2p5"),
+    element(10, "Ne", "Neon", "[He] 2s2 2p6"),
+    element(11, "Na", "Sodium", "[Ne] 3s1"),
+    element(12, "Mg", "Magnesium", "[Ne] 3s2"),
+    element(13, "Al", "Aluminium", "[Ne] 3s2 3p1"),
+    element(14, "Si", "Silicon", "[Ne] 3s2 3p2"),
+    element(15, "P", "Phosphorus", "[Ne] 3s2 3p3"),
+    element(16, "S", "Sulfur", "[Ne] 3s2 3p4"),
+    element(17, "Cl", "Chlorine", "[Ne] 3s2 3p5"),
+    element(18, "Ar", "Argon", "[Ne] 3s2 3p6"),
+    element(19, "K", "Potassium", "[Ar] 4s1"),
+    element(20, "Ca", "Calcium", "[Ar] 4s2"),
+    element(21, "Sc", "Scandium", "[Ar] 3d1 4s2"),
+    element(22, "Ti", "Titanium", "[Ar] 3d2 4s2"),
+    element(23, "V", "Vanadium", "[Ar] 3d3 4s2"),
+    element(24, "Cr", "Chromium", "[Ar] 3d5 4s1"),
+    element(25, "Mn", "Manganese", "[Ar] 3d5 4s2"),
+    element(26, "Fe", "Iron", "[Ar] 3d6 4s2"),
+    element(27, "Co", "Cobalt", "[Ar] 3d7 4s2"),
+    element(28, "Ni", "Nickel", "[Ar] 3d8 4s2"),
+    element(29, "Cu", "Copper", "[Ar] 3d10 4s1"),
+    element(30, "Zn", "Zinc", "[Ar] 3d10 4s2"),
+    element(31, "Ga", "Gallium", "[Ar] 3d10 4s2 4p1"),
+    element(32, "Ge", "Germanium", "[Ar] 3d10 4s2 4p2"),
+    element(33, "As", "Arsenic", "[Ar] 3d10 4s2 4p3"),
+    element(34, "Se", "Selenium", "[Ar] 3d10 4s2 4p4"),
+    element(35, "Br", "Bromine", "[Ar] 3d10 4s2 4p5"),
+    element(36, "Kr", "Krypton", "[Ar] 3d10 4s2 4p6"),
+    element(37, "Rb", "Rubidium", "[Kr] 5s1"),
+    element(38, "Sr", "Strontium", "[Kr] 5s2"),
+    element(39, "Y", "Yttrium", "[Kr] 4d1 5s2"),
+    element(40, "Zr", "Zirconium", "[Kr] 4d2 5s2"),
+    element(41, "Nb", "Niobium", "[Kr] 4d4 5s1"),
+    element(42, "Mo", "Molybdenum", "[Kr] 4d5 5s1"),
+    element(43, "Tc", "Technetium", "[Kr] 4d5 5s2"),
+    element(44, "Ru", "Ruthenium", "[Kr] 4d7 5s1"),
+    element(45, "Rh", "Rhodium", "[Kr] 4d8 5s1"),
+    element(46, "Pd", "Palladium", "[Kr] 4d10"),
+    element(47, "Ag", "Silver", "[Kr] 4d10 5s1"),
+    element(48, "Cd", "Cadmium", "[Kr] 4d10 5s2"),
+    element(49, "In", "Indium", "[Kr] 4d10 5s2 5p1"),
+    element(50, "Sn", "Tin", "[Kr] 4d10 5s2 5p2"),
+    element(51, "Sb", "Antimony", "[Kr] 4d10 5s2 5p3"),
+    element(52, "Te", "Tellurium", "[Kr] 4d10 5s2 5p4"),
+    element(53, "I", "Iodine", "[Kr] 4d10 5s2 5p5"),
+    element(54, "Xe", "Xenon", "[Kr] 4d10 5s2 5p6"),
+    element(55, "Cs", "Caesium", "[Xe] 6s1"),
+    element(56, "Ba", "Barium", "[Xe] 6s2"),
+    element(57, "La", "Lanthanum", "[Xe] 5d1 6s2"),
+    element(58, "Ce", "Cerium", "[Xe] 4f1 5d1 6s2"),
+    element(59, "Pr", "Praseodymium", "[Xe] 4f3 6s2"),
+    element(60, "Nd", "Neodymium", "[Xe] 4f4 6s2"),
+    element(61, "Pm", "Promethium", "[Xe] 4f5 6s2"),
+    element(62, "Sm", "Samarium", "[Xe] 4f6 6s2"),
+    element(63, "Eu", "Europium", "[Xe] 4f7 6s2"),
+    element(64, "Gd", "Gadolinium", "[Xe] 4f7 5d1 6s2"),
+    element(65, "Tb", "Terbium", "[Xe] 4f9 6s2"),
+    element(66, "Dy", "Dysprosium", "[Xe] 4f10 6s2"),
+    element(67, "Ho", "Holmium", "[Xe] 4f11 6s2"),
+    element(68, "Er", "Erbium", "[Xe] 4f12 6s2"),
+    element(69, "Tm", "Thulium", "[Xe] 4f13 6s2"),
+    element(70, "Yb", "Ytterbium", "[Xe] 4f14 6s2"),
+    element(71, "Lu", "Lutetium", "[Xe] 4f14 5d1 6s2"),
+    element(72, "Hf", "Hafnium", "[Xe] 4f14 5d2 6s2"),
+    element(73, "Ta", "Tantalum", "[Xe] 4f14 5d3 6s2"),
+    element(74, "W", "Tungsten", "[Xe] 4f14 5d4 6s2"),
+    element(75, "Re", "Rhenium", "[Xe] 4f14 5d5 6s2"),
+    element(76, "Os", "Osmium", "[Xe] 4f14 5d6 6s2"),
+    element(77, "Ir", "Iridium", "[Xe] 4f14 5d7 6s2"),
+    element(78, "Pt", "Platinum", "[Xe] 4f14 5d9 6s1"),
+    element(79, "Au", "Gold", "[Xe] 4f14 5d10 6s1"),
+    element(80, "Hg", "Mercury", "[Xe] 4f14 5d10 6s2"),
+    element(81, "Tl", "Thallium", "[Xe] 4f14 5d10 6s2 6p1"),
+    element(82, "Pb", "Lead", "[Xe] 4f14 5d10 6s2 6p2"),
+    element(83, "Bi", "Bismuth", "[Xe] 4f14 5d10 6s2 6p3"),
+    element(84, "Po", "Polonium", "[Xe] 4f14 5d10 6s2 6p4"),
+    element(85, "At", "Astatine", "[Xe] 4f14 5d10 6s2 6p5"),
+    element(86, "Rn", "Radon", "[Xe] 4f14 5d10 6s2 6p6"),
+    element(87, "Fr", "Francium", "[Rn] 7s1"),
+    element(88, "Ra", "Radium", "[Rn] 7s2"),
+    element(89, "Ac", "Actinium", "[Rn] 6d1 7s2"),
+    element(90, "Th", "Thorium", "[Rn] 6d2 7s2"),
+    element(91, "Pa", "Protactinium", "[Rn] 5f2 6d1 7s2"),
+    element(92, "U", "Uranium", "[Rn] 5f3 6d1 7s2"),
+    element(93, "Np", "Neptunium", "[Rn] 5f4 6d1 7s2"),
+    element(94, "Pu", "Plutonium", "[Rn] 5f6 7s2"),
+    element(95, "Am", "Americium", "[Rn] 5f7 7s2"),
+    element(96, "Cm", "Curium", "[Rn] 5f7 6d1 7s2"),
+    element(97, "Bk", "Berkelium", "[Rn] 5f9 7s2"),
+    element(98, "Cf", "Californium", "[Rn] 5f10 7s2"),
+    element(99, "Es", "Einsteinium", "[Rn] 5f11 7s2"),
+    element(100, "Fm", "Fermium", "[Rn] 5f12 7s2"),
+    element(101, "Md", "Mendelevium", "[Rn] 5f13 7s2"),
+    element(102, "No", "Nobelium", "[Rn] 5f14 7s2"),
+    element(103, "Lr", "Lawrencium", "[Rn] 5f14 7s2 7p1"),
+    element(104, "Rf", "Rutherfordium", "[Rn] 5f14 6d2 7s2"),
+    element(105, "Db", "Dubnium", "[Rn] 5f14 6d3 7s2"),
+    element(106, "Sg", "Seaborgium", "[Rn] 5f14 6d4 7s2"),
+    element(107, "Bh", "Bohrium", "[Rn] 5f14 6d5 7s2"),
+    element(108, "Hs", "Hassium", "[Rn] 5f14 6d6 7s2"),
+    element(109, "Mt", "Meitnerium", "[Rn] 5f14 6d7 7s2"),
+    element(110, "Ds", "Darmstadtium", "[Rn] 5f14 6d8 7s2"),
+    element(111, "Rg", "Roentgenium", "[Rn] 5f14 6d9 7s2"),
+    element(112, "Cn", "Copernicium", "[Rn] 5f14 6d10 7s2"),
+    element(113, "Nh", "Nihonium", "[Rn] 5f14 6d10 7s2 7p1"),
+    element(114, "Fl", "Flerovium", "[Rn] 5f14 6d10 7s2 7p2"),
+    element(115, "Mc", "Moscovium", "[Rn] 5f14 6d10 7s2 7p3"),
+    element(116, "Lv", "Livermorium", "[Rn] 5f14 6d10 7s2 7p4"),
+    element(117, "Ts", "Tennessine", "[Rn] 5f14 6d10 7s2 7p5"),
+    element(118, "Og", "Oganesson", "[Rn] 5f14 6d10 7s2 7p6"),
+];
+
+impl Element {
+    /// All of the elements, ordered by atomic number
+    pub fn all() -> &'static [Element] {
+        ELEMENTS
+    }
+
+    pub fn by_number(number: u8) -> Option<&'static Element> {
+        ELEMENTS.get(usize::from(number).checked_sub(1)?)
+    }
+
+    /// Finds the element by its symbol, ignoring the case
+    pub fn by_symbol(symbol: &str) -> Option<&'static Element> {
+        ELEMENTS
+            .iter()
+            .find(|element| element.symbol.eq_ignore_ascii_case(symbol))
+    }
+
+    /// Ground-state configuration of the neutral atom, written with the noble gas core
+    pub fn configuration_str(&self) -> &'static str {
+        self.configuration
+    }
+
+    /// Number of subshells in the noble gas core of the configuration
+    fn core_size(&self) -> usize {
+        self.configuration
+            .split_whitespace()
+            .next()
+            .filter(|core| core.starts_with('['))
+            .map(|core| {
+                core.parse::<Configuration>()
+                    .expect("Cores of the table are valid")
+                    .sublevels
+                    .len()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Ground-state configuration of the neutral atom, with the noble gas core expanded
+    pub fn configuration(&self) -> Configuration {
+        self.configuration
+            .parse()
+            .expect("Configurations of the table are valid")
+    }
+}
+
+/// Parses element symbols like `Fe`, ignoring the case, or the element names like `Iron`
+impl FromStr for &'static Element {
+    type Err = UnknownElement;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Element::by_symbol(s)
+            .or_else(|| {
+                ELEMENTS
+                    .iter()
+                    .find(|element| element.name.eq_ignore_ascii_case(s))
+            })
+            .ok_or_else(|| UnknownElement(s.to_string()))
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("Unknown element \"{0}\"")]
+pub struct UnknownElement(String);
+
+/// Atom or ion of the element, with the charge in units of the elementary charge
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ion {
+    pub element: &'static Element,
+    pub charge: i8,
+}
+
+impl Ion {
+    /// Most electrons of an ion, as many as the subshells filled by [`Ion::configuration`] (n up to 8, and l up to 3) hold
+    pub const MAX_ELECTRONS: u8 = 188;
+
+    pub fn new(element: &'static Element, charge: i8) -> Result<Self, IonError> {
+        let electrons = i16::from(element.number) - i16::from(charge);
+        if electrons < 0 {
+            return Err(IonError::TooHighCharge(charge, element));
+        }
+        if electrons > i16::from(Self::MAX_ELECTRONS) {
+            return Err(IonError::TooLowCharge(charge, element));
+        }
+        Ok(Self { element, charge })
+    }
+
+    pub fn electrons(&self) -> u8 {
+        (i16::from(self.element.number) - i16::from(self.charge)) as u8
+    }
+
+    /// Ground-state configuration of the ion
+    ///
+    /// Electrons are removed from the ground configuration of the neutral atom starting with the outermost subshell outside of the noble gas core: the one with the highest n, and then with the highest l (so that 4s is ionized before 3d, and 6s and 5d before 4f, like in Fe^2+ = `[Ar] 3d^6`). Core electrons are removed only after the rest of them. Extra electrons of negative ions fill the subshells in the Aufbau (Madelung) order, up to [`Ion::MAX_ELECTRONS`] of them
+    pub fn configuration(&self) -> Configuration {
+        let mut configuration = self.element.configuration();
+        let core = self.element.core_size();
+        let sublevels = &mut configuration.sublevels;
+        for _ in 0..self.charge.max(0) {
+            if let Some((_, outermost)) = sublevels
+                .iter_mut()
+                .enumerate()
+                .filter(|(_, sublevel)| sublevel.electrons > 0)
+                .max_by_key(|(i, sublevel)| (*i >= core, sublevel.n, sublevel.tp.0))
+            {
+                outermost.electrons -= 1;
+            }
+        }
+        sublevels.retain(|sublevel| sublevel.electrons > 0);
+        for _ in 0..self.charge.min(0).unsigned_abs() {
+            let free = (1u8..=8)
+                .flat_map(|n| (0..n.min(4)).map(move |l| (n, l)))
+                .filter(|&(n, l)| {
+                    !sublevels.iter().any(|sublevel| {
+                        sublevel.n == Some(n)
+                            && sublevel.tp.0 == l
+                            && sublevel.electrons == sublevel.tp.max_electrons()
+                    })
+                })
+                .min_by_key(|&(n, l)| (n + l, n));
+            // only the ions not created by `Ion::new` run out of the subshells
+            let Some((n, l)) = free else {
+                break;
+            };
+            match sublevels
+                .iter_mut()
+                .find(|sublevel| sublevel.n == Some(n) && sublevel.tp.0 == l)
+            {
+                Some(sublevel) => sublevel.electrons += 1,
+                None => sublevels.push(SubLevel {
+                    n: Some(n),
+                    tp: SubLevelType(l),
+                    electrons: 1,
+                }),
+            }
+        }
+        configuration
+    }
+}
+
+/// Charge goes as a superscript, like `Fe^2+` or `Cl^-`
+impl Notated for Ion {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_str(self.element.symbol)?;
+        let sign = if self.charge > 0 { '+' } else { '-' };
+        match self.charge.unsigned_abs() {
+            0 => Ok(()),
+            1 => notation::superscript(notation, sign, f),
+            charge => notation::superscript(notation, format_args!("{charge}{sign}"), f),
+        }
+    }
+}
+
+impl Display for Ion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+/// Parses an element with the optional charge, like `Fe`, `Fe2+`, `Fe^2+`, `Cl-`, or in the spectroscopic notation, like `Fe III` (that is Fe^2+)
+impl FromStr for Ion {
+    type Err = IonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || IonError::Charge(s.to_string());
+        if let Some((symbol, spectrum)) = s.split_once(char::is_whitespace) {
+            let element = symbol.parse()?;
+            let spectrum = roman(spectrum.trim()).ok_or_else(invalid)?;
+            let charge = i8::try_from(spectrum - 1).map_err(|_| invalid())?;
+            return Self::new(element, charge);
+        }
+        let split = s
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len());
+        let (symbol, charge) = s.split_at(split);
+        let element = symbol.parse()?;
+        let charge = notation::normal_digits(charge.strip_prefix('^').unwrap_or(charge));
+        let charge = if charge.is_empty() {
+            0
+        } else {
+            let (magnitude, sign) = charge.split_at(charge.len() - 1);
+            let magnitude: i8 = if magnitude.is_empty() {
+                1
+            } else {
+                magnitude.parse().map_err(|_| invalid())?
+            };
+            match sign {
+                "+" => magnitude,
+                "-" => -magnitude,
+                _ => return Err(invalid()),
+            }
+        };
+        Self::new(element, charge)
+    }
+}
+
+/// Value of a Roman numeral, like `III`
+fn roman(s: &str) -> Option<u16> {
+    let digit = |c| match c {
+        'I' => Some(1),
+        'V' => Some(5),
+        'X' => Some(10),
+        'L' => Some(50),
+        'C' => Some(100),
+        _ => None,
+    };
+    let digits = s.chars().map(digit).collect::<Option<Vec<u16>>>()?;
+    let mut value = 0;
+    for (i, digit) in digits.iter().enumerate() {
+        if digits.get(i + 1).is_some_and(|next| next > digit) {
+            value -= *digit as i32;
+        } else {
+            value += *digit as i32;
+        }
+    }
+    u16::try_from(value).ok().filter(|value| *value > 0)
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum IonError {
+    #[error(transparent)]
+    Element(#[from] UnknownElement),
+    #[error("Invalid charge of the ion \"{0}\"")]
+    Charge(String),
+    #[error("Charge {0} is higher than the number of electrons of {}", .1.symbol)]
+    TooHighCharge(i8, &'static Element),
+    #[error("Charge {0} gives {} more than {max} electrons, which is not supported", .1.symbol, max = Ion::MAX_ELECTRONS)]
+    TooLowCharge(i8, &'static Element),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Element, Ion, Notated, Notation};
+
+    #[test]
+    fn ground_configurations() {
+        for (z, element) in Element::all().iter().enumerate() {
+            assert_eq!(usize::from(element.number), z + 1);
+            let electrons: usize = element
+                .configuration()
+                .sublevels()
+                .iter()
+                .map(|sublevel| usize::from(sublevel.electrons()))
+                .sum();
+            assert_eq!(electrons, z + 1, "{}", element.symbol);
+        }
+        let iron: &Element = "fe".parse().unwrap();
+        assert_eq!(iron.configuration_str(), "[Ar] 3d6 4s2");
+        assert_eq!("Iron".parse::<&Element>().unwrap(), iron);
+        assert_eq!(Element::by_number(26), Some(iron));
+        assert!(Element::by_number(0).is_none());
+        assert!("Xx".parse::<&Element>().is_err());
+    }
+
+    #[test]
+    fn ions() {
+        let configuration = |ion: &str| {
+            ion.parse::<Ion>()
+                .unwrap()
+                .configuration()
+                .open_subshells()
+                .to_string()
+        };
+        assert_eq!(configuration("Fe2+"), "3d^6");
+        assert_eq!(configuration("Fe III"), "3d^6");
+        assert_eq!(configuration("Fe^3+"), "3d^5");
+        assert_eq!(configuration("Cu+"), "");
+        assert_eq!(configuration("Ce3+"), "4f^1");
+        assert_eq!(configuration("Nd3+"), "4f^3");
+        assert_eq!(configuration("O-"), "2p^5");
+        assert_eq!(configuration("Cl-"), "");
+        assert_eq!(configuration("H-"), "");
+        let ion: Ion = "Fe2+".parse().unwrap();
+        assert_eq!(ion.electrons(), 24);
+        assert_eq!(ion.to_string(), "Fe^2+");
+        assert_eq!(ion.notated(Notation::Unicode).to_string(), "Fe²⁺");
+        assert!("He3+".parse::<Ion>().is_err());
+        assert!("Og127-".parse::<Ion>().is_err());
+        let electrons = "Og70-"
+            .parse::<Ion>()
+            .unwrap()
+            .configuration()
+            .sublevels()
+            .iter()
+            .map(|sublevel| sublevel.electrons())
+            .sum::<u8>();
+        assert_eq!(electrons, Ion::MAX_ELECTRONS);
+        assert!("Fe2*".parse::<Ion>().is_err());
+        assert!("Fe IIII2".parse::<Ion>().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    logger::logln, DerivationError, DerivationLogger, HalfInt, Microstate, SubLevel, TermType,
+};
+
+/// Step of the derivation of the terms of a sublevel, in the order they happen, see [`crate::ee_terms_observed`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "event"))]
+pub enum DerivationEvent {
+    /// Derivation of the terms of the sublevel starts, it has `single_states` spin-orbitals
+    Started {
+        sublevel: SubLevel,
+        single_states: usize,
+    },
+    /// Spin-orbital number `index` is enumerated
+    SingleStateEnumerated { index: usize, ml: i8, ms: HalfInt },
+    /// Microstates are enumerated and binned next, `count` of them; if the sublevel is more than half-filled, `holes` are placed instead of the electrons
+    MicrostatesEnumerated { count: usize, holes: Option<u8> },
+    /// Microstate is put into the table by its ML and MS
+    MicrostateBinned {
+        microstate: Microstate,
+        ml: i32,
+        ms: HalfInt,
+    },
+    /// Every microstate is binned, terms are extracted next
+    ExtractionStarted,
+    /// Term is extracted along with the microstates assigned to it
+    TermExtracted {
+        term: TermType,
+        states: Vec<Microstate>,
+    },
+}
+
+/// Stage of the derivation of the terms of a sublevel, see [`DerivationProgress`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DerivationPhase {
+    /// Microstates are enumerated and counted by their ML and MS
+    Enumeration,
+    /// Terms are extracted from the counts, taking the microstates of each
+    Extraction,
+}
+
+/// Progress of the derivation of the terms of a sublevel, reported by [`ee_terms_progress`](crate::ee_terms_progress)
+///
+/// Either phase goes through all of the microstates (of the hole equivalent, if the sublevel is more than half-filled), so `total` is the same in both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DerivationProgress {
+    pub phase: DerivationPhase,
+    /// Microstates enumerated, or taken by the extracted terms, so far
+    pub processed: u128,
+    pub total: u128,
+}
+
+/// Receiver of the [derivation events](DerivationEvent)
+///
+/// Implemented for closures taking `&DerivationEvent`, and for [`NoLog`](crate::NoLog), which skips creating the events altogether
+pub trait DerivationObserver {
+    /// Whether the events are used at all; if not, they are not even created
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn event(&mut self, event: &DerivationEvent);
+
+    /// Whether the derivation should stop, checked before every microstate and term; once it is, the derivation fails with [`DerivationError::Cancelled`](crate::DerivationError::Cancelled)
+    fn cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl<F: FnMut(&DerivationEvent)> DerivationObserver for F {
+    fn event(&mut self, event: &DerivationEvent) {
+        self(event)
+    }
+}
+
+impl DerivationObserver for crate::NoLog {
+    fn enabled(&self) -> bool {
+        false
+    }
+
+    fn event(&mut self, _event: &DerivationEvent) {}
+}
+
+const SEPARATOR: &str = " ----- ";
+
+/// Writes the events to a logger as prose; the first failure is kept, and the rest of the log is skipped
+pub(crate) struct LogObserver<'a, L: DerivationLogger + ?Sized> {
+    log: &'a mut L,
+    error: Option<std::io::Error>,
+}
+
+impl<'a, L: DerivationLogger + ?Sized> LogObserver<'a, L> {
+    pub(crate) fn new(log: &'a mut L) -> Self {
+        Self { log, error: None }
+    }
+
+    pub(crate) fn finish(self) -> Result<(), DerivationError> {
+        match self.error {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn write(&mut self, event: &DerivationEvent) -> std::io::Result<()> {
+        let log = &mut *self.log;
+        match event {
+            DerivationEvent::Started {
+                sublevel,
+                single_states,
+            } => {
+                logln!(log, "Sublevel: {sublevel}")?;
+                logln!(log, "{SEPARATOR}")?;
+                logln!(log, "Single electron states ({single_states} total)")
+            }
+            DerivationEvent::SingleStateEnumerated { index, ml, ms } => {
+                logln!(log, "{index}: ({ml}, {ms})")
+            }
+            DerivationEvent::MicrostatesEnumerated { count, holes } => {
+                logln!(log, "{SEPARATOR}")?;
+                if let Some(holes) = holes {
+                    logln!(
+                        log,
+                        "Sublevel is more than half-filled, enumerating states of {holes} holes instead"
+                    )?;
+                }
+                logln!(log, "Level states")?;
+                logln!(log, "({count} total)")
+            }
+            DerivationEvent::MicrostateBinned { microstate, ml, ms } => {
+                logln!(log, "{}: ({ml}, {ms})", IndexLabel(microstate))
+            }
+            DerivationEvent::ExtractionStarted => {
+                logln!(log, "{SEPARATOR}")?;
+                logln!(log, "Terms:")
+            }
+            DerivationEvent::TermExtracted { term, states } => {
+                logln!(log, "{term}")?;
+                states
+                    .iter()
+                    .try_for_each(|state| logln!(log, "- {}", IndexLabel(state)))
+            }
+        }
+    }
+}
+
+impl<L: DerivationLogger + ?Sized> DerivationObserver for LogObserver<'_, L> {
+    fn enabled(&self) -> bool {
+        self.log.enabled() && self.error.is_none()
+    }
+
+    fn event(&mut self, event: &DerivationEvent) {
+        if let Err(error) = self.write(event) {
+            self.error = Some(error);
+        }
+    }
+}
+
+/// Lists 1-based indices of the occupied single-electron states, like `1 2 5 `, straight into the log line
+struct IndexLabel<'a>(&'a Microstate);
+
+impl std::fmt::Display for IndexLabel<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0
+            .indices()
+            .try_for_each(|index| write!(f, "{} ", index + 1))
+    }
+}
@@ -0,0 +1,230 @@
+//! Energies of the magnetic sublevels of a term as the field grows, from the anomalous Zeeman effect to the Paschen–Back one
+
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::{EnergyUnit, HalfInt, Level, MagneticField, Notated, Notation, TermType};
+
+/// Script drawing the chart of [`ZeemanFan::to_html`]
+const PLOTLY: &str = "https://cdn.plot.ly/plotly-2.35.2.min.js";
+/// Colors of the levels the curves start from
+const COLORS: [&str; 8] = [
+    "#1f77b4", "#d62728", "#2ca02c", "#9467bd", "#ff7f0e", "#17becf", "#8c564b", "#e377c2",
+];
+
+/// Magnetic sublevel of a term followed through a range of fields
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FanCurve {
+    /// Projection M_L + M_S conserved by the field, which is M_J in a weak field
+    pub m: HalfInt,
+    /// J of the level the sublevel belongs to without the field
+    pub j: HalfInt,
+    /// Energy at every field of the chart
+    pub energies: Vec<f64>,
+}
+
+/// Energies of the magnetic sublevels of a term at evenly spaced fields, see [`TermType::zeeman_fan`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZeemanFan {
+    pub term: TermType,
+    /// Spin-orbit constant A, in `unit`
+    pub spin_orbit: f64,
+    pub unit: EnergyUnit,
+    pub fields: Vec<MagneticField>,
+    pub curves: Vec<FanCurve>,
+}
+
+impl TermType {
+    /// Energies of every magnetic sublevel of the term at `steps + 1` evenly spaced fields from zero to `max_field`, see [`TermType::in_field`]
+    ///
+    /// Sublevels with the same M never cross, so each curve is the sublevel with the same M and the same place in energy among them at every field. Curves are labeled with the J of the levels they start from, which are only distinct for a nonzero `spin_orbit`
+    pub fn zeeman_fan(
+        &self,
+        spin_orbit: f64,
+        max_field: MagneticField,
+        steps: usize,
+        unit: EnergyUnit,
+    ) -> ZeemanFan {
+        let steps = steps.max(1);
+        let fields = (0..=steps)
+            .map(|step| MagneticField::tesla(max_field.as_tesla() * step as f64 / steps as f64))
+            .collect_vec();
+        // the states are sorted by energy, so the sort keeps them in order within every M
+        let spectrum = |field| {
+            let mut states = self.in_field(spin_orbit, field, unit);
+            states.sort_by_key(|state| state.m);
+            states
+        };
+        let levels = self
+            .levels()
+            .into_iter()
+            .map(|level| (level.j, spin_orbit * self.spin_orbit_factor(level.j)))
+            .collect_vec();
+        let mut curves = spectrum(fields[0])
+            .into_iter()
+            .map(|state| {
+                let (j, _) = levels
+                    .iter()
+                    .min_by(|a, b| {
+                        (a.1 - state.energy)
+                            .abs()
+                            .total_cmp(&(b.1 - state.energy).abs())
+                    })
+                    .expect("Term has at least one level");
+                FanCurve {
+                    m: state.m,
+                    j: *j,
+                    energies: Vec::with_capacity(fields.len()),
+                }
+            })
+            .collect_vec();
+        for field in &fields {
+            for (curve, state) in curves.iter_mut().zip(spectrum(*field)) {
+                curve.energies.push(state.energy);
+            }
+        }
+        ZeemanFan {
+            term: self.clone(),
+            spin_orbit,
+            unit,
+            fields,
+            curves,
+        }
+    }
+
+    /// [J(J+1) - L(L+1) - S(S+1)] / 2, the fine-structure shift of a level in the units of A
+    fn spin_orbit_factor(&self, j: HalfInt) -> f64 {
+        let casimir = |x: HalfInt| x.casimir() as f64 / 4.0;
+        (casimir(j) - casimir(self.l_half_int()) - casimir(self.s())) / 2.0
+    }
+}
+
+impl Level {
+    /// Same as [`TermType::zeeman_fan`], but with only the sublevels starting from this level
+    pub fn zeeman_fan(
+        &self,
+        spin_orbit: f64,
+        max_field: MagneticField,
+        steps: usize,
+        unit: EnergyUnit,
+    ) -> ZeemanFan {
+        let mut fan = self.term.zeeman_fan(spin_orbit, max_field, steps, unit);
+        fan.curves.retain(|curve| curve.j == self.j);
+        fan
+    }
+}
+
+impl ZeemanFan {
+    /// Standalone HTML page with an interactive chart of the energies versus the field, drawn by plotly.js (loaded from its CDN); curves of the same zero-field level share a color
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        self.write_html(&mut out)
+            .expect("Writing to a string can't fail");
+        out
+    }
+
+    fn write_html(&self, out: &mut String) -> std::fmt::Result {
+        let term = self.term.notated(Notation::Html);
+        let number_list = |values: &mut dyn Iterator<Item = f64>| values.join(",");
+        let fields = number_list(&mut self.fields.iter().map(MagneticField::as_tesla));
+        let js = self.term.levels().iter().map(|level| level.j).collect_vec();
+
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html lang=\"en\">")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(
+            out,
+            "<title>Zeeman effect of {}</title>",
+            self.term.notated(Notation::Unicode)
+        )?;
+        writeln!(out, "<script src=\"{PLOTLY}\"></script>")?;
+        writeln!(
+            out,
+            "<style>body {{ font-family: sans-serif; margin: 2em; color: #222; }} #fan {{ height: 80vh; }}</style>"
+        )?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+        writeln!(out, "<h1>Zeeman effect of the {term} term</h1>")?;
+        writeln!(
+            out,
+            "<p>Spin-orbit constant <i>A</i> = {} {}. Weak fields split every level into 2<i>J</i>+1 sublevels with the Landé g-factor (anomalous Zeeman effect); strong ones decouple the orbital and spin momenta, so that the energies go as <i>μ</i><sub>B</sub><i>B</i> (<i>M<sub>L</sub></i> + <i>g<sub>s</sub> M<sub>S</sub></i>) (Paschen–Back effect).</p>",
+            self.spin_orbit, self.unit
+        )?;
+        writeln!(out, "<div id=\"fan\"></div>")?;
+        writeln!(out, "<script>")?;
+        writeln!(out, "const fields = [{fields}];")?;
+        writeln!(out, "const traces = [")?;
+        for curve in &self.curves {
+            let index = js.iter().position(|j| *j == curve.j).unwrap_or_default();
+            let level = Level {
+                term: self.term.clone(),
+                j: curve.j,
+            };
+            writeln!(
+                out,
+                "  {{ x: fields, y: [{}], name: \"M = {}\", legendgroup: \"{}\", legendgrouptitle: {{ text: \"{}\" }}, mode: \"lines\", line: {{ color: \"{}\" }} }},",
+                number_list(&mut curve.energies.iter().copied()),
+                curve.m,
+                curve.j,
+                level.notated(Notation::Html),
+                COLORS[index % COLORS.len()]
+            )?;
+        }
+        writeln!(out, "];")?;
+        writeln!(
+            out,
+            "Plotly.newPlot(\"fan\", traces, {{ xaxis: {{ title: {{ text: \"B, T\" }} }}, yaxis: {{ title: {{ text: \"E, {}\" }} }}, hovermode: \"closest\" }});",
+            self.unit
+        )?;
+        writeln!(out, "</script>")?;
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EnergyUnit, HalfInt, Level, MagneticField, TermType};
+
+    #[test]
+    fn doublet_p() {
+        let term: TermType = "2P".parse().unwrap();
+        let fan = term.zeeman_fan(
+            10.0,
+            MagneticField::tesla(100.0),
+            50,
+            EnergyUnit::Wavenumber,
+        );
+        assert_eq!(fan.fields.len(), 51);
+        assert_eq!(fan.curves.len(), 6);
+        assert_eq!(
+            fan.curves
+                .iter()
+                .filter(|curve| curve.j == HalfInt::from_doubled(3))
+                .count(),
+            4
+        );
+        for curve in &fan.curves {
+            assert_eq!(curve.energies.len(), 51);
+        }
+        // M = 3/2 is alone, so it goes straight up from the ^2P_3/2 level
+        let top = fan
+            .curves
+            .iter()
+            .find(|curve| curve.m == HalfInt::from_doubled(3))
+            .unwrap();
+        assert!((top.energies[0] - 5.0).abs() < 1e-9);
+        assert!(top.energies.windows(2).all(|pair| pair[1] > pair[0]));
+
+        let level: Level = "2P_1/2".parse().unwrap();
+        let fan = level.zeeman_fan(10.0, MagneticField::tesla(1.0), 10, EnergyUnit::Wavenumber);
+        assert_eq!(fan.curves.len(), 2);
+        let html = fan.to_html();
+        assert!(html.contains("Plotly.newPlot"));
+        assert_eq!(html.matches("legendgroup:").count(), 2);
+    }
+}
@@ -0,0 +1,438 @@
+//! C interface to the term derivation and the angular momentum coefficients, for the atomic structure codes in C and Fortran
+//!
+//! Half-integers are passed doubled (3 for j = 3/2), so that the callers only deal with integers, and strings are NUL-terminated UTF-8. Functions that may fail return null or a nonzero status, and [`chitose_last_error`] tells why. Panics don't cross the boundary: a panicking function fails the same way (coefficients become NaN)
+//!
+//! The library is built with `cargo rustc --lib --release --features ffi --crate-type cdylib` (or `staticlib`), and `include/chitose.h` is generated with `cbindgen --config cbindgen.toml --output include/chitose.h`
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{c_char, c_int, CStr, CString},
+    num::TryFromIntError,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{
+    clebsch_gordan, momenta_in_9j_range, momenta_in_range, wigner_3j, wigner_6j, wigner_9j,
+    Configuration, HalfInt, Level, Parity, TermType,
+};
+
+/// LS term
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChitoseTerm {
+    /// Orbital momentum L
+    pub momentum: u32,
+    /// Spin S, doubled
+    pub doubled_spin: u32,
+    /// 1 for the odd terms, 0 for the even ones
+    pub odd: c_int,
+}
+
+/// Level of an LS term
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChitoseLevel {
+    pub term: ChitoseTerm,
+    /// Total momentum J, doubled
+    pub doubled_j: u32,
+}
+
+/// Terms of a configuration, created by [`chitose_terms_new`] and destroyed by [`chitose_terms_free`]
+#[derive(Debug)]
+pub struct ChitoseTerms(Vec<ChitoseTerm>);
+
+impl TryFrom<&TermType> for ChitoseTerm {
+    type Error = TryFromIntError;
+
+    fn try_from(term: &TermType) -> Result<Self, Self::Error> {
+        Ok(Self {
+            momentum: u32::try_from(term.momentum.0)?,
+            doubled_spin: u32::try_from(term.spin.doubled())?,
+            odd: c_int::from(term.parity == Parity::Odd),
+        })
+    }
+}
+
+impl TryFrom<&Level> for ChitoseLevel {
+    type Error = TryFromIntError;
+
+    fn try_from(level: &Level) -> Result<Self, Self::Error> {
+        Ok(Self {
+            term: (&level.term).try_into()?,
+            doubled_j: u32::try_from(level.j.doubled())?,
+        })
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn fail(error: impl ToString) {
+    // the input came NUL-terminated, so the messages have no NUL in them
+    let message = CString::new(error.to_string()).ok();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Runs the body of an entry point, failing with `fallback` if it panics, since unwinding into the caller is undefined behavior
+fn guarded<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        fail(format!("Internal error: {}", panic_message(&*payload)));
+        fallback
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("panic")
+}
+
+/// Message of the last failure on the calling thread, or null if nothing failed yet
+///
+/// The message is owned by the library, and stays valid until the next failure on the same thread
+#[no_mangle]
+pub extern "C" fn chitose_last_error() -> *const c_char {
+    guarded(ptr::null(), || {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(ptr::null(), |message| message.as_ptr())
+        })
+    })
+}
+
+/// # Safety
+///
+/// `text` must be null or a NUL-terminated string
+unsafe fn configuration(text: *const c_char) -> Option<Configuration> {
+    if text.is_null() {
+        fail("Configuration is null");
+        return None;
+    }
+    // SAFETY: NUL-terminated, by the contract of the caller
+    let text = unsafe { CStr::from_ptr(text) };
+    let parsed = text
+        .to_str()
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            text.parse::<Configuration>()
+                .map_err(|error| error.to_string())
+        });
+    parsed.map_err(fail).ok()
+}
+
+/// Terms of the configuration, like "2p2 3d1", in spectroscopic order and with repetitions, or null if the configuration is invalid or its momenta don't fit into the fields
+///
+/// # Safety
+///
+/// `configuration` must be null or a NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn chitose_terms_new(configuration: *const c_char) -> *mut ChitoseTerms {
+    guarded(ptr::null_mut(), || {
+        // SAFETY: same contract
+        let Some(configuration) = (unsafe { self::configuration(configuration) }) else {
+            return ptr::null_mut();
+        };
+        let terms = match configuration.ls_terms() {
+            Ok(terms) => terms,
+            Err(error) => {
+                fail(error);
+                return ptr::null_mut();
+            }
+        };
+        let converted = terms
+            .iter()
+            .map(|term| ChitoseTerm::try_from(term).map_err(|_| term))
+            .collect::<Result<Vec<_>, _>>();
+        match converted {
+            Ok(terms) => Box::into_raw(Box::new(ChitoseTerms(terms))),
+            Err(term) => {
+                fail(format!("Momenta of the {term} term don't fit into 32 bits"));
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Number of the terms
+///
+/// # Safety
+///
+/// `terms` must be created by [`chitose_terms_new`] and not destroyed yet
+#[no_mangle]
+pub unsafe extern "C" fn chitose_terms_len(terms: *const ChitoseTerms) -> usize {
+    // SAFETY: valid, by the contract of the caller
+    guarded(0, || unsafe { &*terms }.0.len())
+}
+
+/// The terms themselves, [`chitose_terms_len`] of them, valid until the terms are destroyed
+///
+/// # Safety
+///
+/// `terms` must be created by [`chitose_terms_new`] and not destroyed yet
+#[no_mangle]
+pub unsafe extern "C" fn chitose_terms_data(terms: *const ChitoseTerms) -> *const ChitoseTerm {
+    // SAFETY: valid, by the contract of the caller
+    guarded(ptr::null(), || unsafe { &*terms }.0.as_ptr())
+}
+
+/// Destroys the terms; null is ignored
+///
+/// # Safety
+///
+/// `terms` must be null, or created by [`chitose_terms_new`] and not destroyed yet
+#[no_mangle]
+pub unsafe extern "C" fn chitose_terms_free(terms: *mut ChitoseTerms) {
+    guarded((), || {
+        if !terms.is_null() {
+            // SAFETY: created by `Box::into_raw`, by the contract of the caller
+            drop(unsafe { Box::from_raw(terms) });
+        }
+    })
+}
+
+/// Writes the ground level of the configuration by Hund's rules to `level`; returns 0 on success, and -1 if the configuration is invalid or its momenta don't fit into the fields
+///
+/// # Safety
+///
+/// `configuration` must be null or a NUL-terminated string, and `level` must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn chitose_ground_level(
+    configuration: *const c_char,
+    level: *mut ChitoseLevel,
+) -> c_int {
+    guarded(-1, || {
+        // SAFETY: same contract
+        let Some(configuration) = (unsafe { self::configuration(configuration) }) else {
+            return -1;
+        };
+        let ground = configuration.ground_term();
+        let Ok(ground_level) = ChitoseLevel::try_from(&ground) else {
+            fail(format!(
+                "Momenta of the {ground} level don't fit into 32 bits"
+            ));
+            return -1;
+        };
+        // SAFETY: valid for writes, by the contract of the caller
+        unsafe { level.write(ground_level) };
+        0
+    })
+}
+
+/// Clebsch–Gordan coefficient ⟨j1 m1 j2 m2 | j m⟩, all of the momenta doubled; 0 for the forbidden ones, and NaN for the ones too large to compute
+#[no_mangle]
+pub extern "C" fn chitose_clebsch_gordan(
+    j1: c_int,
+    m1: c_int,
+    j2: c_int,
+    m2: c_int,
+    j: c_int,
+    m: c_int,
+) -> f64 {
+    guarded(f64::NAN, || {
+        if !allowed(&[(j1, m1), (j2, m2), (j, m)]) {
+            return 0.0;
+        }
+        let half = HalfInt::from_doubled;
+        if !in_range(&[j1, m1, j2, m2, j, m].map(half), momenta_in_range) {
+            return f64::NAN;
+        }
+        clebsch_gordan(
+            (half(j1), half(m1)),
+            (half(j2), half(m2)),
+            (half(j), half(m)),
+        )
+    })
+}
+
+/// Wigner 3j symbol (j1 j2 j3; m1 m2 m3), all of the momenta doubled; 0 for the forbidden ones, and NaN for the ones too large to compute
+#[no_mangle]
+pub extern "C" fn chitose_wigner_3j(
+    j1: c_int,
+    j2: c_int,
+    j3: c_int,
+    m1: c_int,
+    m2: c_int,
+    m3: c_int,
+) -> f64 {
+    guarded(f64::NAN, || {
+        if !allowed(&[(j1, m1), (j2, m2), (j3, m3)]) {
+            return 0.0;
+        }
+        let half = HalfInt::from_doubled;
+        if !in_range(&[j1, j2, j3, m1, m2, m3].map(half), momenta_in_range) {
+            return f64::NAN;
+        }
+        wigner_3j(
+            (half(j1), half(m1)),
+            (half(j2), half(m2)),
+            (half(j3), half(m3)),
+        )
+    })
+}
+
+/// Wigner 6j symbol {j1 j2 j3; j4 j5 j6}, all of the momenta doubled; 0 for the forbidden ones, and NaN for the ones too large to compute
+#[no_mangle]
+pub extern "C" fn chitose_wigner_6j(
+    j1: c_int,
+    j2: c_int,
+    j3: c_int,
+    j4: c_int,
+    j5: c_int,
+    j6: c_int,
+) -> f64 {
+    guarded(f64::NAN, || {
+        if [j1, j2, j3, j4, j5, j6].iter().any(|&j| j < 0) {
+            return 0.0;
+        }
+        let [j1, j2, j3, j4, j5, j6] = [j1, j2, j3, j4, j5, j6].map(HalfInt::from_doubled);
+        if !in_range(&[j1, j2, j3, j4, j5, j6], momenta_in_range) {
+            return f64::NAN;
+        }
+        wigner_6j([j1, j2, j3], [j4, j5, j6])
+    })
+}
+
+/// Wigner 9j symbol with the rows `{j[0] j[1] j[2]; j[3] j[4] j[5]; j[6] j[7] j[8]}`, all of the momenta doubled; 0 for the forbidden ones, and NaN for the ones too large to compute
+///
+/// # Safety
+///
+/// `j` must point to 9 integers
+#[no_mangle]
+pub unsafe extern "C" fn chitose_wigner_9j(j: *const c_int) -> f64 {
+    guarded(f64::NAN, || {
+        // SAFETY: 9 integers, by the contract of the caller
+        let j = unsafe { &*j.cast::<[c_int; 9]>() };
+        if j.iter().any(|&j| j < 0) {
+            return 0.0;
+        }
+        let half = HalfInt::from_doubled;
+        if !in_range(&j.map(half), momenta_in_9j_range) {
+            return f64::NAN;
+        }
+        wigner_9j([
+            [half(j[0]), half(j[1]), half(j[2])],
+            [half(j[3]), half(j[4]), half(j[5])],
+            [half(j[6]), half(j[7]), half(j[8])],
+        ])
+    })
+}
+
+/// Whether the doubled momenta and their projections are physical: j ≥ 0, |m| ≤ j, and j - m is an integer
+fn allowed(momenta: &[(c_int, c_int)]) -> bool {
+    momenta
+        .iter()
+        .map(|&(j, m)| (i64::from(j), i64::from(m)))
+        .all(|(j, m)| j >= 0 && m.abs() <= j && (j - m) % 2 == 0)
+}
+
+/// Whether the coefficient of the momenta can be computed, checked by `check`, failing if it can't
+fn in_range(momenta: &[HalfInt], check: fn(&[HalfInt]) -> bool) -> bool {
+    let fits = check(momenta);
+    if !fits {
+        fail("Momenta are too large to compute the coefficient");
+    }
+    fits
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_int, CStr, CString};
+
+    use crate::ffi::{
+        chitose_clebsch_gordan, chitose_ground_level, chitose_last_error, chitose_terms_data,
+        chitose_terms_free, chitose_terms_len, chitose_terms_new, chitose_wigner_6j,
+        chitose_wigner_9j, guarded, ChitoseLevel, ChitoseTerm,
+    };
+    use crate::{HalfInt, Parity, TermMomentum, TermType};
+
+    #[test]
+    fn terms() {
+        let configuration = CString::new("2p2").expect("Should be ok");
+        let terms = unsafe { chitose_terms_new(configuration.as_ptr()) };
+        assert!(!terms.is_null());
+        let listed = unsafe {
+            std::slice::from_raw_parts(chitose_terms_data(terms), chitose_terms_len(terms))
+        };
+        let term = |momentum, doubled_spin| ChitoseTerm {
+            momentum,
+            doubled_spin,
+            odd: 0,
+        };
+        assert_eq!(listed, [term(1, 2), term(0, 0), term(2, 0)]);
+        unsafe { chitose_terms_free(terms) };
+
+        let mut level = ChitoseLevel {
+            term: term(0, 0),
+            doubled_j: 0,
+        };
+        let configuration = CString::new("3d3").expect("Should be ok");
+        assert_eq!(
+            unsafe { chitose_ground_level(configuration.as_ptr(), &mut level) },
+            0
+        );
+        // ^4F_3/2
+        assert_eq!(
+            level,
+            ChitoseLevel {
+                term: ChitoseTerm {
+                    momentum: 3,
+                    doubled_spin: 3,
+                    odd: 0
+                },
+                doubled_j: 3,
+            }
+        );
+
+        let invalid = CString::new("2x2").expect("Should be ok");
+        assert!(unsafe { chitose_terms_new(invalid.as_ptr()) }.is_null());
+        let error = unsafe { CStr::from_ptr(chitose_last_error()) };
+        assert!(!error.to_bytes().is_empty());
+
+        // not truncated to 32 bits
+        let large = TermType {
+            momentum: TermMomentum(usize::MAX),
+            spin: HalfInt::HALF,
+            parity: Parity::Even,
+        };
+        assert!(ChitoseTerm::try_from(&large).is_err());
+        let small = TermType {
+            momentum: TermMomentum(1),
+            ..large
+        };
+        assert!(ChitoseTerm::try_from(&small).is_ok());
+    }
+
+    #[test]
+    fn coefficients() {
+        // ⟨1/2 1/2 1/2 -1/2 | 1 0⟩ = 1/√2
+        assert!((chitose_clebsch_gordan(1, 1, 1, -1, 2, 0) - 0.5f64.sqrt()).abs() < 1e-12);
+        // unphysical projections
+        assert_eq!(chitose_clebsch_gordan(1, 3, 1, -1, 2, 2), 0.0);
+        assert_eq!(chitose_clebsch_gordan(2, 1, 1, -1, 2, 0), 0.0);
+        // {1 1 1; 1 1 1} = 1/6
+        assert!((chitose_wigner_6j(2, 2, 2, 2, 2, 2) - 1.0 / 6.0).abs() < 1e-12);
+        assert_eq!(chitose_wigner_6j(-2, 2, 2, 2, 2, 2), 0.0);
+        // a 9j symbol with a zero is a 6j symbol: {1 1 0; 1 1 0; 0 0 0} = 1/3
+        let j = [2, 2, 0, 2, 2, 0, 0, 0, 0];
+        assert!((unsafe { chitose_wigner_9j(j.as_ptr()) } - 1.0 / 3.0).abs() < 1e-12);
+        // the sums of the Racah formula would overflow
+        let large = 2_000_000_000;
+        assert!(chitose_clebsch_gordan(large, 0, 2, 0, large, 0).is_nan());
+        assert!(chitose_wigner_6j(large, large, 0, large, large, 0).is_nan());
+        assert_eq!(chitose_clebsch_gordan(1, c_int::MIN, 1, -1, 2, 0), 0.0);
+    }
+
+    #[test]
+    fn panics() {
+        let value = guarded(f64::NAN, || -> f64 { panic!("Unexpected") });
+        assert!(value.is_nan());
+        let error = unsafe { CStr::from_ptr(chitose_last_error()) };
+        assert_eq!(error.to_str(), Ok("Internal error: Unexpected"));
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::{
+    ee_terms_observed, solution::latex_half, DerivationError, DerivationEvent, HalfInt, Microstate,
+    SubLevel,
+};
+
+/// Classic textbook table of the microstates of a sublevel: a row for every ML and a column for every MS, both descending
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MicrostateGrid {
+    sublevel: SubLevel,
+    mls: Vec<i32>,
+    mss: Vec<HalfInt>,
+    /// Microstates of every cell, row by row
+    cells: Vec<Vec<Vec<Microstate>>>,
+}
+
+impl MicrostateGrid {
+    /// Enumerates the microstates of the sublevel and sorts them into the table
+    pub fn new(sublevel: SubLevel) -> Result<Self, DerivationError> {
+        let mut microstates = Vec::new();
+        ee_terms_observed(sublevel.clone(), &mut |event: &DerivationEvent| {
+            if let DerivationEvent::MicrostateBinned { microstate, .. } = event {
+                microstates.push(*microstate);
+            }
+        })?;
+        Ok(Self::from_microstates(sublevel, &microstates))
+    }
+
+    /// Sorts already enumerated microstates of the sublevel into the table
+    pub(crate) fn from_microstates(sublevel: SubLevel, microstates: &[Microstate]) -> Self {
+        let max_ml = microstates
+            .iter()
+            .map(Microstate::ml)
+            .max()
+            .unwrap_or_default();
+        let max_ms = microstates
+            .iter()
+            .map(Microstate::ms)
+            .max()
+            .unwrap_or(HalfInt::ZERO);
+        let mls = (-max_ml..=max_ml).rev().collect_vec();
+        let mut mss = (-max_ms).up_to(max_ms).collect_vec();
+        mss.reverse();
+        let mut cells = vec![vec![Vec::new(); mss.len()]; mls.len()];
+        for microstate in microstates {
+            let row = (max_ml - microstate.ml()) as usize;
+            let column = ((max_ms - microstate.ms()).doubled() / 2) as usize;
+            cells[row][column].push(*microstate);
+        }
+        Self {
+            sublevel,
+            mls,
+            mss,
+            cells,
+        }
+    }
+
+    pub fn sublevel(&self) -> &SubLevel {
+        &self.sublevel
+    }
+
+    /// ML of the rows, descending
+    pub fn mls(&self) -> &[i32] {
+        &self.mls
+    }
+
+    /// MS of the columns, descending
+    pub fn mss(&self) -> &[HalfInt] {
+        &self.mss
+    }
+
+    /// Microstates with the given ML and MS, empty outside of the table
+    pub fn microstates(&self, ml: i32, ms: HalfInt) -> &[Microstate] {
+        let row = self.mls.iter().position(|row| *row == ml);
+        let column = self.mss.iter().position(|column| *column == ms);
+        match (row, column) {
+            (Some(row), Some(column)) => &self.cells[row][column],
+            _ => &[],
+        }
+    }
+
+    pub fn count(&self, ml: i32, ms: HalfInt) -> usize {
+        self.microstates(ml, ms).len()
+    }
+
+    /// Rows of the table, with the ML of each row and the microstates of its cells
+    pub fn rows(&self) -> impl Iterator<Item = (i32, &[Vec<Microstate>])> {
+        self.mls
+            .iter()
+            .copied()
+            .zip(self.cells.iter().map(Vec::as_slice))
+    }
+
+    /// LaTeX tabular; cells list the microstates if `states` is set, and count them otherwise
+    pub fn to_latex(&self, states: bool) -> String {
+        let mut out = String::new();
+        self.write_latex(states, &mut out)
+            .expect("Writing to a string can't fail");
+        out
+    }
+
+    fn write_latex(&self, states: bool, out: &mut String) -> std::fmt::Result {
+        writeln!(
+            out,
+            "\\begin{{tabular}}{{r|{}}}",
+            "c".repeat(self.mss.len())
+        )?;
+        let header = self.mss.iter().map(|ms| format!("${}$", latex_half(*ms)));
+        writeln!(out, "$M_L \\backslash M_S$ & {} \\\\", header.format(" & "))?;
+        writeln!(out, "\\hline")?;
+        for (ml, cells) in self.rows() {
+            let cells = cells.iter().map(|cell| match (states, cell.len()) {
+                (_, 0) => String::new(),
+                (false, count) => count.to_string(),
+                // a nested tabular stacks the microstates
+                (true, _) => format!(
+                    "\\begin{{tabular}}{{@{{}}c@{{}}}}{}\\end{{tabular}}",
+                    cell.iter()
+                        .map(|microstate| format!("${}$", latex_microstate(microstate)))
+                        .join(" \\\\ ")
+                ),
+            });
+            writeln!(out, "${ml}$ & {} \\\\", cells.format(" & "))?;
+        }
+        writeln!(out, "\\end{{tabular}}")
+    }
+
+    /// HTML table; cells list the microstates if `states` is set, and count them otherwise
+    pub fn to_html(&self, states: bool) -> String {
+        let mut out = String::new();
+        self.write_html(states, &mut out)
+            .expect("Writing to a string can't fail");
+        out
+    }
+
+    fn write_html(&self, states: bool, out: &mut String) -> std::fmt::Result {
+        writeln!(out, "<table>")?;
+        writeln!(
+            out,
+            "<tr><th><i>M<sub>L</sub></i> \\ <i>M<sub>S</sub></i></th>{}</tr>",
+            self.mss
+                .iter()
+                .map(|ms| format!("<th>{ms}</th>"))
+                .format("")
+        )?;
+        for (ml, cells) in self.rows() {
+            let cells = cells.iter().map(|cell| match (states, cell.len()) {
+                (_, 0) => "<td></td>".to_string(),
+                (false, count) => format!("<td>{count}</td>"),
+                (true, _) => format!("<td>{}</td>", cell.iter().map(html_microstate).join("<br>")),
+            });
+            writeln!(out, "<tr><th>{ml}</th>{}</tr>", cells.format(""))?;
+        }
+        writeln!(out, "</table>")
+    }
+}
+
+/// Occupied spin-orbitals as `(1^{+}, 0^{-})`
+fn latex_microstate(microstate: &Microstate) -> String {
+    let orbitals = microstate
+        .spin_orbitals()
+        .map(|orbital| {
+            let sign = if orbital.ms > HalfInt::ZERO { '+' } else { '-' };
+            format!("{}^{{{sign}}}", orbital.ml)
+        })
+        .join(", ");
+    format!("({orbitals})")
+}
+
+/// Occupied spin-orbitals as `(1<sup>+</sup>, 0<sup>−</sup>)`
+pub(crate) fn html_microstate(microstate: &Microstate) -> String {
+    let orbitals = microstate
+        .spin_orbitals()
+        .map(|orbital| {
+            let sign = if orbital.ms > HalfInt::ZERO {
+                '+'
+            } else {
+                '−'
+            };
+            format!("{}<sup>{sign}</sup>", orbital.ml)
+        })
+        .join(", ");
+    format!("({orbitals})")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HalfInt, MicrostateGrid};
+
+    #[test]
+    fn p2() {
+        let grid = MicrostateGrid::new("p2".parse().expect("Should be ok")).expect("Should be ok");
+        assert_eq!(grid.mls(), [2, 1, 0, -1, -2]);
+        assert_eq!(grid.mss(), [HalfInt::ONE, HalfInt::ZERO, -HalfInt::ONE]);
+        assert_eq!(grid.count(0, HalfInt::ZERO), 3);
+        assert_eq!(grid.count(2, HalfInt::ONE), 0);
+        assert_eq!(grid.count(5, HalfInt::ZERO), 0);
+        let total: usize = grid.rows().flat_map(|(_, cells)| cells).map(Vec::len).sum();
+        assert_eq!(total, 15);
+        let latex = grid.to_latex(false);
+        assert!(latex.contains("$0$ & 1 & 3 & 1 \\\\"));
+        let html = grid.to_html(true);
+        assert!(html.contains(
+            "<tr><th>2</th><td></td><td>(1<sup>−</sup>, 1<sup>+</sup>)</td><td></td></tr>"
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    fmt::Display,
+    num::ParseIntError,
+    ops::{Add, AddAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+/// Integer or half-integer number, like spin or angular momentum projection
+///
+/// Stored as a doubled value, so all of the arithmetic stays exact
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct HalfInt(i32);
+
+impl HalfInt {
+    pub const ZERO: Self = Self(0);
+    pub const HALF: Self = Self(1);
+    pub const ONE: Self = Self(2);
+
+    /// Creates a number equal to `doubled / 2`
+    pub const fn from_doubled(doubled: i32) -> Self {
+        Self(doubled)
+    }
+
+    /// Creates a number equal to `value`
+    ///
+    /// # Panics
+    ///
+    /// If the doubled value doesn't fit into `i32`, that is `value` is outside of `i32::MIN / 2..=i32::MAX / 2` (see [`HalfInt::checked_from_int`])
+    pub const fn from_int(value: i32) -> Self {
+        match Self::checked_from_int(value) {
+            Some(value) => value,
+            None => panic!("Integer is too large to be stored as a half-integer"),
+        }
+    }
+
+    /// Same as [`HalfInt::from_int`], but `None` if the doubled value doesn't fit into `i32`
+    pub const fn checked_from_int(value: i32) -> Option<Self> {
+        match value.checked_mul(2) {
+            Some(doubled) => Some(Self(doubled)),
+            None => None,
+        }
+    }
+
+    /// Value multiplied by 2 (always an integer)
+    pub const fn doubled(self) -> i32 {
+        self.0
+    }
+
+    pub const fn is_integer(self) -> bool {
+        self.0 % 2 == 0
+    }
+
+    pub const fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+
+    /// Sum, or `None` if it overflows
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(sum) => Some(Self(sum)),
+            None => None,
+        }
+    }
+
+    /// Difference, or `None` if it overflows
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(difference) => Some(Self(difference)),
+            None => None,
+        }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        f64::from(self.0) / 2.0
+    }
+
+    /// Iterates from `self` up to `to` (inclusive) with a step of 1
+    ///
+    /// Both ends should be integers or half-integers at the same time, otherwise `to` is never reached exactly
+    pub fn up_to(self, to: Self) -> impl Iterator<Item = Self> + Clone {
+        (self.0..=to.0).step_by(2).map(Self)
+    }
+
+    /// 4X(X+1), the doubled-valued Casimir of the momentum, computed in `i64` so that it doesn't overflow
+    pub(crate) fn casimir(self) -> i64 {
+        let doubled = i64::from(self.0);
+        doubled * (doubled + 2)
+    }
+}
+
+impl TryFrom<i32> for HalfInt {
+    type Error = HalfIntParseError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        Self::checked_from_int(value).ok_or_else(|| HalfIntParseError::TooLarge(value.to_string()))
+    }
+}
+
+impl Add for HalfInt {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for HalfInt {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for HalfInt {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for HalfInt {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for HalfInt {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+impl Display for HalfInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            f.write_fmt(format_args!("{}", self.0 / 2))
+        } else {
+            f.write_fmt(format_args!("{}/2", self.0))
+        }
+    }
+}
+
+/// Parses either an integer (`2`, `-1`) or a half-integer written as a fraction (`3/2`, `-1/2`)
+impl FromStr for HalfInt {
+    type Err = HalfIntParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((doubled, "2")) => {
+                let doubled: i32 = doubled.parse()?;
+                if doubled % 2 == 0 {
+                    Err(HalfIntParseError::NotReduced(s.to_string()))
+                } else {
+                    Ok(Self(doubled))
+                }
+            }
+            Some(_) => Err(HalfIntParseError::Denominator(s.to_string())),
+            None => {
+                let value: i32 = s.parse()?;
+                Self::checked_from_int(value)
+                    .ok_or_else(|| HalfIntParseError::TooLarge(s.to_string()))
+            }
+        }
+    }
+}
+
+/// Serialized as a string, like `"3/2"`
+#[cfg(feature = "serde")]
+impl serde::Serialize for HalfInt {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for HalfInt {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum HalfIntParseError {
+    #[error("Half-integer \"{0}\" should have a denominator of 2")]
+    Denominator(String),
+    #[error("Half-integer \"{0}\" should be written as an integer")]
+    NotReduced(String),
+    #[error("Half-integer \"{0}\" is too large")]
+    TooLarge(String),
+    #[error(transparent)]
+    Int(
+        #[from]
+        #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialize_display"))]
+        ParseIntError,
+    ),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HalfInt;
+
+    #[test]
+    fn arithmetic_and_display() {
+        let three_halves = HalfInt::ONE + HalfInt::HALF;
+        assert_eq!(three_halves.to_string(), "3/2");
+        assert_eq!((-three_halves).to_string(), "-3/2");
+        assert_eq!((three_halves - HalfInt::HALF).to_string(), "1");
+        assert!(-three_halves < HalfInt::ZERO);
+        assert_eq!((-three_halves).up_to(three_halves).count(), 4);
+        assert_eq!("3/2".parse::<HalfInt>().unwrap(), three_halves);
+        assert_eq!("-1".parse::<HalfInt>().unwrap(), -HalfInt::ONE);
+        assert!("4/2".parse::<HalfInt>().is_err());
+        assert!("1/3".parse::<HalfInt>().is_err());
+        assert!("2000000000".parse::<HalfInt>().is_err());
+        assert_eq!(
+            HalfInt::checked_from_int(-3),
+            Some(HalfInt::from_doubled(-6))
+        );
+        assert_eq!(HalfInt::checked_from_int(i32::MAX / 2 + 1), None);
+        assert!(HalfInt::try_from(i32::MIN).is_err());
+        assert_eq!(
+            HalfInt::try_from(i32::MIN / 2).ok(),
+            Some(HalfInt::from_doubled(i32::MIN))
+        );
+        assert_eq!(
+            HalfInt::from_doubled(i32::MAX).checked_add(HalfInt::HALF),
+            None
+        );
+        assert_eq!(
+            HalfInt::from_doubled(i32::MIN).checked_sub(HalfInt::HALF),
+            None
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Standalone HTML page with the terms of a configuration, for sharing the results
+
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::{
+    solution::SubshellSolution, Configuration, DerivationError, MicrostateGrid, Notated, Notation,
+    WorkedSolution,
+};
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; color: #222; }
+h1 { font-weight: normal; }
+dl.summary { display: grid; grid-template-columns: max-content auto; gap: 0.3em 1.5em; }
+dl.summary dt { font-weight: bold; }
+dl.summary dd { margin: 0; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.75em; text-align: center; }
+th { background: #f0f0f0; }
+details { margin: 0.5em 0; }
+summary { cursor: pointer; }
+";
+
+/// Standalone HTML page with the terms of the configuration: a summary, the collapsible ML/MS table of every open subshell along with its microstates, and the tables of the terms and their levels
+///
+/// The terms are derived within the [limit](Configuration::with_limit) of the configuration, see [`WorkedSolution::to_html`] for a page of the terms already derived
+pub fn render_html(configuration: &Configuration) -> Result<String, DerivationError> {
+    Ok(WorkedSolution::new(configuration)?.to_html())
+}
+
+impl WorkedSolution {
+    /// Same page as [`render_html`] makes, of the derivation this solution recorded
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        write_page(&self.configuration, self, &mut out).expect("Writing to a string can't fail");
+        out
+    }
+}
+
+fn write_page(
+    configuration: &Configuration,
+    solution: &WorkedSolution,
+    out: &mut String,
+) -> std::fmt::Result {
+    let html = Notation::Html;
+    let mut terms = solution.terms().to_vec();
+    terms.sort();
+    let terms = terms.into_iter().dedup_with_count().collect_vec();
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html lang=\"en\">")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    // tags are not allowed in the title
+    writeln!(
+        out,
+        "<title>Terms of {}</title>",
+        configuration.notated(Notation::Unicode)
+    )?;
+    writeln!(out, "<style>\n{STYLE}</style>")?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    writeln!(
+        out,
+        "<h1>Terms of the {} configuration</h1>",
+        configuration.notated(html)
+    )?;
+
+    let open = configuration.open_subshells();
+    let microstates = configuration.microstate_count().map_or_else(
+        || "too many to count".to_string(),
+        |count| count.to_string(),
+    );
+    let levels: usize = terms
+        .iter()
+        .map(|(count, term)| count * term.levels().len())
+        .sum();
+    writeln!(out, "<dl class=\"summary\">")?;
+    let open = if open.sublevels().is_empty() {
+        "none".to_string()
+    } else {
+        open.notated(html).to_string()
+    };
+    writeln!(out, "<dt>Open subshells</dt><dd>{open}</dd>")?;
+    writeln!(out, "<dt>Microstates</dt><dd>{microstates}</dd>")?;
+    writeln!(
+        out,
+        "<dt>Terms</dt><dd>{} ({} distinct)</dd>",
+        solution.terms().len(),
+        terms.len()
+    )?;
+    writeln!(out, "<dt>Levels</dt><dd>{levels}</dd>")?;
+    writeln!(
+        out,
+        "<dt>Ground level (Hund's rules)</dt><dd>{}</dd>",
+        configuration.ground_term().notated(html)
+    )?;
+    writeln!(out, "</dl>")?;
+
+    if !solution.subshells.is_empty() {
+        writeln!(out, "<h2>Microstates</h2>")?;
+        for subshell in &solution.subshells {
+            write_subshell(subshell, out)?;
+        }
+    }
+
+    writeln!(out, "<h2>Terms</h2>")?;
+    writeln!(out, "<table>")?;
+    writeln!(
+        out,
+        "<tr><th>Term</th><th><i>S</i></th><th><i>L</i></th><th>Parity</th><th>Count</th><th>Degeneracy</th></tr>"
+    )?;
+    for (count, term) in &terms {
+        writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{count}</td><td>{}</td></tr>",
+            term.notated(html),
+            term.s(),
+            term.l(),
+            term.parity(),
+            term.degeneracy()
+        )?;
+    }
+    writeln!(out, "</table>")?;
+
+    writeln!(out, "<h2>Levels</h2>")?;
+    writeln!(out, "<table>")?;
+    writeln!(
+        out,
+        "<tr><th>Level</th><th><i>J</i></th><th>Count</th><th>Degeneracy</th><th><i>g<sub>J</sub></i></th></tr>"
+    )?;
+    for (count, term) in &terms {
+        for level in term.levels() {
+            let g = level
+                .lande_g_exact()
+                .map_or_else(|| "—".to_string(), |g| g.to_string());
+            writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{count}</td><td>{}</td><td>{g}</td></tr>",
+                level.notated(html),
+                level.j(),
+                level.degeneracy()
+            )?;
+        }
+    }
+    writeln!(out, "</table>")?;
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")
+}
+
+/// Collapsed ML/MS table of the subshell, with the counts and then with the microstates themselves
+fn write_subshell(subshell: &SubshellSolution, out: &mut String) -> std::fmt::Result {
+    let html = Notation::Html;
+    writeln!(out, "<details>")?;
+    write!(
+        out,
+        "<summary>{}: {} microstates",
+        subshell.sublevel.notated(html),
+        subshell.microstates.len()
+    )?;
+    if let Some(holes) = subshell.holes {
+        write!(out, " (enumerated as {holes} holes)")?;
+    }
+    writeln!(out, "</summary>")?;
+
+    let grid = MicrostateGrid::from_microstates(subshell.sublevel.clone(), &subshell.microstates);
+    out.push_str(&grid.to_html(false));
+    out.push_str(&grid.to_html(true));
+    writeln!(out, "</details>")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{render_html, Configuration};
+
+    #[test]
+    fn p2() {
+        let configuration: Configuration = "1s2 2p2".parse().expect("Should be ok");
+        let page = render_html(&configuration).expect("Should be ok");
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>Terms of 1s² 2p²</title>"));
+        assert!(page.contains("<dt>Microstates</dt><dd>15</dd>"));
+        assert!(page.contains("<dt>Terms</dt><dd>3 (3 distinct)</dd>"));
+        assert_eq!(page.matches("<details>").count(), 1);
+        assert!(page.contains(
+            "<tr><td><sup>3</sup>P<sub>2</sub></td><td>2</td><td>1</td><td>5</td><td>3/2</td></tr>"
+        ));
+        assert!(page.trim_end().ends_with("</html>"));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    Configuration, DerivationError, HalfInt, Level, LevelError, Microstate, Parity, SpinOrbital,
+    SubLevel, SubLevelType, TermMomentum, TermType,
+};
+
+impl SubLevel {
+    /// Whether there are more electrons than half of the sublevel capacity
+    ///
+    /// Multiplets of such sublevels are inverted (J = L+S is the lowest level, according to Hund's third rule)
+    pub fn is_more_than_half_filled(&self) -> bool {
+        self.electrons > self.tp.max_electrons() / 2
+    }
+
+    /// Sublevel with electrons replaced by holes (`4l+2-n` electrons instead of `n`), if this sublevel is more than half filled
+    ///
+    /// Both sublevels have the same terms, but multiplets of the hole configuration are inverted, see [`SubLevel::is_more_than_half_filled`]
+    pub fn hole_equivalent(&self) -> Option<SubLevel> {
+        self.is_more_than_half_filled().then(|| SubLevel {
+            electrons: self.tp.max_electrons() - self.electrons,
+            ..self.clone()
+        })
+    }
+
+    /// Sublevel with the given number of holes, that is `4l+2-holes` electrons
+    pub fn from_holes(t: SubLevelType, holes: u8) -> Result<SubLevel, LevelError> {
+        let Some(electrons) = t.max_electrons().checked_sub(holes) else {
+            return Err(LevelError::ToMuch(t));
+        };
+        SubLevel::new(t, electrons)
+    }
+
+    /// Number of vacant spin-orbitals of the sublevel
+    pub fn holes(&self) -> u8 {
+        self.tp.max_electrons() - self.electrons
+    }
+
+    /// Number of the electrons of the [hole equivalent](SubLevel::hole_equivalent), or of this sublevel if it is at most half filled
+    ///
+    /// Terms are derived for these, as holes have the same terms, and there are less of them to place
+    pub(crate) fn placed_electrons(&self) -> u8 {
+        self.electrons.min(self.holes())
+    }
+
+    /// Total L and S of the state following the first two Hund's rules
+    fn hund_momenta(&self) -> (usize, HalfInt) {
+        let orbitals = usize::from(self.tp.max_electrons() / 2);
+        let electrons = usize::from(self.electrons);
+        // spin-up electrons come first, spin-down ones fill orbitals with the greatest ml again
+        let (unpaired, paired) = if electrons <= orbitals {
+            (electrons, 0)
+        } else {
+            (2 * orbitals - electrons, electrons - orbitals)
+        };
+        let l = usize::from(self.tp.0);
+        let ml_sum = |count: usize| (0..count).map(|i| l as isize - i as isize).sum::<isize>();
+        let momentum = (ml_sum(electrons.min(orbitals)) + ml_sum(paired)).unsigned_abs();
+        (momentum, HalfInt::from_doubled(unpaired as i32))
+    }
+
+    /// Microstate of the first two Hund's rules: spin-up electrons take the orbitals from the greatest ml down, then spin-down ones fill them again in the same order
+    pub fn hund_microstate(&self) -> Result<Microstate, DerivationError> {
+        let l = self.tp.0 as i8;
+        let spin_orbitals = [HalfInt::HALF, -HalfInt::HALF]
+            .into_iter()
+            .flat_map(|ms| (-l..=l).rev().map(move |ml| SpinOrbital { ml, ms }))
+            .take(usize::from(self.electrons));
+        Microstate::from_spin_orbitals(&self.tp, spin_orbitals)
+            .ok_or_else(|| DerivationError::TooLarge(self.tp.clone()))
+    }
+
+    /// Ground term of the sublevel according to Hund's rules (maximal S, then maximal L, then J = |L-S| for at most half-filled sublevel and J = L+S otherwise)
+    ///
+    /// Does not enumerate any microstates
+    pub fn ground_term(&self) -> Level {
+        let (momentum, spin) = self.hund_momenta();
+        hund_level(
+            momentum,
+            spin,
+            self.parity(),
+            self.is_more_than_half_filled(),
+        )
+    }
+}
+
+impl Configuration {
+    /// Ground term of the configuration according to Hund's rules, applied to every subshell separately.
+    ///
+    /// Hund's rules are only strictly justified for a single open subshell; here J = L+S is chosen only if the multiplets are inverted, see [`Configuration::has_inverted_multiplets`]
+    pub fn ground_term(&self) -> Level {
+        let (momentum, spin) =
+            self.open_sublevels()
+                .fold((0, HalfInt::ZERO), |(momentum, spin), sublevel| {
+                    let (l, s) = sublevel.hund_momenta();
+                    (momentum + l, spin + s)
+                });
+        hund_level(
+            momentum,
+            spin,
+            self.parity(),
+            self.has_inverted_multiplets(),
+        )
+    }
+
+    /// Whether the multiplets are expected to be inverted, which is the case if every open subshell is more than half filled (see [`Configuration::ground_term`])
+    ///
+    /// Exactly half-filled subshells have L = 0, so they don't take part: 4d^7 5s^1 is inverted just like 4d^7 alone
+    pub fn has_inverted_multiplets(&self) -> bool {
+        let mut deciding = self
+            .open_sublevels()
+            .filter(|sublevel| 2 * sublevel.electrons != sublevel.tp.max_electrons())
+            .peekable();
+        deciding.peek().is_some() && deciding.all(SubLevel::is_more_than_half_filled)
+    }
+
+    fn open_sublevels(&self) -> impl Iterator<Item = &SubLevel> {
+        self.sublevels().iter().filter(|sublevel| {
+            sublevel.electrons != 0 && sublevel.electrons != sublevel.tp.max_electrons()
+        })
+    }
+}
+
+/// Sorts terms in the order of energy expected from Hund's rules: greater S first, then greater L
+///
+/// The sort is stable, and only the lowest term is actually guaranteed by the rules
+pub fn hund_order(terms: &mut [TermType]) {
+    terms.sort_by_key(|term| std::cmp::Reverse((term.spin, term.momentum.0)));
+}
+
+fn hund_level(momentum: usize, spin: HalfInt, parity: Parity, inverted: bool) -> Level {
+    let l = HalfInt::from_int(momentum as i32);
+    Level {
+        term: TermType {
+            momentum: TermMomentum(momentum),
+            spin,
+            parity,
+        },
+        j: if inverted { l + spin } else { (l - spin).abs() },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{hund_order, Configuration, SubLevel, SubLevelType};
+
+    #[test]
+    fn hund_microstate() {
+        for (l, n) in [(1, 2), (2, 6), (3, 9), (2, 10)] {
+            let sublevel = SubLevel::new(SubLevelType::new(l).unwrap(), n).unwrap();
+            let microstate = sublevel.hund_microstate().unwrap();
+            let ground = sublevel.ground_term();
+            assert_eq!(microstate.electrons(), u32::from(n));
+            assert_eq!(microstate.ml() as usize, ground.term().l());
+            assert_eq!(microstate.ms(), ground.term().s());
+        }
+        assert_eq!(
+            "d6".parse::<SubLevel>()
+                .unwrap()
+                .hund_microstate()
+                .unwrap()
+                .to_string(),
+            "-2+ -1+ 0+ 1+ 2- 2+"
+        );
+    }
+
+    #[test]
+    fn known_ground_terms() {
+        let ground = |l, n| {
+            SubLevel::new(SubLevelType::new(l).unwrap(), n)
+                .unwrap()
+                .ground_term()
+                .to_string()
+        };
+        assert_eq!(ground(1, 2), "^3P_0");
+        assert_eq!(ground(1, 4), "^3P_2");
+        assert_eq!(ground(2, 5), "^6S_5/2");
+        assert_eq!(ground(2, 6), "^5D_4");
+        assert_eq!(ground(3, 7), "^8S*_7/2");
+        assert_eq!(ground(3, 9), "^6H*_15/2");
+        assert_eq!(ground(2, 10), "^1S_0");
+
+        let f13 = SubLevel::from_holes(SubLevelType::new(3).unwrap(), 1).unwrap();
+        assert_eq!(f13.electrons(), 13);
+        assert_eq!(f13.holes(), 1);
+        assert_eq!(f13.hole_equivalent().unwrap().to_string(), "f^1");
+        assert_eq!(f13.ground_term().to_string(), "^2F*_7/2");
+        assert_eq!(
+            f13.hole_equivalent().unwrap().ground_term().to_string(),
+            "^2F*_5/2"
+        );
+
+        let gadolinium: Configuration = "[Xe] 4f7 5d1 6s2".parse().unwrap();
+        assert_eq!(gadolinium.ground_term().to_string(), "^9D*_2");
+
+        // half-filled s subshells don't prevent the inversion, as in NIST
+        for (configuration, ground) in [
+            ("[Kr] 4d7 5s1", "^5F_5"),
+            ("[Kr] 4d8 5s1", "^4F_9/2"),
+            ("[Xe] 4f14 5d9 6s1", "^3D_3"),
+            ("[Ar] 3d6 4s1", "^6D_9/2"),
+        ] {
+            let configuration: Configuration = configuration.parse().unwrap();
+            assert_eq!(configuration.ground_term().to_string(), ground);
+        }
+    }
+
+    #[test]
+    fn expected_order() {
+        let p4: Configuration = "2p4".parse().unwrap();
+        let mut terms = p4.ls_terms().unwrap();
+        hund_order(&mut terms);
+        assert_eq!(terms.iter().join(" "), "^3P ^1D ^1S");
+        assert!(p4.has_inverted_multiplets());
+        assert!(!"3d5 4s1"
+            .parse::<Configuration>()
+            .unwrap()
+            .has_inverted_multiplets());
+        assert!(!"1s2"
+            .parse::<Configuration>()
+            .unwrap()
+            .has_inverted_multiplets());
+    }
+}
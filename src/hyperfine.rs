@@ -0,0 +1,70 @@
+use crate::{HalfInt, Level};
+
+/// Hyperfine level F of a fine-structure level, F = I + J
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HyperfineLevel {
+    pub f: HalfInt,
+    /// Energy relative to the fine-structure level, in the units of A and B constants
+    pub energy: f64,
+}
+
+impl HyperfineLevel {
+    /// Number of states, 2F+1
+    pub fn degeneracy(&self) -> usize {
+        (self.f.doubled() + 1) as usize
+    }
+}
+
+impl Level {
+    /// Hyperfine levels F = |I-J|..=I+J of the level for the nuclear spin I, with magnetic dipole constant A and electric quadrupole constant B
+    ///
+    /// Energies are given by the Casimir formula E = A K/2 + B [3/2 K(K+1) - 2I(I+1)J(J+1)] / [4I(2I-1)J(2J-1)], where K = F(F+1) - I(I+1) - J(J+1). Quadrupole term is only present for I ≥ 1 and J ≥ 1
+    pub fn hyperfine(&self, nuclear_spin: HalfInt, a: f64, b: f64) -> Vec<HyperfineLevel> {
+        let casimir = |x: HalfInt| x.to_f64() * (x.to_f64() + 1.0);
+        let (i, j) = (nuclear_spin, self.j);
+        let quadrupole = i >= HalfInt::ONE && j >= HalfInt::ONE;
+        (i - j)
+            .abs()
+            .up_to(i + j)
+            .map(|f| {
+                let k = casimir(f) - casimir(i) - casimir(j);
+                let mut energy = a * k / 2.0;
+                if quadrupole {
+                    energy += b * (1.5 * k * (k + 1.0) - 2.0 * casimir(i) * casimir(j))
+                        / (4.0
+                            * i.to_f64()
+                            * (2.0 * i.to_f64() - 1.0)
+                            * j.to_f64()
+                            * (2.0 * j.to_f64() - 1.0));
+                }
+                HyperfineLevel { f, energy }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HalfInt, Level};
+
+    #[test]
+    fn rubidium_87() {
+        let i = HalfInt::from_doubled(3);
+        // 5s ^2S_1/2: A = 3417.34 MHz, F = 1, 2 split by 2A
+        let ground: Level = "2S_1/2".parse().unwrap();
+        let levels = ground.hyperfine(i, 3417.34, 0.0);
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[1].f, HalfInt::from_int(2));
+        assert_eq!(levels[1].degeneracy(), 5);
+        assert!((levels[1].energy - levels[0].energy - 2.0 * 3417.34).abs() < 1e-9);
+        // 5p ^2P_3/2: A = 84.7185 MHz, B = 12.4965 MHz, F = 3 lies 193.74 MHz above the center
+        let excited: Level = "2P*_3/2".parse().unwrap();
+        let levels = excited.hyperfine(i, 84.7185, 12.4965);
+        assert_eq!(levels.len(), 4);
+        assert!((levels[3].energy - 193.7408).abs() < 1e-3);
+        // states are conserved
+        let states: usize = levels.iter().map(|level| level.degeneracy()).sum();
+        assert_eq!(states, 16);
+    }
+}
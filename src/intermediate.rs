@@ -0,0 +1,287 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use crate::{
+    clebsch_gordan,
+    linalg::symmetric_eigen,
+    slater::{self, Combination},
+    Configuration, DerivationError, HalfInt, Level, Notated, Notation, Parity, SeniorityTerm,
+    SlaterError, SlaterIntegral, SubLevel, TermMomentum, TermType,
+};
+
+/// Level in the intermediate coupling: eigenstate of the electrostatic and spin-orbit interactions together
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntermediateLevel {
+    pub j: HalfInt,
+    /// Energy relative to the closed subshells
+    pub energy: f64,
+    /// Coefficients of the LS-coupling levels ^(2S+1)L_J with the same J, in the order of the terms
+    pub components: Vec<(SeniorityTerm, f64)>,
+}
+
+impl IntermediateLevel {
+    /// LS-coupling level with the largest weight, conventionally used as the label of the level
+    pub fn leading(&self) -> &SeniorityTerm {
+        &self
+            .components
+            .iter()
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+            .expect("Level has at least one component")
+            .0
+    }
+
+    /// Landé g-factor, the average of the ones of the LS-coupling components weighted by their squared coefficients
+    ///
+    /// Returns `None` for J = 0, as the g-factor is undefined there
+    pub fn lande_g(&self) -> Option<f64> {
+        self.components
+            .iter()
+            .map(|(term, coefficient)| {
+                let level = Level::new(term.term().clone(), self.j)
+                    .expect("Components have the J of the level");
+                Some(coefficient * coefficient * level.lande_g()?)
+            })
+            .sum()
+    }
+}
+
+/// Energy, J and the composition in percents (components below 0.5% are omitted), like `-1.2345 J=2: 97.5% ^3_2P + 2.5% ^1_2D`
+impl Notated for IntermediateLevel {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_fmt(format_args!("{:.4} J={}: ", self.energy, self.j))?;
+        let components = self
+            .components
+            .iter()
+            .filter(|(_, coefficient)| coefficient * coefficient >= 0.005)
+            .map(|(term, coefficient)| {
+                format!(
+                    "{:.1}% {}",
+                    coefficient * coefficient * 100.0,
+                    term.notated(notation)
+                )
+            })
+            .join(" + ");
+        f.write_str(&components)
+    }
+}
+
+impl Display for IntermediateLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+impl SubLevel {
+    /// Levels of the sublevel in the intermediate coupling, sorted by energy
+    ///
+    /// Energy matrix of the electron repulsion and the spin-orbit interaction ζ Σ l_i·s_i is constructed for every J in the basis of the LS-coupling levels (explicitly built from [`SubLevel::eigenstates`]), and diagonalized. Slater integrals are given in the Condon–Shortley normalization as `slater = [F_0, F_2, F_4, ...]` (see [`SlaterIntegral`]), missing ones are taken to be zero. Both pure LS (ζ = 0) and jj (F_k = 0 for k > 0) coupling are the limiting cases
+    pub fn intermediate_coupling(
+        &self,
+        slater: &[f64],
+        zeta: f64,
+    ) -> Result<Vec<IntermediateLevel>, DerivationError> {
+        let l = self.tp.l();
+        let terms = self.eigenstates()?;
+        let max_j = terms
+            .iter()
+            .map(|term| HalfInt::from_int(term.term.term.momentum.0 as i32) + term.term.term.spin)
+            .max()
+            .unwrap_or_default();
+        let mut levels = Vec::new();
+        for j in HalfInt::from_doubled(max_j.doubled() % 2).up_to(max_j) {
+            // states |ν L S J, M_J = J⟩ of every term that has such a level
+            let basis = terms
+                .iter()
+                .filter_map(|term| {
+                    let momentum = HalfInt::from_int(term.term.term.momentum.0 as i32);
+                    let spin = term.term.term.spin;
+                    if j < (momentum - spin).abs() || j > momentum + spin {
+                        return None;
+                    }
+                    let mut state = Combination::new();
+                    for eigenstate in &term.states {
+                        let ml = HalfInt::from_int(eigenstate.ml);
+                        if ml + eigenstate.ms != j {
+                            continue;
+                        }
+                        let coefficient =
+                            clebsch_gordan((momentum, ml), (spin, eigenstate.ms), (j, j));
+                        for (microstate, amplitude) in &eigenstate.components {
+                            *state.entry(microstate.occupation).or_default() +=
+                                coefficient * amplitude;
+                        }
+                    }
+                    Some((term.term.clone(), state))
+                })
+                .collect_vec();
+            let images = basis
+                .iter()
+                .map(|(_, state)| {
+                    let mut image = slater::spin_orbit(l, state);
+                    image.values_mut().for_each(|value| *value *= zeta);
+                    for (i, value) in slater.iter().enumerate() {
+                        let k = 2 * i as u8;
+                        if k > 2 * l {
+                            break;
+                        }
+                        let scale = value * SlaterIntegral::direct(l, l, k).denominator() as f64;
+                        for (occupation, coefficient) in slater::repulsion(l, k, state) {
+                            *image.entry(occupation).or_default() += scale * coefficient;
+                        }
+                    }
+                    image
+                })
+                .collect_vec();
+            let matrix = basis
+                .iter()
+                .map(|(_, state)| {
+                    images
+                        .iter()
+                        .map(|image| slater::dot(state, image))
+                        .collect_vec()
+                })
+                .collect_vec();
+            levels.extend(symmetric_eigen(matrix).into_iter().map(|(energy, vector)| {
+                IntermediateLevel {
+                    j,
+                    energy,
+                    components: basis
+                        .iter()
+                        .map(|(term, _)| term.clone())
+                        .zip(vector)
+                        .collect(),
+                }
+            }));
+        }
+        levels.sort_by(|a, b| a.energy.total_cmp(&b.energy).then(a.j.cmp(&b.j)));
+        Ok(levels)
+    }
+}
+
+impl Configuration {
+    /// Levels of the configuration with a single open subshell in the intermediate coupling, see [`SubLevel::intermediate_coupling`]
+    pub fn intermediate_coupling(
+        &self,
+        slater: &[f64],
+        zeta: f64,
+    ) -> Result<Vec<IntermediateLevel>, SlaterError> {
+        let open = self.open_subshells();
+        match open.sublevels() {
+            [sublevel] => Ok(sublevel.intermediate_coupling(slater, zeta)?),
+            [] => Ok(vec![IntermediateLevel {
+                j: HalfInt::ZERO,
+                energy: 0.0,
+                components: vec![(
+                    SeniorityTerm {
+                        term: TermType {
+                            momentum: TermMomentum(0),
+                            spin: HalfInt::ZERO,
+                            parity: Parity::Even,
+                        },
+                        seniority: 0,
+                    },
+                    1.0,
+                )],
+            }]),
+            _ => Err(SlaterError::NotSingleSubshell(Box::new(self.clone()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Configuration, HalfInt, SubLevel};
+
+    fn energies(sublevel: &str, slater: &[f64], zeta: f64) -> Vec<(i32, f64)> {
+        sublevel
+            .parse::<SubLevel>()
+            .unwrap()
+            .intermediate_coupling(slater, zeta)
+            .unwrap()
+            .into_iter()
+            .map(|level| (level.j.doubled(), level.energy))
+            .collect()
+    }
+
+    /// Compares the levels ordered by J, as the degenerate levels may come in any order
+    fn assert_close(actual: &[(i32, f64)], expected: &[(i32, f64)]) {
+        let sorted = |levels: &[(i32, f64)]| {
+            let mut levels = levels.to_vec();
+            levels.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+            levels
+        };
+        let (actual, expected) = (sorted(actual), sorted(expected));
+        assert_eq!(actual.len(), expected.len());
+        for ((j, energy), (expected_j, expected_energy)) in actual.iter().zip(&expected) {
+            assert_eq!(j, expected_j);
+            assert!((energy - expected_energy).abs() < 1e-8, "{actual:?}");
+        }
+    }
+
+    #[test]
+    fn p2_limits() {
+        // LS coupling: F_0 - 5F_2, F_0 + F_2, F_0 + 10F_2
+        assert_close(
+            &energies("p2", &[1.0, 10.0], 0.0),
+            &[(0, -49.0), (2, -49.0), (4, -49.0), (4, 11.0), (0, 101.0)],
+        );
+        // jj coupling: one electron has -ζ for j = 1/2 and ζ/2 for j = 3/2
+        assert_close(
+            &energies("p2", &[], 2.0),
+            &[(0, -4.0), (2, -1.0), (4, -1.0), (0, 2.0), (4, 2.0)],
+        );
+        // weak spin-orbit interaction splits ^3P as ζ/2 [J(J+1) - L(L+1) - S(S+1)] / 2
+        let levels = "p2"
+            .parse::<SubLevel>()
+            .unwrap()
+            .intermediate_coupling(&[0.0, 10.0], 0.01)
+            .unwrap();
+        for level in &levels[..3] {
+            assert_eq!(level.leading().to_string(), "^3_2P");
+            let j = level.j.to_f64();
+            let expected = -50.0 + 0.01 / 4.0 * (j * (j + 1.0) - 4.0);
+            assert!((level.energy - expected).abs() < 1e-5);
+        }
+        assert_eq!(levels[0].j, HalfInt::ZERO);
+        assert!(levels[0].lande_g().is_none());
+        assert!((levels[1].lande_g().unwrap() - 1.5).abs() < 1e-3);
+        // sum of the g-factors of the levels with the same J doesn't depend on the coupling
+        let levels = "p2"
+            .parse::<SubLevel>()
+            .unwrap()
+            .intermediate_coupling(&[0.0, 1.0], 5.0)
+            .unwrap();
+        let g_sum: f64 = levels
+            .iter()
+            .filter(|level| level.j == HalfInt::from_int(2))
+            .map(|level| level.lande_g().unwrap())
+            .sum();
+        assert!((g_sum - 2.5).abs() < 1e-8);
+    }
+
+    #[test]
+    fn spin_orbit_is_traceless() {
+        let d3: SubLevel = "d3".parse().unwrap();
+        let trace = |zeta| {
+            let levels = d3.intermediate_coupling(&[2.0, 1.0, 0.1], zeta).unwrap();
+            let states: i32 = levels.iter().map(|level| level.j.doubled() + 1).sum();
+            assert_eq!(states, 120);
+            levels
+                .iter()
+                .map(|level| f64::from(level.j.doubled() + 1) * level.energy)
+                .sum::<f64>()
+        };
+        assert!((trace(0.5) - trace(0.0)).abs() < 1e-8);
+        assert!("[Ar] 3d2 4s1"
+            .parse::<Configuration>()
+            .unwrap()
+            .intermediate_coupling(&[], 1.0)
+            .is_err());
+    }
+}
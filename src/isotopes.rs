@@ -0,0 +1,192 @@
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{HalfInt, HyperfineLevel, Level};
+
+/// Nuclear data of an isotope
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Isotope {
+    pub symbol: &'static str,
+    /// Mass number A
+    pub mass: u16,
+    /// Nuclear spin I
+    pub spin: HalfInt,
+    /// Natural abundance, in percent
+    pub abundance: f64,
+    /// Nuclear magnetic dipole moment, in nuclear magnetons
+    pub magnetic_moment: f64,
+}
+
+const fn isotope(
+    symbol: &'static str,
+    mass: u16,
+    doubled_spin: i32,
+    abundance: f64,
+    magnetic_moment: f64,
+) -> Isotope {
+    Isotope {
+        symbol,
+        mass,
+        spin: HalfInt::from_doubled(doubled_spin),
+        abundance,
+        magnetic_moment,
+    }
+}
+
+/// Stable (and primordial) isotopes commonly met in atomic spectroscopy
+///
+/// Abundances follow IUPAC, magnetic moments follow the compilation by N. J. Stone. The list is not exhaustive
+static ISOTOPES: &[Isotope] = &[
+    isotope("H", 1, 1, 99.9885, 2.792_847),
+    isotope("H", 2, 2, 0.0115, 0.857_438),
+    isotope("He", 3, 1, 0.000_134, -2.127_625),
+    isotope("He", 4, 0, 99.999_866, 0.0),
+    isotope("Li", 6, 2, 7.59, 0.822_047),
+    isotope("Li", 7, 3, 92.41, 3.256_427),
+    isotope("Be", 9, 3, 100.0, -1.1776),
+    isotope("B", 10, 6, 19.9, 1.800_645),
+    isotope("B", 11, 3, 80.1, 2.688_649),
+    isotope("C", 12, 0, 98.93, 0.0),
+    isotope("C", 13, 1, 1.07, 0.702_412),
+    isotope("N", 14, 2, 99.636, 0.403_761),
+    isotope("N", 15, 1, 0.364, -0.283_189),
+    isotope("O", 16, 0, 99.757, 0.0),
+    isotope("O", 17, 5, 0.038, -1.893_79),
+    isotope("F", 19, 1, 100.0, 2.628_868),
+    isotope("Ne", 20, 0, 90.48, 0.0),
+    isotope("Ne", 21, 3, 0.27, -0.661_797),
+    isotope("Ne", 22, 0, 9.25, 0.0),
+    isotope("Na", 23, 3, 100.0, 2.217_522),
+    isotope("Mg", 24, 0, 78.99, 0.0),
+    isotope("Mg", 25, 5, 10.00, -0.855_45),
+    isotope("Mg", 26, 0, 11.01, 0.0),
+    isotope("Al", 27, 5, 100.0, 3.641_507),
+    isotope("P", 31, 1, 100.0, 1.131_60),
+    isotope("Cl", 35, 3, 75.76, 0.821_874),
+    isotope("Cl", 37, 3, 24.24, 0.684_124),
+    isotope("Ar", 40, 0, 99.6035, 0.0),
+    isotope("K", 39, 3, 93.2581, 0.391_466),
+    isotope("K", 40, 8, 0.0117, -1.298_100),
+    isotope("K", 41, 3, 6.7302, 0.214_870),
+    isotope("Ca", 40, 0, 96.941, 0.0),
+    isotope("Ca", 43, 7, 0.135, -1.317_643),
+    isotope("Sc", 45, 7, 100.0, 4.756_487),
+    isotope("Mn", 55, 5, 100.0, 3.4532),
+    isotope("Fe", 56, 0, 91.754, 0.0),
+    isotope("Fe", 57, 1, 2.119, 0.090_623),
+    isotope("Co", 59, 7, 100.0, 4.627),
+    isotope("Cu", 63, 3, 69.15, 2.2233),
+    isotope("Cu", 65, 3, 30.85, 2.3817),
+    isotope("Kr", 83, 9, 11.50, -0.970_669),
+    isotope("Kr", 84, 0, 56.99, 0.0),
+    isotope("Rb", 85, 5, 72.17, 1.353_352),
+    isotope("Rb", 87, 3, 27.83, 2.751_818),
+    isotope("Sr", 87, 9, 7.00, -1.093_603),
+    isotope("Sr", 88, 0, 82.58, 0.0),
+    isotope("Ag", 107, 1, 51.839, -0.113_57),
+    isotope("Ag", 109, 1, 48.161, -0.130_691),
+    isotope("Cd", 111, 1, 12.80, -0.594_886),
+    isotope("Cd", 113, 1, 12.22, -0.622_301),
+    isotope("In", 113, 9, 4.29, 5.5289),
+    isotope("In", 115, 9, 95.71, 5.5408),
+    isotope("Xe", 129, 1, 26.40, -0.777_976),
+    isotope("Xe", 131, 3, 21.23, 0.691_862),
+    isotope("Cs", 133, 7, 100.0, 2.582_025),
+    isotope("Ba", 135, 3, 6.592, 0.837_943),
+    isotope("Ba", 137, 3, 11.232, 0.937_365),
+    isotope("Ba", 138, 0, 71.698, 0.0),
+    isotope("Au", 197, 3, 100.0, 0.145_746),
+    isotope("Hg", 199, 1, 16.87, 0.505_886),
+    isotope("Hg", 201, 3, 13.18, -0.560_226),
+    isotope("Hg", 202, 0, 29.86, 0.0),
+    isotope("Tl", 203, 1, 29.524, 1.622_258),
+    isotope("Tl", 205, 1, 70.476, 1.638_213),
+    isotope("Pb", 207, 1, 22.1, 0.592_583),
+    isotope("Bi", 209, 9, 100.0, 4.1106),
+];
+
+impl Isotope {
+    /// All of the known isotopes, ordered by element and then by mass number
+    pub fn all() -> &'static [Isotope] {
+        ISOTOPES
+    }
+
+    pub fn find(symbol: &str, mass: u16) -> Option<&'static Isotope> {
+        ISOTOPES
+            .iter()
+            .find(|isotope| isotope.symbol.eq_ignore_ascii_case(symbol) && isotope.mass == mass)
+    }
+
+    /// Hyperfine levels of the fine-structure level in this isotope, see [`Level::hyperfine`]
+    pub fn hyperfine(&self, level: &Level, a: f64, b: f64) -> Vec<HyperfineLevel> {
+        level.hyperfine(self.spin, a, b)
+    }
+}
+
+/// Parses isotope names like `87Rb`, `Rb87` or `Rb-87`
+impl FromStr for &'static Isotope {
+    type Err = UnknownIsotope;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || UnknownIsotope(s.to_string());
+        let s = s.trim();
+        let (mass, symbol) = if s.starts_with(|c: char| c.is_ascii_digit()) {
+            let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(unknown)?;
+            let (mass, symbol) = s.split_at(split);
+            (mass, symbol)
+        } else {
+            let split = s
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .ok_or_else(unknown)?;
+            let (symbol, mass) = s.split_at(split);
+            (mass.trim_start_matches('-'), symbol)
+        };
+        let mass = mass.parse().map_err(|_| unknown())?;
+        Isotope::find(symbol, mass).ok_or_else(unknown)
+    }
+}
+
+/// Hyperfine levels of the fine-structure level in the isotope specified by name (like `87Rb`), see [`Level::hyperfine`]
+pub fn hyperfine(
+    isotope: &str,
+    level: &Level,
+    a: f64,
+    b: f64,
+) -> Result<Vec<HyperfineLevel>, UnknownIsotope> {
+    let isotope: &Isotope = isotope.parse()?;
+    Ok(isotope.hyperfine(level, a, b))
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("Unknown isotope \"{0}\"")]
+pub struct UnknownIsotope(String);
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{hyperfine, HalfInt, Isotope, Level};
+
+    #[test]
+    fn isotope_lookup() {
+        for name in ["87Rb", "Rb87", "Rb-87", "87rb"] {
+            let isotope: &Isotope = name.parse().expect("Should be known");
+            assert_eq!(isotope.spin, HalfInt::from_doubled(3));
+        }
+        assert!("88Rb".parse::<&Isotope>().is_err());
+        assert!("Rb".parse::<&Isotope>().is_err());
+        // abundances of every element sum up to (at most) 100%
+        for (symbol, isotopes) in &Isotope::all().iter().group_by(|isotope| isotope.symbol) {
+            let total: f64 = isotopes.map(|isotope| isotope.abundance).sum();
+            assert!(total <= 100.0 + 1e-6, "{symbol}");
+        }
+        let level: Level = "2S_1/2".parse().unwrap();
+        let levels = hyperfine("133Cs", &level, 2_298.157_942_5, 0.0).unwrap();
+        assert_eq!(levels.len(), 2);
+        // the SI second
+        assert!((levels[1].energy - levels[0].energy - 9_192.631_770).abs() < 1e-6);
+    }
+}
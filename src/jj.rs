@@ -0,0 +1,352 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{
+    notation, Configuration, HalfInt, LevelError, Notated, Notation, SubLevel, SubLevelType,
+};
+
+/// Relativistic subshell `(n l j)^k`: electrons sharing the same n, l and total angular momentum j = l ± 1/2
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JjSubLevel {
+    pub(crate) n: Option<u8>,
+    pub(crate) tp: SubLevelType,
+    pub(crate) j: HalfInt,
+    pub(crate) electrons: u8,
+}
+
+impl JjSubLevel {
+    pub fn new(tp: SubLevelType, j: HalfInt, electrons: u8) -> Result<Self, JjError> {
+        let l = HalfInt::from_int(i32::from(tp.l()));
+        if j.is_integer() || j < HalfInt::ZERO || (j - l).abs() != HalfInt::HALF {
+            return Err(JjError::ForbiddenJ(tp, j));
+        }
+        let subshell = Self {
+            n: None,
+            tp,
+            j,
+            electrons,
+        };
+        if electrons > subshell.max_electrons() {
+            return Err(JjError::ToMuch(subshell));
+        }
+        Ok(subshell)
+    }
+
+    /// Same as [`JjSubLevel::new`], but also specifies principal quantum number of the subshell
+    pub fn with_principal(
+        n: u8,
+        tp: SubLevelType,
+        j: HalfInt,
+        electrons: u8,
+    ) -> Result<Self, JjError> {
+        if tp.l() >= n {
+            return Err(LevelError::NoSuchSublevel(n, tp).into());
+        }
+        Ok(Self {
+            n: Some(n),
+            ..Self::new(tp, j, electrons)?
+        })
+    }
+
+    pub fn n(&self) -> Option<u8> {
+        self.n
+    }
+
+    pub fn tp(&self) -> &SubLevelType {
+        &self.tp
+    }
+
+    pub fn j(&self) -> HalfInt {
+        self.j
+    }
+
+    pub fn electrons(&self) -> u8 {
+        self.electrons
+    }
+
+    /// Number of states in the subshell, 2j+1
+    pub fn max_electrons(&self) -> u8 {
+        (self.j.doubled() + 1) as u8
+    }
+
+    /// Allowed total J values of the subshell along with the number of times each of them occurs, by ascending J
+    ///
+    /// Obtained by enumerating all of the Pauli-allowed mj combinations and counting states with each total M_J: the number of levels with some J equals N(M_J = J) - N(M_J = J+1)
+    pub fn total_js(&self) -> Vec<(HalfInt, usize)> {
+        let mjs = (-self.j).up_to(self.j).collect_vec();
+        let mut projections: BTreeMap<HalfInt, usize> = BTreeMap::new();
+        for state in mjs.into_iter().combinations(self.electrons as usize) {
+            let mj = state.into_iter().fold(HalfInt::ZERO, |sum, mj| sum + mj);
+            *projections.entry(mj).or_default() += 1;
+        }
+        let count = |mj| projections.get(&mj).copied().unwrap_or_default();
+        projections
+            .keys()
+            .copied()
+            .filter(|mj| *mj >= HalfInt::ZERO)
+            .map(|j| (j, count(j) - count(j + HalfInt::ONE)))
+            .filter(|(_, times)| *times > 0)
+            .collect()
+    }
+}
+
+/// Relativistic configuration, a set of `(n l j)^k` subshells
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JjConfiguration {
+    subshells: Vec<JjSubLevel>,
+}
+
+impl JjConfiguration {
+    pub fn new(subshells: impl IntoIterator<Item = JjSubLevel>) -> Self {
+        Self {
+            subshells: subshells.into_iter().collect(),
+        }
+    }
+
+    pub fn subshells(&self) -> &[JjSubLevel] {
+        &self.subshells
+    }
+
+    /// Total J values of the configuration along with the number of times each of them occurs, by ascending J
+    ///
+    /// Electrons of different subshells are non-equivalent, so J values of the subshells are coupled by the triangle rule
+    pub fn total_js(&self) -> Vec<(HalfInt, usize)> {
+        let mut js = BTreeMap::from([(HalfInt::ZERO, 1)]);
+        for subshell in &self.subshells {
+            let mut coupled = BTreeMap::new();
+            for ((j1, times1), (j2, times2)) in js.iter().cartesian_product(subshell.total_js()) {
+                for j in (*j1 - j2).abs().up_to(*j1 + j2) {
+                    *coupled.entry(j).or_default() += times1 * times2;
+                }
+            }
+            js = coupled;
+        }
+        js.into_iter().collect()
+    }
+}
+
+impl Configuration {
+    /// Every distribution of the electrons of each subshell over its j = l±1/2 subshells (see [`SubLevel::relativistic_split`]), which are the configurations of jj coupling
+    pub fn relativistic_split(&self) -> Vec<JjConfiguration> {
+        self.sublevels.iter().fold(
+            vec![JjConfiguration::new([])],
+            |configurations, sublevel| {
+                configurations
+                    .iter()
+                    .cartesian_product(sublevel.relativistic_split())
+                    .map(|(configuration, split)| {
+                        JjConfiguration::new(
+                            configuration
+                                .subshells
+                                .iter()
+                                .chain(&split.subshells)
+                                .cloned(),
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+impl SubLevel {
+    /// Splits the sublevel into all possible distributions of its electrons over the j = l-1/2 and j = l+1/2 subshells, like d^5 into d_3/2^a d_5/2^b with a + b = 5
+    ///
+    /// Distributions are listed starting from the one with most electrons on the lower j subshell. Empty subshells are omitted, and s sublevels only have the j = 1/2 subshell
+    pub fn relativistic_split(&self) -> Vec<JjConfiguration> {
+        let l = HalfInt::from_int(i32::from(self.tp.l()));
+        let subshell = |j: HalfInt, electrons: u8| JjSubLevel {
+            n: self.n,
+            tp: self.tp.clone(),
+            j,
+            electrons,
+        };
+        if self.tp.l() == 0 {
+            return vec![JjConfiguration::new([subshell(
+                HalfInt::HALF,
+                self.electrons,
+            )])];
+        }
+        let lower = subshell(l - HalfInt::HALF, 0).max_electrons();
+        let upper = subshell(l + HalfInt::HALF, 0).max_electrons();
+        (self.electrons.saturating_sub(upper)..=self.electrons.min(lower))
+            .rev()
+            .map(|on_lower| {
+                JjConfiguration::new(
+                    [
+                        subshell(l - HalfInt::HALF, on_lower),
+                        subshell(l + HalfInt::HALF, self.electrons - on_lower),
+                    ]
+                    .into_iter()
+                    .filter(|subshell| subshell.electrons > 0),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Notated for JjConfiguration {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_str(
+            &self
+                .subshells
+                .iter()
+                .map(|subshell| subshell.notated(notation))
+                .join(" "),
+        )
+    }
+}
+
+impl Display for JjConfiguration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+impl Notated for JjSubLevel {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if let Some(n) = self.n {
+            f.write_fmt(format_args!("{n}"))?;
+        }
+        f.write_fmt(format_args!("{}", self.tp))?;
+        notation::subscript(notation, self.j, f)?;
+        notation::superscript(notation, self.electrons, f)
+    }
+}
+
+impl Display for JjSubLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum JjError {
+    #[error("Electron on the {0} sublevel can't have j={1}, it should be l±1/2")]
+    ForbiddenJ(SubLevelType, HalfInt),
+    #[error("There could be at most {} electrons on the {0} subshell", .0.max_electrons())]
+    ToMuch(JjSubLevel),
+    #[error(transparent)]
+    Level(#[from] LevelError),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Configuration, HalfInt, JjConfiguration, JjError, JjSubLevel, SubLevel, SubLevelType,
+    };
+
+    fn total_js(l: u8, doubled_j: i32, electrons: u8) -> String {
+        let subshell = JjSubLevel::new(
+            SubLevelType::new(l).unwrap(),
+            HalfInt::from_doubled(doubled_j),
+            electrons,
+        )
+        .unwrap();
+        subshell
+            .total_js()
+            .into_iter()
+            .map(|(j, times)| format!("{j}x{times}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn known_total_js() {
+        // p3/2^2: J = 0, 2
+        assert_eq!(total_js(1, 3, 2), "0x1 2x1");
+        // closed p1/2^2
+        assert_eq!(total_js(1, 1, 2), "0x1");
+        // d5/2^3: J = 3/2, 5/2, 9/2
+        assert_eq!(total_js(2, 5, 3), "3/2x1 5/2x1 9/2x1");
+        // f7/2^4: J = 0, 2 (x2), 4 (x2), 5, 6, 8
+        assert_eq!(total_js(3, 7, 4), "0x1 2x2 4x2 5x1 6x1 8x1");
+        let subshell =
+            JjSubLevel::with_principal(6, SubLevelType::new(1).unwrap(), HalfInt::HALF, 1).unwrap();
+        assert_eq!(subshell.to_string(), "6p_1/2^1");
+        assert!(matches!(
+            JjSubLevel::new(SubLevelType::new(1).unwrap(), HalfInt::from_doubled(5), 1),
+            Err(JjError::ForbiddenJ(_, _))
+        ));
+        assert!(matches!(
+            JjSubLevel::new(SubLevelType::new(0).unwrap(), HalfInt::HALF, 3),
+            Err(JjError::ToMuch(_))
+        ));
+    }
+
+    #[test]
+    fn relativistic_split() {
+        let split = |sublevel: &str| {
+            sublevel
+                .parse::<SubLevel>()
+                .unwrap()
+                .relativistic_split()
+                .into_iter()
+                .map(|configuration| configuration.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            split("3d5"),
+            [
+                "3d_3/2^4 3d_5/2^1",
+                "3d_3/2^3 3d_5/2^2",
+                "3d_3/2^2 3d_5/2^3",
+                "3d_3/2^1 3d_5/2^4",
+                "3d_5/2^5"
+            ]
+        );
+        assert_eq!(split("2p6"), ["2p_1/2^2 2p_3/2^4"]);
+        assert_eq!(split("4s1"), ["4s_1/2^1"]);
+    }
+
+    #[test]
+    fn configuration_js() {
+        let configuration: Configuration = "2p1 3d1".parse().unwrap();
+        let split = configuration.relativistic_split();
+        assert_eq!(
+            split.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "2p_1/2^1 3d_3/2^1",
+                "2p_1/2^1 3d_5/2^1",
+                "2p_3/2^1 3d_3/2^1",
+                "2p_3/2^1 3d_5/2^1"
+            ]
+        );
+        // 12 levels, as many as of the LS terms 1PDF and 3PDF
+        let js = split
+            .iter()
+            .flat_map(JjConfiguration::total_js)
+            .map(|(_, times)| times)
+            .sum::<usize>();
+        assert_eq!(js, 12);
+        let p3: Configuration = "2p3".parse().unwrap();
+        let js = p3
+            .relativistic_split()
+            .iter()
+            .map(|configuration| configuration.total_js())
+            .collect::<Vec<_>>();
+        let h = HalfInt::from_doubled;
+        // p1/2^2 p3/2^1, p1/2^1 p3/2^2 and p3/2^3
+        assert_eq!(
+            js,
+            [
+                vec![(h(3), 1)],
+                vec![(h(1), 1), (h(3), 1), (h(5), 1)],
+                vec![(h(3), 1)]
+            ]
+        );
+    }
+}
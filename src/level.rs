@@ -0,0 +1,296 @@
+use std::{fmt::Display, str::FromStr};
+
+use num_rational::Rational64;
+
+use crate::{
+    notation, parse_term_symbol, HalfInt, Notated, Notation, Parity, TermError, TermParseError,
+    TermType,
+};
+
+/// Fine-structure level of a term, `^{2S+1}L_J`
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "LevelFields"))]
+pub struct Level {
+    pub(crate) term: TermType,
+    pub(crate) j: HalfInt,
+}
+
+/// Unchecked fields of a deserialized [`Level`]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct LevelFields {
+    term: TermType,
+    j: HalfInt,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<LevelFields> for Level {
+    type Error = TermError;
+
+    fn try_from(fields: LevelFields) -> Result<Self, Self::Error> {
+        Self::new(fields.term, fields.j)
+    }
+}
+
+impl Level {
+    pub fn new(term: TermType, j: HalfInt) -> Result<Self, TermError> {
+        let too_large = || TermError::TooLarge(term.momentum, term.spin);
+        let l = i32::try_from(term.momentum.0)
+            .ok()
+            .and_then(|l| l.checked_mul(2))
+            .map(HalfInt::from_doubled)
+            .ok_or_else(too_large)?;
+        let min = (l - term.spin).abs();
+        let max = l.checked_add(term.spin).ok_or_else(too_large)?;
+        let allowed = min <= j && j <= max;
+        if !allowed || !(j - min).is_integer() {
+            Err(TermError::ForbiddenJ(term, j))
+        } else {
+            Ok(Self { term, j })
+        }
+    }
+
+    pub fn term(&self) -> &TermType {
+        &self.term
+    }
+
+    pub fn j(&self) -> HalfInt {
+        self.j
+    }
+
+    /// Number of states in the level, 2J+1
+    pub fn degeneracy(&self) -> usize {
+        (self.j.doubled() + 1) as usize
+    }
+
+    /// Landé g-factor, g_J = 1 + [J(J+1) + S(S+1) - L(L+1)] / [2J(J+1)]
+    ///
+    /// Returns `None` for J = 0, as the g-factor is undefined there
+    pub fn lande_g(&self) -> Option<f64> {
+        let j = self.j.casimir() as f64;
+        if j == 0.0 {
+            return None;
+        }
+        let s = self.term.spin.casimir() as f64;
+        let l = self.term.l_half_int().casimir() as f64;
+        Some(1.0 + (j + s - l) / (2.0 * j))
+    }
+
+    /// Landé g-factor as an exact fraction, see [`Level::lande_g`]
+    pub fn lande_g_exact(&self) -> Option<Rational64> {
+        let j = self.j.casimir();
+        if j == 0 {
+            return None;
+        }
+        let s = self.term.spin.casimir();
+        let l = self.term.l_half_int().casimir();
+        Some(Rational64::new(3 * j + s - l, 2 * j))
+    }
+}
+
+impl TermType {
+    /// Levels of the term, J going from |L-S| to L+S
+    pub fn levels(&self) -> Vec<Level> {
+        let l = self.l_half_int();
+        (l - self.spin)
+            .abs()
+            .up_to(l + self.spin)
+            .map(|j| Level {
+                term: self.clone(),
+                j,
+            })
+            .collect()
+    }
+}
+
+/// Parses level symbols like `2D_{3/2}`, `^2D_3/2` or `²D₃/₂`
+impl FromStr for Level {
+    type Err = TermParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (term, j) = parse_term_symbol(s)?;
+        let j = j.ok_or_else(|| TermParseError::MissingJ(s.to_string()))?;
+        Ok(Self::new(term, j)?)
+    }
+}
+
+/// Levels are ordered by their terms, and then by J
+impl Ord for Level {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.term.cmp(&other.term).then(self.j.cmp(&other.j))
+    }
+}
+
+impl PartialOrd for Level {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Notated for Level {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if notation == Notation::MathMl {
+            return notation::mathml_term(
+                self.term.multiplicity(),
+                None,
+                self.term.momentum,
+                self.term.parity == Parity::Odd,
+                Some(self.j),
+                f,
+            );
+        }
+        self.term.fmt_notation(notation, f)?;
+        notation::subscript(notation, self.j, f)
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_rational::Rational64;
+
+    use crate::{HalfInt, Level, Parity, TermError, TermMomentum, TermParseError, TermType};
+
+    #[test]
+    fn triplet_p_levels() {
+        let term = TermType {
+            momentum: TermMomentum(1),
+            spin: HalfInt::ONE,
+            parity: Parity::Even,
+        };
+        let levels = term.levels();
+        assert_eq!(
+            levels.iter().map(|level| level.j()).collect::<Vec<_>>(),
+            [HalfInt::ZERO, HalfInt::ONE, HalfInt::from_int(2)]
+        );
+        assert_eq!(
+            levels.iter().map(|level| level.degeneracy()).sum::<usize>(),
+            9
+        );
+        assert_eq!(levels[2].to_string(), "^3P_2");
+        assert_eq!(levels[0].lande_g(), None);
+        assert_eq!(levels[1].lande_g(), Some(1.5));
+        assert_eq!(levels[2].lande_g(), Some(1.5));
+        assert_eq!(levels[1].lande_g_exact(), Some(Rational64::new(3, 2)));
+        assert_eq!(
+            "^4F_3/2".parse::<Level>().unwrap().lande_g_exact(),
+            Some(Rational64::new(2, 5))
+        );
+    }
+
+    #[test]
+    fn parse_symbols() {
+        for input in [
+            "3P",
+            "^3P",
+            "^{3}P",
+            "³P",
+            "<sup>3</sup>P",
+            "{}^{3}\\mathrm{P}",
+        ] {
+            let term: TermType = input.parse().expect("Should parse");
+            assert_eq!(term.to_string(), "^3P");
+        }
+        for input in [
+            "2D_{3/2}",
+            "^2D_3/2",
+            "²D₃/₂",
+            "<sup>2</sup>D<sub>3/2</sub>",
+        ] {
+            let level: Level = input.parse().expect("Should parse");
+            assert_eq!(level.to_string(), "^2D_3/2");
+        }
+        for input in ["4S*_3/2", "⁴S°₃/₂", "{}^{4}\\mathrm{S}^{\\circ}_{3/2}"] {
+            let level: Level = input.parse().expect("Should parse");
+            assert_eq!(level.term().parity(), Parity::Odd);
+            assert_eq!(level.to_string(), "^4S*_3/2");
+        }
+        assert!(matches!(
+            "3P_2".parse::<TermType>(),
+            Err(TermParseError::UnexpectedJ(_))
+        ));
+        assert!(matches!(
+            "3P".parse::<Level>(),
+            Err(TermParseError::MissingJ(_))
+        ));
+        assert!(matches!(
+            "3P_3".parse::<Level>(),
+            Err(TermParseError::Term(TermError::ForbiddenJ(_, _)))
+        ));
+        assert!(matches!(
+            "3P_1/2".parse::<Level>(),
+            Err(TermParseError::Term(TermError::ForbiddenJ(_, _)))
+        ));
+        for input in ["3P_", "3P*_", "^3P_{}"] {
+            assert!(matches!(
+                input.parse::<Level>(),
+                Err(TermParseError::Invalid(_))
+            ));
+        }
+        for input in ["3J", "3q"] {
+            assert!(matches!(
+                input.parse::<TermType>(),
+                Err(TermParseError::UnknownLetter(_))
+            ));
+        }
+        // letters continue after I without J, and the momenta above Z are written out
+        for l in 0..=24 {
+            let term = TermType::new(TermMomentum(l), HalfInt::ONE).unwrap();
+            let symbol = term.to_string();
+            assert_eq!(symbol.parse::<TermType>().unwrap(), term, "{symbol}");
+        }
+        for (l, symbol) in [
+            (7, "^3K"),
+            (11, "^3O"),
+            (12, "^3Q"),
+            (20, "^3Z"),
+            (21, "^3(L=21)"),
+        ] {
+            let term = TermType::new(TermMomentum(l), HalfInt::ONE).unwrap();
+            assert_eq!(term.to_string(), symbol);
+        }
+        for input in [
+            "3(L=1500000000)",
+            "2147483647S",
+            "3(L=99999999999999999999)",
+        ] {
+            assert!(input.parse::<TermType>().is_err());
+        }
+        let level: Level = "1073741824(L=536870911)_1073741823/2"
+            .parse()
+            .expect("Largest L and S are supported");
+        assert!(level.lande_g().is_some());
+        assert!(level.lande_g_exact().is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let level: Level = "2D_5/2".parse().unwrap();
+        let json = serde_json::to_string(&level).expect("Should serialize");
+        assert_eq!(
+            json,
+            r#"{"term":{"momentum":2,"spin":"1/2","parity":"even"},"j":"5/2"}"#
+        );
+        let deserialized: Level = serde_json::from_str(&json).expect("Should deserialize");
+        assert_eq!(deserialized, level);
+
+        // checked the same as the constructors do
+        for invalid in [
+            r#"{"term":{"momentum":0,"spin":"-1","parity":"even"},"j":"1"}"#,
+            r#"{"term":{"momentum":0,"spin":"0","parity":"even"},"j":"7"}"#,
+        ] {
+            assert!(serde_json::from_str::<Level>(invalid).is_err());
+        }
+    }
+}
@@ -1,32 +1,161 @@
 use itertools::Itertools;
+use num_bigint::BigUint;
 use std::{
-    collections::{BTreeMap, HashMap},
+    cmp::Ordering,
     fmt::Display,
-    io::{sink, Write},
+    str::FromStr,
+    sync::atomic::{self, AtomicBool},
 };
 
 use thiserror::Error;
 
-#[derive(Debug)]
-pub struct SubLevelType(pub u8);
+mod boxes;
+mod cache;
+mod census;
+mod cfp;
+mod configuration;
+mod coupling;
+mod degeneracy;
+mod eigenstate;
+mod elements;
+mod events;
+mod fan;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod grid;
+mod half_int;
+mod html;
+mod hund;
+mod hyperfine;
+mod intermediate;
+mod isotopes;
+mod jj;
+mod level;
+mod limit;
+mod linalg;
+mod logger;
+mod microstate;
+mod multiplicities;
+mod notation;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod parity;
+mod recursive;
+mod seniority;
+mod slater;
+mod slater_condon;
+mod solution;
+mod state_table;
+mod svg;
+mod tables;
+mod transformation;
+mod transitions;
+mod tree;
+mod units;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+mod wigner;
+mod zeeman;
+pub use boxes::OrbitalBoxes;
+use cache::TermCache;
+pub use cache::{cached_term_tables, clear_term_cache};
+pub use census::{ee_term_counts, ee_terms_analytic};
+pub use cfp::CfpError;
+pub use configuration::{couple_terms, Configuration, ConfigurationParseError, CoupledTerm};
+pub use coupling::{
+    clebsch_gordan, clebsch_gordan_exact, momenta_exact, momenta_in_range, SqrtRational,
+    MAX_EXACT_MOMENTUM, MAX_MOMENTUM,
+};
+pub use degeneracy::{check_degeneracy, DegeneracyMismatch};
+pub use eigenstate::{Eigenstate, TermEigenstates};
+pub use elements::{Element, Ion, IonError, UnknownElement};
+use events::LogObserver;
+pub use events::{DerivationEvent, DerivationObserver, DerivationPhase, DerivationProgress};
+pub use fan::{FanCurve, ZeemanFan};
+pub use grid::MicrostateGrid;
+pub use half_int::{HalfInt, HalfIntParseError};
+pub use html::render_html;
+pub use hund::hund_order;
+pub use hyperfine::HyperfineLevel;
+pub use intermediate::IntermediateLevel;
+pub use isotopes::{hyperfine, Isotope, UnknownIsotope};
+pub use jj::{JjConfiguration, JjError, JjSubLevel};
+pub use level::Level;
+pub use limit::EnumerationLimit;
+pub use logger::{DerivationLogger, NoLog};
+use microstate::{occupations, Combinations};
+pub use microstate::{Microstate, MicrostateError, SpinOrbital, SpinOrbitalParseError};
+pub use notation::{mathml, Notated, Notation, UnknownNotation, WithNotation};
+pub use parity::Parity;
+pub use recursive::{ee_terms_parentage, ee_terms_recursive};
+pub use seniority::SeniorityTerm;
+pub use slater_condon::{SlaterError, SlaterExpression, SlaterIntegral, TermEnergy};
+pub use solution::WorkedSolution;
+use state_table::StateTable;
+pub use tables::tabulated_terms;
+pub use transformation::{LsJjMatrix, TransformationError};
+pub use transitions::{
+    allowed_e1, lowest_multipole, multiplet_strengths, sublevel_violations, violations,
+    MultipletLine, Multipole, RuleViolation, UnknownMultipole,
+};
+pub use tree::CouplingTree;
+pub use units::{EnergyUnit, MagneticField, UnitParseError};
+pub use wigner::{
+    momenta_in_9j_range, wigner_3j, wigner_3j_exact, wigner_6j, wigner_6j_exact, wigner_9j,
+    MAX_9J_MOMENTUM,
+};
+pub use zeeman::{FieldState, ZeemanSublevel};
+
+/// For the types that can't be serialized otherwise (like [`std::num::ParseIntError`])
+#[cfg(feature = "serde")]
+fn serialize_display<S: serde::Serializer>(
+    value: &impl Display,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
 
-static WHY: &str = "Should be able to express as u8 (why would you need sublevel with L=50, lol?)";
+/// Orbital momentum L of a sublevel
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8"))]
+pub struct SubLevelType(u8);
 
 impl SubLevelType {
-    pub fn max_electrons(&self) -> u8 {
+    /// Largest supported orbital momentum, so that the number of electrons still fits into `u8` (why would you need sublevel with L=50, lol?)
+    pub const MAX_L: u8 = 63;
+
+    pub fn new(l: u8) -> Result<Self, LevelError> {
+        if l <= Self::MAX_L {
+            Ok(Self(l))
+        } else {
+            Err(LevelError::TooLarge(l))
+        }
+    }
+
+    pub fn l(&self) -> u8 {
         self.0
-            .checked_mul(2)
-            .and_then(|r| r.checked_add(1))
-            .and_then(|r| r.checked_mul(2))
-            .expect(WHY)
+    }
+
+    pub fn max_electrons(&self) -> u8 {
+        // can't overflow, since L is at most `MAX_L`
+        (2 * self.0 + 1) * 2
     }
 
     pub fn mls(&self) -> impl IntoIterator<Item = i8> {
-        let l: i8 = self.0.try_into().expect(WHY);
+        let l = self.0 as i8;
         -l..=l
     }
 }
 
+impl TryFrom<u8> for SubLevelType {
+    type Error = LevelError;
+
+    fn try_from(l: u8) -> Result<Self, Self::Error> {
+        Self::new(l)
+    }
+}
+
 impl Display for SubLevelType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = format!("(L={})", self.0);
@@ -43,214 +172,1260 @@ impl Display for SubLevelType {
     }
 }
 
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SubLevelParseError {
+    #[error("Can't parse sublevel \"{0}\", expected something like \"d\", \"3d5\" or \"p^3\"")]
+    Invalid(String),
+    #[error("Unknown sublevel letter \"{0}\"")]
+    UnknownLetter(String),
+    #[error(transparent)]
+    Level(#[from] LevelError),
+}
+
+/// Parses sublevel letter, like `d`, or the `(L=7)` form used for momenta without a letter
+impl FromStr for SubLevelType {
+    type Err = SubLevelParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(l) = s.strip_prefix("(L=").and_then(|s| s.strip_suffix(')')) {
+            let l = l
+                .parse()
+                .map_err(|_| SubLevelParseError::Invalid(s.to_string()))?;
+            return Ok(Self::new(l)?);
+        }
+        Ok(Self(match s {
+            "s" => 0,
+            "p" => 1,
+            "d" => 2,
+            "f" => 3,
+            "g" => 4,
+            "h" => 5,
+            "i" => 6,
+            _ => return Err(SubLevelParseError::UnknownLetter(s.to_string())),
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SubLevelFields"))]
 pub struct SubLevel {
+    n: Option<u8>,
+    tp: SubLevelType,
+    electrons: u8,
+}
+
+/// Unchecked fields of a deserialized [`SubLevel`]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SubLevelFields {
+    n: Option<u8>,
     tp: SubLevelType,
     electrons: u8,
 }
 
+#[cfg(feature = "serde")]
+impl TryFrom<SubLevelFields> for SubLevel {
+    type Error = LevelError;
+
+    fn try_from(fields: SubLevelFields) -> Result<Self, Self::Error> {
+        match fields.n {
+            Some(n) => Self::with_principal(n, fields.tp, fields.electrons),
+            None => Self::new(fields.tp, fields.electrons),
+        }
+    }
+}
+
+impl Notated for SubLevel {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if notation == Notation::MathMl {
+            let n = self.n.map(|n| format!("<mn>{n}</mn>")).unwrap_or_default();
+            return write!(
+                f,
+                "<msup><mrow>{n}<mi>{}</mi></mrow><mn>{}</mn></msup>",
+                self.tp, self.electrons
+            );
+        }
+        if let Some(n) = self.n {
+            f.write_fmt(format_args!("{n}"))?;
+        }
+        f.write_fmt(format_args!("{}", self.tp))?;
+        notation::superscript(notation, self.electrons, f)
+    }
+}
+
 impl Display for SubLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("{}^{{{}}}", self.tp, self.electrons))
+        self.fmt_notation(Notation::default(), f)
     }
 }
 
 #[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LevelError {
     #[error("There could be at most {} electrons on the {0} sublevel", .0.max_electrons())]
     ToMuch(SubLevelType),
+    #[error("There's no {1} sublevel for n={0}, as L must be less than n")]
+    NoSuchSublevel(u8, SubLevelType),
+    #[error("Sublevel with L={0} is not supported, L should be at most {max}", max = SubLevelType::MAX_L)]
+    TooLarge(u8),
 }
 
 impl SubLevel {
     pub fn new(t: SubLevelType, electrons: u8) -> Result<Self, LevelError> {
         if electrons <= t.max_electrons() {
-            Ok(Self { tp: t, electrons })
+            Ok(Self {
+                n: None,
+                tp: t,
+                electrons,
+            })
         } else {
             Err(LevelError::ToMuch(t))
         }
     }
+
+    /// Same as [`SubLevel::new`], but also specifies principal quantum number of the sublevel
+    pub fn with_principal(n: u8, t: SubLevelType, electrons: u8) -> Result<Self, LevelError> {
+        if t.0 >= n {
+            return Err(LevelError::NoSuchSublevel(n, t));
+        }
+        Ok(Self {
+            n: Some(n),
+            ..Self::new(t, electrons)?
+        })
+    }
+
+    pub fn n(&self) -> Option<u8> {
+        self.n
+    }
+
+    pub fn electrons(&self) -> u8 {
+        self.electrons
+    }
+
+    pub fn level_type(&self) -> &SubLevelType {
+        &self.tp
+    }
+}
+
+/// Parses sublevel like `d`, `3d5`, `p^3`, `2p^{3}` or `3d⁵` (principal quantum number is optional, electron number defaults to 1)
+impl FromStr for SubLevel {
+    type Err = SubLevelParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SubLevelParseError::Invalid(s.to_string());
+        let normalized = notation::normal_digits(s);
+        let letter_start = normalized
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let (n, rest) = normalized.split_at(letter_start);
+        let letter_end = if rest.starts_with('(') {
+            rest.find(')').ok_or_else(invalid)? + 1
+        } else {
+            rest.chars().next().ok_or_else(invalid)?.len_utf8()
+        };
+        let (tp, electrons) = rest.split_at(letter_end);
+        let tp: SubLevelType = tp.parse()?;
+        let electrons = electrons.strip_prefix('^').unwrap_or(electrons);
+        let electrons = electrons
+            .strip_prefix('{')
+            .and_then(|e| e.strip_suffix('}'))
+            .unwrap_or(electrons);
+        let electrons = if electrons.is_empty() {
+            1
+        } else {
+            electrons.parse().map_err(|_| invalid())?
+        };
+        if n.is_empty() {
+            Ok(Self::new(tp, electrons)?)
+        } else {
+            let n = n.parse().map_err(|_| invalid())?;
+            Ok(Self::with_principal(n, tp, electrons)?)
+        }
+    }
 }
 
-#[derive(Debug, Hash, PartialEq, Eq)]
-struct TermMomentum(usize);
+/// Total orbital momentum L of a term
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TermMomentum(pub usize);
+
+/// Letters of the term momenta, L = 0, 1, 2, ...: alphabetical after F, skipping J and the letters already used (P and S)
+const TERM_LETTERS: &str = "SPDFGHIKLMNOQRTUVWXYZ";
 
 impl Display for TermMomentum {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = format!("(L={})", self.0);
-        f.write_str(match self.0 {
-            0 => "S",
-            1 => "P",
-            2 => "D",
-            3 => "F",
-            4 => "G",
-            5 => "H",
-            6 => "I",
-            _ => &s,
-        })
+        match TERM_LETTERS.get(self.0..=self.0) {
+            Some(letter) => f.write_str(letter),
+            None => write!(f, "(L={})", self.0),
+        }
     }
 }
 
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TermFields"))]
 pub struct TermType {
     momentum: TermMomentum,
-    multiplet: usize,
+    spin: HalfInt,
+    parity: Parity,
+}
+
+/// Unchecked fields of a deserialized [`TermType`]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TermFields {
+    momentum: TermMomentum,
+    spin: HalfInt,
+    parity: Parity,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TermFields> for TermType {
+    type Error = TermError;
+
+    fn try_from(fields: TermFields) -> Result<Self, Self::Error> {
+        Ok(Self::new(fields.momentum, fields.spin)?.with_parity(fields.parity))
+    }
+}
+
+/// Spectroscopic order: greater S goes first, then terms are ordered by L (and even terms go before odd ones)
+impl Ord for TermType {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .spin
+            .cmp(&self.spin)
+            .then(self.momentum.cmp(&other.momentum))
+            .then(self.parity.cmp(&other.parity))
+    }
+}
+
+impl PartialOrd for TermType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TermError {
+    #[error("Spin can't be negative, got S={0}")]
+    NegativeSpin(HalfInt),
+    #[error("J={1} is not allowed for {0} term")]
+    ForbiddenJ(TermType, HalfInt),
+    #[error("Term with L={} and S={1} is not supported, L should be at most {max_l} and S at most {max_s}", .0.0, max_l = TermType::MAX_L, max_s = TermType::MAX_S)]
+    TooLarge(TermMomentum, HalfInt),
+}
+
+impl TermType {
+    /// Largest L of a term, so that the doubled momenta of its levels (and their sums) fit into `i32`
+    pub const MAX_L: usize = (i32::MAX / 4) as usize;
+    /// Largest S of a term, see [`TermType::MAX_L`]
+    pub const MAX_S: HalfInt = HalfInt::from_doubled(i32::MAX / 2);
+
+    /// Creates an even term, use [`TermType::with_parity`] to change it
+    pub fn new(momentum: TermMomentum, spin: HalfInt) -> Result<Self, TermError> {
+        if spin < HalfInt::ZERO {
+            Err(TermError::NegativeSpin(spin))
+        } else if momentum.0 > Self::MAX_L || spin > Self::MAX_S {
+            Err(TermError::TooLarge(momentum, spin))
+        } else {
+            Ok(Self {
+                momentum,
+                spin,
+                parity: Parity::Even,
+            })
+        }
+    }
+
+    pub fn with_parity(self, parity: Parity) -> Self {
+        Self { parity, ..self }
+    }
+
+    pub fn parity(&self) -> Parity {
+        self.parity
+    }
+
+    pub fn momentum(&self) -> TermMomentum {
+        self.momentum
+    }
+
+    /// Total orbital momentum L
+    pub fn l(&self) -> usize {
+        self.momentum.0
+    }
+
+    /// L as a [`HalfInt`], which can't overflow since it's at most [`TermType::MAX_L`]
+    pub(crate) fn l_half_int(&self) -> HalfInt {
+        HalfInt::from_int(self.momentum.0 as i32)
+    }
+
+    /// Total spin S
+    pub fn s(&self) -> HalfInt {
+        self.spin
+    }
+
+    /// Multiplicity of the term, 2S+1
+    pub fn multiplicity(&self) -> usize {
+        (self.spin.doubled() + 1) as usize
+    }
+
+    /// Number of states in the term, (2S+1)(2L+1)
+    pub fn degeneracy(&self) -> usize {
+        self.multiplicity() * (2 * self.l() + 1)
+    }
+}
+
+impl Notated for TermType {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        // so that the superscript does not attach to whatever precedes the term
+        match notation {
+            Notation::Latex => f.write_str("{}")?,
+            Notation::Typst => f.write_str("\"\"")?,
+            Notation::MathMl => {
+                return notation::mathml_term(
+                    self.multiplicity(),
+                    None,
+                    self.momentum,
+                    self.parity == Parity::Odd,
+                    None,
+                    f,
+                )
+            }
+            _ => {}
+        }
+        notation::superscript(notation, self.multiplicity(), f)?;
+        notation::letter(notation, self.momentum, f)?;
+        if self.parity == Parity::Odd {
+            notation::odd_mark(notation, f)?;
+        }
+        Ok(())
+    }
 }
 
 impl Display for TermType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("^{{{}}}{}", self.multiplet, self.momentum))
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TermParseError {
+    #[error("Can't parse term symbol \"{0}\", expected something like \"3P\", \"^2D_{{3/2}}\" or \"³P₂\"")]
+    Invalid(String),
+    #[error("Unknown term letter \"{0}\"")]
+    UnknownLetter(String),
+    #[error("Term symbol \"{0}\" should not specify J")]
+    UnexpectedJ(String),
+    #[error("Level symbol \"{0}\" should specify J")]
+    MissingJ(String),
+    #[error(transparent)]
+    Term(#[from] TermError),
+    #[error(transparent)]
+    J(#[from] HalfIntParseError),
+}
+
+/// Parses term symbol, optionally followed by J. Ascii, Unicode, HTML and LaTeX notations are all accepted
+fn parse_term_symbol(s: &str) -> Result<(TermType, Option<HalfInt>), TermParseError> {
+    let invalid = || TermParseError::Invalid(s.to_string());
+    let normalized = notation::normal_digits(s)
+        .replace("\\mathrm", "")
+        .replace("\\circ", "°")
+        .replace("<sub>", "_")
+        .replace(['^', '{', '}', ' '], "")
+        .replace("<sup>", "")
+        .replace("</sup>", "")
+        .replace("</sub>", "");
+    let letter_start = normalized
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (multiplet, rest) = normalized.split_at(letter_start);
+    let multiplet: i32 = multiplet.parse().map_err(|_| invalid())?;
+    if multiplet < 1 {
+        return Err(invalid());
+    }
+    let letter_end = if rest.starts_with('(') {
+        rest.find(')').ok_or_else(invalid)? + 1
+    } else {
+        rest.chars().next().ok_or_else(invalid)?.len_utf8()
+    };
+    let (momentum, rest) = rest.split_at(letter_end);
+    let (parity, j) = match rest.strip_prefix(['*', '°', 'o']) {
+        Some(j) => (Parity::Odd, j),
+        None => (Parity::Even, rest),
+    };
+    let momentum = if let Some(l) = momentum
+        .strip_prefix("(L=")
+        .and_then(|l| l.strip_suffix(')'))
+    {
+        l.parse().map_err(|_| invalid())?
+    } else {
+        TERM_LETTERS
+            .find(momentum)
+            .ok_or_else(|| TermParseError::UnknownLetter(momentum.to_string()))?
+    };
+    let term = TermType::new(TermMomentum(momentum), HalfInt::from_doubled(multiplet - 1))?
+        .with_parity(parity);
+    let j = match j.strip_prefix('_') {
+        Some("") => return Err(invalid()),
+        Some(j) => Some(j.parse()?),
+        None if j.is_empty() => None,
+        None => Some(j.parse()?),
+    };
+    Ok((term, j))
+}
+
+/// Parses term symbols like `3P`, `^3P`, `^{3}P` or `³P`
+impl FromStr for TermType {
+    type Err = TermParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_term_symbol(s)? {
+            (term, None) => Ok(term),
+            (_, Some(_)) => Err(TermParseError::UnexpectedJ(s.to_string())),
+        }
     }
 }
 
-pub fn ee_terms(l: SubLevel) -> Result<Vec<TermType>, std::io::Error> {
-    ee_terms_log(l, sink)
+/// Terms of equivalent electrons of the sublevel, sorted in spectroscopic order
+///
+/// Terms occurring several times (like ^2D of d^3) are repeated the corresponding number of times
+///
+/// More than half-filled sublevels are handled via their [hole equivalent](SubLevel::hole_equivalent)
+///
+/// Terms of s, p, d and f sublevels are [precomputed](tabulated_terms), and the others are remembered for the rest of the program, see [`clear_term_cache`]
+///
+/// Any sublevel is enumerated, however long it takes, see [`ee_terms_limited`]
+pub fn ee_terms(l: SubLevel) -> Result<Vec<TermType>, DerivationError> {
+    ee_terms_limited(l, EnumerationLimit::default())
+}
+
+/// Same as [`ee_terms`], but fails with [`DerivationError::MicrostateLimit`] instead of enumerating more microstates than `limit` allows
+pub fn ee_terms_limited(
+    l: SubLevel,
+    limit: EnumerationLimit,
+) -> Result<Vec<TermType>, DerivationError> {
+    counted_terms(
+        l,
+        |_| {},
+        &AtomicBool::new(false),
+        limit,
+        TermCache::global(),
+    )
+}
+
+/// Lazily yields terms of the sublevel as they are extracted, so that the caller may stop early
+///
+/// Unlike [`ee_terms`], terms are yielded in the extraction order (by descending L and S), and not sorted
+///
+/// Microstates are enumerated on the first call to `next`, and only their ML and MS are kept
+pub fn ee_terms_iter(l: SubLevel) -> impl Iterator<Item = TermType> {
+    let parity = l.parity();
+    let mut table = None;
+    std::iter::from_fn(move || {
+        table
+            .get_or_insert_with(|| {
+                count_table(&l, |_, _| {}, &AtomicBool::new(false))
+                    .expect("Enumeration is never cancelled")
+            })
+            .extract()
+            .map(|(term, _)| term.with_parity(parity))
+    })
 }
 
-static SEPARATOR: &[u8] = " ----- \n".as_bytes();
-static SPINS: [i8; 2] = [-1, 1]; // SPINS ARE DOUBLED IN THE CODE!!!!!
+/// Same as [`ee_terms_iter`], but fails with [`DerivationError::MicrostateLimit`] right away if enumerating the sublevel exceeds `limit`
+pub fn ee_terms_iter_limited(
+    l: SubLevel,
+    limit: EnumerationLimit,
+) -> Result<impl Iterator<Item = TermType>, DerivationError> {
+    limit.check(&l, false)?;
+    Ok(ee_terms_iter(l))
+}
+
+/// Same as [`ee_terms`], but calls `progress` with the phase of the derivation and the numbers of processed and total microstates, for every microstate enumerated and every term extracted
+///
+/// Cached terms are not enumerated again, so `progress` is not called for them
+pub fn ee_terms_progress(
+    l: SubLevel,
+    progress: impl FnMut(DerivationProgress),
+) -> Result<Vec<TermType>, DerivationError> {
+    counted_terms(
+        l,
+        progress,
+        &AtomicBool::new(false),
+        EnumerationLimit::default(),
+        TermCache::global(),
+    )
+}
 
-pub fn ee_terms_log<W: Write>(
+/// Same as [`ee_terms`], but stops with [`DerivationError::Cancelled`] as soon as `cancel` is set, possibly from another thread
+///
+/// Cancelled derivations are not cached, so the same sublevel may be derived again later
+pub fn ee_terms_cancellable(
     l: SubLevel,
-    log: impl Fn() -> W,
-) -> Result<Vec<TermType>, std::io::Error> {
-    writeln!(log(), "Sublevel: {l}")?;
-    log().write(SEPARATOR)?;
+    cancel: &AtomicBool,
+) -> Result<Vec<TermType>, DerivationError> {
+    counted_terms(
+        l,
+        |_| {},
+        cancel,
+        EnumerationLimit::default(),
+        TermCache::global(),
+    )
+}
+
+/// Terms of the sublevel from `cache`, or counting microstates by ML and MS, unless the enumeration exceeds `limit`
+pub(crate) fn counted_terms(
+    l: SubLevel,
+    progress: impl FnMut(DerivationProgress),
+    cancel: &AtomicBool,
+    limit: EnumerationLimit,
+    cache: &TermCache,
+) -> Result<Vec<TermType>, DerivationError> {
+    cache.terms(&l, || {
+        limit.check(&l, false)?;
+        derive_counted_terms(&l, progress, cancel)
+    })
+}
 
+/// Terms of the sublevel, counting microstates by ML and MS, derived anew
+fn derive_counted_terms(
+    l: &SubLevel,
+    mut progress: impl FnMut(DerivationProgress),
+    cancel: &AtomicBool,
+) -> Result<Vec<TermType>, DerivationError> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("ee_terms", sublevel = %l).entered();
+    let mut total = 0;
+    let mut table = count_table(
+        l,
+        |processed, all| {
+            total = all;
+            progress(DerivationProgress {
+                phase: DerivationPhase::Enumeration,
+                processed,
+                total,
+            })
+        },
+        cancel,
+    )
+    .ok_or(DerivationError::Cancelled)?;
+    let mut processed = 0;
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("extraction").entered();
+    let mut terms = std::iter::from_fn(|| {
+        // the table is complete, but there's no need to extract the rest
+        if cancel.load(atomic::Ordering::Relaxed) {
+            return Some(Err(DerivationError::Cancelled));
+        }
+        let (term, states) = table.extract()?;
+        #[cfg(feature = "tracing")]
+        tracing::trace!(term = %term, states = states.len(), "term extracted");
+        processed += states.len() as u128;
+        progress(DerivationProgress {
+            phase: DerivationPhase::Extraction,
+            processed,
+            total,
+        });
+        Some(Ok(term.with_parity(l.parity())))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+    terms.sort();
+    Ok(terms)
+}
+
+/// Table of the numbers of microstates with each ML and MS (of the [hole equivalent](SubLevel::hole_equivalent), if there is one), or `None` if `cancel` was set before every microstate was enumerated
+fn count_table(
+    l: &SubLevel,
+    progress: impl FnMut(u128, u128),
+    cancel: &AtomicBool,
+) -> Option<StateTable<()>> {
+    let l = l.hole_equivalent().unwrap_or_else(|| l.clone());
+    let total = l.microstate_count().unwrap_or(u128::MAX);
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("enumeration", placed = %l, microstates = total).entered();
     let single_states =
         l.tp.mls()
             .into_iter()
             .cartesian_product(SPINS)
             .collect_vec();
-    let single_states_num = single_states.len();
-    writeln!(log(), "Single electron states ({single_states_num} total)")?;
-    single_states
-        .iter()
-        .enumerate()
-        .map(|(i, (ml, ms))| writeln!(log(), "{i}: ({ml}, {ms}/2)"))
-        .try_collect()?;
-    log().write(SEPARATOR)?;
-
-    let level_states = (0..single_states_num)
-        .combinations(l.electrons as usize)
-        .map(|state| {
-            let mut repr = (String::new(), 0, 0);
-            state.into_iter().for_each(|next| {
-                repr.0.push_str(&format!("{} ", next + 1));
-                repr.1 += single_states[next].0;
-                repr.2 += single_states[next].1;
-            });
-            repr
-        })
-        .collect_vec();
-    writeln!(log(), "Level states")?;
-    writeln!(log(), "({} total)", level_states.len())?;
-    level_states
-        .iter()
-        .map(|(name, ml, ms)| {
-            writeln!(
-                log(),
-                "{}: ({}, {})",
-                name,
-                ml,
-                if ms & 1 == 0 {
-                    (ms / 2).to_string()
-                } else {
-                    format!("{}/2", ms)
-                }
-            )
-        })
-        .try_collect()?;
-    log().write(SEPARATOR)?;
+    #[cfg(feature = "tracing")]
+    tracing::trace!(
+        count = single_states.len(),
+        "single-electron states enumerated"
+    );
+    #[cfg(feature = "parallel")]
+    if total >= parallel::MIN_MICROSTATES {
+        return parallel::count_table(&single_states, l.electrons, total, progress, cancel);
+    }
+    serial_count_table(&single_states, l.electrons, total, progress, cancel)
+}
+
+/// Same as [`count_table`], binning the microstates on the calling thread
+fn serial_count_table(
+    single_states: &[(i8, HalfInt)],
+    electrons: u8,
+    total: u128,
+    mut progress: impl FnMut(u128, u128),
+    cancel: &AtomicBool,
+) -> Option<StateTable<()>> {
+    let mut combinations = Combinations::new(single_states.len() as u32, u32::from(electrons));
+    let mut processed = 0;
+    let mut complete = false;
+    let table = StateTable::new(std::iter::from_fn(|| {
+        if cancel.load(atomic::Ordering::Relaxed) {
+            return None;
+        }
+        let Some(state) = combinations.advance() else {
+            complete = true;
+            return None;
+        };
+        processed += 1;
+        progress(processed, total);
+        let (ml, ms) = state.iter().fold((0, HalfInt::ZERO), |(ml, ms), &next| {
+            let (next_ml, next_ms) = single_states[next as usize];
+            (ml + i32::from(next_ml), ms + next_ms)
+        });
+        // only the state count matters here, and `Vec<()>` does not allocate
+        Some((ml, ms, ()))
+    }));
+    complete.then_some(table)
+}
+
+static SPINS: [HalfInt; 2] = [HalfInt::from_doubled(-1), HalfInt::HALF];
+
+/// Error of the term derivation
+#[derive(Debug, Error)]
+pub enum DerivationError {
+    #[error("Microstates of the {0} sublevel can't be represented, L should be at most {max}", max = Microstate::MAX_L)]
+    TooLarge(SubLevelType),
+    #[error("Numbers of microstates of the {0} sublevel don't fit into 128 bits")]
+    Overflow(SubLevel),
+    #[error("The {sublevel} sublevel has {microstates} microstates to enumerate, more than the limit of {limit}")]
+    MicrostateLimit {
+        sublevel: SubLevel,
+        microstates: BigUint,
+        limit: u128,
+    },
+    #[error(
+        "Microstates of the {sublevel} sublevel take {bytes} bytes, more than the limit of {limit}"
+    )]
+    MemoryLimit {
+        sublevel: SubLevel,
+        bytes: BigUint,
+        limit: u128,
+    },
+    #[error("Coupling the subshells gives {terms} terms, more than the limit of {limit}")]
+    TermLimit { terms: BigUint, limit: u128 },
+    #[error("Terms coupled from the subshells take {bytes} bytes, more than the limit of {limit}")]
+    CouplingMemoryLimit { bytes: BigUint, limit: u128 },
+    #[error("Derivation was cancelled")]
+    Cancelled,
+    #[error(transparent)]
+    Log(#[from] std::io::Error),
+}
 
-    // Here's a fancy approach with itertool's groups, but it ends up with some states lost for some reason :idk:
-    /*
-    let mut sorted_states: BTreeMap<i8, BTreeMap<i8, Vec<String>>> = level_states
+/// Same as [`ee_terms`], but logs every step of the derivation: single-electron states, microstates and the ones assigned to each term
+///
+/// A disabled logger, like [`NoLog`], makes it the same as [`ee_terms`], which only counts the microstates
+pub fn ee_terms_log(
+    l: SubLevel,
+    log: &mut (impl DerivationLogger + ?Sized),
+) -> Result<Vec<TermType>, DerivationError> {
+    // there's nothing to log the microstates to, so they are only counted
+    if !log.enabled() {
+        return ee_terms(l);
+    }
+    Ok(ee_terms_detailed_log(l, log)?
         .into_iter()
-        .group_by(|(_, ml, _)| *ml)
+        .map(|(term, _)| term)
+        .collect())
+}
+
+/// Same as [`ee_terms`], but also returns microstates assigned to each of the terms
+pub fn ee_terms_detailed(l: SubLevel) -> Result<Vec<(TermType, Vec<Microstate>)>, DerivationError> {
+    ee_terms_detailed_limited(l, EnumerationLimit::default())
+}
+
+/// Same as [`ee_terms_detailed`], but fails with [`DerivationError::MicrostateLimit`] or [`DerivationError::MemoryLimit`] instead of keeping more microstates than `limit` allows
+pub fn ee_terms_detailed_limited(
+    l: SubLevel,
+    limit: EnumerationLimit,
+) -> Result<Vec<(TermType, Vec<Microstate>)>, DerivationError> {
+    observed_terms(l, &mut NoLog, limit)
+}
+
+/// Same as [`ee_terms_detailed`], but logs every step of the derivation, like [`ee_terms_log`]
+pub fn ee_terms_detailed_log(
+    l: SubLevel,
+    log: &mut (impl DerivationLogger + ?Sized),
+) -> Result<Vec<(TermType, Vec<Microstate>)>, DerivationError> {
+    let mut observer = LogObserver::new(log);
+    let terms = ee_terms_detailed_observed(l, &mut observer)?;
+    observer.finish()?;
+    Ok(terms)
+}
+
+/// Same as [`ee_terms_detailed`], but reports every step of the derivation to `observer` as a [`DerivationEvent`]
+pub fn ee_terms_observed(
+    l: SubLevel,
+    observer: &mut (impl DerivationObserver + ?Sized),
+) -> Result<Vec<TermType>, DerivationError> {
+    Ok(ee_terms_detailed_observed(l, observer)?
         .into_iter()
-        .map(|(ml, l_states)| {
-            (
-                ml,
-                l_states
-                    .into_iter()
-                    .group_by(|(_, _, ms)| *ms)
-                    .into_iter()
-                    .map(|(ms, ls_states)| {
-                        (
-                            ms,
-                            ls_states.into_iter().map(|(name, _, _)| name).collect_vec(),
-                        )
-                    })
-                    .collect(),
-            )
-        })
-        .collect();
-     */
-    let mut sorted_states: BTreeMap<i8, BTreeMap<i8, Vec<String>>> = BTreeMap::new();
-    level_states.into_iter().for_each(|(name, ml, ms)| {
-        sorted_states
-            .entry(ml)
-            .or_default()
-            .entry(ms)
-            .or_default()
-            .push(name);
-    });
-
-    writeln!(log(), "Terms:")?;
-    let mut term_states: HashMap<TermType, Vec<String>> = HashMap::new();
-    while let Some((&l, _)) = sorted_states.last_key_value() {
-        let l_states = sorted_states
-            .get(&l)
-            .expect("Should be entry for this key, enforced above");
-        let (&s, _) = l_states
-            .last_key_value()
-            .expect("There's at least one state, so entry should exist");
-        let term = TermType {
-            momentum: TermMomentum(l.try_into().expect("Max momentum must be nonnegative!")),
-            multiplet: (s + 1).try_into().expect("Max spin must be nonnegative!"), // SPIN IS DOUBLED!
-        };
-        writeln!(log(), "{term}")?;
-        let this_term_states = term_states.entry(term).or_default();
-
-        for l in -l..=l {
-            let l_states = sorted_states
-                .get_mut(&l)
-                .expect("Should be entry for this key, enforced above");
-            for s in (-s..=s).step_by(2) {
-                let sl_states = l_states
-                    .get_mut(&s)
-                    .expect("Should be entry states with this spin!");
-                let this_state = sl_states
-                    .pop()
-                    .expect("Should be at least one state, will be enforced now");
-                writeln!(log(), "- {this_state}")?;
-                this_term_states.push(this_state);
-                if sl_states.is_empty() {
-                    l_states.remove_entry(&s);
-                }
-            }
+        .map(|(term, _)| term)
+        .collect())
+}
+
+/// Same as [`ee_terms_observed`], but also returns microstates assigned to each of the terms
+pub fn ee_terms_detailed_observed(
+    l: SubLevel,
+    observer: &mut (impl DerivationObserver + ?Sized),
+) -> Result<Vec<(TermType, Vec<Microstate>)>, DerivationError> {
+    observed_terms(l, observer, EnumerationLimit::default())
+}
+
+/// Same as [`ee_terms_detailed_observed`], unless keeping the microstates exceeds `limit`
+pub(crate) fn observed_terms(
+    l: SubLevel,
+    observer: &mut (impl DerivationObserver + ?Sized),
+    limit: EnumerationLimit,
+) -> Result<Vec<(TermType, Vec<Microstate>)>, DerivationError> {
+    if l.tp.0 > Microstate::MAX_L {
+        return Err(DerivationError::TooLarge(l.tp));
+    }
+    limit.check(&l, true)?;
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("ee_terms_detailed", sublevel = %l).entered();
+    let single_states =
+        l.tp.mls()
+            .into_iter()
+            .cartesian_product(SPINS)
+            .collect_vec();
+    let single_states_num = single_states.len();
+    #[cfg(feature = "tracing")]
+    tracing::trace!(
+        count = single_states_num,
+        "single-electron states enumerated"
+    );
+    if observer.enabled() {
+        observer.event(&DerivationEvent::Started {
+            sublevel: l.clone(),
+            single_states: single_states_num,
+        });
+        for (index, &(ml, ms)) in single_states.iter().enumerate() {
+            observer.event(&DerivationEvent::SingleStateEnumerated { index, ml, ms });
+        }
+    }
+
+    let holes = l.hole_equivalent();
+    let placed = holes.as_ref().unwrap_or(&l);
+    #[cfg(feature = "tracing")]
+    let enumeration = tracing::debug_span!(
+        "enumeration",
+        placed = %placed,
+        microstates = placed.microstate_count().unwrap_or(u128::MAX)
+    )
+    .entered();
+    if observer.enabled() {
+        observer.event(&DerivationEvent::MicrostatesEnumerated {
+            count: placed
+                .microstate_count()
+                .and_then(|count| usize::try_from(count).ok())
+                .unwrap_or(usize::MAX),
+            holes: holes.as_ref().map(|holes| holes.electrons),
+        });
+    }
 
-            if l_states.is_empty() {
-                sorted_states.remove_entry(&l);
+    // streamed straight into the table, so that only the binned microstates are kept
+    let level_states =
+        occupations(single_states_num as u32, u32::from(placed.electrons)).map(|occupation| {
+            let microstate = Microstate {
+                l: l.tp.0,
+                occupation,
+            };
+            if holes.is_some() {
+                // electrons occupy every state not occupied by holes
+                microstate.complement()
+            } else {
+                microstate
             }
+        });
+    let mut complete = true;
+    let mut table = StateTable::new(level_states.map_while(|microstate| {
+        if observer.cancelled() {
+            complete = false;
+            return None;
+        }
+        let (ml, ms) = (microstate.ml(), microstate.ms());
+        if observer.enabled() {
+            observer.event(&DerivationEvent::MicrostateBinned { microstate, ml, ms });
         }
+        Some((ml, ms, microstate))
+    }));
+    if !complete {
+        return Err(DerivationError::Cancelled);
     }
+    #[cfg(feature = "tracing")]
+    drop(enumeration);
 
-    Ok(term_states.into_keys().collect())
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("extraction").entered();
+    if observer.enabled() {
+        observer.event(&DerivationEvent::ExtractionStarted);
+    }
+    let mut term_states: Vec<(TermType, Vec<Microstate>)> = Vec::new();
+    while let Some((term, states)) = table.extract() {
+        if observer.cancelled() {
+            return Err(DerivationError::Cancelled);
+        }
+        let term = term.with_parity(l.parity());
+        #[cfg(feature = "tracing")]
+        tracing::trace!(term = %term, states = states.len(), "term extracted");
+        if observer.enabled() {
+            observer.event(&DerivationEvent::TermExtracted {
+                term: term.clone(),
+                states: states.clone(),
+            });
+        }
+        term_states.push((term, states));
+    }
+    // stable, so repeated terms stay in extraction order
+    term_states.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+    Ok(term_states)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ee_terms_log;
+    use itertools::Itertools;
+
+    use std::{
+        cell::Cell,
+        sync::atomic::{self, AtomicBool},
+    };
+
+    use crate::{
+        cache::TermCache, census::terms_from_census, count_table, counted_terms,
+        derive_counted_terms, ee_terms, ee_terms_detailed, ee_terms_iter, ee_terms_log,
+        ee_terms_observed, recursive::terms_from_recursion, tabulated_terms, Configuration,
+        DerivationError, DerivationEvent, DerivationObserver, DerivationPhase, EnumerationLimit,
+        LevelError, SubLevel, SubLevelParseError, SubLevelType,
+    };
 
     #[test]
     fn it_works() {
         ee_terms_log(
             crate::SubLevel {
+                n: None,
                 tp: crate::SubLevelType(1),
                 electrons: 3,
             },
-            std::io::stdout,
+            &mut std::io::stdout(),
         )
         .expect("Should be ok");
     }
+
+    #[test]
+    fn log_to_writer() {
+        let mut log = Vec::new();
+        let d2 = SubLevel::new(SubLevelType::new(2).unwrap(), 2).unwrap();
+        let logged =
+            ee_terms_log(d2.clone(), &mut log as &mut dyn std::io::Write).expect("Should be ok");
+        let text = String::from_utf8(log).expect("Log is UTF-8");
+        assert!(text.starts_with("Sublevel: d^2\n ----- \n"));
+        assert!(text.contains("Single electron states (10 total)\n"));
+        assert_eq!(logged, ee_terms(d2).expect("Should be ok"));
+    }
+
+    #[test]
+    fn events() {
+        let p2 = SubLevel::new(SubLevelType::new(1).unwrap(), 2).unwrap();
+        let mut events = Vec::new();
+        let terms = ee_terms_observed(p2.clone(), &mut |event: &DerivationEvent| {
+            events.push(event.clone())
+        })
+        .expect("Should be ok");
+        assert_eq!(terms, ee_terms(p2).expect("Should be ok"));
+        assert!(matches!(
+            events[0],
+            DerivationEvent::Started {
+                single_states: 6,
+                ..
+            }
+        ));
+        let count = |matches: fn(&DerivationEvent) -> bool| {
+            events.iter().filter(|event| matches(event)).count()
+        };
+        assert_eq!(
+            count(|event| matches!(event, DerivationEvent::SingleStateEnumerated { .. })),
+            6
+        );
+        assert_eq!(
+            count(|event| matches!(event, DerivationEvent::MicrostateBinned { .. })),
+            15
+        );
+        let extracted = events
+            .iter()
+            .filter_map(|event| match event {
+                DerivationEvent::TermExtracted { term, states } => {
+                    Some((term.degeneracy(), states.len()))
+                }
+                _ => None,
+            })
+            .collect_vec();
+        // ^1D has the greatest ML, and is extracted first
+        assert_eq!(extracted, [(5, 5), (9, 9), (1, 1)]);
+    }
+
+    #[test]
+    fn detailed_microstates() {
+        let terms = ee_terms_detailed(SubLevel::new(SubLevelType::new(2).unwrap(), 2).unwrap())
+            .expect("Should be ok");
+        assert_eq!(terms.len(), 5);
+        for (term, microstates) in &terms {
+            assert_eq!(microstates.len(), term.degeneracy());
+        }
+        assert_eq!(
+            terms
+                .iter()
+                .map(|(_, microstates)| microstates.len())
+                .sum::<usize>(),
+            45
+        );
+    }
+
+    #[test]
+    fn sorted_terms() {
+        let terms = ee_terms("p2".parse().unwrap()).expect("Should be ok");
+        assert_eq!(terms.iter().join(" "), "^3P ^1S ^1D");
+    }
+
+    #[test]
+    fn lazy_terms() {
+        let sublevel: SubLevel = "d3".parse().unwrap();
+        let mut lazy = ee_terms_iter(sublevel.clone()).collect::<Vec<_>>();
+        lazy.sort();
+        assert_eq!(lazy, ee_terms(sublevel).expect("Should be ok"));
+    }
+
+    #[test]
+    fn progress() {
+        // enumerated as 3 holes
+        let sublevel: SubLevel = "g15".parse().unwrap();
+        let mut reported = Vec::new();
+        let terms = derive_counted_terms(
+            &sublevel,
+            |progress| {
+                assert_eq!(progress.total, 816);
+                reported.push((progress.phase, progress.processed));
+            },
+            &AtomicBool::new(false),
+        )
+        .expect("Should be ok");
+        let (enumerated, extracted) = reported.split_at(816);
+        assert!(enumerated
+            .iter()
+            .zip(1..)
+            .all(|(&reported, processed)| reported == (DerivationPhase::Enumeration, processed)));
+        // every term takes some of the microstates, until there are none left
+        assert_eq!(extracted.len(), terms.len());
+        assert!(extracted
+            .iter()
+            .tuple_windows()
+            .all(
+                |((_, before), (phase, after))| *phase == DerivationPhase::Extraction
+                    && before < after
+            ));
+        assert_eq!(extracted.last(), Some(&(DerivationPhase::Extraction, 816)));
+
+        let cache = TermCache::new();
+        let never = AtomicBool::new(false);
+        let limit = EnumerationLimit::default();
+        let mut calls = 0;
+        let counted = counted_terms(sublevel.clone(), |_| calls += 1, &never, limit, &cache);
+        assert_eq!(counted.expect("Should be ok"), terms);
+        assert_eq!(calls, reported.len());
+        let cached = counted_terms(
+            sublevel,
+            |_| panic!("Terms should be cached"),
+            &never,
+            limit,
+            &cache,
+        );
+        assert_eq!(cached.expect("Should be ok"), terms);
+    }
+
+    #[test]
+    fn cancellation() {
+        let sublevel: SubLevel = "(L=9)3".parse().expect("Should be ok");
+        let cancel = AtomicBool::new(true);
+        assert!(matches!(
+            derive_counted_terms(&sublevel, |_| {}, &cancel),
+            Err(DerivationError::Cancelled)
+        ));
+        // cancelled derivations are not cached
+        let cache = TermCache::new();
+        let limit = EnumerationLimit::default();
+        assert!(matches!(
+            counted_terms(sublevel.clone(), |_| {}, &cancel, limit, &cache),
+            Err(DerivationError::Cancelled)
+        ));
+        assert_eq!(cache.len(), 0);
+        cancel.store(false, atomic::Ordering::Relaxed);
+        assert_eq!(
+            counted_terms(sublevel.clone(), |_| {}, &cancel, limit, &cache).expect("Should be ok"),
+            ee_terms_iter(sublevel).sorted().collect_vec()
+        );
+        assert_eq!(cache.len(), 1);
+
+        /// Cancels after the given number of microstates
+        struct Budget(usize);
+        impl DerivationObserver for Budget {
+            fn event(&mut self, event: &DerivationEvent) {
+                if let DerivationEvent::MicrostateBinned { .. } = event {
+                    self.0 -= 1;
+                }
+            }
+            fn cancelled(&self) -> bool {
+                self.0 == 0
+            }
+        }
+        let d5: SubLevel = "3d5".parse().expect("Should be ok");
+        assert!(matches!(
+            ee_terms_observed(d5.clone(), &mut Budget(10)),
+            Err(DerivationError::Cancelled)
+        ));
+        // one more than there are microstates
+        assert!(ee_terms_observed(d5.clone(), &mut Budget(253)).is_ok());
+
+        // the token may be reset before the extraction, leaving the table incomplete anyway
+        let cancel = AtomicBool::new(false);
+        let table = count_table(
+            &d5,
+            |processed, _| {
+                if processed == 10 {
+                    cancel.store(true, atomic::Ordering::Relaxed);
+                }
+            },
+            &cancel,
+        );
+        cancel.store(false, atomic::Ordering::Relaxed);
+        assert!(table.is_none());
+
+        /// Cancels only once, after the given number of microstates
+        struct Blip(usize, Cell<bool>);
+        impl DerivationObserver for Blip {
+            fn event(&mut self, event: &DerivationEvent) {
+                if let DerivationEvent::MicrostateBinned { .. } = event {
+                    self.0 -= 1;
+                }
+            }
+            fn cancelled(&self) -> bool {
+                self.0 == 0 && !self.1.replace(true)
+            }
+        }
+        assert!(matches!(
+            ee_terms_observed(d5, &mut Blip(10, Cell::new(false))),
+            Err(DerivationError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn hole_equivalence() {
+        for (electrons, holes) in [("p4", "p2"), ("d7", "d3"), ("f13", "f1")] {
+            let detailed = ee_terms_detailed(electrons.parse().unwrap()).expect("Should be ok");
+            let terms = detailed.iter().map(|(term, _)| term.clone()).collect_vec();
+            assert_eq!(terms, ee_terms(electrons.parse().unwrap()).unwrap());
+            assert_eq!(terms, ee_terms(holes.parse().unwrap()).unwrap());
+            for (term, microstates) in detailed {
+                assert_eq!(microstates.len(), term.degeneracy());
+            }
+        }
+    }
+
+    #[test]
+    fn backends_agree() {
+        // every s, p, d and f sublevel is tabulated, and a few g ones are not
+        let sublevels = (0..=3)
+            .flat_map(|l| {
+                let tp = SubLevelType::new(l).unwrap();
+                (0..=tp.max_electrons()).map(move |electrons| SubLevel::new(tp.clone(), electrons))
+            })
+            .map(Result::unwrap)
+            .chain(["5g1", "5g4", "5g5"].map(|sublevel| sublevel.parse().unwrap()));
+        for sublevel in sublevels {
+            // derived anew, unlike `ee_terms`, which may take the terms from the tables or the cache
+            let enumerated = ee_terms_iter(sublevel.clone()).sorted().collect_vec();
+            assert_eq!(terms_from_recursion(&sublevel), enumerated, "{sublevel}");
+            assert_eq!(
+                terms_from_census(&sublevel).unwrap(),
+                enumerated,
+                "{sublevel}"
+            );
+            if sublevel.tp.0 <= 3 {
+                assert_eq!(
+                    tabulated_terms(&sublevel).unwrap(),
+                    enumerated,
+                    "{sublevel}"
+                );
+            } else {
+                assert!(tabulated_terms(&sublevel).is_none());
+            }
+        }
+        let configuration: Configuration = "2p2 3d1".parse().unwrap();
+        let terms = configuration.ls_terms().unwrap();
+        assert_eq!(configuration.ls_terms_recursive().unwrap(), terms);
+        assert_eq!(configuration.ls_terms_analytic().unwrap(), terms);
+    }
+
+    #[test]
+    fn known_d_terms() {
+        let terms = |sublevel: &str| {
+            ee_terms(sublevel.parse().unwrap())
+                .expect("Should be ok")
+                .iter()
+                .join(" ")
+        };
+        assert_eq!(terms("d1"), "^2D");
+        assert_eq!(terms("d2"), "^3P ^3F ^1S ^1D ^1G");
+        assert_eq!(terms("d3"), "^4P ^4F ^2P ^2D ^2D ^2F ^2G ^2H");
+        assert_eq!(
+            terms("d4"),
+            "^5D ^3P ^3P ^3D ^3F ^3F ^3G ^3H ^1S ^1S ^1D ^1D ^1F ^1G ^1G ^1I"
+        );
+        assert_eq!(
+            terms("d5"),
+            "^6S ^4P ^4D ^4F ^4G ^2S ^2P ^2D ^2D ^2D ^2F ^2F ^2G ^2G ^2H ^2I"
+        );
+    }
+
+    #[test]
+    fn known_f_term_counts() {
+        for (electrons, count) in [1, 7, 17, 47, 73, 119, 119].into_iter().enumerate() {
+            let sublevel =
+                SubLevel::new(SubLevelType::new(3).unwrap(), electrons as u8 + 1).unwrap();
+            assert_eq!(ee_terms_iter(sublevel).count(), count);
+        }
+    }
+
+    #[test]
+    fn parse_sublevels() {
+        for (input, display) in [
+            ("d", "d^1"),
+            ("3d5", "3d^5"),
+            ("p^3", "p^3"),
+            ("2p^{3}", "2p^3"),
+            ("4f¹³", "4f^13"),
+            ("(L=7)^2", "(L=7)^2"),
+        ] {
+            let sublevel: SubLevel = input.parse().expect("Should parse");
+            assert_eq!(sublevel.to_string(), display);
+        }
+        assert!(matches!(
+            "x".parse::<SubLevelType>(),
+            Err(SubLevelParseError::UnknownLetter(_))
+        ));
+        assert!(matches!(
+            "p7".parse::<SubLevel>(),
+            Err(SubLevelParseError::Level(LevelError::ToMuch(_)))
+        ));
+        assert!(matches!(
+            "3d".parse::<SubLevelType>(),
+            Err(SubLevelParseError::UnknownLetter(_))
+        ));
+        assert!(matches!(
+            "3dx".parse::<SubLevel>(),
+            Err(SubLevelParseError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn sublevel_type_bound() {
+        assert!(SubLevelType::new(SubLevelType::MAX_L).is_ok());
+        assert!(matches!(
+            SubLevelType::new(SubLevelType::MAX_L + 1),
+            Err(LevelError::TooLarge(_))
+        ));
+        assert_eq!(
+            SubLevelType::new(SubLevelType::MAX_L)
+                .unwrap()
+                .max_electrons(),
+            254
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_sublevels() {
+        let sublevel: SubLevel =
+            serde_json::from_str(r#"{"n":3,"tp":2,"electrons":5}"#).expect("Should deserialize");
+        assert_eq!(sublevel.to_string(), "3d^5");
+        // checked the same as the constructors do
+        for invalid in [
+            r#"{"n":null,"tp":1,"electrons":200}"#,
+            r#"{"n":1,"tp":3,"electrons":1}"#,
+            r#"{"n":null,"tp":64,"electrons":1}"#,
+        ] {
+            assert!(serde_json::from_str::<SubLevel>(invalid).is_err());
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn traced_phases() {
+        use std::sync::{Arc, Mutex};
+
+        use tracing::{span, Event, Metadata, Subscriber};
+
+        /// Names of the spans, in the order they are created
+        struct Spans(Arc<Mutex<Vec<&'static str>>>);
+
+        impl Subscriber for Spans {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                let mut names = self.0.lock().unwrap();
+                names.push(span.metadata().name());
+                span::Id::from_u64(names.len() as u64)
+            }
+
+            fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+            fn event(&self, _event: &Event<'_>) {}
+
+            fn enter(&self, _span: &span::Id) {}
+
+            fn exit(&self, _span: &span::Id) {}
+        }
+
+        let names = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Spans(names.clone()), || {
+            ee_terms_detailed("p2".parse().unwrap()).expect("Should be ok");
+            ee_terms_iter("g2".parse().unwrap()).for_each(drop);
+        });
+        assert_eq!(
+            *names.lock().unwrap(),
+            [
+                "ee_terms_detailed",
+                "enumeration",
+                "extraction",
+                "enumeration"
+            ]
+        );
+    }
 }
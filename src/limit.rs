@@ -0,0 +1,186 @@
+//! Limits of the microstate enumeration, so that an absurd sublevel fails right away instead of being enumerated for hours
+
+use std::mem::size_of;
+
+use itertools::Itertools;
+use num_bigint::BigUint;
+
+use crate::{CoupledTerm, DerivationError, Microstate, SubLevel, TermType};
+
+/// Largest enumeration the derivations agree to do, see [`Configuration::with_limit`](crate::Configuration::with_limit) and [`ee_terms_limited`](crate::ee_terms_limited); unlimited by default
+///
+/// Tabulated and cached terms are not enumerated, and neither are the ones derived by [`ee_terms_analytic`](crate::ee_terms_analytic) or [`ee_terms_recursive`](crate::ee_terms_recursive), so the limits of the microstates don't apply to them. The terms coupled from the terms of the subshells are limited whichever way the latter are derived
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnumerationLimit {
+    /// Most microstates enumerated for a single subshell (for its hole equivalent, if it's more than half-filled)
+    pub microstates: Option<u128>,
+    /// Most bytes of the microstates kept in memory, by the derivations assigning them to the terms, like [`ee_terms_detailed`](crate::ee_terms_detailed), and of the terms coupled from the terms of the subshells
+    pub memory: Option<u128>,
+    /// Most terms coupled from the terms of the subshells, at every step of [`Configuration::ls_terms`](crate::Configuration::ls_terms)
+    pub terms: Option<u128>,
+}
+
+impl EnumerationLimit {
+    /// Fails with [`DerivationError::MicrostateLimit`] or [`DerivationError::MemoryLimit`] if enumerating the sublevel exceeds the limit; `kept` is whether the microstates are kept in memory, and not only counted
+    ///
+    /// The `_limited` derivations for a single sublevel, like [`ee_terms_limited`](crate::ee_terms_limited), check it themselves
+    pub fn check(&self, l: &SubLevel, kept: bool) -> Result<(), DerivationError> {
+        let placed = l.hole_equivalent().unwrap_or_else(|| l.clone());
+        let microstates = placed.microstate_count_big();
+        if let Some(max) = self.microstates {
+            if microstates > BigUint::from(max) {
+                return Err(DerivationError::MicrostateLimit {
+                    sublevel: l.clone(),
+                    microstates,
+                    limit: max,
+                });
+            }
+        }
+        if let Some(max) = self.memory.filter(|_| kept) {
+            let bytes = microstates * size_of::<Microstate>();
+            if bytes > BigUint::from(max) {
+                return Err(DerivationError::MemoryLimit {
+                    sublevel: l.clone(),
+                    bytes,
+                    limit: max,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails with [`DerivationError::TermLimit`] or [`DerivationError::CouplingMemoryLimit`] if [coupling](crate::couple_terms) these terms exceeds the limit; the terms are counted by their L and S, without coupling them
+    pub fn check_coupling(
+        &self,
+        left: &[TermType],
+        right: &[TermType],
+    ) -> Result<(), DerivationError> {
+        if self.terms.is_none() && self.memory.is_none() {
+            return Ok(());
+        }
+        let terms = coupled_count(left, right);
+        if let Some(max) = self.terms {
+            if terms > BigUint::from(max) {
+                return Err(DerivationError::TermLimit { terms, limit: max });
+            }
+        }
+        if let Some(max) = self.memory {
+            let bytes = terms * size_of::<CoupledTerm>();
+            if bytes > BigUint::from(max) {
+                return Err(DerivationError::CouplingMemoryLimit { bytes, limit: max });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Number of terms [`couple_terms`](crate::couple_terms) gives: every pair of parents with L1, S1 and L2, S2 gives (2 min(L1, L2) + 1)(2 min(S1, S2) + 1) of them
+fn coupled_count(left: &[TermType], right: &[TermType]) -> BigUint {
+    let by_momenta = |terms: &[TermType]| terms.iter().map(|term| (term.l(), term.s())).counts();
+    let right = by_momenta(right);
+    by_momenta(left)
+        .into_iter()
+        .cartesian_product(&right)
+        .map(|(((l1, s1), left_count), ((l2, s2), right_count))| {
+            let momenta = 2 * l1.min(*l2) + 1;
+            let spins = s1.min(*s2).doubled() as usize + 1;
+            BigUint::from(left_count) * *right_count * momenta * spins
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        couple_terms, ee_terms, ee_terms_detailed_limited, ee_terms_iter_limited, ee_terms_limited,
+        Configuration, DerivationError, EnumerationLimit, SubLevel, WorkedSolution,
+    };
+
+    #[test]
+    fn limits() {
+        let limit = EnumerationLimit {
+            microstates: Some(1000),
+            memory: Some(1000),
+            terms: None,
+        };
+        // 120 microstates, as many as of d^3
+        let d7: SubLevel = "3d7".parse().expect("Should be ok");
+        assert!(limit.check(&d7, false).is_ok());
+        assert!(matches!(
+            limit.check(&d7, true),
+            Err(DerivationError::MemoryLimit { .. })
+        ));
+        let g5: SubLevel = "5g5".parse().expect("Should be ok");
+        assert!(matches!(
+            limit.check(&g5, false),
+            Err(DerivationError::MicrostateLimit { .. })
+        ));
+        assert!(EnumerationLimit::default().check(&g5, true).is_ok());
+    }
+
+    #[test]
+    fn limited_derivations() {
+        let limit = EnumerationLimit {
+            microstates: Some(1000),
+            memory: Some(1000),
+            terms: None,
+        };
+        let g5: SubLevel = "5g5".parse().expect("Should be ok");
+        assert!(matches!(
+            ee_terms_limited(g5.clone(), limit),
+            Err(DerivationError::MicrostateLimit { .. })
+        ));
+        assert!(ee_terms_iter_limited(g5.clone(), limit).is_err());
+        let d7: SubLevel = "3d7".parse().expect("Should be ok");
+        assert_eq!(
+            ee_terms_limited(d7.clone(), limit).expect("Should be ok"),
+            ee_terms(d7.clone()).expect("Should be ok")
+        );
+        assert_eq!(
+            ee_terms_iter_limited(d7.clone(), limit)
+                .expect("Should be ok")
+                .count(),
+            8
+        );
+        assert!(matches!(
+            ee_terms_detailed_limited(d7, limit),
+            Err(DerivationError::MemoryLimit { .. })
+        ));
+        // the configurations apply their own limit
+        let configuration = Configuration::new([g5]).with_limit(limit);
+        assert!(configuration.coupling_tree().is_err());
+        assert!(WorkedSolution::new(&configuration).is_err());
+    }
+
+    #[test]
+    fn coupled_terms() {
+        // about 10^9 terms, which wouldn't fit into memory
+        let configuration: Configuration = "5g9 6h11".parse().expect("Should be ok");
+        let limit = |terms, memory| EnumerationLimit {
+            terms,
+            memory,
+            ..EnumerationLimit::default()
+        };
+        assert!(matches!(
+            configuration
+                .clone()
+                .with_limit(limit(Some(1_000_000), None))
+                .ls_terms_analytic(),
+            Err(DerivationError::TermLimit { .. })
+        ));
+        assert!(matches!(
+            configuration
+                .with_limit(limit(None, Some(1 << 30)))
+                .ls_terms_analytic(),
+            Err(DerivationError::CouplingMemoryLimit { .. })
+        ));
+        // counted the same as they are coupled
+        let d2 = ee_terms("3d2".parse().unwrap()).expect("Should be ok");
+        let p3 = ee_terms("4p3".parse().unwrap()).expect("Should be ok");
+        let count = couple_terms(&d2, &p3).len() as u128;
+        assert!(limit(Some(count), None).check_coupling(&d2, &p3).is_ok());
+        assert!(limit(Some(count - 1), None)
+            .check_coupling(&d2, &p3)
+            .is_err());
+    }
+}
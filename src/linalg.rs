@@ -0,0 +1,138 @@
+//! Small dense linear algebra, enough for the matrices of a single term or configuration
+
+/// Eigenvalues and normalized eigenvectors of a real symmetric matrix, sorted by ascending eigenvalue
+///
+/// Computed with the cyclic Jacobi method, which is slow for big matrices, but simple and accurate
+pub(crate) fn symmetric_eigen(mut matrix: Vec<Vec<f64>>) -> Vec<(f64, Vec<f64>)> {
+    let size = matrix.len();
+    let mut vectors = (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|column| f64::from(row == column))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let scale = matrix
+        .iter()
+        .flatten()
+        .map(|entry| entry * entry)
+        .sum::<f64>()
+        .sqrt();
+    for _ in 0..100 {
+        let off_diagonal = (0..size)
+            .flat_map(|p| (0..size).filter(move |&q| q != p).map(move |q| (p, q)))
+            .map(|(p, q)| matrix[p][q] * matrix[p][q])
+            .sum::<f64>()
+            .sqrt();
+        if off_diagonal <= f64::EPSILON * scale {
+            break;
+        }
+        for p in 0..size {
+            for q in p + 1..size {
+                if matrix[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (matrix[q][q] - matrix[p][p]) / (2.0 * matrix[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in matrix.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+                let (row_p, row_q) = (matrix[p].clone(), matrix[q].clone());
+                for k in 0..size {
+                    matrix[p][k] = c * row_p[k] - s * row_q[k];
+                    matrix[q][k] = s * row_p[k] + c * row_q[k];
+                }
+                for row in vectors.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+            }
+        }
+    }
+    let mut eigen = (0..size)
+        .map(|index| {
+            let vector = vectors.iter().map(|row| row[index]).collect();
+            (matrix[index][index], vector)
+        })
+        .collect::<Vec<_>>();
+    eigen.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    eigen
+}
+
+/// Orthonormal basis of the space spanned by the columns of a projector, of the given rank
+///
+/// Gram–Schmidt over the columns, taking the one with the greatest remainder first, so the basis is as accurate as the columns are. Much cheaper than [`symmetric_eigen`] when only the image is needed
+pub(crate) fn projector_image(matrix: &[Vec<f64>], rank: usize) -> Vec<Vec<f64>> {
+    let mut remainders = (0..matrix.len())
+        .map(|column| matrix.iter().map(|row| row[column]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut basis: Vec<Vec<f64>> = Vec::with_capacity(rank);
+    while basis.len() < rank {
+        let norm = |vector: &Vec<f64>| vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let Some((pivot, norm)) = remainders
+            .iter()
+            .map(norm)
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        else {
+            break;
+        };
+        let vector = remainders
+            .swap_remove(pivot)
+            .into_iter()
+            .map(|x| x / norm)
+            .collect::<Vec<_>>();
+        for remainder in &mut remainders {
+            let projection: f64 = remainder.iter().zip(&vector).map(|(a, b)| a * b).sum();
+            for (x, basis) in remainder.iter_mut().zip(&vector) {
+                *x -= projection * basis;
+            }
+        }
+        basis.push(vector);
+    }
+    basis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{projector_image, symmetric_eigen};
+
+    #[test]
+    fn small_matrix() {
+        let matrix = vec![
+            vec![2.0, 1.0, 0.0],
+            vec![1.0, 2.0, 1.0],
+            vec![0.0, 1.0, 2.0],
+        ];
+        let eigen = symmetric_eigen(matrix.clone());
+        let expected = [2.0 - 2f64.sqrt(), 2.0, 2.0 + 2f64.sqrt()];
+        for ((value, vector), expected) in eigen.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-12);
+            for (row, component) in matrix.iter().zip(vector) {
+                let product: f64 = row.iter().zip(vector).map(|(a, b)| a * b).sum();
+                assert!((product - value * component).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn image() {
+        // projector onto (1, 1, 0) / √2
+        let matrix = vec![
+            vec![0.5, 0.5, 0.0],
+            vec![0.5, 0.5, 0.0],
+            vec![0.0, 0.0, 0.0],
+        ];
+        let basis = projector_image(&matrix, 1);
+        assert_eq!(basis.len(), 1);
+        let expected = [0.5f64.sqrt(), 0.5f64.sqrt(), 0.0];
+        for (component, expected) in basis[0].iter().zip(expected) {
+            assert!((component - expected).abs() < 1e-12);
+        }
+    }
+}
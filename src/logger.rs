@@ -0,0 +1,47 @@
+use std::{fmt::Arguments, io::Write};
+
+/// Receiver of the human-readable derivation log, see [`crate::ee_terms_log`]
+///
+/// Implemented for every [`Write`] (including `&mut dyn Write`), which gets one line per call, and for [`NoLog`], which skips the logging altogether
+pub trait DerivationLogger {
+    /// Whether the lines are used at all; if not, they are not even formatted
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    /// Logs a single line, without the trailing newline
+    fn line(&mut self, line: Arguments<'_>) -> std::io::Result<()>;
+}
+
+impl<W: Write + ?Sized> DerivationLogger for W {
+    fn line(&mut self, line: Arguments<'_>) -> std::io::Result<()> {
+        self.write_fmt(line)?;
+        self.write_all(b"\n")
+    }
+}
+
+/// Logger discarding everything, at no cost
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoLog;
+
+impl DerivationLogger for NoLog {
+    fn enabled(&self) -> bool {
+        false
+    }
+
+    fn line(&mut self, _line: Arguments<'_>) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Like `writeln!`, but skips formatting if the logger is disabled
+macro_rules! logln {
+    ($log:expr, $($arg:tt)*) => {
+        if $crate::DerivationLogger::enabled(&*$log) {
+            $crate::DerivationLogger::line(&mut *$log, format_args!($($arg)*))
+        } else {
+            Ok(())
+        }
+    };
+}
+pub(crate) use logln;
@@ -0,0 +1,361 @@
+use std::{fmt::Display, str::FromStr};
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{HalfInt, SubLevelType};
+
+/// Single-electron state, defined by orbital and spin momentum projections
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SpinOrbitalFields"))]
+pub struct SpinOrbital {
+    pub ml: i8,
+    pub ms: HalfInt,
+}
+
+/// Unchecked fields of a deserialized [`SpinOrbital`]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SpinOrbitalFields {
+    ml: i8,
+    ms: HalfInt,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SpinOrbitalFields> for SpinOrbital {
+    type Error = MicrostateError;
+
+    fn try_from(fields: SpinOrbitalFields) -> Result<Self, Self::Error> {
+        let ml_allowed = fields.ml.unsigned_abs() <= SubLevelType::MAX_L;
+        if ml_allowed && fields.ms.abs() == HalfInt::HALF {
+            Ok(Self {
+                ml: fields.ml,
+                ms: fields.ms,
+            })
+        } else {
+            Err(MicrostateError::SpinOrbital(fields.ml, fields.ms))
+        }
+    }
+}
+
+impl SpinOrbital {
+    /// Index of the spin-orbital within a sublevel: orbitals go by ascending ml, spin-down goes first
+    fn index(&self, l: u8) -> Option<u32> {
+        let orbital = i32::from(self.ml) + i32::from(l);
+        let spin = match self.ms.doubled() {
+            -1 => 0,
+            1 => 1,
+            _ => return None,
+        };
+        (0..=2 * i32::from(l))
+            .contains(&orbital)
+            .then_some((2 * orbital + spin) as u32)
+    }
+
+    fn from_index(l: u8, index: u32) -> Self {
+        Self {
+            ml: (index / 2) as i8 - l as i8,
+            ms: if index.is_multiple_of(2) {
+                -HalfInt::HALF
+            } else {
+                HalfInt::HALF
+            },
+        }
+    }
+}
+
+impl Display for SpinOrbital {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let spin = if self.ms > HalfInt::ZERO { '+' } else { '-' };
+        f.write_fmt(format_args!("{}{}", self.ml, spin))
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("Can't parse spin-orbital \"{0}\", expected ml followed by the spin, like \"1+\", \"-2-\" or \"0↑\"")]
+pub struct SpinOrbitalParseError(String);
+
+/// Parses spin-orbital in the form it is printed in, like `1+` or `-2-`; spin may also be given as an arrow, like `0↑`
+impl FromStr for SpinOrbital {
+    type Err = SpinOrbitalParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SpinOrbitalParseError(s.to_string());
+        let trimmed = s.trim();
+        let spin = trimmed.chars().last().ok_or_else(invalid)?;
+        let ms = match spin {
+            '+' | '↑' => HalfInt::HALF,
+            '-' | '−' | '↓' => -HalfInt::HALF,
+            _ => return Err(invalid()),
+        };
+        let ml = trimmed[..trimmed.len() - spin.len_utf8()]
+            .parse()
+            .map_err(|_| invalid())?;
+        Ok(Self { ml, ms })
+    }
+}
+
+/// State of a whole sublevel, defined by the occupied spin-orbitals (a Slater determinant)
+///
+/// Stored as an occupation bitmask, see [`Microstate::occupation`]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MicrostateFields"))]
+pub struct Microstate {
+    pub(crate) l: u8,
+    pub(crate) occupation: u64,
+}
+
+/// Unchecked fields of a deserialized [`Microstate`]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MicrostateFields {
+    l: u8,
+    occupation: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MicrostateFields> for Microstate {
+    type Error = MicrostateError;
+
+    fn try_from(fields: MicrostateFields) -> Result<Self, Self::Error> {
+        SubLevelType::new(fields.l)
+            .ok()
+            .and_then(|tp| Self::new(&tp, fields.occupation))
+            .ok_or(MicrostateError::Occupation(fields.l, fields.occupation))
+    }
+}
+
+/// Spin-orbital or microstate which doesn't belong to any supported sublevel
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MicrostateError {
+    #[error("There's no spin-orbital with ml={0} and ms={1}, ms should be ±1/2 and |ml| at most {max}", max = SubLevelType::MAX_L)]
+    SpinOrbital(i8, HalfInt),
+    #[error("Occupation {1:#b} is not a microstate of a sublevel with l={0}, l should be at most {max}", max = Microstate::MAX_L)]
+    Occupation(u8, u64),
+}
+
+impl Microstate {
+    /// Largest orbital momentum of a sublevel, which spin-orbitals still fit into the bitmask
+    pub const MAX_L: u8 = 15;
+
+    /// Creates microstate of a sublevel from the occupation bitmask
+    ///
+    /// Returns `None` if the sublevel is too large, or if the bitmask has bits set outside of the sublevel
+    pub fn new(tp: &SubLevelType, occupation: u64) -> Option<Self> {
+        let l = tp.l();
+        if l > Self::MAX_L || occupation >> tp.max_electrons() != 0 {
+            return None;
+        }
+        Some(Self { l, occupation })
+    }
+
+    /// Creates microstate of a sublevel with the specified spin-orbitals occupied
+    ///
+    /// Returns `None` if the sublevel is too large, or if some spin-orbital does not belong to it
+    pub fn from_spin_orbitals(
+        tp: &SubLevelType,
+        spin_orbitals: impl IntoIterator<Item = SpinOrbital>,
+    ) -> Option<Self> {
+        let occupation = spin_orbitals
+            .into_iter()
+            .map(|orbital| Some(1u64 << orbital.index(tp.l())?))
+            .try_fold(0, |occupation, bit| Some(occupation | bit?))?;
+        Self::new(tp, occupation)
+    }
+
+    /// Occupation bitmask: bit `2(ml+l)` stands for spin-down, and bit `2(ml+l)+1` for spin-up electron with the corresponding ml
+    pub fn occupation(&self) -> u64 {
+        self.occupation
+    }
+
+    /// Orbital momentum of the sublevel
+    pub fn l(&self) -> u8 {
+        self.l
+    }
+
+    pub fn electrons(&self) -> u32 {
+        self.occupation.count_ones()
+    }
+
+    pub fn is_occupied(&self, orbital: SpinOrbital) -> bool {
+        orbital
+            .index(self.l)
+            .is_some_and(|index| self.occupation & (1 << index) != 0)
+    }
+
+    /// Indices of the occupied spin-orbitals (in order of the bitmask)
+    pub fn indices(&self) -> impl Iterator<Item = u32> + '_ {
+        let mut rest = self.occupation;
+        std::iter::from_fn(move || {
+            (rest != 0).then(|| {
+                let index = rest.trailing_zeros();
+                rest &= rest - 1;
+                index
+            })
+        })
+    }
+
+    pub fn spin_orbitals(&self) -> impl Iterator<Item = SpinOrbital> + '_ {
+        self.indices()
+            .map(|index| SpinOrbital::from_index(self.l, index))
+    }
+
+    /// Microstate with every spin-orbital occupied by a hole instead of an electron and vice versa
+    pub fn complement(&self) -> Self {
+        let all = (1u64 << (4 * u32::from(self.l) + 2)) - 1;
+        Self {
+            l: self.l,
+            occupation: !self.occupation & all,
+        }
+    }
+
+    /// Total orbital momentum projection
+    pub fn ml(&self) -> i32 {
+        let orbitals = self.indices().map(|index| index as i32 / 2).sum::<i32>();
+        orbitals - i32::from(self.l) * self.electrons() as i32
+    }
+
+    /// Total spin projection
+    pub fn ms(&self) -> HalfInt {
+        // odd bits are the spin-up electrons
+        let up = (self.occupation & 0xAAAA_AAAA_AAAA_AAAA).count_ones() as i32;
+        HalfInt::from_doubled(2 * up - self.electrons() as i32)
+    }
+}
+
+/// Combinations of `k` out of `n` indices, in the order of `(0..n).combinations(k)`, reusing a single buffer instead of allocating every one of them
+pub(crate) struct Combinations {
+    indices: Vec<u32>,
+    n: u32,
+    started: bool,
+}
+
+impl Combinations {
+    pub(crate) fn new(n: u32, k: u32) -> Self {
+        Self {
+            indices: (0..k).collect(),
+            n,
+            // so that `advance` finds out there's nothing to advance
+            started: k > n,
+        }
+    }
+
+    /// Moves to the next combination, returning its indices in ascending order
+    pub(crate) fn advance(&mut self) -> Option<&[u32]> {
+        if !self.started {
+            self.started = true;
+            return Some(&self.indices);
+        }
+        // there are no combinations at all if k > n
+        let free = self.n.checked_sub(self.indices.len() as u32)?;
+        // the last index which can still be moved right, with the following ones packed after it
+        let i = (0..self.indices.len())
+            .rev()
+            .find(|&i| self.indices[i] < free + i as u32)?;
+        self.indices[i] += 1;
+        for j in i + 1..self.indices.len() {
+            self.indices[j] = self.indices[j - 1] + 1;
+        }
+        Some(&self.indices)
+    }
+}
+
+/// Occupation bitmasks of `k` electrons on `n` spin-orbitals, in the order of [`Combinations`]
+pub(crate) fn occupations(n: u32, k: u32) -> impl Iterator<Item = u64> {
+    let mut combinations = Combinations::new(n, k);
+    std::iter::from_fn(move || {
+        Some(
+            combinations
+                .advance()?
+                .iter()
+                .fold(0, |occupation, index| occupation | 1 << index),
+        )
+    })
+}
+
+impl Display for Microstate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.spin_orbitals().join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{microstate::occupations, HalfInt, Microstate, SpinOrbital, SubLevelType};
+
+    #[test]
+    fn bitmask_microstate() {
+        let p = SubLevelType::new(1).unwrap();
+        let microstate = Microstate::from_spin_orbitals(
+            &p,
+            [
+                SpinOrbital {
+                    ml: 1,
+                    ms: HalfInt::HALF,
+                },
+                SpinOrbital {
+                    ml: 0,
+                    ms: HalfInt::HALF,
+                },
+            ],
+        )
+        .unwrap();
+        assert_eq!(microstate.occupation(), 0b101000);
+        assert_eq!(microstate.ml(), 1);
+        assert_eq!(microstate.ms(), HalfInt::ONE);
+        assert_eq!(microstate.to_string(), "0+ 1+");
+        assert_eq!(microstate.complement().electrons(), 4);
+        assert_eq!(microstate.complement().ms(), -HalfInt::ONE);
+        assert!(Microstate::new(&p, 1 << 6).is_none());
+        assert!(Microstate::new(&SubLevelType::new(16).unwrap(), 1).is_none());
+    }
+
+    #[test]
+    fn combinations() {
+        for (n, k) in [(6, 0), (6, 2), (10, 5), (3, 4)] {
+            let expected = (0..n)
+                .combinations(k as usize)
+                .map(|indices| {
+                    indices
+                        .into_iter()
+                        .fold(0, |occupation, index| occupation | 1 << index)
+                })
+                .collect_vec();
+            assert_eq!(occupations(n, k).collect_vec(), expected);
+        }
+    }
+
+    #[test]
+    fn parse_spin_orbital() {
+        let parsed = |s: &str| s.parse::<SpinOrbital>().map(|orbital| orbital.to_string());
+        assert_eq!(parsed("1+").unwrap(), "1+");
+        assert_eq!(parsed("-2-").unwrap(), "-2-");
+        assert_eq!(parsed("+1↓").unwrap(), "1-");
+        assert!(parsed("1").is_err());
+        assert!(parsed("+").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_microstates() {
+        let microstate: Microstate =
+            serde_json::from_str(r#"{"l":1,"occupation":40}"#).expect("Should deserialize");
+        assert_eq!(microstate.to_string(), "0+ 1+");
+        // checked the same as the constructors do
+        for invalid in [r#"{"l":40,"occupation":1}"#, r#"{"l":1,"occupation":64}"#] {
+            assert!(serde_json::from_str::<Microstate>(invalid).is_err());
+        }
+        let orbital: SpinOrbital =
+            serde_json::from_str(r#"{"ml":-2,"ms":"1/2"}"#).expect("Should deserialize");
+        assert_eq!(orbital.to_string(), "-2+");
+        for invalid in [r#"{"ml":0,"ms":"3/2"}"#, r#"{"ml":100,"ms":"1/2"}"#] {
+            assert!(serde_json::from_str::<SpinOrbital>(invalid).is_err());
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Numbers of terms of equivalent electrons, from the numbers of microstates with each ML and MS
+//!
+//! Uses nothing but `std`, since the build script includes it as well to generate the tables of [`crate::tabulated_terms`]
+
+use std::{cmp::Reverse, collections::BTreeMap};
+
+/// Integer the states are counted in, native or of arbitrary precision
+pub(crate) trait Count: Clone + Default + PartialEq {
+    /// Σ a·b over the pairs, `None` if it does not fit
+    fn sum_of_products(pairs: impl Iterator<Item = (u128, u128)>) -> Option<Self>;
+    /// `None` if the sum does not fit
+    fn checked_sum(&self, other: &Self) -> Option<Self>;
+    /// Only called for `self` at least `other`
+    fn difference(&self, other: &Self) -> Self;
+}
+
+impl Count for u128 {
+    fn sum_of_products(mut pairs: impl Iterator<Item = (u128, u128)>) -> Option<Self> {
+        pairs.try_fold(0u128, |sum, (a, b)| sum.checked_add(a.checked_mul(b)?))
+    }
+    fn checked_sum(&self, other: &Self) -> Option<Self> {
+        self.checked_add(*other)
+    }
+    fn difference(&self, other: &Self) -> Self {
+        self - other
+    }
+}
+
+/// Numbers of ways to choose `k` of `orbitals` orbitals with ml = 0, 1, 2, ..., by the sum of their ml, for every `k` up to `max`
+///
+/// These are the coefficients of the Gaussian binomials [orbitals choose k]_q, found by adding the orbitals one by one. None of them exceeds C(127, 63) < 2^128
+fn subsets(orbitals: usize, max: usize) -> Vec<Vec<u128>> {
+    let mut subsets = (0..=max)
+        .map(|k| vec![0; k * (2 * orbitals - k - 1) / 2 + 1])
+        .collect::<Vec<_>>();
+    subsets[0][0] = 1;
+    for ml in 0..orbitals {
+        // the greatest numbers first, so that every orbital is chosen only once
+        for k in (0..max.min(ml + 1)).rev() {
+            let (fewer, more) = subsets.split_at_mut(k + 1);
+            // the greater sums of `k` orbitals are only reached with the orbitals not added yet
+            for (sum, count) in fewer[k].iter().enumerate().take(more[0].len() - ml) {
+                more[0][sum + ml] += count;
+            }
+        }
+    }
+    subsets
+}
+
+/// Numbers of microstates with each nonnegative ML and doubled MS
+///
+/// Electrons of either spin occupy distinct orbitals, so the microstates with `up` and `down` electrons of each spin are pairs of such [`subsets`], and the numbers of them by ML are the products of the two Gaussian binomials
+fn census<C: Count>(l: u8, electrons: u8) -> Option<BTreeMap<(i32, i32), C>> {
+    let orbitals = 2 * usize::from(l) + 1;
+    let electrons = usize::from(electrons);
+    let subsets = subsets(orbitals, electrons.min(orbitals));
+    // ML of the subsets are offset by l for every electron
+    let offset = electrons * usize::from(l);
+    let mut census = BTreeMap::new();
+    // MS is nonnegative, so there are at least as many electrons with spin up
+    for down in 0..=electrons / 2 {
+        let up = electrons - down;
+        if up > orbitals {
+            continue;
+        }
+        let (ups, downs) = (&subsets[up], &subsets[down]);
+        for ml in offset..ups.len() + downs.len() - 1 {
+            let pairs = (ml.saturating_sub(downs.len() - 1)..ups.len().min(ml + 1))
+                .map(|sum| (ups[sum], downs[ml - sum]));
+            census.insert(
+                ((ml - offset) as i32, (up - down) as i32),
+                C::sum_of_products(pairs)?,
+            );
+        }
+    }
+    Some(census)
+}
+
+/// Terms of `electrons` equivalent electrons with orbital momentum `l` as `(L, 2S, count)`, in spectroscopic order (greater S first, then by L)
+///
+/// The number of terms with given L and S is N(L, S) - N(L+1, S) - N(L, S+1) + N(L+1, S+1), where N(ML, MS) is the number of microstates
+///
+/// Microstates are counted for the electrons or the holes, whichever are fewer. Returns `None` if the numbers of microstates don't fit into `C`
+pub(crate) fn term_multiplicities<C: Count>(l: u8, electrons: u8) -> Option<Vec<(usize, i32, C)>> {
+    let electrons = electrons.min(2 * (2 * l + 1) - electrons);
+    let census = census::<C>(l, electrons)?;
+    let count = |ml, ms| census.get(&(ml, ms)).cloned().unwrap_or_default();
+    let mut terms = Vec::new();
+    for &(ml, ms) in census.keys() {
+        if ml < 0 || ms < 0 {
+            continue;
+        }
+        // N(ML, MS) - N(ML, MS+1) is the number of terms with S = MS and L ≥ ML, which only decreases with ML
+        let multiplicity = count(ml, ms)
+            .checked_sum(&count(ml + 1, ms + 2))?
+            .difference(&count(ml + 1, ms).checked_sum(&count(ml, ms + 2))?);
+        if multiplicity != C::default() {
+            terms.push((ml as usize, ms, multiplicity));
+        }
+    }
+    terms.sort_by_key(|&(l, doubled_spin, _)| (Reverse(doubled_spin), l));
+    Some(terms)
+}
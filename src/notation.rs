@@ -0,0 +1,313 @@
+use std::{
+    fmt::{Display, Formatter, Result},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::HalfInt;
+
+/// Style of superscripts, subscripts and letters used for term symbols and configurations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Notation {
+    /// `^3P_2`, `3d^5`
+    #[default]
+    Ascii,
+    /// `³P₂`, `3d⁵`
+    Unicode,
+    /// `<sup>3</sup>P<sub>2</sub>`, `3d<sup>5</sup>`
+    Html,
+    /// `{}^{3}\mathrm{P}_{2}`, `3d^{5}`
+    Latex,
+    /// `""^(3)"P"_(2)`, `3d^(5)` (Typst math)
+    Typst,
+    /// `<mmultiscripts>` of the terms, `<msup>` of the subshells (MathML presentation markup, to be put into a `<math>` element, see [`mathml`])
+    MathMl,
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("Unknown notation \"{0}\", expected one of: ascii, unicode, html, latex, typst, mathml")]
+pub struct UnknownNotation(String);
+
+impl Notation {
+    /// Every notation, in the order of declaration
+    pub const ALL: [Self; 6] = [
+        Self::Ascii,
+        Self::Unicode,
+        Self::Html,
+        Self::Latex,
+        Self::Typst,
+        Self::MathMl,
+    ];
+
+    /// Lowercase name of the notation, as it is parsed
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ascii => "ascii",
+            Self::Unicode => "unicode",
+            Self::Html => "html",
+            Self::Latex => "latex",
+            Self::Typst => "typst",
+            Self::MathMl => "mathml",
+        }
+    }
+}
+
+impl FromStr for Notation {
+    type Err = UnknownNotation;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|notation| notation.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownNotation(s.to_string()))
+    }
+}
+
+/// Something that can be written in different [`Notation`]s
+pub trait Notated {
+    fn fmt_notation(&self, notation: Notation, f: &mut Formatter<'_>) -> Result;
+
+    /// Wraps the value, so that it's [`Display`]ed in a specified notation
+    fn notated(&self, notation: Notation) -> WithNotation<'_, Self> {
+        WithNotation {
+            value: self,
+            notation,
+        }
+    }
+}
+
+pub struct WithNotation<'a, T: ?Sized> {
+    value: &'a T,
+    notation: Notation,
+}
+
+impl<T: Notated + ?Sized> Display for WithNotation<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.value.fmt_notation(self.notation, f)
+    }
+}
+
+/// `<math>` element with the value in the [`Notation::MathMl`], ready to be embedded into a web page
+pub fn mathml(value: &(impl Notated + ?Sized)) -> String {
+    format!(
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{}</math>",
+        value.notated(Notation::MathMl)
+    )
+}
+
+static SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+static SUBSCRIPT_DIGITS: [char; 10] = ['₀', '₁', '₂', '₃', '₄', '₅', '₆', '₇', '₈', '₉'];
+
+fn map_unicode(text: &str, digits: &[char; 10], (minus, plus): (char, char)) -> String {
+    text.chars()
+        .map(|c| match c {
+            '0'..='9' => digits[c as usize - '0' as usize],
+            '-' => minus,
+            '+' => plus,
+            _ => c,
+        })
+        .collect()
+}
+
+/// Replaces Unicode superscript and subscript digits and signs with the ordinary ones
+pub(crate) fn normal_digits(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if let Some(digit) = SUPERSCRIPT_DIGITS
+                .iter()
+                .chain(&SUBSCRIPT_DIGITS)
+                .position(|&d| d == c)
+            {
+                char::from(b'0' + (digit % 10) as u8)
+            } else {
+                match c {
+                    '⁻' | '₋' => '-',
+                    '⁺' | '₊' => '+',
+                    _ => c,
+                }
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn superscript(notation: Notation, text: impl Display, f: &mut Formatter<'_>) -> Result {
+    match notation {
+        Notation::Ascii => f.write_fmt(format_args!("^{text}")),
+        Notation::Unicode => f.write_str(&map_unicode(
+            &text.to_string(),
+            &SUPERSCRIPT_DIGITS,
+            ('⁻', '⁺'),
+        )),
+        Notation::Html => f.write_fmt(format_args!("<sup>{text}</sup>")),
+        Notation::Latex => f.write_fmt(format_args!("^{{{text}}}")),
+        Notation::Typst => f.write_fmt(format_args!("^({})", typst_escape(text))),
+        // attached to an empty base, like the LaTeX one; terms and subshells are written as whole elements instead
+        Notation::MathMl => {
+            f.write_fmt(format_args!("<msup><mrow/>{}</msup>", mathml_number(text)))
+        }
+    }
+}
+
+pub(crate) fn subscript(notation: Notation, text: impl Display, f: &mut Formatter<'_>) -> Result {
+    match notation {
+        Notation::Ascii => f.write_fmt(format_args!("_{text}")),
+        Notation::Unicode => f.write_str(&map_unicode(
+            &text.to_string(),
+            &SUBSCRIPT_DIGITS,
+            ('₋', '₊'),
+        )),
+        Notation::Html => f.write_fmt(format_args!("<sub>{text}</sub>")),
+        Notation::Latex => f.write_fmt(format_args!("_{{{text}}}")),
+        Notation::Typst => f.write_fmt(format_args!("_({})", typst_escape(text))),
+        Notation::MathMl => {
+            f.write_fmt(format_args!("<msub><mrow/>{}</msub>", mathml_number(text)))
+        }
+    }
+}
+
+/// Slashes make fractions in Typst math, so the ones of `3/2` are escaped
+fn typst_escape(text: impl Display) -> String {
+    text.to_string().replace('/', "\\/")
+}
+
+/// Mark of an odd term, like `°` in `⁴S°`
+pub(crate) fn odd_mark(notation: Notation, f: &mut Formatter<'_>) -> Result {
+    f.write_str(match notation {
+        Notation::Ascii => "*",
+        Notation::Unicode | Notation::Html => "°",
+        Notation::Latex => "^{\\circ}",
+        Notation::Typst => "^degree",
+        Notation::MathMl => "<msup><mrow/><mo>°</mo></msup>",
+    })
+}
+
+/// Upright letter, like the momentum letter of a term
+pub(crate) fn letter(notation: Notation, text: impl Display, f: &mut Formatter<'_>) -> Result {
+    match notation {
+        Notation::Latex => f.write_fmt(format_args!("\\mathrm{{{text}}}")),
+        Notation::Typst => f.write_fmt(format_args!("\"{text}\"")),
+        Notation::MathMl => f.write_fmt(format_args!("<mi mathvariant=\"normal\">{text}</mi>")),
+        _ => f.write_fmt(format_args!("{text}")),
+    }
+}
+
+/// Number (or a charge, like `2+`) as a MathML element, with the fractions like `3/2` bevelled
+pub(crate) fn mathml_number(text: impl Display) -> String {
+    let text = text.to_string();
+    let digits = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    if let Some((numerator, denominator)) = text.split_once('/') {
+        let fraction = |numerator: &str| {
+            format!("<mfrac bevelled=\"true\"><mn>{numerator}</mn><mn>{denominator}</mn></mfrac>")
+        };
+        return match numerator.strip_prefix('-') {
+            Some(numerator) => format!("<mrow><mo>-</mo>{}</mrow>", fraction(numerator)),
+            None => fraction(numerator),
+        };
+    }
+    if digits(&text) {
+        return format!("<mn>{text}</mn>");
+    }
+    if let Some(number) = text.strip_prefix('-').filter(|number| digits(number)) {
+        return format!("<mrow><mo>-</mo><mn>{number}</mn></mrow>");
+    }
+    match text.strip_suffix(['+', '-']) {
+        Some("") => format!("<mo>{text}</mo>"),
+        Some(charge) if digits(charge) => {
+            format!(
+                "<mrow><mn>{charge}</mn><mo>{}</mo></mrow>",
+                &text[charge.len()..]
+            )
+        }
+        _ => format!("<mi>{text}</mi>"),
+    }
+}
+
+/// Term symbol as a MathML `<mmultiscripts>`: the multiplicity (over the seniority) before the letter, and the odd mark (over J) after it
+pub(crate) fn mathml_term(
+    multiplicity: usize,
+    seniority: Option<u8>,
+    letter: impl Display,
+    odd: bool,
+    j: Option<HalfInt>,
+    f: &mut Formatter<'_>,
+) -> Result {
+    write!(f, "<mmultiscripts><mi mathvariant=\"normal\">{letter}</mi>")?;
+    if odd || j.is_some() {
+        let j = j.map_or("<none/>".to_string(), mathml_number);
+        let mark = if odd { "<mo>°</mo>" } else { "<none/>" };
+        write!(f, "{j}{mark}")?;
+    }
+    let seniority = seniority.map_or("<none/>".to_string(), mathml_number);
+    write!(
+        f,
+        "<mprescripts/>{seniority}{}</mmultiscripts>",
+        mathml_number(multiplicity)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HalfInt, Notated, Notation, Parity, TermMomentum, TermType};
+
+    #[test]
+    fn term_notations() {
+        let level = TermType {
+            momentum: TermMomentum(2),
+            spin: HalfInt::HALF,
+            parity: Parity::Odd,
+        }
+        .levels()
+        .remove(0);
+        let term = level.term();
+        assert_eq!(term.notated(Notation::Ascii).to_string(), "^2D*");
+        assert_eq!(term.notated(Notation::Unicode).to_string(), "²D°");
+        assert_eq!(term.notated(Notation::Html).to_string(), "<sup>2</sup>D°");
+        assert_eq!(
+            term.notated(Notation::Latex).to_string(),
+            "{}^{2}\\mathrm{D}^{\\circ}"
+        );
+        assert_eq!(
+            level.notated(Notation::Typst).to_string(),
+            "\"\"^(2)\"D\"^degree_(3\\/2)"
+        );
+        assert_eq!(level.notated(Notation::Unicode).to_string(), "²D°₃/₂");
+        assert_eq!(level.to_string(), "^2D*_3/2");
+    }
+
+    #[test]
+    fn names() {
+        for notation in Notation::ALL {
+            assert_eq!(notation.name().parse::<Notation>().ok(), Some(notation));
+        }
+        assert_eq!("LaTeX".parse::<Notation>().ok(), Some(Notation::Latex));
+        assert!("tex".parse::<Notation>().is_err());
+    }
+
+    #[test]
+    fn mathml() {
+        let level = TermType {
+            momentum: TermMomentum(2),
+            spin: HalfInt::HALF,
+            parity: Parity::Odd,
+        }
+        .levels()
+        .remove(0);
+        assert_eq!(
+            level.term().notated(Notation::MathMl).to_string(),
+            "<mmultiscripts><mi mathvariant=\"normal\">D</mi><none/><mo>°</mo><mprescripts/><none/><mn>2</mn></mmultiscripts>"
+        );
+        assert_eq!(
+            crate::mathml(&level),
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mmultiscripts><mi mathvariant=\"normal\">D</mi><mfrac bevelled=\"true\"><mn>3</mn><mn>2</mn></mfrac><mo>°</mo><mprescripts/><none/><mn>2</mn></mmultiscripts></math>"
+        );
+        let configuration: crate::Configuration = "3d5 4s".parse().expect("Should be ok");
+        assert_eq!(
+            configuration.notated(Notation::MathMl).to_string(),
+            "<msup><mrow><mn>3</mn><mi>d</mi></mrow><mn>5</mn></msup><mspace width=\"0.3em\"/><msup><mrow><mn>4</mn><mi>s</mi></mrow><mn>1</mn></msup>"
+        );
+    }
+}
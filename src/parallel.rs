@@ -0,0 +1,142 @@
+//! Binning of the microstates by ML and MS on every core with `rayon`, merging the counts of the parts at the end
+
+use std::{
+    collections::BTreeMap,
+    panic,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    thread,
+    time::Duration,
+};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+
+use crate::{microstate::Combinations, state_table::StateTable, HalfInt};
+
+/// Fewer microstates are binned faster than the threads are started
+pub(crate) const MIN_MICROSTATES: u128 = 1 << 16;
+
+/// Microstates counted by a part between the updates of the shared progress
+const BATCH: u64 = 4096;
+
+type Counts = BTreeMap<(i32, HalfInt), usize>;
+
+/// Same as [`count_table`](crate::count_table), binning the microstates on every core
+///
+/// Microstates are split by their first two occupied spin-orbitals, so that no part is much larger than the others. `progress` is called from the calling thread, as it doesn't have to be `Send`. `None` if any part was cancelled before it was binned
+pub(crate) fn count_table(
+    single_states: &[(i8, HalfInt)],
+    electrons: u8,
+    total: u128,
+    mut progress: impl FnMut(u128, u128),
+    cancel: &AtomicBool,
+) -> Option<StateTable<()>> {
+    let n = single_states.len() as u32;
+    let k = u32::from(electrons);
+    let prefix = k.min(2);
+    let mut prefixes = Combinations::new(n, prefix);
+    let parts = std::iter::from_fn(|| prefixes.advance().map(<[u32]>::to_vec)).collect_vec();
+    let processed = AtomicU64::new(0);
+    let bin = |mut counts: Counts, part: &Vec<u32>| {
+        let (ml, ms) = sum(single_states, part, 0);
+        // the rest of the spin-orbitals follow the last one of the prefix
+        let from = part.last().map_or(0, |last| last + 1);
+        let mut rest = Combinations::new(n - from, k - prefix);
+        let mut batch = 0;
+        while let Some(state) = rest.advance() {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let (rest_ml, rest_ms) = sum(single_states, state, from);
+            *counts.entry((ml + rest_ml, ms + rest_ms)).or_default() += 1;
+            batch += 1;
+            if batch == BATCH {
+                processed.fetch_add(batch, Ordering::Relaxed);
+                batch = 0;
+            }
+        }
+        processed.fetch_add(batch, Ordering::Relaxed);
+        Some(counts)
+    };
+    let merge = |mut merged: Counts, counts: Counts| {
+        for (key, count) in counts {
+            *merged.entry(key).or_default() += count;
+        }
+        Some(merged)
+    };
+    let counts = thread::scope(|scope| {
+        // the parts are binned on the pool of `rayon`, while this thread reports the progress
+        let binning = scope.spawn(|| {
+            parts
+                .par_iter()
+                .try_fold(Counts::new, bin)
+                .try_reduce(Counts::new, merge)
+        });
+        while !binning.is_finished() {
+            progress(u128::from(processed.load(Ordering::Relaxed)), total);
+            thread::sleep(Duration::from_millis(20));
+        }
+        progress(u128::from(processed.load(Ordering::Relaxed)), total);
+        binning
+            .join()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload))
+    });
+    counts.map(StateTable::from_counts)
+}
+
+/// ML and MS of the spin-orbitals with the given indices, counted from `offset`
+fn sum(single_states: &[(i8, HalfInt)], indices: &[u32], offset: u32) -> (i32, HalfInt) {
+    indices.iter().fold((0, HalfInt::ZERO), |(ml, ms), &index| {
+        let (next_ml, next_ms) = single_states[(offset + index) as usize];
+        (ml + i32::from(next_ml), ms + next_ms)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use itertools::Itertools;
+
+    use crate::{parallel::count_table, serial_count_table, state_table::StateTable, SubLevel};
+
+    fn terms(mut table: StateTable<()>) -> Vec<(String, usize)> {
+        std::iter::from_fn(|| table.extract())
+            .map(|(term, states)| (term.to_string(), states.len()))
+            .collect_vec()
+    }
+
+    #[test]
+    fn same_as_serial() {
+        let cancel = AtomicBool::new(false);
+        for sublevel in ["2p2", "3d5", "5g5", "(L=7)^3", "1s1", "4f0"] {
+            let sublevel: SubLevel = sublevel.parse().expect("Should be ok");
+            let single_states = sublevel
+                .tp
+                .mls()
+                .into_iter()
+                .cartesian_product(crate::SPINS)
+                .collect_vec();
+            let total = sublevel.microstate_count().expect("Should be ok");
+            let mut reported = 0;
+            let parallel = count_table(
+                &single_states,
+                sublevel.electrons,
+                total,
+                |processed, _| reported = processed,
+                &cancel,
+            )
+            .expect("Should not be cancelled");
+            assert_eq!(reported, total);
+            let serial = serial_count_table(
+                &single_states,
+                sublevel.electrons,
+                total,
+                |_, _| {},
+                &cancel,
+            )
+            .expect("Should not be cancelled");
+            assert_eq!(terms(parallel), terms(serial), "{sublevel}");
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::{fmt::Display, ops::Mul};
+
+use crate::{Configuration, SubLevel};
+
+/// Parity of a configuration, (-1)^(sum of l)
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Parity {
+    #[default]
+    Even,
+    Odd,
+}
+
+impl Parity {
+    pub fn from_momentum_sum(sum: usize) -> Self {
+        if sum.is_multiple_of(2) {
+            Self::Even
+        } else {
+            Self::Odd
+        }
+    }
+}
+
+/// Parities multiply like the corresponding signs
+impl Mul for Parity {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        if self == rhs {
+            Self::Even
+        } else {
+            Self::Odd
+        }
+    }
+}
+
+impl Display for Parity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Even => "even",
+            Self::Odd => "odd",
+        })
+    }
+}
+
+impl SubLevel {
+    pub fn parity(&self) -> Parity {
+        Parity::from_momentum_sum(usize::from(self.tp.0) * usize::from(self.electrons))
+    }
+}
+
+impl Configuration {
+    pub fn parity(&self) -> Parity {
+        self.sublevels()
+            .iter()
+            .map(SubLevel::parity)
+            .fold(Parity::Even, Parity::mul)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Configuration, Parity};
+
+    #[test]
+    fn configuration_parity() {
+        let parity = |configuration: &str| configuration.parse::<Configuration>().unwrap().parity();
+        assert_eq!(parity("[Ne] 3s2 3p3"), Parity::Odd);
+        assert_eq!(parity("1s2 2s2 2p2"), Parity::Even);
+        assert_eq!(parity("3d1 4p1"), Parity::Odd);
+        assert_eq!(parity("4f7 5d1"), Parity::Odd);
+        let terms = "2p1 3d1"
+            .parse::<Configuration>()
+            .unwrap()
+            .ls_terms()
+            .unwrap();
+        assert!(terms.iter().all(|term| term.parity() == Parity::Odd));
+        assert_eq!(terms[0].to_string(), "^3P*");
+    }
+}
@@ -0,0 +1,242 @@
+//! Terms of equivalent electrons built from the terms of one electron less, by Racah's recursion over the coefficients of fractional parentage
+//!
+//! Every term of l^n is some antisymmetric combination of the terms of l^(n-1) coupled with one more electron. The antisymmetrizer in the basis of such parents only needs the coefficients of fractional parentage of l^(n-1) and the recoupling 6j symbols, and its eigenvectors with eigenvalue 1 are the coefficients of fractional parentage of l^n
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    cache::cached_terms, linalg::projector_image, slater::TOLERANCE, tables::term, wigner_6j,
+    Configuration, DerivationError, HalfInt, Parity, SubLevel, TermType,
+};
+
+/// Term of l^n with its coefficients of fractional parentage, by the index of the parent term of l^(n-1)
+#[derive(Debug, Clone)]
+struct Parented {
+    momentum: usize,
+    doubled_spin: i32,
+    cfps: Vec<(usize, f64)>,
+}
+
+/// Terms of l^0, ..., l^electrons, the last two of which are returned
+fn recursion(l: u8, electrons: u8) -> (Vec<Parented>, Vec<Parented>) {
+    let empty = vec![Parented {
+        momentum: 0,
+        doubled_spin: 0,
+        cfps: Vec::new(),
+    }];
+    let (mut grandparents, mut parents) = (Vec::new(), empty);
+    for n in 1..=electrons {
+        let next = next_level(l, n, &grandparents, &parents);
+        grandparents = std::mem::replace(&mut parents, next);
+    }
+    (grandparents, parents)
+}
+
+/// Terms of l^n from the `parents` of l^(n-1), whose own parents are the `grandparents` of l^(n-2)
+fn next_level(l: u8, n: u8, grandparents: &[Parented], parents: &[Parented]) -> Vec<Parented> {
+    let l = usize::from(l);
+    // parents each term of l^n may come from
+    let mut candidates: BTreeMap<(usize, i32), Vec<usize>> = BTreeMap::new();
+    for (index, parent) in parents.iter().enumerate() {
+        for momentum in parent.momentum.abs_diff(l)..=parent.momentum + l {
+            for doubled_spin in [parent.doubled_spin - 1, parent.doubled_spin + 1] {
+                if doubled_spin >= 0 {
+                    candidates
+                        .entry((momentum, doubled_spin))
+                        .or_default()
+                        .push(index);
+                }
+            }
+        }
+    }
+    let mut recoupling = Recoupling::default();
+    let n = f64::from(n);
+    let mut terms = Vec::new();
+    for ((momentum, doubled_spin), indices) in candidates {
+        // parents sharing each grandparent, by their position among the `indices`
+        let mut shared: BTreeMap<usize, Vec<(usize, f64)>> = BTreeMap::new();
+        for (position, &index) in indices.iter().enumerate() {
+            for &(grandparent, cfp) in &parents[index].cfps {
+                shared.entry(grandparent).or_default().push((position, cfp));
+            }
+        }
+        // (1 - (n-1)·P) / n, where P exchanges the last two electrons
+        let mut antisymmetrizer = (0..indices.len())
+            .map(|row| {
+                (0..indices.len())
+                    .map(|column| f64::from(row == column) / n)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        // recoupling only depends on L and S of the parents, which few of them differ in
+        let mut kinds: Vec<(usize, i32)> = Vec::new();
+        let kind = indices
+            .iter()
+            .map(|&index| {
+                let parent = &parents[index];
+                let kind = (parent.momentum, parent.doubled_spin);
+                kinds
+                    .iter()
+                    .position(|&known| known == kind)
+                    .unwrap_or_else(|| {
+                        kinds.push(kind);
+                        kinds.len() - 1
+                    })
+            })
+            .collect::<Vec<_>>();
+        for (grandparent, shared) in shared {
+            let grandparent = &grandparents[grandparent];
+            let factors = kinds
+                .iter()
+                .map(|&(bra_momentum, bra_spin)| {
+                    kinds
+                        .iter()
+                        .map(|&(ket_momentum, ket_spin)| {
+                            let orbital = recoupling.get(
+                                2 * l as i32,
+                                [momentum, grandparent.momentum, bra_momentum, ket_momentum]
+                                    .map(|momentum| 2 * momentum as i32),
+                            );
+                            let spin = recoupling.get(
+                                1,
+                                [doubled_spin, grandparent.doubled_spin, bra_spin, ket_spin],
+                            );
+                            (n - 1.0) / n * orbital * spin
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            for &(bra, bra_cfp) in &shared {
+                let factors = &factors[kind[bra]];
+                let row = &mut antisymmetrizer[bra];
+                for &(ket, ket_cfp) in &shared {
+                    row[ket] -= bra_cfp * ket_cfp * factors[kind[ket]];
+                }
+            }
+        }
+        // a projector, so its trace is its rank
+        let rank = (0..indices.len())
+            .map(|index| antisymmetrizer[index][index])
+            .sum::<f64>()
+            .round() as usize;
+        for vector in projector_image(&antisymmetrizer, rank) {
+            terms.push(Parented {
+                momentum,
+                doubled_spin,
+                cfps: indices
+                    .iter()
+                    .copied()
+                    .zip(vector)
+                    .filter(|(_, cfp)| cfp.abs() > TOLERANCE)
+                    .collect(),
+            });
+        }
+    }
+    terms
+}
+
+/// Recoupling coefficients ⟨(Q j) P' j; J| (Q j) P j; J⟩ with the last two momenta j exchanged, remembered by the doubled momenta, since many parents share them
+#[derive(Debug, Default)]
+struct Recoupling(HashMap<(i32, [i32; 4]), f64>);
+
+impl Recoupling {
+    /// `momenta` are the doubled J, Q, P' and P
+    fn get(&mut self, one: i32, momenta: [i32; 4]) -> f64 {
+        *self.0.entry((one, momenta)).or_insert_with(|| {
+            let [total, grandparent, bra, ket] = momenta.map(HalfInt::from_doubled);
+            let one = HalfInt::from_doubled(one);
+            let phase = if (one + one + bra + ket).doubled() % 4 == 0 {
+                1.0
+            } else {
+                -1.0
+            };
+            let weight = f64::from((bra.doubled() + 1) * (ket.doubled() + 1)).sqrt();
+            let (top, bottom) = ([one, grandparent, bra], [one, total, ket]);
+            phase * weight * wigner_6j(top, bottom)
+        })
+    }
+}
+
+fn parented_term(parented: &Parented, parity: Parity) -> TermType {
+    term(parented.momentum, parented.doubled_spin, parity)
+}
+
+/// Same as [`ee_terms`](crate::ee_terms), but builds the terms of l^n from the terms of l^(n-1) by Racah's recursion over the coefficients of fractional parentage
+///
+/// The matrices are only as large as the number of parents of each L and S, so the cost grows with the number of terms rather than microstates. Slower than enumeration for the sublevels small enough to enumerate, but it is an independent check of it, and the same recursion gives [`ee_terms_parentage`]
+pub fn ee_terms_recursive(l: SubLevel) -> Result<Vec<TermType>, DerivationError> {
+    cached_terms(&l, || Ok(terms_from_recursion(&l)))
+}
+
+pub(crate) fn terms_from_recursion(l: &SubLevel) -> Vec<TermType> {
+    let mut terms = recursion(l.tp.0, l.placed_electrons())
+        .1
+        .iter()
+        .map(|parented| parented_term(parented, l.parity()))
+        .collect::<Vec<_>>();
+    terms.sort();
+    terms
+}
+
+/// Terms of the sublevel in spectroscopic order, each with the distinct terms of one electron less it is built from (parents with nonzero coefficients of fractional parentage)
+///
+/// Repeated terms are arbitrary combinations of each other, so only the parents of all of them together are meaningful
+pub fn ee_terms_parentage(l: SubLevel) -> Vec<(TermType, Vec<TermType>)> {
+    let parent_parity =
+        Parity::from_momentum_sum(usize::from(l.tp.0) * usize::from(l.electrons.saturating_sub(1)));
+    let (parents, terms) = recursion(l.tp.0, l.electrons);
+    let mut terms = terms
+        .iter()
+        .map(|parented| {
+            let mut parents = parented
+                .cfps
+                .iter()
+                .map(|&(parent, _)| parented_term(&parents[parent], parent_parity))
+                .collect::<Vec<_>>();
+            parents.sort();
+            parents.dedup();
+            (parented_term(parented, l.parity()), parents)
+        })
+        .collect::<Vec<_>>();
+    terms.sort();
+    terms
+}
+
+impl Configuration {
+    /// Same as [`Configuration::ls_terms`], but derives the terms of each subshell with [`ee_terms_recursive`]
+    pub fn ls_terms_recursive(&self) -> Result<Vec<TermType>, DerivationError> {
+        self.ls_terms_by(|sublevel| ee_terms_recursive(sublevel.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{ee_terms_parentage, SubLevel, TermType};
+
+    #[test]
+    fn parentage() {
+        let d3: SubLevel = "3d3".parse().expect("Should be ok");
+        let parentage = ee_terms_parentage(d3);
+        let parents = |term: &str| {
+            let term: TermType = term.parse().expect("Should be ok");
+            parentage
+                .iter()
+                .find(|(child, _)| *child == term)
+                .expect("Should be ok")
+                .1
+                .clone()
+        };
+        let terms = |terms: &str| {
+            terms
+                .split(' ')
+                .map(|term| term.parse::<TermType>().expect("Should be ok"))
+                .collect_vec()
+        };
+        // quartets only come from triplets, and H needs L of at least 3
+        assert_eq!(parents("4F"), terms("3P 3F"));
+        assert_eq!(parents("2H"), terms("3F 1G"));
+        assert_eq!(parents("4P"), terms("3P 3F"));
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::Display;
+
+use crate::{ee_terms_iter, notation, Notated, Notation, Parity, SubLevel, TermType};
+
+/// Term of equivalent electrons labeled with its seniority ν: the number of electrons in the configuration l^ν where this term first appears
+///
+/// Seniority tells apart repeated terms of p^n and d^n sublevels, like the two ^2D terms of d^3. Some of the f^n terms are still repeated with the same seniority, and require further classification
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeniorityTerm {
+    pub(crate) term: TermType,
+    pub(crate) seniority: u8,
+}
+
+impl SeniorityTerm {
+    pub fn term(&self) -> &TermType {
+        &self.term
+    }
+
+    pub fn seniority(&self) -> u8 {
+        self.seniority
+    }
+}
+
+impl SubLevel {
+    /// Terms of equivalent electrons of the sublevel along with their seniority, sorted in spectroscopic order and then by seniority
+    ///
+    /// Terms of l^n are terms of l^(n-2) plus the new ones, so the terms of seniority ν are obtained as terms of l^ν without terms of l^(ν-2). More than half-filled sublevels have the same seniority classification as their [hole equivalent](SubLevel::hole_equivalent)
+    pub fn terms_with_seniority(&self) -> Vec<SeniorityTerm> {
+        let parity = self.parity();
+        let sublevel = self.hole_equivalent().unwrap_or_else(|| self.clone());
+        let terms_of = |electrons: u8| {
+            let mut terms = ee_terms_iter(SubLevel {
+                electrons,
+                ..sublevel.clone()
+            })
+            .map(|term| term.with_parity(Parity::Even))
+            .collect::<Vec<_>>();
+            terms.sort();
+            terms
+        };
+        let mut result = Vec::new();
+        let mut previous = Vec::new();
+        for seniority in (sublevel.electrons % 2..=sublevel.electrons).step_by(2) {
+            let current = terms_of(seniority);
+            let mut new = current.clone();
+            for term in &previous {
+                if let Some(position) = new.iter().position(|other| other == term) {
+                    new.remove(position);
+                }
+            }
+            result.extend(new.into_iter().map(|term| SeniorityTerm {
+                term: term.with_parity(parity),
+                seniority,
+            }));
+            previous = current;
+        }
+        result.sort();
+        result
+    }
+}
+
+impl Notated for SeniorityTerm {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match notation {
+            Notation::Latex => f.write_str("{}")?,
+            Notation::Typst => f.write_str("\"\"")?,
+            Notation::MathMl => {
+                return notation::mathml_term(
+                    self.term.multiplicity(),
+                    Some(self.seniority),
+                    self.term.momentum,
+                    self.term.parity == Parity::Odd,
+                    None,
+                    f,
+                )
+            }
+            _ => {}
+        }
+        notation::superscript(notation, self.term.multiplicity(), f)?;
+        notation::subscript(notation, self.seniority, f)?;
+        notation::letter(notation, self.term.momentum, f)?;
+        if self.term.parity == Parity::Odd {
+            notation::odd_mark(notation, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Racah notation, seniority goes as a left subscript: `^2_3D`
+impl Display for SeniorityTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Notated, Notation, SubLevel};
+
+    fn seniority_terms(sublevel: &str) -> String {
+        sublevel
+            .parse::<SubLevel>()
+            .unwrap()
+            .terms_with_seniority()
+            .into_iter()
+            .map(|term| term.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn known_seniorities() {
+        assert_eq!(
+            seniority_terms("d3"),
+            "^4_3P ^4_3F ^2_3P ^2_1D ^2_3D ^2_3F ^2_3G ^2_3H"
+        );
+        // same as for d^3
+        assert_eq!(
+            seniority_terms("d7"),
+            "^4_3P ^4_3F ^2_3P ^2_1D ^2_3D ^2_3F ^2_3G ^2_3H"
+        );
+        assert_eq!(seniority_terms("p2"), "^3_2P ^1_0S ^1_2D");
+        let d4 = "d4".parse::<SubLevel>().unwrap().terms_with_seniority();
+        assert_eq!(d4.len(), 16);
+        assert_eq!(d4.iter().filter(|term| term.seniority() == 4).count(), 11);
+        assert_eq!(d4[0].notated(Notation::Unicode).to_string(), "⁵₄D");
+    }
+}
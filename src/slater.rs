@@ -0,0 +1,387 @@
+//! Linear combinations of Slater determinants of a single sublevel, and angular momentum operators acting on them
+
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+
+use crate::{microstate::occupations, wigner_3j, HalfInt};
+
+/// Linear combination of Slater determinants, keyed by their occupation bitmasks (see [`crate::Microstate::occupation`])
+///
+/// Determinant with occupation bits `i1 < i2 < ...` stands for the state `a†_i1 a†_i2 ... |0⟩`
+pub(crate) type Combination = BTreeMap<u64, f64>;
+
+/// Coefficients smaller than this are considered to be zero
+pub(crate) const TOLERANCE: f64 = 1e-10;
+
+/// Index of the spin-orbital in the occupation bitmask
+pub(crate) fn index(l: u8, ml: i32, spin_up: bool) -> u32 {
+    (2 * (ml + i32::from(l)) + i32::from(spin_up)) as u32
+}
+
+/// Sign acquired by an operator moving past the spin-orbitals occupied below `index`
+fn sign_below(occupation: u64, index: u32) -> f64 {
+    if (occupation & ((1 << index) - 1))
+        .count_ones()
+        .is_multiple_of(2)
+    {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn add(target: &mut Combination, occupation: u64, coefficient: f64) {
+    let entry = target.entry(occupation).or_default();
+    *entry += coefficient;
+    if entry.abs() < TOLERANCE {
+        target.remove(&occupation);
+    }
+}
+
+/// Applies `a_index`
+pub(crate) fn annihilate(state: &Combination, index: u32) -> Combination {
+    let mut result = Combination::new();
+    for (&occupation, &coefficient) in state {
+        if occupation & (1 << index) != 0 {
+            let sign = sign_below(occupation, index);
+            add(&mut result, occupation & !(1 << index), sign * coefficient);
+        }
+    }
+    result
+}
+
+/// Applies `a†_index`
+pub(crate) fn create(state: &Combination, index: u32) -> Combination {
+    let mut result = Combination::new();
+    for (&occupation, &coefficient) in state {
+        if occupation & (1 << index) == 0 {
+            let sign = sign_below(occupation, index);
+            add(&mut result, occupation | (1 << index), sign * coefficient);
+        }
+    }
+    result
+}
+
+/// Computes `target += factor * state`
+fn add_scaled(target: &mut Combination, state: &Combination, factor: f64) {
+    for (&occupation, &coefficient) in state {
+        add(target, occupation, factor * coefficient);
+    }
+}
+
+/// Applies L+ = Σ sqrt(l(l+1) - ml(ml+1)) a†_(ml+1) a_ml
+pub(crate) fn raise_l(l: u8, state: &Combination) -> Combination {
+    let l = i32::from(l);
+    let mut result = Combination::new();
+    for ml in -l..l {
+        let factor = f64::from(l * (l + 1) - ml * (ml + 1)).sqrt();
+        for spin_up in [false, true] {
+            let from = index(l as u8, ml, spin_up);
+            let to = index(l as u8, ml + 1, spin_up);
+            add_scaled(&mut result, &create(&annihilate(state, from), to), factor);
+        }
+    }
+    result
+}
+
+/// Applies L- = Σ sqrt(l(l+1) - ml(ml-1)) a†_(ml-1) a_ml
+pub(crate) fn lower_l(l: u8, state: &Combination) -> Combination {
+    let l = i32::from(l);
+    let mut result = Combination::new();
+    for ml in -l + 1..=l {
+        let factor = f64::from(l * (l + 1) - ml * (ml - 1)).sqrt();
+        for spin_up in [false, true] {
+            let from = index(l as u8, ml, spin_up);
+            let to = index(l as u8, ml - 1, spin_up);
+            add_scaled(&mut result, &create(&annihilate(state, from), to), factor);
+        }
+    }
+    result
+}
+
+/// Applies S- = Σ a†_(ml,-1/2) a_(ml,+1/2)
+pub(crate) fn lower_s(l: u8, state: &Combination) -> Combination {
+    let mut result = Combination::new();
+    for ml in -i32::from(l)..=i32::from(l) {
+        let (from, to) = (index(l, ml, true), index(l, ml, false));
+        add_scaled(&mut result, &create(&annihilate(state, from), to), 1.0);
+    }
+    result
+}
+
+/// Applies S+ = Σ a†_(ml,+1/2) a_(ml,-1/2)
+pub(crate) fn raise_s(l: u8, state: &Combination) -> Combination {
+    let mut result = Combination::new();
+    for ml in -i32::from(l)..=i32::from(l) {
+        let (from, to) = (index(l, ml, false), index(l, ml, true));
+        add_scaled(&mut result, &create(&annihilate(state, from), to), 1.0);
+    }
+    result
+}
+
+/// Adds an electron pair coupled to L = 0 and S = 0 (up to normalization): Σ (-1)^(l-ml) (-1)^(1/2-ms) a†_(ml,ms) a†_(-ml,-ms)
+///
+/// This operator commutes with both L and S, and conserves seniority
+pub(crate) fn create_pair(l: u8, state: &Combination) -> Combination {
+    let mut result = Combination::new();
+    for ml in -i32::from(l)..=i32::from(l) {
+        for spin_up in [false, true] {
+            let sign = if (i32::from(l) - ml + i32::from(!spin_up)) % 2 == 0 {
+                1.0
+            } else {
+                -1.0
+            };
+            let pair = create(
+                &create(state, index(l, -ml, !spin_up)),
+                index(l, ml, spin_up),
+            );
+            add_scaled(&mut result, &pair, sign);
+        }
+    }
+    result
+}
+
+pub(crate) fn dot(left: &Combination, right: &Combination) -> f64 {
+    left.iter()
+        .filter_map(|(occupation, coefficient)| Some(coefficient * right.get(occupation)?))
+        .sum()
+}
+
+/// Normalizes the state, returning `None` for (numerically) zero states
+pub(crate) fn normalized(mut state: Combination) -> Option<Combination> {
+    let norm = dot(&state, &state).sqrt();
+    if norm < TOLERANCE {
+        return None;
+    }
+    state
+        .values_mut()
+        .for_each(|coefficient| *coefficient /= norm);
+    Some(state)
+}
+
+/// Gram–Schmidt orthonormalization of `states`, that also makes them orthogonal to (already orthonormal) `against`
+///
+/// States linearly dependent on the previous ones are dropped
+pub(crate) fn orthonormalize(
+    states: impl IntoIterator<Item = Combination>,
+    against: &[Combination],
+) -> Vec<Combination> {
+    let mut basis = against.to_vec();
+    for mut state in states {
+        for other in &basis {
+            let projection = dot(other, &state);
+            add_scaled(&mut state, other, -projection);
+        }
+        if let Some(state) = normalized(state) {
+            basis.push(state);
+        }
+    }
+    basis.split_off(against.len())
+}
+
+/// Orthonormal basis of the states of `electrons` electrons on the sublevel with orbital momentum `l` having the highest weight M_L = L, M_S = S of some term ^(2S+1)L
+///
+/// These are the states annihilated by both L+ and S+, their number equals to the number of times the term occurs
+pub(crate) fn highest_weights(
+    l: u8,
+    electrons: u8,
+    momentum: usize,
+    spin: HalfInt,
+) -> Vec<Combination> {
+    let determinants = occupations(4 * u32::from(l) + 2, u32::from(electrons))
+        .filter(|&occupation| {
+            let (ml, ms) = (0..4 * u32::from(l) + 2)
+                .filter(|index| occupation & (1 << index) != 0)
+                .fold((0, 0), |(ml, ms), index| {
+                    (
+                        ml + index as i32 / 2 - i32::from(l),
+                        ms + if index % 2 == 0 { -1 } else { 1 },
+                    )
+                });
+            ml == momentum as i32 && ms == spin.doubled()
+        })
+        .collect_vec();
+    // images under L+ and S+ have different ML or MS, so the kernel of their sum is the intersection of their kernels
+    let images = determinants
+        .iter()
+        .map(|&occupation| {
+            let state = Combination::from([(occupation, 1.0)]);
+            let mut image = raise_l(l, &state);
+            add_scaled(&mut image, &raise_s(l, &state), 1.0);
+            image
+        })
+        .collect_vec();
+    let rows = images
+        .iter()
+        .flat_map(|image| image.keys().copied())
+        .unique()
+        .collect_vec();
+    let mut matrix = rows
+        .iter()
+        .map(|row| {
+            images
+                .iter()
+                .map(|image| image.get(row).copied().unwrap_or_default())
+                .collect_vec()
+        })
+        .collect_vec();
+    // reduced row echelon form
+    let mut pivots = Vec::new();
+    for column in 0..determinants.len() {
+        let row = pivots.len();
+        let Some(pivot) = (row..matrix.len())
+            .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))
+        else {
+            break;
+        };
+        if matrix[pivot][column].abs() < TOLERANCE {
+            continue;
+        }
+        matrix.swap(row, pivot);
+        let value = matrix[row][column];
+        matrix[row].iter_mut().for_each(|entry| *entry /= value);
+        let pivot_row = matrix[row].clone();
+        for (other, entries) in matrix.iter_mut().enumerate() {
+            if other != row {
+                let factor = entries[column];
+                for (entry, pivot_entry) in entries.iter_mut().zip(&pivot_row) {
+                    *entry -= factor * pivot_entry;
+                }
+            }
+        }
+        pivots.push(column);
+    }
+    let kernel = (0..determinants.len())
+        .filter(|column| !pivots.contains(column))
+        .map(|free| {
+            let mut state = Combination::from([(determinants[free], 1.0)]);
+            for (row, &pivot) in pivots.iter().enumerate() {
+                add(&mut state, determinants[pivot], -matrix[row][free]);
+            }
+            state
+        });
+    orthonormalize(kernel, &[])
+}
+
+/// Highest weight states of the term (see [`highest_weights`]) classified by seniority
+///
+/// States of seniority ν < n are obtained by adding electron pairs coupled to ^1S to the states of l^(n-2), and states of seniority n are orthogonal to them. Phases of the latter are chosen so that the coefficient of the first determinant is positive
+pub(crate) fn seniority_states(
+    l: u8,
+    electrons: u8,
+    momentum: usize,
+    spin: HalfInt,
+) -> Vec<(u8, Combination)> {
+    let mut states = Vec::new();
+    if electrons >= 2 {
+        // images of orthonormal states of the same seniority stay orthogonal and have the same norm
+        states.extend(
+            seniority_states(l, electrons - 2, momentum, spin)
+                .into_iter()
+                .filter_map(|(seniority, state)| {
+                    Some((seniority, normalized(create_pair(l, &state))?))
+                }),
+        );
+    }
+    let lower = states.iter().map(|(_, state)| state.clone()).collect_vec();
+    let new = orthonormalize(highest_weights(l, electrons, momentum, spin), &lower);
+    states.extend(new.into_iter().map(|mut state| {
+        let first = state
+            .values()
+            .find(|coefficient| coefficient.abs() > TOLERANCE)
+            .copied()
+            .unwrap_or(1.0);
+        if first < 0.0 {
+            state
+                .values_mut()
+                .for_each(|coefficient| *coefficient = -*coefficient);
+        }
+        (electrons, state)
+    }));
+    states
+}
+
+/// Angular coefficient c^k(l1 m1, l2 m2) = (-1)^m1 sqrt((2l1+1)(2l2+1)) (l1 k l2; 0 0 0) (l1 k l2; -m1 m1-m2 m2) of the electron repulsion
+pub(crate) fn gaunt(k: u8, (l1, m1): (u8, i32), (l2, m2): (u8, i32)) -> f64 {
+    let h = |x: i32| HalfInt::from_int(x);
+    let (l1, l2, k) = (i32::from(l1), i32::from(l2), i32::from(k));
+    let sign = if m1 % 2 == 0 { 1.0 } else { -1.0 };
+    sign * f64::from((2 * l1 + 1) * (2 * l2 + 1)).sqrt()
+        * wigner_3j((h(l1), h(0)), (h(k), h(0)), (h(l2), h(0)))
+        * wigner_3j((h(l1), h(-m1)), (h(k), h(m1 - m2)), (h(l2), h(m2)))
+}
+
+/// Applies the part of the electron repulsion Σ 1/r_ij proportional to the Slater integral F^k of the sublevel:
+/// 1/2 Σ ⟨pq|rs⟩ a†_p a†_q a_s a_r, where ⟨pq|rs⟩ = c^k(p, r) c^k(s, q) for the spin-orbitals with conserved spins
+pub(crate) fn repulsion(l: u8, k: u8, state: &Combination) -> Combination {
+    let orbitals = (-i32::from(l)..=i32::from(l))
+        .cartesian_product([false, true])
+        .collect_vec();
+    let mut result = Combination::new();
+    for (&(mp, sp), &(mr, sr)) in orbitals.iter().cartesian_product(&orbitals) {
+        if sp != sr {
+            continue;
+        }
+        let direct = gaunt(k, (l, mp), (l, mr));
+        if direct == 0.0 {
+            continue;
+        }
+        let removed = annihilate(state, index(l, mr, sr));
+        if removed.is_empty() {
+            continue;
+        }
+        for &(mq, sq) in &orbitals {
+            let m_s = mp + mq - mr;
+            if m_s.abs() > i32::from(l) {
+                continue;
+            }
+            let coefficient = direct * gaunt(k, (l, m_s), (l, mq)) / 2.0;
+            if coefficient == 0.0 {
+                continue;
+            }
+            let moved = create(
+                &create(&annihilate(&removed, index(l, m_s, sq)), index(l, mq, sq)),
+                index(l, mp, sp),
+            );
+            add_scaled(&mut result, &moved, coefficient);
+        }
+    }
+    result
+}
+
+/// Applies the one-electron spin-orbit operator Σ l·s = Σ [l_z s_z + (l+ s- + l- s+) / 2]
+pub(crate) fn spin_orbit(l: u8, state: &Combination) -> Combination {
+    let l = i32::from(l);
+    let mut result = Combination::new();
+    for ml in -l..=l {
+        for spin_up in [false, true] {
+            let from = index(l as u8, ml, spin_up);
+            let ms = if spin_up { 0.5 } else { -0.5 };
+            add_scaled(
+                &mut result,
+                &create(&annihilate(state, from), from),
+                f64::from(ml) * ms,
+            );
+        }
+        if ml < l {
+            // l+ s- moves the spin-up electron to ml + 1 with spin down
+            let factor = f64::from(l * (l + 1) - ml * (ml + 1)).sqrt() / 2.0;
+            let moved = create(
+                &annihilate(state, index(l as u8, ml, true)),
+                index(l as u8, ml + 1, false),
+            );
+            add_scaled(&mut result, &moved, factor);
+        }
+        if ml > -l {
+            // l- s+ moves the spin-down electron to ml - 1 with spin up
+            let factor = f64::from(l * (l + 1) - ml * (ml - 1)).sqrt() / 2.0;
+            let moved = create(
+                &annihilate(state, index(l as u8, ml, false)),
+                index(l as u8, ml - 1, true),
+            );
+            add_scaled(&mut result, &moved, factor);
+        }
+    }
+    result
+}
@@ -0,0 +1,498 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+use num_traits::{Signed, ToPrimitive};
+use thiserror::Error;
+
+use crate::{
+    ee_terms_iter, notation,
+    slater::{self, TOLERANCE},
+    wigner_3j, wigner_3j_exact, wigner_6j, Configuration, DerivationError, HalfInt, Microstate,
+    Notated, Notation, Parity, SeniorityTerm, SubLevel, TermMomentum, TermType,
+};
+
+/// Slater integral in the Condon–Shortley normalization: F_k = F^k / D_k (direct) or G_k = G^k / D_k (exchange)
+///
+/// Denominator D_k is the least common denominator of the squared angular coefficients c^k, that is 25 for F_2 of p^n, 49 and 441 for F_2 and F_4 of d^n, and so on. For the direct integrals of non-equivalent electrons it is the geometric mean of the ones of both subshells, like 35 for F_2 of pd
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SlaterIntegral {
+    F { k: u8, denominator: u64 },
+    G { k: u8, denominator: u64 },
+}
+
+impl SlaterIntegral {
+    pub fn k(&self) -> u8 {
+        match self {
+            Self::F { k, .. } | Self::G { k, .. } => *k,
+        }
+    }
+
+    /// D_k, relating this integral to the unnormalized one
+    pub fn denominator(&self) -> u64 {
+        match self {
+            Self::F { denominator, .. } | Self::G { denominator, .. } => *denominator,
+        }
+    }
+
+    /// Direct integral F_k between the electrons of subshells l1 and l2
+    pub(crate) fn direct(l1: u8, l2: u8, k: u8) -> Self {
+        let product = squared_denominator(l1, l1, k) * squared_denominator(l2, l2, k);
+        let root = (product as f64).sqrt().round() as u64;
+        Self::F {
+            k,
+            denominator: if root * root == product {
+                root
+            } else {
+                product
+            },
+        }
+    }
+
+    /// Exchange integral G_k between the electrons of subshells l1 and l2
+    fn exchange(l1: u8, l2: u8, k: u8) -> Self {
+        Self::G {
+            k,
+            denominator: squared_denominator(l1, l2, k),
+        }
+    }
+}
+
+/// Least common denominator of c^k(l1 m1, l2 m2)^2 over all of the projections
+fn squared_denominator(l1: u8, l2: u8, k: u8) -> u64 {
+    let h = |x: i32| HalfInt::from_int(x);
+    let (l1, l2, k) = (i32::from(l1), i32::from(l2), i32::from(k));
+    let zero = wigner_3j_exact((h(l1), h(0)), (h(k), h(0)), (h(l2), h(0)))
+        .expect("Orbital momenta are small");
+    (-l1..=l1)
+        .cartesian_product(-l2..=l2)
+        .map(|(m1, m2)| {
+            let symbol = wigner_3j_exact((h(l1), h(-m1)), (h(k), h(m1 - m2)), (h(l2), h(m2)))
+                .expect("Orbital momenta are small");
+            let square = zero.signed_square() * symbol.signed_square();
+            (square * num_rational::BigRational::from_integer(((2 * l1 + 1) * (2 * l2 + 1)).into()))
+                .abs()
+                .reduced()
+                .denom()
+                .to_u64()
+                .unwrap_or(1)
+        })
+        .fold(1, |lcm, denominator| {
+            lcm / gcd(lcm, denominator) * denominator
+        })
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Letter with the subscript order, like `F_2`
+impl Notated for SlaterIntegral {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let letter = match self {
+            Self::F { .. } => "F",
+            Self::G { .. } => "G",
+        };
+        f.write_str(letter)?;
+        notation::subscript(notation, self.k(), f)
+    }
+}
+
+impl Display for SlaterIntegral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+/// Linear combination of the Slater integrals, in order of the integrals
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlaterExpression {
+    pub terms: Vec<(SlaterIntegral, f64)>,
+}
+
+impl SlaterExpression {
+    /// Coefficient of the integral, zero if it is absent
+    pub fn coefficient(&self, integral: SlaterIntegral) -> f64 {
+        self.terms
+            .iter()
+            .find(|(other, _)| *other == integral)
+            .map(|(_, coefficient)| *coefficient)
+            .unwrap_or_default()
+    }
+
+    /// Evaluates the expression for the given values of the integrals
+    pub fn evaluate(&self, value: impl Fn(SlaterIntegral) -> f64) -> f64 {
+        self.terms
+            .iter()
+            .map(|(integral, coefficient)| coefficient * value(*integral))
+            .sum()
+    }
+
+    fn push(&mut self, integral: SlaterIntegral, coefficient: f64) {
+        if coefficient.abs() > TOLERANCE {
+            self.terms.push((integral, coefficient));
+        }
+    }
+}
+
+/// Smallest denominator q <= 1000 such that `value * q` is an integer
+fn small_denominator(value: f64) -> Option<u64> {
+    (1..=1000u64).find(|&q| {
+        let scaled = value * q as f64;
+        (scaled - scaled.round()).abs() < 1e-8 * q as f64
+    })
+}
+
+/// Writes the absolute value of the coefficient as a fraction a/q or a square root a sqrt(b)/q (if there's a small enough denominator), falling back to the decimal notation
+fn fmt_coefficient(
+    value: f64,
+    notation: Notation,
+    f: &mut std::fmt::Formatter<'_>,
+) -> std::fmt::Result {
+    let value = value.abs();
+    let (numerator, radicand, denominator) = if let Some(q) = small_denominator(value) {
+        ((value * q as f64).round() as u64, 1, q)
+    } else if let Some(q) = small_denominator(value * value) {
+        // sqrt(p/q) = sqrt(pq)/q = a sqrt(b)/q
+        let product = (value * value * q as f64).round() as u64 * q;
+        let a = (1..=(product as f64).sqrt() as u64)
+            .rev()
+            .find(|a| product.is_multiple_of(a * a))
+            .unwrap_or(1);
+        let common = gcd(a, q);
+        (a / common, product / (a * a), q / common)
+    } else {
+        return f.write_fmt(format_args!("{value:.6}"));
+    };
+    let mut text = String::new();
+    if numerator != 1 || radicand == 1 {
+        text += &numerator.to_string();
+    }
+    if radicand != 1 {
+        text += &match notation {
+            // Typst math has the same function
+            Notation::Ascii | Notation::Typst => format!("sqrt({radicand})"),
+            Notation::Unicode | Notation::Html => format!("√{radicand}"),
+            Notation::Latex => format!("\\sqrt{{{radicand}}}"),
+            Notation::MathMl => format!("<msqrt><mn>{radicand}</mn></msqrt>"),
+        };
+    }
+    match (denominator, notation) {
+        (1, _) if text == "1" => Ok(()),
+        (1, _) => f.write_str(&text),
+        (_, Notation::Latex) => f.write_fmt(format_args!("\\frac{{{text}}}{{{denominator}}} ")),
+        _ => f.write_fmt(format_args!("{text}/{denominator} ")),
+    }
+}
+
+/// Terms are written like `F_0 - 5F_2 + 1/3 G_1`, empty expression is `0`
+impl Notated for SlaterExpression {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if self.terms.is_empty() {
+            return f.write_str("0");
+        }
+        for (i, (integral, coefficient)) in self.terms.iter().enumerate() {
+            match (i, *coefficient < 0.0) {
+                (0, true) => f.write_str("-")?,
+                (0, false) => {}
+                (_, true) => f.write_str(" - ")?,
+                (_, false) => f.write_str(" + ")?,
+            }
+            fmt_coefficient(*coefficient, notation, f)?;
+            integral.fmt_notation(notation, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for SlaterExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+/// Electrostatic energy of a term, relative to the closed subshells
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TermEnergy {
+    pub term: TermType,
+    /// Seniority of the term, for terms of a single open subshell
+    pub seniority: Option<u8>,
+    /// Diagonal matrix element of the electron repulsion
+    pub energy: SlaterExpression,
+    /// Off-diagonal matrix elements with the other occurrences of the same term, labeled with their seniority
+    pub interactions: Vec<(u8, SlaterExpression)>,
+}
+
+/// Like `^3P: F_0 - 5F_2`, off-diagonal elements follow in brackets
+impl Notated for TermEnergy {
+    fn fmt_notation(
+        &self,
+        notation: Notation,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let labeled = |seniority| SeniorityTerm {
+            term: self.term.clone(),
+            seniority,
+        };
+        match self.seniority {
+            Some(seniority) => labeled(seniority).fmt_notation(notation, f)?,
+            None => self.term.fmt_notation(notation, f)?,
+        }
+        f.write_str(": ")?;
+        self.energy.fmt_notation(notation, f)?;
+        for (seniority, expression) in &self.interactions {
+            f.write_fmt(format_args!(
+                " [with {}: {}]",
+                labeled(*seniority).notated(notation),
+                expression.notated(notation)
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for TermEnergy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_notation(Notation::default(), f)
+    }
+}
+
+impl SubLevel {
+    /// Electrostatic energies of the terms of equivalent electrons as combinations of F_k, k = 0, 2, ..., 2l (like ^3P = F_0 - 5F_2 for p^2), in the order of [`SubLevel::terms_with_seniority`]
+    ///
+    /// Energies are computed as the expectation values of the electron repulsion in the states constructed by [`SubLevel::eigenstates`]. Repeated terms (like the two ^2D of d^3) are mixed by the repulsion, so the off-diagonal elements between them are listed as well
+    pub fn slater_energies(&self) -> Result<Vec<TermEnergy>, DerivationError> {
+        let l = self.tp.l();
+        if l > Microstate::MAX_L {
+            return Err(DerivationError::TooLarge(self.tp.clone()));
+        }
+        let parity = self.parity();
+        let integrals = (0..=2 * l)
+            .step_by(2)
+            .map(|k| SlaterIntegral::direct(l, l, k))
+            .collect_vec();
+        let mut result = Vec::new();
+        for term in ee_terms_iter(self.clone()).sorted().dedup() {
+            let states = slater::seniority_states(l, self.electrons, term.momentum.0, term.spin);
+            let images = integrals
+                .iter()
+                .map(|integral| {
+                    states
+                        .iter()
+                        .map(|(_, state)| slater::repulsion(l, integral.k(), state))
+                        .collect_vec()
+                })
+                .collect_vec();
+            let element = |i: usize, j: usize| {
+                let mut expression = SlaterExpression::default();
+                for (integral, images) in integrals.iter().zip(&images) {
+                    let coefficient = slater::dot(&states[i].1, &images[j]);
+                    expression.push(*integral, coefficient * integral.denominator() as f64);
+                }
+                expression
+            };
+            for (i, (seniority, _)) in states.iter().enumerate() {
+                result.push(TermEnergy {
+                    term: term.clone().with_parity(parity),
+                    seniority: Some(*seniority),
+                    energy: element(i, i),
+                    interactions: (0..states.len())
+                        .filter(|&j| j != i)
+                        .map(|j| (states[j].0, element(i, j)))
+                        .collect(),
+                });
+            }
+        }
+        result.sort_by(|a, b| a.term.cmp(&b.term).then(a.seniority.cmp(&b.seniority)));
+        Ok(result)
+    }
+}
+
+impl Configuration {
+    /// Electrostatic energies of the terms as combinations of the Slater integrals, relative to the closed subshells (which shift all of the terms equally)
+    ///
+    /// Supported are configurations with a single open subshell (see [`SubLevel::slater_energies`]), and with two open subshells of one electron each. Energies of the latter include both the direct F_k and exchange G_k integrals, like ^3P = F_0 - G_1 for sp
+    pub fn slater_energies(&self) -> Result<Vec<TermEnergy>, SlaterError> {
+        let open = self.open_subshells();
+        match open.sublevels() {
+            [] => Ok(vec![TermEnergy {
+                term: TermType {
+                    momentum: TermMomentum(0),
+                    spin: HalfInt::ZERO,
+                    parity: Parity::Even,
+                },
+                seniority: None,
+                energy: SlaterExpression::default(),
+                interactions: Vec::new(),
+            }]),
+            [sublevel] => Ok(sublevel.slater_energies()?),
+            [first, second] if first.electrons == 1 && second.electrons == 1 => {
+                Ok(two_electron_energies(first, second))
+            }
+            _ => Err(SlaterError::Unsupported(Box::new(self.clone()))),
+        }
+    }
+}
+
+/// Reduced matrix element ⟨l1||C^k||l2⟩ of the spherical tensor
+fn reduced(l1: u8, k: u8, l2: u8) -> f64 {
+    let h = |x: u8| HalfInt::from_int(i32::from(x));
+    let sign = if l1.is_multiple_of(2) { 1.0 } else { -1.0 };
+    sign * f64::from((2 * u32::from(l1) + 1) * (2 * u32::from(l2) + 1)).sqrt()
+        * wigner_3j(
+            (h(l1), HalfInt::ZERO),
+            (h(k), HalfInt::ZERO),
+            (h(l2), HalfInt::ZERO),
+        )
+}
+
+/// Terms of two non-equivalent electrons l1 l2: E = Σ f_k F^k + Σ g_k G^k, with
+/// f_k = (-1)^(l1+l2+L) ⟨l1||C^k||l1⟩ ⟨l2||C^k||l2⟩ {l1 l2 L; l2 l1 k} and
+/// g_k = (-1)^S ⟨l1||C^k||l2⟩^2 {l1 l2 L; l1 l2 k}
+fn two_electron_energies(first: &SubLevel, second: &SubLevel) -> Vec<TermEnergy> {
+    let (l1, l2) = (first.tp.l(), second.tp.l());
+    let h = |x: u8| HalfInt::from_int(i32::from(x));
+    let parity = first.parity() * second.parity();
+    let mut result = Vec::new();
+    for momentum in l1.abs_diff(l2)..=l1 + l2 {
+        for spin in [HalfInt::ZERO, HalfInt::ONE] {
+            let mut energy = SlaterExpression::default();
+            let sign = if (l1 + l2 + momentum) % 2 == 0 {
+                1.0
+            } else {
+                -1.0
+            };
+            for k in (0..=2 * l1.min(l2)).step_by(2) {
+                let integral = SlaterIntegral::direct(l1, l2, k);
+                let coefficient = sign
+                    * reduced(l1, k, l1)
+                    * reduced(l2, k, l2)
+                    * wigner_6j([h(l1), h(l2), h(momentum)], [h(l2), h(l1), h(k)]);
+                energy.push(integral, coefficient * integral.denominator() as f64);
+            }
+            let sign = if spin == HalfInt::ZERO { 1.0 } else { -1.0 };
+            for k in (l1.abs_diff(l2)..=l1 + l2).step_by(2) {
+                let integral = SlaterIntegral::exchange(l1, l2, k);
+                let coefficient = sign
+                    * reduced(l1, k, l2).powi(2)
+                    * wigner_6j([h(l1), h(l2), h(momentum)], [h(l1), h(l2), h(k)]);
+                energy.push(integral, coefficient * integral.denominator() as f64);
+            }
+            result.push(TermEnergy {
+                term: TermType {
+                    momentum: TermMomentum(usize::from(momentum)),
+                    spin,
+                    parity,
+                },
+                seniority: None,
+                energy,
+                interactions: Vec::new(),
+            });
+        }
+    }
+    result.sort_by(|a, b| a.term.cmp(&b.term));
+    result
+}
+
+#[derive(Debug, Error)]
+pub enum SlaterError {
+    #[error("Term energies of the {0} configuration are not supported: it should have a single open subshell, or two open subshells with one electron each")]
+    Unsupported(Box<Configuration>),
+    #[error("Intermediate coupling of the {0} configuration is not supported: it should have a single open subshell")]
+    NotSingleSubshell(Box<Configuration>),
+    #[error(transparent)]
+    Derivation(#[from] DerivationError),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Configuration, SubLevel};
+
+    fn energies(configuration: &str) -> Vec<String> {
+        configuration
+            .parse::<Configuration>()
+            .unwrap()
+            .slater_energies()
+            .unwrap()
+            .into_iter()
+            .map(|energy| energy.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn equivalent_electrons() {
+        assert_eq!(
+            energies("2p2"),
+            [
+                "^3_2P: F_0 - 5F_2",
+                "^1_0S: F_0 + 10F_2",
+                "^1_2D: F_0 + F_2"
+            ]
+        );
+        assert_eq!(
+            energies("1s2 2s2 2p3"),
+            [
+                "^4_3S*: 3F_0 - 15F_2",
+                "^2_1P*: 3F_0",
+                "^2_3D*: 3F_0 - 6F_2"
+            ]
+        );
+        assert_eq!(
+            energies("3d2"),
+            [
+                "^3_2P: F_0 + 7F_2 - 84F_4",
+                "^3_2F: F_0 - 8F_2 - 9F_4",
+                "^1_0S: F_0 + 14F_2 + 126F_4",
+                "^1_2D: F_0 - 3F_2 + 36F_4",
+                "^1_2G: F_0 + 4F_2 + F_4",
+            ]
+        );
+        // the two ^2D terms of d^3 are mixed, but the matrix is symmetric
+        let d3 = "d3".parse::<SubLevel>().unwrap().slater_energies().unwrap();
+        let doublets = d3
+            .iter()
+            .filter(|energy| energy.term.to_string() == "^2D")
+            .collect::<Vec<_>>();
+        assert_eq!(doublets.len(), 2);
+        assert_eq!(doublets[0].interactions[0].1, doublets[1].interactions[0].1);
+        assert_eq!(
+            doublets[0].interactions[0].1.to_string(),
+            "3sqrt(21)F_2 - 15sqrt(21)F_4"
+        );
+    }
+
+    #[test]
+    fn non_equivalent_electrons() {
+        assert_eq!(energies("2s1 2p1"), ["^3P*: F_0 - G_1", "^1P*: F_0 + G_1"]);
+        assert_eq!(
+            energies("2p1 3p1"),
+            [
+                "^3S: F_0 + 10F_2 - G_0 - 10G_2",
+                "^3P: F_0 - 5F_2 + G_0 - 5G_2",
+                "^3D: F_0 + F_2 - G_0 - G_2",
+                "^1S: F_0 + 10F_2 + G_0 + 10G_2",
+                "^1P: F_0 - 5F_2 - G_0 + 5G_2",
+                "^1D: F_0 + F_2 + G_0 + G_2",
+            ]
+        );
+        assert!("2p2 3p1"
+            .parse::<Configuration>()
+            .unwrap()
+            .slater_energies()
+            .is_err());
+    }
+}
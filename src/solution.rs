@@ -0,0 +1,552 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use itertools::Itertools;
+
+use crate::{
+    observed_terms, Configuration, DerivationError, DerivationEvent, HalfInt, Microstate, Notated,
+    Notation, SubLevel, TermType,
+};
+
+/// Step-by-step derivation of the terms of a configuration, for homework solutions and lecture notes
+///
+/// Keeps everything the derivation went through: single-electron states, microstates, the ML/MS table and the terms extracted from it one by one, see [`WorkedSolution::to_latex`] and [`WorkedSolution::to_svg`]
+#[derive(Debug, Clone)]
+pub struct WorkedSolution {
+    pub(crate) configuration: Configuration,
+    pub(crate) subshells: Vec<SubshellSolution>,
+    /// Terms of the whole configuration, with the repeated ones
+    terms: Vec<TermType>,
+}
+
+/// Derivation of the terms of a single open subshell
+#[derive(Debug, Clone)]
+pub(crate) struct SubshellSolution {
+    pub(crate) sublevel: SubLevel,
+    single_states: Vec<(i8, HalfInt)>,
+    pub(crate) holes: Option<u8>,
+    pub(crate) microstates: Vec<Microstate>,
+    pub(crate) table: CountTable,
+    pub(crate) steps: Vec<Step>,
+}
+
+/// Extraction of a single term from the table
+#[derive(Debug, Clone)]
+pub(crate) struct Step {
+    pub(crate) term: TermType,
+    /// Number of the removed microstates, the degeneracy of the term
+    removed: usize,
+    pub(crate) remaining: CountTable,
+}
+
+/// Numbers of the microstates with each ML and MS
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CountTable {
+    counts: BTreeMap<(i32, HalfInt), usize>,
+    max_ml: i32,
+    max_ms: HalfInt,
+}
+
+impl CountTable {
+    fn add(&mut self, ml: i32, ms: HalfInt) {
+        *self.counts.entry((ml, ms)).or_default() += 1;
+        self.max_ml = self.max_ml.max(ml);
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    /// Removes a microstate from every cell of the term
+    fn remove(&mut self, term: &TermType) {
+        let (l, s) = (term.l() as i32, term.s());
+        for ml in -l..=l {
+            for ms in (-s).up_to(s) {
+                let count = self
+                    .counts
+                    .get_mut(&(ml, ms))
+                    .expect("Term is extracted from the table");
+                *count -= 1;
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.counts.values().all(|count| *count == 0)
+    }
+
+    /// Rows of the table: ML descending
+    pub(crate) fn mls(&self) -> impl Iterator<Item = i32> {
+        (-self.max_ml..=self.max_ml).rev()
+    }
+
+    /// Columns of the table: MS descending
+    pub(crate) fn mss(&self) -> Vec<HalfInt> {
+        let mut mss = (-self.max_ms).up_to(self.max_ms).collect_vec();
+        mss.reverse();
+        mss
+    }
+
+    pub(crate) fn count(&self, ml: i32, ms: HalfInt) -> usize {
+        self.counts.get(&(ml, ms)).copied().unwrap_or_default()
+    }
+}
+
+impl WorkedSolution {
+    /// Derives the terms of every open subshell of the configuration, recording each step, within the [limit](Configuration::with_limit) of the configuration
+    pub fn new(configuration: &Configuration) -> Result<Self, DerivationError> {
+        let open = configuration.open_subshells();
+        let subshells = open
+            .sublevels()
+            .iter()
+            .map(|sublevel| {
+                let mut solution = SubshellSolution {
+                    sublevel: sublevel.clone(),
+                    single_states: Vec::new(),
+                    holes: None,
+                    microstates: Vec::new(),
+                    table: CountTable::default(),
+                    steps: Vec::new(),
+                };
+                let mut remaining = CountTable::default();
+                observed_terms(
+                    sublevel.clone(),
+                    &mut |event: &DerivationEvent| match event {
+                        DerivationEvent::SingleStateEnumerated { ml, ms, .. } => {
+                            solution.single_states.push((*ml, *ms))
+                        }
+                        DerivationEvent::MicrostatesEnumerated { holes, .. } => {
+                            solution.holes = *holes
+                        }
+                        DerivationEvent::MicrostateBinned { microstate, ml, ms } => {
+                            solution.microstates.push(*microstate);
+                            solution.table.add(*ml, *ms);
+                            remaining.add(*ml, *ms);
+                        }
+                        DerivationEvent::TermExtracted { term, states } => {
+                            remaining.remove(term);
+                            solution.steps.push(Step {
+                                term: term.clone(),
+                                removed: states.len(),
+                                remaining: remaining.clone(),
+                            });
+                        }
+                        DerivationEvent::Started { .. } | DerivationEvent::ExtractionStarted => {}
+                    },
+                    configuration.limit(),
+                )?;
+                Ok(solution)
+            })
+            .collect::<Result<_, DerivationError>>()?;
+        Ok(Self {
+            configuration: configuration.clone(),
+            subshells,
+            terms: open.ls_terms()?,
+        })
+    }
+
+    /// Terms of the configuration, with the repeated ones
+    pub fn terms(&self) -> &[TermType] {
+        &self.terms
+    }
+
+    /// LaTeX fragment with the whole derivation, to be included into a document using the `longtable` package
+    pub fn to_latex(&self) -> String {
+        let mut out = String::new();
+        self.write_latex(&mut out)
+            .expect("Writing to a string can't fail");
+        out
+    }
+
+    fn write_latex(&self, out: &mut String) -> std::fmt::Result {
+        let latex = Notation::Latex;
+        let configuration = self.configuration.notated(latex);
+        writeln!(out, "% requires \\usepackage{{longtable}}")?;
+        writeln!(
+            out,
+            "\\subsection*{{Terms of the ${configuration}$ configuration}}"
+        )?;
+        let open = self.configuration.open_subshells();
+        if open.sublevels().len() < self.configuration.sublevels().len() {
+            let open = if open.sublevels().is_empty() {
+                "none".to_string()
+            } else {
+                format!("${}$", open.notated(latex))
+            };
+            writeln!(
+                out,
+                "Closed subshells only contribute a ${{}}^{{1}}\\mathrm{{S}}$ term, so only the open ones are considered: {open}.\n"
+            )?;
+        }
+        for subshell in &self.subshells {
+            subshell.write_latex(out)?;
+        }
+        if self.subshells.len() > 1 {
+            writeln!(out, "\\subsubsection*{{Coupling of the subshells}}")?;
+            writeln!(
+                out,
+                "Electrons of different subshells are not equivalent, so every term of one subshell is coupled with every term of the others ($L = |L_1 - L_2|, \\dots, L_1 + L_2$, $S = |S_1 - S_2|, \\dots, S_1 + S_2$).\n"
+            )?;
+        }
+        writeln!(
+            out,
+            "\\paragraph{{Result.}} Terms of the ${configuration}$ configuration: {}.",
+            latex_terms(&self.terms)
+        )
+    }
+
+    /// Same derivation as [`WorkedSolution::to_latex`], as a Typst fragment
+    pub fn to_typst(&self) -> String {
+        let mut out = String::new();
+        self.write_typst(&mut out)
+            .expect("Writing to a string can't fail");
+        out
+    }
+
+    fn write_typst(&self, out: &mut String) -> std::fmt::Result {
+        let typst = Notation::Typst;
+        let configuration = self.configuration.notated(typst);
+        writeln!(out, "== Terms of the ${configuration}$ configuration")?;
+        let open = self.configuration.open_subshells();
+        if open.sublevels().len() < self.configuration.sublevels().len() {
+            let open = if open.sublevels().is_empty() {
+                "none".to_string()
+            } else {
+                format!("${}$", open.notated(typst))
+            };
+            writeln!(
+                out,
+                "Closed subshells only contribute a $\"\"^(1) \"S\"$ term, so only the open ones are considered: {open}.\n"
+            )?;
+        }
+        for subshell in &self.subshells {
+            subshell.write_typst(out)?;
+        }
+        if self.subshells.len() > 1 {
+            writeln!(out, "=== Coupling of the subshells")?;
+            writeln!(
+                out,
+                "Electrons of different subshells are not equivalent, so every term of one subshell is coupled with every term of the others ($L = |L_1 - L_2|, dots, L_1 + L_2$, $S = |S_1 - S_2|, dots, S_1 + S_2$).\n"
+            )?;
+        }
+        writeln!(
+            out,
+            "*Result.* Terms of the ${configuration}$ configuration: {}.",
+            typst_terms(&self.terms)
+        )
+    }
+}
+
+impl SubshellSolution {
+    fn write_latex(&self, out: &mut String) -> std::fmt::Result {
+        let latex = Notation::Latex;
+        let sublevel = self.sublevel.notated(latex);
+        writeln!(out, "\\subsubsection*{{Subshell ${sublevel}$}}")?;
+
+        writeln!(
+            out,
+            "\\paragraph{{Single-electron states.}} The subshell has ${}$ spin-orbitals $(m_l, m_s)$:\n",
+            self.single_states.len()
+        )?;
+        // wide tables are split into chunks, so that they fit the page
+        for chunk in &self.single_states.iter().enumerate().chunks(10) {
+            let chunk = chunk.collect_vec();
+            writeln!(out, "\\begin{{tabular}}{{c|{}}}", "c".repeat(chunk.len()))?;
+            let indices = chunk.iter().map(|(index, _)| index + 1).join(" & ");
+            writeln!(out, "$i$ & {indices} \\\\ \\hline")?;
+            let mls = chunk
+                .iter()
+                .map(|(_, (ml, _))| format!("${ml}$"))
+                .join(" & ");
+            writeln!(out, "$m_l$ & {mls} \\\\")?;
+            let mss = chunk
+                .iter()
+                .map(|(_, (_, ms))| format!("${}$", latex_half(*ms)))
+                .join(" & ");
+            writeln!(out, "$m_s$ & {mss} \\\\")?;
+            writeln!(out, "\\end{{tabular}}\n")?;
+        }
+
+        write!(
+            out,
+            "\\paragraph{{Microstates.}} There are ${}$ ways to place the electrons",
+            self.microstates.len()
+        )?;
+        if let Some(holes) = self.holes {
+            write!(
+                out,
+                " (the subshell is more than half-filled, so its ${holes}$ holes are placed instead, and the electrons occupy the rest of the spin-orbitals)"
+            )?;
+        }
+        writeln!(out, ":\n")?;
+        writeln!(out, "\\begin{{longtable}}{{rlrr}}")?;
+        writeln!(
+            out,
+            "\\# & Occupied $m_l^{{m_s}}$ & $M_L$ & $M_S$ \\\\ \\hline"
+        )?;
+        writeln!(out, "\\endhead")?;
+        for (index, microstate) in self.microstates.iter().enumerate() {
+            let orbitals = microstate
+                .spin_orbitals()
+                .map(|orbital| {
+                    let sign = if orbital.ms > HalfInt::ZERO { '+' } else { '-' };
+                    format!("{}^{{{sign}}}", orbital.ml)
+                })
+                .join(", ");
+            writeln!(
+                out,
+                "{} & $({orbitals})$ & ${}$ & ${}$ \\\\",
+                index + 1,
+                microstate.ml(),
+                latex_half(microstate.ms())
+            )?;
+        }
+        writeln!(out, "\\end{{longtable}}\n")?;
+
+        writeln!(
+            out,
+            "\\paragraph{{Microstate table.}} Numbers of the microstates with each $M_L$ and $M_S$:\n"
+        )?;
+        write_table_latex(&self.table, out)?;
+
+        writeln!(
+            out,
+            "\\paragraph{{Elimination.}} The greatest $M_L$ left, with the greatest $M_S$ among its microstates, starts a term with $L = M_L$ and $S = M_S$, which has a microstate in every cell with $|M_L| \\le L$ and $|M_S| \\le S$; one microstate is removed from each of them.\n"
+        )?;
+        writeln!(out, "\\begin{{enumerate}}")?;
+        for step in &self.steps {
+            write!(
+                out,
+                "\\item $M_L = {l}$, $M_S = {s}$: ${term}$ ($L = {l}$, $S = {s}$), ${removed}$ {microstates} removed",
+                l = step.term.l(),
+                s = latex_half(step.term.s()),
+                term = step.term.notated(latex),
+                removed = step.removed,
+                microstates = if step.removed == 1 {
+                    "microstate is"
+                } else {
+                    "microstates are"
+                },
+            )?;
+            if step.remaining.is_empty() {
+                writeln!(out, ", and none is left.")?;
+            } else {
+                writeln!(out, ", leaving\n")?;
+                write_table_latex(&step.remaining, out)?;
+            }
+        }
+        writeln!(out, "\\end{{enumerate}}\n")?;
+
+        let mut terms = self
+            .steps
+            .iter()
+            .map(|step| step.term.clone())
+            .collect_vec();
+        terms.sort();
+        writeln!(out, "Terms of ${sublevel}$: {}.\n", latex_terms(&terms))
+    }
+}
+
+impl SubshellSolution {
+    fn write_typst(&self, out: &mut String) -> std::fmt::Result {
+        let typst = Notation::Typst;
+        let sublevel = self.sublevel.notated(typst);
+        writeln!(out, "=== Subshell ${sublevel}$")?;
+
+        writeln!(
+            out,
+            "*Single-electron states.* The subshell has ${}$ spin-orbitals $(m_l, m_s)$:\n",
+            self.single_states.len()
+        )?;
+        // wide tables are split into chunks, so that they fit the page
+        for chunk in &self.single_states.iter().enumerate().chunks(10) {
+            let chunk = chunk.collect_vec();
+            writeln!(out, "#table(")?;
+            writeln!(out, "  columns: {},", chunk.len() + 1)?;
+            let indices = chunk.iter().map(|(index, _)| format!("[{}]", index + 1));
+            writeln!(out, "  [$i$], {},", indices.format(", "))?;
+            let mls = chunk.iter().map(|(_, (ml, _))| format!("[${ml}$]"));
+            writeln!(out, "  [$m_l$], {},", mls.format(", "))?;
+            let mss = chunk.iter().map(|(_, (_, ms))| format!("[${ms}$]"));
+            writeln!(out, "  [$m_s$], {},", mss.format(", "))?;
+            writeln!(out, ")\n")?;
+        }
+
+        write!(
+            out,
+            "*Microstates.* There are ${}$ ways to place the electrons",
+            self.microstates.len()
+        )?;
+        if let Some(holes) = self.holes {
+            write!(
+                out,
+                " (the subshell is more than half-filled, so its ${holes}$ holes are placed instead, and the electrons occupy the rest of the spin-orbitals)"
+            )?;
+        }
+        writeln!(out, ":\n")?;
+        writeln!(out, "#table(")?;
+        writeln!(out, "  columns: 4,")?;
+        writeln!(
+            out,
+            "  table.header([\\#], [Occupied $m_l^(m_s)$], [$M_L$], [$M_S$]),"
+        )?;
+        for (index, microstate) in self.microstates.iter().enumerate() {
+            let orbitals = microstate
+                .spin_orbitals()
+                .map(|orbital| {
+                    let sign = if orbital.ms > HalfInt::ZERO { '+' } else { '-' };
+                    format!("{}^({sign})", orbital.ml)
+                })
+                .join(", ");
+            writeln!(
+                out,
+                "  [{}], [$({orbitals})$], [${}$], [${}$],",
+                index + 1,
+                microstate.ml(),
+                microstate.ms()
+            )?;
+        }
+        writeln!(out, ")\n")?;
+
+        writeln!(
+            out,
+            "*Microstate table.* Numbers of the microstates with each $M_L$ and $M_S$:\n"
+        )?;
+        write_table_typst(&self.table, "", out)?;
+
+        writeln!(
+            out,
+            "*Elimination.* The greatest $M_L$ left, with the greatest $M_S$ among its microstates, starts a term with $L = M_L$ and $S = M_S$, which has a microstate in every cell with $|M_L| <= L$ and $|M_S| <= S$; one microstate is removed from each of them.\n"
+        )?;
+        for step in &self.steps {
+            write!(
+                out,
+                "+ $M_L = {l}$, $M_S = {s}$: ${term}$ ($L = {l}$, $S = {s}$), ${removed}$ {microstates} removed",
+                l = step.term.l(),
+                s = step.term.s(),
+                term = step.term.notated(typst),
+                removed = step.removed,
+                microstates = if step.removed == 1 {
+                    "microstate is"
+                } else {
+                    "microstates are"
+                },
+            )?;
+            if step.remaining.is_empty() {
+                writeln!(out, ", and none is left.")?;
+            } else {
+                writeln!(out, ", leaving\n")?;
+                // indented, so that the table belongs to the list item
+                write_table_typst(&step.remaining, "  ", out)?;
+            }
+        }
+        writeln!(out)?;
+
+        let mut terms = self
+            .steps
+            .iter()
+            .map(|step| step.term.clone())
+            .collect_vec();
+        terms.sort();
+        writeln!(out, "Terms of ${sublevel}$: {}.\n", typst_terms(&terms))
+    }
+}
+
+/// Centered table with rows of ML and columns of MS, zeros are left blank; Typst math makes fractions of `1/2` by itself
+fn write_table_typst(table: &CountTable, indent: &str, out: &mut String) -> std::fmt::Result {
+    let mss = table.mss();
+    writeln!(out, "{indent}#align(center, table(")?;
+    writeln!(out, "{indent}  columns: {},", mss.len() + 1)?;
+    let header = mss.iter().map(|ms| format!("[${ms}$]"));
+    writeln!(
+        out,
+        "{indent}  [$M_L backslash M_S$], {},",
+        header.format(", ")
+    )?;
+    for ml in table.mls() {
+        let counts = mss.iter().map(|ms| match table.count(ml, *ms) {
+            0 => "[]".to_string(),
+            count => format!("[{count}]"),
+        });
+        writeln!(out, "{indent}  [${ml}$], {},", counts.format(", "))?;
+    }
+    writeln!(out, "{indent}))\n")
+}
+
+/// Sorted terms, the repeated ones like `$2 times ""^(2) "D"$`
+fn typst_terms(terms: &[TermType]) -> String {
+    terms
+        .iter()
+        .dedup_with_count()
+        .map(|(count, term)| match count {
+            1 => format!("${}$", term.notated(Notation::Typst)),
+            count => format!("${count} times {}$", term.notated(Notation::Typst)),
+        })
+        .join(", ")
+}
+
+/// Centered tabular with rows of ML and columns of MS, zeros are left blank
+fn write_table_latex(table: &CountTable, out: &mut String) -> std::fmt::Result {
+    let mss = table.mss();
+    writeln!(out, "\\begin{{center}}")?;
+    writeln!(out, "\\begin{{tabular}}{{r|{}}}", "c".repeat(mss.len()))?;
+    let header = mss
+        .iter()
+        .map(|ms| format!("${}$", latex_half(*ms)))
+        .join(" & ");
+    writeln!(out, "$M_L \\backslash M_S$ & {header} \\\\ \\hline")?;
+    for ml in table.mls() {
+        let counts = mss
+            .iter()
+            .map(|ms| match table.count(ml, *ms) {
+                0 => String::new(),
+                count => count.to_string(),
+            })
+            .join(" & ");
+        writeln!(out, "${ml}$ & {counts} \\\\")?;
+    }
+    writeln!(out, "\\end{{tabular}}")?;
+    writeln!(out, "\\end{{center}}\n")
+}
+
+/// Half-integers as `\frac{1}{2}`
+pub(crate) fn latex_half(value: HalfInt) -> String {
+    if value.is_integer() {
+        value.to_string()
+    } else {
+        let sign = if value < HalfInt::ZERO { "-" } else { "" };
+        format!("{sign}\\frac{{{}}}{{2}}", value.doubled().abs())
+    }
+}
+
+/// Sorted terms, the repeated ones like `$2 \times {}^{2}\mathrm{D}$`
+fn latex_terms(terms: &[TermType]) -> String {
+    terms
+        .iter()
+        .dedup_with_count()
+        .map(|(count, term)| match count {
+            1 => format!("${}$", term.notated(Notation::Latex)),
+            count => format!("${count} \\times {}$", term.notated(Notation::Latex)),
+        })
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Configuration, WorkedSolution};
+
+    #[test]
+    fn p2() {
+        let configuration: Configuration = "1s2 2p2".parse().expect("Should be ok");
+        let solution = WorkedSolution::new(&configuration).expect("Should be ok");
+        assert_eq!(solution.terms().len(), 3);
+        let latex = solution.to_latex();
+        assert!(latex.contains("\\subsubsection*{Subshell $2p^{2}$}"));
+        assert!(latex.contains("There are $15$ ways"));
+        // ^1D, ^3P and ^1S are extracted in turn
+        assert_eq!(latex.matches("\\item").count(), 3);
+        assert!(latex.contains("and none is left."));
+        assert!(latex.contains("Closed subshells only contribute"));
+
+        let typst = solution.to_typst();
+        assert!(typst.contains("=== Subshell $2p^(2)$"));
+        assert_eq!(typst.matches("\n+ ").count(), 3);
+        assert!(typst.contains("$\"\"^(3)\"P\"$"));
+    }
+}
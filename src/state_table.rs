@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use crate::{HalfInt, Parity, TermMomentum, TermType};
+
+/// Microstates of a sublevel, sorted by their ML and MS
+///
+/// Terms are extracted from the table one by one: the state with the greatest ML (and then the greatest MS) defines the next term, and one state of every (ML, MS) pair belonging to this term is removed
+pub(crate) struct StateTable<T> {
+    states: BTreeMap<i32, BTreeMap<HalfInt, Vec<T>>>,
+}
+
+impl StateTable<()> {
+    /// Table of the given numbers of states with each ML and MS, as only the counts matter for the terms
+    #[cfg(feature = "parallel")]
+    pub(crate) fn from_counts(counts: impl IntoIterator<Item = ((i32, HalfInt), usize)>) -> Self {
+        let mut states: BTreeMap<i32, BTreeMap<HalfInt, Vec<()>>> = BTreeMap::new();
+        for ((ml, ms), count) in counts {
+            // `Vec<()>` does not allocate
+            states.entry(ml).or_default().insert(ms, vec![(); count]);
+        }
+        Self { states }
+    }
+}
+
+impl<T> StateTable<T> {
+    pub(crate) fn new(states: impl IntoIterator<Item = (i32, HalfInt, T)>) -> Self {
+        let mut sorted_states: BTreeMap<i32, BTreeMap<HalfInt, Vec<T>>> = BTreeMap::new();
+        states.into_iter().for_each(|(ml, ms, state)| {
+            sorted_states
+                .entry(ml)
+                .or_default()
+                .entry(ms)
+                .or_default()
+                .push(state);
+        });
+        Self {
+            states: sorted_states,
+        }
+    }
+
+    /// Extracts the next term along with the states assigned to it
+    pub(crate) fn extract(&mut self) -> Option<(TermType, Vec<T>)> {
+        let sorted_states = &mut self.states;
+        let (&l, l_states) = sorted_states.last_key_value()?;
+        let (&s, _) = l_states
+            .last_key_value()
+            .expect("There's at least one state, so entry should exist");
+        let term = TermType {
+            // max ML is nonnegative, since states are symmetric w.r.t. ML sign
+            momentum: TermMomentum(l.unsigned_abs() as usize),
+            spin: s,
+            parity: Parity::Even,
+        };
+        let mut this_term_states = Vec::with_capacity(term.degeneracy());
+
+        for l in -l..=l {
+            let l_states = sorted_states
+                .get_mut(&l)
+                .expect("Should be entry for this key, enforced above");
+            for s in (-s).up_to(s) {
+                let sl_states = l_states
+                    .get_mut(&s)
+                    .expect("Should be entry states with this spin!");
+                let this_state = sl_states
+                    .pop()
+                    .expect("Should be at least one state, will be enforced now");
+                this_term_states.push(this_state);
+                if sl_states.is_empty() {
+                    l_states.remove_entry(&s);
+                }
+            }
+
+            if l_states.is_empty() {
+                sorted_states.remove_entry(&l);
+            }
+        }
+        Some((term, this_term_states))
+    }
+}
@@ -0,0 +1,211 @@
+//! SVG pictures of the term extraction: the ML/MS table of a subshell, and the cells every term takes a microstate from
+
+use std::fmt::Write;
+
+use crate::{solution::CountTable, Notated, Notation, TermType, WorkedSolution};
+
+/// Side of a table cell
+const CELL: usize = 44;
+/// Room for the ML labels
+const LEFT: usize = 56;
+/// Room for the caption and the MS labels
+const TOP: usize = 72;
+const PADDING: usize = 16;
+
+const STYLE: &str = "text { font-family: sans-serif; font-size: 15px; text-anchor: middle; dominant-baseline: central; } \
+.caption { font-size: 16px; text-anchor: start; } \
+.label { fill: #555; } \
+rect { fill: #fff; stroke: #999; } \
+rect.taken { fill: #f4a261; }";
+
+/// Single picture: the table before a term is extracted, with the cells of the term highlighted
+struct Frame<'a> {
+    caption: String,
+    /// Table with every microstate, which sets the rows and the columns
+    grid: &'a CountTable,
+    counts: &'a CountTable,
+    term: Option<&'a TermType>,
+}
+
+impl Frame<'_> {
+    fn size(&self) -> (usize, usize) {
+        let columns = self.grid.mss().len();
+        let rows = self.grid.mls().count();
+        let width = (LEFT + columns * CELL + PADDING).max(self.caption.chars().count() * 9);
+        (width, TOP + rows * CELL + PADDING)
+    }
+
+    fn write(&self, y: usize, out: &mut String) -> std::fmt::Result {
+        writeln!(out, "<g transform=\"translate(0 {y})\">")?;
+        writeln!(
+            out,
+            "<text class=\"caption\" x=\"{PADDING}\" y=\"{}\">{}</text>",
+            PADDING + 4,
+            self.caption
+        )?;
+        let label_y = TOP - CELL / 2;
+        writeln!(
+            out,
+            "<text class=\"label\" x=\"{}\" y=\"{label_y}\">M<tspan baseline-shift=\"sub\">L</tspan> \\ M<tspan baseline-shift=\"sub\">S</tspan></text>",
+            LEFT / 2 + 4
+        )?;
+        let mss = self.grid.mss();
+        for (column, ms) in mss.iter().enumerate() {
+            let x = LEFT + column * CELL + CELL / 2;
+            writeln!(
+                out,
+                "<text class=\"label\" x=\"{x}\" y=\"{label_y}\">{ms}</text>"
+            )?;
+        }
+        for (row, ml) in self.grid.mls().enumerate() {
+            let y = TOP + row * CELL;
+            writeln!(
+                out,
+                "<text class=\"label\" x=\"{}\" y=\"{}\">{ml}</text>",
+                LEFT / 2,
+                y + CELL / 2
+            )?;
+            for (column, ms) in mss.iter().enumerate() {
+                let x = LEFT + column * CELL;
+                let taken = self.term.is_some_and(|term| {
+                    let (l, s) = (term.l() as i32, term.s());
+                    ml.abs() <= l && ms.abs() <= s
+                });
+                let class = if taken { " class=\"taken\"" } else { "" };
+                writeln!(
+                    out,
+                    "<rect{class} x=\"{x}\" y=\"{y}\" width=\"{CELL}\" height=\"{CELL}\"/>"
+                )?;
+                let text = match self.counts.count(ml, *ms) {
+                    0 => continue,
+                    count if taken => format!("{count}→{}", count - 1),
+                    count => count.to_string(),
+                };
+                writeln!(
+                    out,
+                    "<text x=\"{}\" y=\"{}\">{text}</text>",
+                    x + CELL / 2,
+                    y + CELL / 2
+                )?;
+            }
+        }
+        writeln!(out, "</g>")
+    }
+}
+
+impl WorkedSolution {
+    /// Pictures of the ML/MS table of every open subshell, one per step: the table as it is binned, then before every term is extracted (the highlighted cells lose a microstate to the term), and the empty table in the end
+    pub fn svg_frames(&self) -> Vec<String> {
+        self.frames()
+            .iter()
+            .map(|frame| {
+                let (width, height) = frame.size();
+                let mut out = String::new();
+                write_svg(width, height, STYLE, &mut out, |out| frame.write(0, out))
+                    .expect("Writing to a string can't fail");
+                out
+            })
+            .collect()
+    }
+
+    /// Every frame of [`WorkedSolution::svg_frames`] in a single picture, one under another
+    pub fn to_svg(&self) -> String {
+        let frames = self.frames();
+        let sizes = frames.iter().map(Frame::size).collect::<Vec<_>>();
+        let width = sizes
+            .iter()
+            .map(|(width, _)| *width)
+            .max()
+            .unwrap_or(PADDING);
+        let height = sizes.iter().map(|(_, height)| height).sum::<usize>();
+        let mut out = String::new();
+        write_svg(width, height, STYLE, &mut out, |out| {
+            let mut y = 0;
+            for (frame, (_, height)) in frames.iter().zip(&sizes) {
+                frame.write(y, out)?;
+                y += height;
+            }
+            Ok(())
+        })
+        .expect("Writing to a string can't fail");
+        out
+    }
+
+    fn frames(&self) -> Vec<Frame<'_>> {
+        let unicode = Notation::Unicode;
+        let mut frames = Vec::new();
+        for subshell in &self.subshells {
+            let sublevel = subshell.sublevel.notated(unicode);
+            let grid = &subshell.table;
+            frames.push(Frame {
+                caption: format!(
+                    "{sublevel}: {} microstates binned by M_L and M_S",
+                    subshell.microstates.len()
+                ),
+                grid,
+                counts: grid,
+                term: None,
+            });
+            let mut counts = grid;
+            for (index, step) in subshell.steps.iter().enumerate() {
+                frames.push(Frame {
+                    caption: format!(
+                        "{sublevel}, step {}: {} (L = {}, S = {}) takes a microstate from every highlighted cell",
+                        index + 1,
+                        step.term.notated(unicode),
+                        step.term.l(),
+                        step.term.s()
+                    ),
+                    grid,
+                    counts,
+                    term: Some(&step.term),
+                });
+                counts = &step.remaining;
+            }
+            frames.push(Frame {
+                caption: format!("{sublevel}: every microstate is assigned to a term"),
+                grid,
+                counts,
+                term: None,
+            });
+        }
+        frames
+    }
+}
+
+pub(crate) fn write_svg(
+    width: usize,
+    height: usize,
+    style: &str,
+    out: &mut String,
+    content: impl FnOnce(&mut String) -> std::fmt::Result,
+) -> std::fmt::Result {
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+    )?;
+    writeln!(out, "<style>{style}</style>")?;
+    content(out)?;
+    writeln!(out, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Configuration, WorkedSolution};
+
+    #[test]
+    fn p2() {
+        let configuration: Configuration = "2p2".parse().expect("Should be ok");
+        let solution = WorkedSolution::new(&configuration).expect("Should be ok");
+        let frames = solution.svg_frames();
+        // binned table, three terms, and the empty table
+        assert_eq!(frames.len(), 5);
+        // ^1D takes a microstate from every row of the MS = 0 column
+        assert_eq!(frames[1].matches("class=\"taken\"").count(), 5);
+        assert!(frames[1].contains(">2→1<"));
+        assert!(!frames[4].contains("→"));
+        let svg = solution.to_svg();
+        assert_eq!(svg.matches("<svg").count(), 1);
+        assert_eq!(svg.matches("<g ").count(), 5);
+    }
+}
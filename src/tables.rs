@@ -0,0 +1,24 @@
+//! Terms of every s, p, d and f sublevel, generated at build time from the same [`term_multiplicities`](crate::multiplicities) as [`ee_terms_analytic`](crate::ee_terms_analytic)
+
+use crate::{HalfInt, Parity, SubLevel, TermMomentum, TermType};
+
+include!(concat!(env!("OUT_DIR"), "/term_tables.rs"));
+
+/// Term with the given L and doubled S, for the tables and the derivations building the terms from such numbers
+pub(crate) const fn term(momentum: usize, doubled_spin: i32, parity: Parity) -> TermType {
+    TermType {
+        momentum: TermMomentum(momentum),
+        spin: HalfInt::from_doubled(doubled_spin),
+        parity,
+    }
+}
+
+/// Terms of the sublevel in spectroscopic order, like [`ee_terms`](crate::ee_terms) returns, if it's an s, p, d or f one
+///
+/// Looking them up takes no time and allocates nothing; [`ee_terms`](crate::ee_terms) and the other derivations return these for such sublevels too
+pub fn tabulated_terms(l: &SubLevel) -> Option<&'static [TermType]> {
+    TERM_TABLES
+        .get(usize::from(l.tp.0))?
+        .get(usize::from(l.electrons))
+        .copied()
+}
@@ -0,0 +1,164 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{wigner_9j, Configuration, HalfInt, Level, SubLevelType};
+
+/// Unitary transformation between the LS- and jj-coupled states of some J of a two-electron configuration
+///
+/// Element `[row][column]` equals ⟨(j1 j2) J | (L S) J⟩
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LsJjMatrix {
+    pub j: HalfInt,
+    /// LS-coupled states, in spectroscopic order
+    pub ls: Vec<Level>,
+    /// jj-coupled states, as pairs of j1 and j2
+    pub jj: Vec<(HalfInt, HalfInt)>,
+    pub elements: Vec<Vec<f64>>,
+}
+
+impl Configuration {
+    /// Transformation between LS and jj coupling of the states with total momentum J, see [`LsJjMatrix`]
+    ///
+    /// Configuration should have exactly two electrons outside of the closed subshells, either equivalent (like p^2) or not (like 3d 4p). Elements are built from 9j symbols: ⟨(j1 j2) J | (L S) J⟩ = sqrt((2j1+1)(2j2+1)(2L+1)(2S+1)) {l1 1/2 j1; l2 1/2 j2; L S J}, with an additional factor of sqrt(2) for the antisymmetrized j1 ≠ j2 states of equivalent electrons
+    pub fn ls_jj_matrix(&self, j: HalfInt) -> Result<LsJjMatrix, TransformationError> {
+        let open = self
+            .sublevels
+            .iter()
+            .filter(|sublevel| sublevel.electrons != sublevel.tp.max_electrons())
+            .collect_vec();
+        let (l1, l2, equivalent) = match open[..] {
+            [sublevel] if sublevel.electrons == 2 => {
+                (sublevel.tp.clone(), sublevel.tp.clone(), true)
+            }
+            [first, second] if first.electrons == 1 && second.electrons == 1 => {
+                (first.tp.clone(), second.tp.clone(), false)
+            }
+            _ => return Err(TransformationError::NotTwoElectron(Box::new(self.clone()))),
+        };
+        let ls = self
+            .ls_terms()
+            .map_err(|_| TransformationError::NotTwoElectron(Box::new(self.clone())))?
+            .into_iter()
+            .filter_map(|term| Level::new(term, j).ok())
+            .collect_vec();
+        let single_js = |tp: &SubLevelType| {
+            let l = HalfInt::from_int(i32::from(tp.l()));
+            [l - HalfInt::HALF, l + HalfInt::HALF]
+                .into_iter()
+                .filter(|j| *j > HalfInt::ZERO)
+                .collect_vec()
+        };
+        let jj = single_js(&l1)
+            .into_iter()
+            .cartesian_product(single_js(&l2))
+            .filter(|&(j1, j2)| (j1 - j2).abs() <= j && j <= j1 + j2)
+            // antisymmetric states of equivalent electrons: j1 and j2 are not ordered, and J of two identical j is even
+            .filter(|&(j1, j2)| !equivalent || j1 < j2 || (j1 == j2 && j.doubled() % 4 == 0))
+            .collect_vec();
+        let elements = jj
+            .iter()
+            .map(|&(j1, j2)| {
+                ls.iter()
+                    .map(|level| {
+                        let term = level.term();
+                        let momentum = HalfInt::from_int(term.momentum.0 as i32);
+                        let orbital = |tp: &SubLevelType| HalfInt::from_int(i32::from(tp.l()));
+                        let degeneracies = [j1, j2, momentum, term.spin]
+                            .map(|x| f64::from(x.doubled() + 1))
+                            .into_iter()
+                            .product::<f64>();
+                        let symmetry = if equivalent && j1 != j2 {
+                            2f64.sqrt()
+                        } else {
+                            1.0
+                        };
+                        symmetry
+                            * degeneracies.sqrt()
+                            * wigner_9j([
+                                [orbital(&l1), HalfInt::HALF, j1],
+                                [orbital(&l2), HalfInt::HALF, j2],
+                                [momentum, term.spin, j],
+                            ])
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        Ok(LsJjMatrix {
+            j,
+            ls,
+            jj,
+            elements,
+        })
+    }
+}
+
+/// Printed as a table with LS states as columns, and jj states as rows
+impl Display for LsJjMatrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{:>12}", ""))?;
+        for level in &self.ls {
+            f.write_fmt(format_args!(" {:>10}", level.to_string()))?;
+        }
+        for ((j1, j2), row) in self.jj.iter().zip(&self.elements) {
+            f.write_fmt(format_args!("\n{:>12}", format!("({j1}, {j2})")))?;
+            for element in row {
+                f.write_fmt(format_args!(" {element:>10.6}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TransformationError {
+    #[error("Configuration {0} should have exactly two electrons outside of the closed subshells")]
+    NotTwoElectron(Box<Configuration>),
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{Configuration, HalfInt, TransformationError};
+
+    fn assert_unitary(configuration: &str) {
+        let configuration: Configuration = configuration.parse().unwrap();
+        for j in HalfInt::ZERO.up_to(HalfInt::from_int(6)) {
+            let matrix = configuration.ls_jj_matrix(j).unwrap();
+            assert_eq!(matrix.ls.len(), matrix.jj.len(), "{configuration} J={j}");
+            for (a, b) in (0..matrix.jj.len()).cartesian_product(0..matrix.jj.len()) {
+                let product: f64 = (0..matrix.ls.len())
+                    .map(|column| matrix.elements[a][column] * matrix.elements[b][column])
+                    .sum();
+                let expected = if a == b { 1.0 } else { 0.0 };
+                assert!((product - expected).abs() < 1e-12, "{configuration} J={j}");
+            }
+        }
+    }
+
+    #[test]
+    fn unitary_matrices() {
+        for configuration in ["2p1 3p1", "3d1 4p1", "4f1 5d1", "[Ne] 3p2", "3d2", "4f2"] {
+            assert_unitary(configuration);
+        }
+        let p2: Configuration = "2p2".parse().unwrap();
+        let matrix = p2.ls_jj_matrix(HalfInt::ZERO).unwrap();
+        assert_eq!(
+            matrix.ls.iter().map(ToString::to_string).join(" "),
+            "^3P_0 ^1S_0"
+        );
+        // |(1/2)^2 J=0> has 2/3 of ^3P_0
+        assert!((matrix.elements[0][0].powi(2) - 2.0 / 3.0).abs() < 1e-12);
+        assert!(matches!(
+            "2p3"
+                .parse::<Configuration>()
+                .unwrap()
+                .ls_jj_matrix(HalfInt::HALF),
+            Err(TransformationError::NotTwoElectron(_))
+        ));
+    }
+}
@@ -0,0 +1,350 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{wigner_6j, HalfInt, Level, Parity, TermType};
+
+/// Type of a radiative transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Multipole {
+    /// Electric dipole, the only "allowed" one
+    E1,
+    /// Magnetic dipole
+    M1,
+    /// Electric quadrupole
+    E2,
+}
+
+impl Multipole {
+    /// Rank of the transition operator
+    pub fn rank(&self) -> i32 {
+        match self {
+            Self::E1 | Self::M1 => 1,
+            Self::E2 => 2,
+        }
+    }
+
+    /// Whether the operator changes parity
+    pub fn parity(&self) -> Parity {
+        match self {
+            Self::E1 => Parity::Odd,
+            Self::M1 | Self::E2 => Parity::Even,
+        }
+    }
+
+    /// Lines of any multipole other than E1 are called forbidden
+    pub fn is_forbidden(&self) -> bool {
+        *self != Self::E1
+    }
+}
+
+impl Display for Multipole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::E1 => "E1",
+            Self::M1 => "M1",
+            Self::E2 => "E2",
+        })
+    }
+}
+
+impl FromStr for Multipole {
+    type Err = UnknownMultipole;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "E1" => Self::E1,
+            "M1" => Self::M1,
+            "E2" => Self::E2,
+            _ => return Err(UnknownMultipole(s.to_string())),
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("Unknown multipole \"{0}\", expected one of: E1, M1, E2")]
+pub struct UnknownMultipole(String);
+
+/// Selection rule broken by a transition
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RuleViolation {
+    #[error("Parity should change")]
+    ParityUnchanged,
+    #[error("Parity should not change")]
+    ParityChanged,
+    #[error("ΔJ = {0} is not allowed")]
+    DeltaJ(HalfInt),
+    #[error("J = 0 → 0 is not allowed")]
+    JZeroToZero,
+    #[error("J = {0} ↔ {1} is not allowed")]
+    JPair(HalfInt, HalfInt),
+    #[error("ΔL = {0} is not allowed (LS coupling)")]
+    DeltaL(i32),
+    #[error("L = 0 → 0 is not allowed (LS coupling)")]
+    LZeroToZero,
+    #[error("L = {0} ↔ {1} is not allowed (LS coupling)")]
+    LPair(usize, usize),
+    #[error("ΔS = {0} is not allowed, should be 0 (LS coupling)")]
+    DeltaS(HalfInt),
+    #[error("ΔM_J = {0} is not allowed")]
+    DeltaMj(HalfInt),
+    #[error("M_J = 0 → 0 is not allowed for ΔJ = 0")]
+    MjZeroToZero,
+}
+
+impl RuleViolation {
+    /// Whether the rule is exact, and not only holds in pure LS coupling
+    pub fn is_strict(&self) -> bool {
+        !matches!(
+            self,
+            Self::DeltaL(_) | Self::LZeroToZero | Self::LPair(_, _) | Self::DeltaS(_)
+        )
+    }
+}
+
+/// Checks selection rules of the multipole transition between two levels, returning all of the broken ones
+///
+/// Rules for L and S only hold in pure LS coupling (see [`RuleViolation::is_strict`]), so transitions breaking only them (like intercombination lines) are still observed, but are weak:
+/// - E1: parity changes, ΔJ = 0, ±1 (not 0 ↔ 0), ΔL = 0, ±1 (not 0 ↔ 0), ΔS = 0
+/// - M1: parity does not change, ΔJ = 0, ±1 (not 0 ↔ 0), ΔL = 0, ΔS = 0
+/// - E2: parity does not change, ΔJ = 0, ±1, ±2 (not 0 ↔ 0, 1/2 ↔ 1/2, 0 ↔ 1), ΔL = 0, ±1, ±2 (not 0 ↔ 0, 0 ↔ 1), ΔS = 0
+pub fn violations(multipole: Multipole, upper: &Level, lower: &Level) -> Vec<RuleViolation> {
+    let rank = multipole.rank();
+    let mut violations = Vec::new();
+    match (upper.term.parity * lower.term.parity, multipole.parity()) {
+        (Parity::Even, Parity::Odd) => violations.push(RuleViolation::ParityUnchanged),
+        (Parity::Odd, Parity::Even) => violations.push(RuleViolation::ParityChanged),
+        _ => {}
+    }
+    // J, J' and the rank should satisfy the triangle rule
+    let delta_j = upper.j - lower.j;
+    if delta_j.abs() > HalfInt::from_int(rank) {
+        violations.push(RuleViolation::DeltaJ(delta_j));
+    } else if upper.j + lower.j < HalfInt::from_int(rank) {
+        violations.push(if upper.j == HalfInt::ZERO && lower.j == HalfInt::ZERO {
+            RuleViolation::JZeroToZero
+        } else {
+            RuleViolation::JPair(upper.j, lower.j)
+        });
+    }
+    let (upper_l, lower_l) = (upper.term.momentum.0, lower.term.momentum.0);
+    let delta_l = upper_l as i32 - lower_l as i32;
+    if multipole == Multipole::M1 {
+        // L + 2S does not act on the orbital part
+        if delta_l != 0 {
+            violations.push(RuleViolation::DeltaL(delta_l));
+        }
+    } else if delta_l.abs() > rank {
+        violations.push(RuleViolation::DeltaL(delta_l));
+    } else if ((upper_l + lower_l) as i32) < rank {
+        violations.push(if upper_l == 0 && lower_l == 0 {
+            RuleViolation::LZeroToZero
+        } else {
+            RuleViolation::LPair(upper_l, lower_l)
+        });
+    }
+    let delta_s = upper.term.spin - lower.term.spin;
+    if delta_s != HalfInt::ZERO {
+        violations.push(RuleViolation::DeltaS(delta_s));
+    }
+    violations
+}
+
+/// Same as [`violations`], but for a transition between the magnetic sublevels M_J of the levels
+pub fn sublevel_violations(
+    multipole: Multipole,
+    (upper, upper_mj): (&Level, HalfInt),
+    (lower, lower_mj): (&Level, HalfInt),
+) -> Vec<RuleViolation> {
+    let mut violations = violations(multipole, upper, lower);
+    let delta_mj = upper_mj - lower_mj;
+    if delta_mj.abs() > HalfInt::from_int(multipole.rank()) {
+        violations.push(RuleViolation::DeltaMj(delta_mj));
+    } else if multipole.rank() % 2 == 1
+        && upper.j == lower.j
+        && upper_mj == HalfInt::ZERO
+        && lower_mj == HalfInt::ZERO
+    {
+        // (J k J; 0 0 0) vanishes for odd k
+        violations.push(RuleViolation::MjZeroToZero);
+    }
+    violations
+}
+
+/// Whether the electric-dipole transition is allowed, see [`violations`]
+pub fn allowed_e1(upper: &Level, lower: &Level) -> bool {
+    violations(Multipole::E1, upper, lower).is_empty()
+}
+
+/// The lowest multipole, that does not break any of the strict selection rules (see [`RuleViolation::is_strict`]), so that the line may be tagged as allowed or forbidden
+pub fn lowest_multipole(upper: &Level, lower: &Level) -> Option<Multipole> {
+    [Multipole::E1, Multipole::M1, Multipole::E2]
+        .into_iter()
+        .find(|multipole| {
+            violations(*multipole, upper, lower)
+                .iter()
+                .all(|violation| !violation.is_strict())
+        })
+}
+
+/// Component J → J' of a multiplet
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultipletLine {
+    pub upper: Level,
+    pub lower: Level,
+    /// Relative line strength, strengths of the whole multiplet sum up to 1
+    pub strength: f64,
+}
+
+/// Relative strengths of the electric-dipole lines between two terms in LS coupling, S(J, J') ∝ (2J+1)(2J'+1) {L J S; J' L' 1}²
+///
+/// Only the non-zero components are listed, ordered by upper and then lower level. Multiplet is empty if terms have different spin, as the lines are forbidden in pure LS coupling
+pub fn multiplet_strengths(upper: &TermType, lower: &TermType) -> Vec<MultipletLine> {
+    if upper.spin != lower.spin {
+        return Vec::new();
+    }
+    let momentum = |term: &TermType| HalfInt::from_int(term.momentum.0 as i32);
+    let mut lines = Vec::new();
+    for upper_level in upper.levels() {
+        for lower_level in lower.levels() {
+            let symbol = wigner_6j(
+                [momentum(upper), upper_level.j, upper.spin],
+                [lower_level.j, momentum(lower), HalfInt::ONE],
+            );
+            let strength = f64::from((upper_level.j.doubled() + 1) * (lower_level.j.doubled() + 1))
+                * symbol
+                * symbol;
+            if strength > 1e-12 {
+                lines.push(MultipletLine {
+                    upper: upper_level.clone(),
+                    lower: lower_level,
+                    strength,
+                });
+            }
+        }
+    }
+    let total: f64 = lines.iter().map(|line| line.strength).sum();
+    lines.iter_mut().for_each(|line| line.strength /= total);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        allowed_e1, lowest_multipole, multiplet_strengths, sublevel_violations, violations,
+        HalfInt, Level, Multipole, RuleViolation, TermType,
+    };
+
+    fn level(symbol: &str) -> Level {
+        symbol.parse().unwrap()
+    }
+
+    #[test]
+    fn e1_rules() {
+        // sodium D lines
+        assert!(allowed_e1(&level("2P*_3/2"), &level("2S_1/2")));
+        assert!(allowed_e1(&level("2P*_1/2"), &level("2S_1/2")));
+        assert_eq!(
+            violations(Multipole::E1, &level("2D_5/2"), &level("2S_1/2")),
+            [
+                RuleViolation::ParityUnchanged,
+                RuleViolation::DeltaJ(HalfInt::from_int(2)),
+                RuleViolation::DeltaL(2),
+            ]
+        );
+        // mercury intercombination line
+        assert_eq!(
+            violations(Multipole::E1, &level("3P*_1"), &level("1S_0")),
+            [RuleViolation::DeltaS(HalfInt::ONE)]
+        );
+        assert_eq!(
+            violations(Multipole::E1, &level("3P_0"), &level("3P*_0")),
+            [RuleViolation::JZeroToZero]
+        );
+        assert_eq!(
+            sublevel_violations(
+                Multipole::E1,
+                (&level("2P*_1/2"), HalfInt::from_doubled(-1)),
+                (&level("2S_1/2"), HalfInt::from_doubled(1))
+            ),
+            []
+        );
+        assert_eq!(
+            sublevel_violations(
+                Multipole::E1,
+                (&level("3P*_1"), HalfInt::ZERO),
+                (&level("3S_1"), HalfInt::ZERO)
+            ),
+            [RuleViolation::MjZeroToZero]
+        );
+    }
+
+    #[test]
+    fn forbidden_lines() {
+        // [O III] 500.7 nm, ^1D_2 → ^3P_2
+        let (upper, lower) = (level("1D_2"), level("3P_2"));
+        assert_eq!(lowest_multipole(&upper, &lower), Some(Multipole::M1));
+        assert_eq!(
+            violations(Multipole::M1, &upper, &lower),
+            [
+                RuleViolation::DeltaL(1),
+                RuleViolation::DeltaS(-HalfInt::ONE)
+            ]
+        );
+        // fine structure transition within a term is pure M1
+        assert!(violations(Multipole::M1, &level("3P_1"), &level("3P_0")).is_empty());
+        // Ca+ 729 nm clock transition, ^2D_5/2 → ^2S_1/2
+        let (upper, lower) = (level("2D_5/2"), level("2S_1/2"));
+        assert!(violations(Multipole::E2, &upper, &lower).is_empty());
+        assert_eq!(lowest_multipole(&upper, &lower), Some(Multipole::E2));
+        assert!(lowest_multipole(&upper, &lower).unwrap().is_forbidden());
+        assert_eq!(
+            violations(Multipole::E2, &level("2P_1/2"), &level("2S_1/2")),
+            [
+                RuleViolation::JPair(HalfInt::HALF, HalfInt::HALF),
+                RuleViolation::LPair(1, 0)
+            ]
+        );
+        assert_eq!(lowest_multipole(&level("3P_0"), &level("1S_0")), None);
+    }
+
+    #[test]
+    fn multiplets() {
+        let strengths = |upper: &str, lower: &str| {
+            let upper: TermType = upper.parse().unwrap();
+            let lower: TermType = lower.parse().unwrap();
+            multiplet_strengths(&upper, &lower)
+                .into_iter()
+                .map(|line| format!("{} {}: {:.4}", line.upper, line.lower, line.strength))
+                .collect::<Vec<_>>()
+        };
+        // sodium D lines, 1:2
+        assert_eq!(
+            strengths("2P*", "2S"),
+            ["^2P*_1/2 ^2S_1/2: 0.3333", "^2P*_3/2 ^2S_1/2: 0.6667"]
+        );
+        // proportional to 2J+1 of the upper level
+        assert_eq!(
+            strengths("3S", "3P*"),
+            [
+                "^3S_1 ^3P*_0: 0.1111",
+                "^3S_1 ^3P*_1: 0.3333",
+                "^3S_1 ^3P*_2: 0.5556"
+            ]
+        );
+        // ^2D_3/2 → ^2P_3/2 is the weak satellite line
+        assert_eq!(
+            strengths("2D", "2P*"),
+            [
+                "^2D_3/2 ^2P*_1/2: 0.3333",
+                "^2D_3/2 ^2P*_3/2: 0.0667",
+                "^2D_5/2 ^2P*_3/2: 0.6000"
+            ]
+        );
+        assert!(strengths("3P*", "1S").is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::{
+    couple_terms, ee_terms_limited, Configuration, CoupledTerm, DerivationError, Notated, Notation,
+    SubLevel, TermType,
+};
+
+/// Genealogy of the terms of a configuration: terms of every open subshell, and the terms obtained by coupling them to the next subshell, one after another
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CouplingTree {
+    /// Open subshells, along with their distinct terms
+    pub subshells: Vec<(SubLevel, Vec<TermType>)>,
+    /// Couplings of the subshells after the first one: distinct terms of the previous stage (of the first subshell, for the first stage) coupled with the terms of the subshell. Terms of the last stage are the terms of the configuration
+    pub stages: Vec<Vec<CoupledTerm>>,
+}
+
+impl Configuration {
+    /// Derives the terms of every open subshell, and couples them in the order of the subshells, within the [limit](Configuration::with_limit) of the configuration
+    pub fn coupling_tree(&self) -> Result<CouplingTree, DerivationError> {
+        let subshells = self
+            .open_subshells()
+            .sublevels()
+            .iter()
+            .map(|sublevel| {
+                let mut terms = ee_terms_limited(sublevel.clone(), self.limit())?;
+                terms.dedup();
+                Ok((sublevel.clone(), terms))
+            })
+            .collect::<Result<Vec<_>, DerivationError>>()?;
+        let mut stages: Vec<Vec<CoupledTerm>> = Vec::new();
+        for (_, terms) in subshells.iter().skip(1) {
+            let mut previous = match stages.last() {
+                Some(stage) => stage.iter().map(|coupled| coupled.term.clone()).collect(),
+                None => subshells[0].1.clone(),
+            };
+            previous.sort();
+            previous.dedup();
+            self.limit().check_coupling(&previous, terms)?;
+            stages.push(couple_terms(&previous, terms));
+        }
+        Ok(CouplingTree { subshells, stages })
+    }
+}
+
+impl CouplingTree {
+    /// Graphviz DOT graph, going from the terms of the subshells through the intermediate terms to the terms of the configuration (drawn with double borders)
+    ///
+    /// [`Notation::Html`] makes HTML-like labels, which Graphviz draws with real superscripts; other notations are used as is, [`Notation::Unicode`] being the most readable one
+    pub fn to_dot(&self, notation: Notation) -> String {
+        let mut out = String::new();
+        self.write_dot(notation, &mut out)
+            .expect("Writing to a string can't fail");
+        out
+    }
+
+    fn write_dot(&self, notation: Notation, out: &mut String) -> std::fmt::Result {
+        let label = |value: &dyn std::fmt::Display| match notation {
+            Notation::Html => format!("<{value}>"),
+            _ => format!("\"{}\"", value.to_string().replace('"', "\\\"")),
+        };
+        let last = self.stages.len();
+        writeln!(out, "digraph coupling {{")?;
+        writeln!(out, "  rankdir=LR;")?;
+        writeln!(out, "  node [shape=box];")?;
+        for (index, (sublevel, terms)) in self.subshells.iter().enumerate() {
+            writeln!(out, "  subgraph cluster_{index} {{")?;
+            writeln!(out, "    label={};", label(&sublevel.notated(notation)))?;
+            for term in terms {
+                // a single subshell is coupled to nothing, so its terms are the final ones
+                let peripheries = if last == 0 { " peripheries=2" } else { "" };
+                writeln!(
+                    out,
+                    "    \"{}\" [label={}{peripheries}];",
+                    node_id(0, index, term),
+                    label(&term.notated(notation))
+                )?;
+            }
+            writeln!(out, "  }}")?;
+        }
+        for (index, stage) in self.stages.iter().enumerate() {
+            let stage_index = index + 1;
+            let peripheries = if stage_index == last {
+                " peripheries=2"
+            } else {
+                ""
+            };
+            for term in stage.iter().map(|coupled| &coupled.term).sorted().dedup() {
+                writeln!(
+                    out,
+                    "  \"{}\" [label={}{peripheries}];",
+                    node_id(stage_index, 0, term),
+                    label(&term.notated(notation))
+                )?;
+            }
+            let edges = stage
+                .iter()
+                .flat_map(|coupled| {
+                    let child = node_id(stage_index, 0, &coupled.term);
+                    [
+                        // the left parent is an intermediate term, or a term of the first subshell for the first stage
+                        (node_id(index, 0, &coupled.left), child.clone()),
+                        (node_id(0, stage_index, &coupled.right), child),
+                    ]
+                })
+                .unique();
+            for (parent, child) in edges {
+                writeln!(out, "  \"{parent}\" -> \"{child}\";")?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+}
+
+/// Terms of the subshells are in stage 0, and the coupled ones are in the later stages
+fn node_id(stage: usize, subshell: usize, term: &TermType) -> String {
+    match stage {
+        0 => format!("subshell {subshell} {}", term.notated(Notation::Ascii)),
+        stage => format!("stage {stage} {}", term.notated(Notation::Ascii)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Configuration, Notation};
+
+    #[test]
+    fn p_d() {
+        let configuration: Configuration = "2p1 3d1".parse().expect("Should be ok");
+        let tree = configuration.coupling_tree().expect("Should be ok");
+        assert_eq!(tree.subshells.len(), 2);
+        assert_eq!(tree.stages.len(), 1);
+        // ^2P* and ^2D make ^1P*, ^3P*, ^1D*, ^3D*, ^1F* and ^3F*
+        assert_eq!(tree.stages[0].len(), 6);
+        let dot = tree.to_dot(Notation::Unicode);
+        assert!(dot.starts_with("digraph coupling {"));
+        assert_eq!(dot.matches("peripheries=2").count(), 6);
+        assert!(dot.contains("\"subshell 0 ^2P*\" -> \"stage 1 ^3F*\";"));
+        assert!(dot.contains("\"subshell 1 ^2D\" -> \"stage 1 ^3F*\";"));
+        assert!(dot.contains("label=\"2p¹\""));
+    }
+}
@@ -0,0 +1,121 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+/// Magnetic field strength (stored in teslas)
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MagneticField {
+    tesla: f64,
+}
+
+impl MagneticField {
+    pub fn tesla(tesla: f64) -> Self {
+        Self { tesla }
+    }
+
+    pub fn gauss(gauss: f64) -> Self {
+        Self {
+            tesla: gauss * 1e-4,
+        }
+    }
+
+    pub fn as_tesla(&self) -> f64 {
+        self.tesla
+    }
+
+    pub fn as_gauss(&self) -> f64 {
+        self.tesla * 1e4
+    }
+}
+
+impl Display for MagneticField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} T", self.tesla))
+    }
+}
+
+/// Parses a field with units, like `0.5T`, `100 mT`, `5000 G` or `2kG`
+impl FromStr for MagneticField {
+    type Err = UnitParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let unit_start = s
+            .find(|c: char| c.is_ascii_alphabetic())
+            .ok_or_else(|| UnitParseError::MissingUnit(s.to_string()))?;
+        let (value, unit) = s.split_at(unit_start);
+        let value: f64 = value
+            .trim()
+            .parse()
+            .map_err(|_| UnitParseError::Value(s.to_string()))?;
+        Ok(match unit {
+            "T" => Self::tesla(value),
+            "mT" => Self::tesla(value * 1e-3),
+            "G" => Self::gauss(value),
+            "kG" => Self::gauss(value * 1e3),
+            _ => return Err(UnitParseError::UnknownUnit(unit.to_string())),
+        })
+    }
+}
+
+/// Unit of the energies computed by the crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EnergyUnit {
+    Joule,
+    ElectronVolt,
+    /// cm⁻¹, the most common one in spectroscopy
+    #[default]
+    Wavenumber,
+    Megahertz,
+}
+
+impl EnergyUnit {
+    /// Bohr magneton μ_B, in this unit per tesla (CODATA 2018)
+    pub fn bohr_magneton(&self) -> f64 {
+        match self {
+            Self::Joule => 9.274_010_078_3e-24,
+            Self::ElectronVolt => 5.788_381_806_0e-5,
+            Self::Wavenumber => 0.466_864_477_83,
+            Self::Megahertz => 13_996.244_93,
+        }
+    }
+}
+
+impl Display for EnergyUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Joule => "J",
+            Self::ElectronVolt => "eV",
+            Self::Wavenumber => "cm-1",
+            Self::Megahertz => "MHz",
+        })
+    }
+}
+
+/// Parses unit symbols: `J`, `eV`, `cm-1` (or `cm^-1`) and `MHz`
+impl FromStr for EnergyUnit {
+    type Err = UnitParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim() {
+            "J" => Self::Joule,
+            "eV" => Self::ElectronVolt,
+            "cm-1" | "cm^-1" | "cm⁻¹" => Self::Wavenumber,
+            "MHz" => Self::Megahertz,
+            _ => return Err(UnitParseError::UnknownUnit(s.to_string())),
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnitParseError {
+    #[error("Unknown unit \"{0}\"")]
+    UnknownUnit(String),
+    #[error("\"{0}\" should have a unit")]
+    MissingUnit(String),
+    #[error("Can't parse the value of \"{0}\"")]
+    Value(String),
+}
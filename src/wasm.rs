@@ -0,0 +1,197 @@
+//! JavaScript interface to the term derivation and the Clebsch–Gordan coefficients, for the web pages
+//!
+//! Configurations are the same as the ones of the command-line tool, like "2p2 3d1", "\[Ar\] 3d5 4s1", or an element or ion, like "Fe", "Fe2+" or "Fe III" (its ground configuration). Momenta are passed as numbers, like 1.5 for j = 3/2, and returned as strings, like "3/2". Invalid arguments throw an `Error` with the message of the failure
+//!
+//! The module is built with `cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm-bindgen --crate-type cdylib`, and bound with `wasm-bindgen --target web --out-dir npm target/wasm32-unknown-unknown/release/chitose.wasm`. The `npm` directory is then the package to publish, its version should follow the one of the crate
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{Configuration, HalfInt, Ion, Level, Notated, Notation, Parity, TermType};
+
+/// LS term, as seen from JavaScript
+#[derive(Debug, Serialize)]
+struct Term {
+    /// Like "³P"
+    symbol: String,
+    #[serde(rename = "L")]
+    momentum: usize,
+    #[serde(rename = "S")]
+    spin: HalfInt,
+    parity: Parity,
+}
+
+impl From<&TermType> for Term {
+    fn from(term: &TermType) -> Self {
+        Self {
+            symbol: term.notated(Notation::Unicode).to_string(),
+            momentum: term.momentum.0,
+            spin: term.spin,
+            parity: term.parity,
+        }
+    }
+}
+
+/// Level of an LS term, as seen from JavaScript
+#[derive(Debug, Serialize)]
+struct GroundLevel {
+    /// Like "⁴F₃/₂"
+    symbol: String,
+    #[serde(rename = "L")]
+    momentum: usize,
+    #[serde(rename = "S")]
+    spin: HalfInt,
+    #[serde(rename = "J")]
+    j: HalfInt,
+    parity: Parity,
+}
+
+impl From<&Level> for GroundLevel {
+    fn from(level: &Level) -> Self {
+        Self {
+            symbol: level.notated(Notation::Unicode).to_string(),
+            momentum: level.term.momentum.0,
+            spin: level.term.spin,
+            j: level.j,
+            parity: level.term.parity,
+        }
+    }
+}
+
+/// Configuration of the text, or the ground configuration of the element (or ion) it names
+fn configuration(text: &str) -> Result<Configuration, String> {
+    if text
+        .trim_start()
+        .starts_with(|c: char| c.is_ascii_uppercase())
+    {
+        return text
+            .parse::<Ion>()
+            .map(|ion| ion.configuration())
+            .map_err(|error| error.to_string());
+    }
+    text.parse::<Configuration>()
+        .map_err(|error| error.to_string())
+}
+
+fn term_list(text: &str) -> Result<Vec<Term>, String> {
+    let terms = configuration(text)?
+        .ls_terms()
+        .map_err(|error| error.to_string())?;
+    Ok(terms.iter().map(Term::from).collect())
+}
+
+fn ground_level(text: &str) -> Result<GroundLevel, String> {
+    let ground = configuration(text)?.open_subshells().ground_term();
+    Ok((&ground).into())
+}
+
+/// Half-integer of the number, or `None` if it isn't one
+fn half(value: f64) -> Option<HalfInt> {
+    let doubled = value * 2.0;
+    let fits = doubled.fract() == 0.0 && doubled.abs() <= f64::from(i32::MAX);
+    fits.then(|| HalfInt::from_doubled(doubled as i32))
+}
+
+/// ⟨j1 m1 j2 m2 | j m⟩ of the momenta, or `Err` if some of them isn't a half-integer
+fn momenta(values: [f64; 6]) -> Result<[(HalfInt, HalfInt); 3], String> {
+    let mut momenta = [HalfInt::ZERO; 6];
+    for (momentum, value) in momenta.iter_mut().zip(values) {
+        *momentum = half(value).ok_or_else(|| format!("Momentum {value} is not a half-integer"))?;
+    }
+    let [j1, m1, j2, m2, j, m] = momenta;
+    Ok([(j1, m1), (j2, m2), (j, m)])
+}
+
+fn js_value(value: &impl Serialize) -> Result<JsValue, JsError> {
+    // plain objects instead of the `Map`s
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    value
+        .serialize(&serializer)
+        .map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// Terms of the configuration in spectroscopic order and with repetitions, as an array of `{symbol, L, S, parity}`
+#[wasm_bindgen]
+pub fn terms(configuration: &str) -> Result<JsValue, JsError> {
+    let terms = term_list(configuration).map_err(|error| JsError::new(&error))?;
+    js_value(&terms)
+}
+
+/// Ground level of the configuration by Hund's rules, as `{symbol, L, S, J, parity}`
+#[wasm_bindgen(js_name = groundTerm)]
+pub fn ground_term(configuration: &str) -> Result<JsValue, JsError> {
+    let ground = ground_level(configuration).map_err(|error| JsError::new(&error))?;
+    js_value(&ground)
+}
+
+/// Clebsch–Gordan coefficient ⟨j1 m1 j2 m2 | j m⟩; 0 for the forbidden ones, and NaN for the ones too large to compute
+#[wasm_bindgen(js_name = clebschGordan)]
+pub fn clebsch_gordan(j1: f64, m1: f64, j2: f64, m2: f64, j: f64, m: f64) -> Result<f64, JsError> {
+    let [first, second, total] =
+        momenta([j1, m1, j2, m2, j, m]).map_err(|error| JsError::new(&error))?;
+    Ok(crate::clebsch_gordan(first, second, total))
+}
+
+/// Same as [`clebsch_gordan`], but exact, like "-sqrt(1/3)"; `undefined` for the momenta too large to compute exactly
+#[wasm_bindgen(js_name = clebschGordanExact)]
+pub fn clebsch_gordan_exact(
+    j1: f64,
+    m1: f64,
+    j2: f64,
+    m2: f64,
+    j: f64,
+    m: f64,
+) -> Result<Option<String>, JsError> {
+    let [first, second, total] =
+        momenta([j1, m1, j2, m2, j, m]).map_err(|error| JsError::new(&error))?;
+    Ok(crate::clebsch_gordan_exact(first, second, total).map(|exact| exact.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        wasm::{ground_level, half, momenta, term_list},
+        HalfInt, Parity,
+    };
+
+    #[test]
+    fn terms() {
+        let symbols = |text| {
+            term_list(text)
+                .expect("Should be ok")
+                .into_iter()
+                .map(|term| term.symbol)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(symbols("2p2"), ["³P", "¹S", "¹D"]);
+        // closed subshells add nothing
+        assert_eq!(symbols("C"), symbols("2p2"));
+        assert!(term_list("2x2").is_err());
+
+        let ground = ground_level("3d3").expect("Should be ok");
+        assert_eq!(ground.symbol, "⁴F₃/₂");
+        assert_eq!(
+            (ground.momentum, ground.spin, ground.j, ground.parity),
+            (
+                3,
+                HalfInt::from_doubled(3),
+                HalfInt::from_doubled(3),
+                Parity::Even
+            )
+        );
+        assert_eq!(ground_level("Fe").expect("Should be ok").symbol, "⁵D₄");
+        assert!(ground_level("Xx").is_err());
+    }
+
+    #[test]
+    fn coefficients() {
+        assert_eq!(half(-1.5), Some(HalfInt::from_doubled(-3)));
+        assert_eq!(half(0.25), None);
+        assert_eq!(half(1e10), None);
+        let [first, second, total] =
+            momenta([0.5, 0.5, 0.5, -0.5, 1.0, 0.0]).expect("Should be ok");
+        // ⟨1/2 1/2 1/2 -1/2 | 1 0⟩ = 1/√2
+        assert!((crate::clebsch_gordan(first, second, total) - 0.5f64.sqrt()).abs() < 1e-12);
+        assert!(momenta([0.5, 0.5, 0.5, -0.5, 1.0, f64::NAN]).is_err());
+    }
+}
@@ -0,0 +1,379 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+
+use crate::{
+    clebsch_gordan, clebsch_gordan_exact,
+    coupling::{
+        exact_factorial, factorial, int, ln_factorial, momenta_within, series_sum, signed_root,
+        MAX_FACTORIAL,
+    },
+    momenta_exact, momenta_in_range, HalfInt, SqrtRational,
+};
+
+/// Largest momentum the 9j symbols are computed for
+///
+/// A 9j symbol is a sum of products of 6j symbols, and takes time cubic in the momenta, so this keeps it below a fraction of a second. Larger ones give NaN
+pub const MAX_9J_MOMENTUM: HalfInt = HalfInt::from_int(500);
+
+/// Whether the 9j symbols of these momenta are computed: every one of them is at most [`MAX_9J_MOMENTUM`] by absolute value
+pub fn momenta_in_9j_range(momenta: &[HalfInt]) -> bool {
+    momenta_within(momenta, MAX_9J_MOMENTUM)
+}
+
+/// Wigner 3j symbol (j1 j2 j3; m1 m2 m3), expressed via the Clebsch–Gordan coefficient ⟨j1 m1 j2 m2 | j3 -m3⟩
+///
+/// Returns NaN if some of the arguments is greater than [`MAX_MOMENTUM`](crate::MAX_MOMENTUM) by absolute value
+pub fn wigner_3j(
+    (j1, m1): (HalfInt, HalfInt),
+    (j2, m2): (HalfInt, HalfInt),
+    (j3, m3): (HalfInt, HalfInt),
+) -> f64 {
+    if !momenta_in_range(&[j1, m1, j2, m2, j3, m3]) {
+        return f64::NAN;
+    }
+    let cg = clebsch_gordan((j1, m1), (j2, m2), (j3, -m3));
+    phase(j1 - j2 - m3) * cg / f64::from(j3.doubled() + 1).sqrt()
+}
+
+/// Same as [`wigner_3j`], but computed exactly
+///
+/// Returns `None` if some of the momenta is greater than [`MAX_EXACT_MOMENTUM`](crate::MAX_EXACT_MOMENTUM)
+pub fn wigner_3j_exact(
+    (j1, m1): (HalfInt, HalfInt),
+    (j2, m2): (HalfInt, HalfInt),
+    (j3, m3): (HalfInt, HalfInt),
+) -> Option<SqrtRational> {
+    if !momenta_exact(&[j1, j2, j3]) {
+        return None;
+    }
+    // so that -m3 doesn't overflow
+    if m3 < -j3 || m3 > j3 {
+        return Some(SqrtRational::zero());
+    }
+    let cg = clebsch_gordan_exact((j1, m1), (j2, m2), (j3, -m3))?;
+    let square = cg.signed_square() / BigInt::from(j3.doubled() + 1);
+    let symbol = SqrtRational::from_signed_square(square);
+    Some(if phase(j1 - j2 - m3) < 0.0 {
+        -symbol
+    } else {
+        symbol
+    })
+}
+
+/// (-1)^x for an integer x
+fn phase(x: HalfInt) -> f64 {
+    if int(x) % 2 == 0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+/// Whether a, b and c can be coupled: they satisfy the triangle rule, and their sum is an integer
+fn triad(a: HalfInt, b: HalfInt, c: HalfInt) -> bool {
+    a >= HalfInt::ZERO
+        && b >= HalfInt::ZERO
+        && c >= HalfInt::ZERO
+        && (a - b).abs() <= c
+        && c <= a + b
+        && (a + b + c).is_integer()
+}
+
+/// Numerators and denominators of Δ(abc)² = (a+b-c)!(a-b+c)!(-a+b+c)!/(a+b+c+1)!
+fn triangle_factors(a: HalfInt, b: HalfInt, c: HalfInt) -> ([HalfInt; 3], HalfInt) {
+    ([a + b - c, a - b + c, b + c - a], a + b + c + HalfInt::ONE)
+}
+
+/// Summation range and the factorial arguments of the Racah formula for the 6j symbol, if it is allowed
+type RacahTerms = (i32, i32, [[HalfInt; 3]; 4], [HalfInt; 4]);
+
+fn racah_6j(j: [[HalfInt; 3]; 2]) -> Option<RacahTerms> {
+    let [[a, b, c], [d, e, f]] = j;
+    let triads = [(a, b, c), (a, e, f), (d, b, f), (d, e, c)];
+    if !triads.into_iter().all(|(x, y, z)| triad(x, y, z)) {
+        return None;
+    }
+    let sums = triads.map(|(x, y, z)| x + y + z);
+    let pairs = [a + b + d + e, a + c + d + f, b + c + e + f];
+    let from = sums.into_iter().map(int).max().expect("Not empty");
+    let to = pairs.into_iter().map(int).min().expect("Not empty");
+    let mut numerators = [[HalfInt::ZERO; 3]; 4];
+    let mut denominators = [HalfInt::ZERO; 4];
+    for (index, (x, y, z)) in triads.into_iter().enumerate() {
+        (numerators[index], denominators[index]) = triangle_factors(x, y, z);
+    }
+    Some((from, to, numerators, denominators))
+}
+
+/// Sum terms of the Racah formula, (-1)^t (t+1)! / [Π(t - triad sum)! Π(pair sum - t)!]
+fn racah_6j_term(j: [[HalfInt; 3]; 2], t: i32) -> (i32, i32, Vec<i32>) {
+    let [[a, b, c], [d, e, f]] = j;
+    let sums = [a + b + c, a + e + f, d + b + f, d + e + c];
+    let pairs = [a + b + d + e, a + c + d + f, b + c + e + f];
+    let denominators = sums
+        .into_iter()
+        .map(|sum| t - int(sum))
+        .chain(pairs.into_iter().map(|pair| int(pair) - t))
+        .collect();
+    (if t % 2 == 0 { 1 } else { -1 }, t + 1, denominators)
+}
+
+/// Wigner 6j symbol {j1 j2 j3; j4 j5 j6}, computed with the Racah formula
+///
+/// Returns zero if some of the triads (j1 j2 j3), (j1 j5 j6), (j4 j2 j6), (j4 j5 j3) can't be coupled, and NaN if some of the momenta is greater than [`MAX_MOMENTUM`](crate::MAX_MOMENTUM). The momenta whose factorials don't fit into `f64` are computed in logarithms, like [`clebsch_gordan`] does, in time quadratic in the momenta. See [`wigner_6j_exact`] for the exact value
+pub fn wigner_6j(top: [HalfInt; 3], bottom: [HalfInt; 3]) -> f64 {
+    if !momenta_in_range(&top) || !momenta_in_range(&bottom) {
+        return f64::NAN;
+    }
+    let Some((from, to, numerators, denominators)) = racah_6j([top, bottom]) else {
+        return 0.0;
+    };
+    // (to+1)! is the largest factorial of the formula
+    if to >= MAX_FACTORIAL as i32 {
+        return ln_wigner_6j([top, bottom], (from, to), numerators, denominators);
+    }
+    let f = |x: HalfInt| factorial(int(x));
+    let prefactor: f64 = numerators
+        .iter()
+        .zip(denominators)
+        .map(|(numerators, denominator)| {
+            (numerators.iter().map(|x| f(*x)).product::<f64>() / f(denominator)).sqrt()
+        })
+        .product();
+    let sum: f64 = (from..=to)
+        .map(|t| {
+            let (sign, numerator, denominators) = racah_6j_term([top, bottom], t);
+            f64::from(sign) * factorial(numerator)
+                / denominators.into_iter().map(factorial).product::<f64>()
+        })
+        .sum();
+    prefactor * sum
+}
+
+/// Same as [`wigner_6j`], for the momenta whose factorials don't fit into `f64`, see [`series_sum`]
+fn ln_wigner_6j(
+    j: [[HalfInt; 3]; 2],
+    (from, to): (i32, i32),
+    numerators: [[HalfInt; 3]; 4],
+    denominators: [HalfInt; 4],
+) -> f64 {
+    let f = |x: HalfInt| ln_factorial(int(x));
+    let ln_prefactor: f64 = numerators
+        .iter()
+        .zip(denominators)
+        .map(|(numerators, denominator)| {
+            0.5 * (numerators.iter().map(|x| f(*x)).sum::<f64>() - f(denominator))
+        })
+        .sum();
+    let (_, numerator, first_denominators) = racah_6j_term(j, from);
+    let ln_first = ln_factorial(numerator)
+        - first_denominators
+            .into_iter()
+            .map(ln_factorial)
+            .sum::<f64>();
+    // the term of t+1 is the one of t times -(t+2) Π(pair sum - t) / Π(t+1 - triad sum)
+    let [[a, b, c], [d, e, f]] = j;
+    let sums = [a + b + c, a + e + f, d + b + f, d + e + c].map(|x| i128::from(int(x)));
+    let pairs = [a + b + d + e, a + c + d + f, b + c + e + f].map(|x| i128::from(int(x)));
+    let ratios = (from..to).map(i128::from).map(|t| {
+        (
+            (t + 2) * pairs.iter().map(|pair| pair - t).product::<i128>(),
+            sums.iter().map(|sum| t + 1 - sum).product(),
+        )
+    });
+    let sign = if from % 2 == 0 { 1.0 } else { -1.0 };
+    sign * series_sum(ln_prefactor + ln_first, ratios)
+}
+
+/// Same as [`wigner_6j`], but computed exactly
+///
+/// Returns `None` if some of the momenta is greater than [`MAX_EXACT_MOMENTUM`](crate::MAX_EXACT_MOMENTUM)
+pub fn wigner_6j_exact(top: [HalfInt; 3], bottom: [HalfInt; 3]) -> Option<SqrtRational> {
+    if !momenta_exact(&top) || !momenta_exact(&bottom) {
+        return None;
+    }
+    let Some((from, to, numerators, denominators)) = racah_6j([top, bottom]) else {
+        return Some(SqrtRational::zero());
+    };
+    let f = |x: HalfInt| exact_factorial(int(x));
+    let squared_prefactor = numerators
+        .iter()
+        .zip(denominators)
+        .map(|(numerators, denominator)| {
+            BigRational::new(numerators.iter().map(|x| f(*x)).product(), f(denominator))
+        })
+        .product();
+    let sum = (from..=to)
+        .map(|t| {
+            let (sign, numerator, denominators) = racah_6j_term([top, bottom], t);
+            BigRational::new(
+                BigInt::from(sign) * exact_factorial(numerator),
+                denominators.into_iter().map(exact_factorial).product(),
+            )
+        })
+        .sum();
+    Some(signed_root(sum, squared_prefactor))
+}
+
+/// Wigner 9j symbol {j11 j12 j13; j21 j22 j23; j31 j32 j33}, expressed as a sum of products of 6j symbols
+///
+/// The 6j symbols of the momenta whose factorials don't fit into `f64` take time quadratic in the momenta, so the 9j symbols of such momenta take time cubic in them. Returns NaN if some of the momenta is greater than [`MAX_9J_MOMENTUM`]
+pub fn wigner_9j(j: [[HalfInt; 3]; 3]) -> f64 {
+    if !j.iter().all(|row| momenta_in_9j_range(row)) {
+        return f64::NAN;
+    }
+    let [[a, b, c], [d, e, f], [g, h, i]] = j;
+    let from = (a - i).abs().max((d - h).abs()).max((b - f).abs());
+    let to = (a + i).min(d + h).min(b + f);
+    if from > to {
+        return 0.0;
+    }
+    from.up_to(to)
+        .map(|x| {
+            let sign = if x.doubled() % 2 == 0 { 1.0 } else { -1.0 };
+            sign * f64::from(x.doubled() + 1)
+                * wigner_6j([a, b, c], [f, i, x])
+                * wigner_6j([d, e, f], [b, x, h])
+                * wigner_6j([g, h, i], [x, a, d])
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{wigner_3j, wigner_3j_exact, wigner_6j, wigner_6j_exact, wigner_9j, HalfInt};
+
+    fn h(doubled: i32) -> HalfInt {
+        HalfInt::from_doubled(doubled)
+    }
+
+    #[test]
+    fn known_3j() {
+        // (1 1 0; 0 0 0) = -1/sqrt(3)
+        assert_eq!(
+            wigner_3j_exact((h(2), h(0)), (h(2), h(0)), (h(0), h(0)))
+                .expect("Momenta are small")
+                .to_string(),
+            "-sqrt(1/3)"
+        );
+        // (1/2 1/2 1; 1/2 -1/2 0) = 1/sqrt(6)
+        assert_eq!(
+            wigner_3j_exact((h(1), h(1)), (h(1), h(-1)), (h(2), h(0)))
+                .expect("Momenta are small")
+                .to_string(),
+            "sqrt(1/6)"
+        );
+        // orthogonality: Σ (2j3+1) (j1 j2 j3; m1 m2 m3)² = 1
+        for (j1, j2) in [(2i32, 2), (3, 5), (4, 6)] {
+            for m1 in (-j1..=j1).step_by(2) {
+                for m2 in (-j2..=j2).step_by(2) {
+                    let sum: f64 = ((j1 - j2).abs()..=j1 + j2)
+                        .step_by(2)
+                        .map(|j3| {
+                            let symbol =
+                                wigner_3j((h(j1), h(m1)), (h(j2), h(m2)), (h(j3), h(-m1 - m2)));
+                            f64::from(j3 + 1) * symbol * symbol
+                        })
+                        .sum();
+                    assert!((sum - 1.0).abs() < 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn known_6j() {
+        assert_eq!(
+            wigner_6j_exact([h(4), h(4), h(4)], [h(4), h(4), h(4)])
+                .expect("Momenta are small")
+                .to_string(),
+            "-3/70"
+        );
+        assert_eq!(
+            wigner_6j_exact([h(2), h(2), h(2)], [h(0), h(2), h(2)])
+                .expect("Momenta are small")
+                .to_string(),
+            "-1/3"
+        );
+        assert_eq!(
+            wigner_6j_exact([h(1), h(1), h(2)], [h(1), h(1), h(0)])
+                .expect("Momenta are small")
+                .to_string(),
+            "1/2"
+        );
+        // triangle rule violated
+        assert_eq!(wigner_6j([h(2), h(2), h(6)], [h(2), h(2), h(2)]), 0.0);
+        for top in [[1, 1, 2], [2, 3, 3], [4, 2, 4], [6, 4, 4]] {
+            for bottom in [[1, 3, 2], [2, 5, 5], [4, 4, 4], [2, 6, 4]] {
+                let (top, bottom) = (top.map(h), bottom.map(h));
+                let exact = wigner_6j_exact(top, bottom)
+                    .expect("Momenta are small")
+                    .to_f64();
+                assert!((wigner_6j(top, bottom) - exact).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn large_momenta() {
+        // factorials up to 401! and 801! are needed
+        for j in [200, 400] {
+            let value = wigner_6j([h(j); 3], [h(j); 3]);
+            let exact = wigner_6j_exact([h(j); 3], [h(j); 3])
+                .expect("Momenta are small")
+                .to_f64();
+            assert!((value - exact).abs() < 1e-12);
+        }
+        // {a b c; 0 c b} = (-1)^(a+b+c) / sqrt((2b+1)(2c+1)), too large to compute exactly
+        let [a, b, c] = [h(4000), h(3000), h(2000)];
+        let value = wigner_6j([a, b, c], [HalfInt::ZERO, c, b]);
+        assert!((value - 1.0 / (3001.0f64 * 2001.0).sqrt()).abs() < 1e-12);
+        assert!(wigner_6j_exact([a, b, c], [HalfInt::ZERO, c, b]).is_none());
+        let three_j = wigner_3j((h(120), h(0)), (h(120), h(0)), (h(120), h(0)));
+        assert!(three_j.is_finite() && three_j != 0.0);
+        let nine_j = wigner_9j([
+            [h(120), h(120), h(0)],
+            [h(120), h(120), h(0)],
+            [h(0), h(0), h(0)],
+        ]);
+        assert!(nine_j.is_finite() && nine_j != 0.0);
+    }
+
+    #[test]
+    fn range() {
+        let [max, min] = [i32::MAX, i32::MIN].map(h);
+        assert!(wigner_3j((max, min), (max, min), (max, min)).is_nan());
+        assert!(wigner_3j_exact((max, min), (max, min), (max, min)).is_none());
+        assert_eq!(
+            wigner_3j_exact((h(2), h(0)), (h(2), h(0)), (h(2), min)).map(|exact| exact.is_zero()),
+            Some(true)
+        );
+        assert!(wigner_6j([max; 3], [max; 3]).is_nan());
+        assert!(wigner_6j([h(200_002); 3], [h(200_002); 3]).is_nan());
+        assert!(wigner_6j_exact([max; 3], [max; 3]).is_none());
+        assert!(wigner_9j([[max; 3]; 3]).is_nan());
+        assert!(wigner_9j([[h(6000); 3]; 3]).is_nan());
+    }
+
+    #[test]
+    fn reduced_9j() {
+        // {a b e; c d e; f f 0} = (-1)^(b+c+e+f) {a b e; d c f} / sqrt((2e+1)(2f+1))
+        for [a, b, c, d, e, f] in [
+            [2, 2, 2, 2, 2, 2],
+            [1, 3, 2, 4, 2, 3],
+            [4, 4, 2, 2, 4, 4],
+            [3, 1, 3, 1, 2, 2],
+        ] {
+            let [a, b, c, d, e, f] = [a, b, c, d, e, f].map(h);
+            let nine = wigner_9j([[a, b, e], [c, d, e], [f, f, HalfInt::ZERO]]);
+            let sign = if (b + c + e + f).doubled() % 4 == 0 {
+                1.0
+            } else {
+                -1.0
+            };
+            let six = wigner_6j([a, b, e], [d, c, f]);
+            let expected = sign * six / f64::from((e.doubled() + 1) * (f.doubled() + 1)).sqrt();
+            assert!((nine - expected).abs() < 1e-12, "{nine} != {expected}");
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use itertools::Itertools;
+
+use crate::{linalg::symmetric_eigen, EnergyUnit, HalfInt, Level, MagneticField, TermType};
+
+/// Free electron spin g-factor (its absolute value, CODATA 2018)
+const ELECTRON_G: f64 = 2.002_319_304_362_56;
+
+/// Magnetic sublevel of a level split by the external magnetic field
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZeemanSublevel {
+    pub mj: HalfInt,
+    /// Energy shift relative to the level without the field
+    pub energy: f64,
+}
+
+impl Level {
+    /// Splitting of the level in a weak magnetic field (anomalous Zeeman effect): E = g_J μ_B B M_J, for M_J from -J to J
+    ///
+    /// Levels with J = 0 are not split, so the only sublevel has zero shift
+    pub fn zeeman(&self, field: MagneticField, unit: EnergyUnit) -> Vec<ZeemanSublevel> {
+        let g = self.lande_g().unwrap_or_default();
+        (-self.j)
+            .up_to(self.j)
+            .map(|mj| ZeemanSublevel {
+                mj,
+                energy: g * unit.bohr_magneton() * field.as_tesla() * mj.to_f64(),
+            })
+            .collect()
+    }
+}
+
+/// Eigenstate of a term in a magnetic field of arbitrary strength
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldState {
+    /// Total momentum projection M_L + M_S, which is conserved by the field
+    pub m: HalfInt,
+    /// Energy relative to the term without spin-orbit interaction and field
+    pub energy: f64,
+    /// Coefficients of the uncoupled |M_L M_S⟩ states
+    pub components: Vec<((i32, HalfInt), f64)>,
+}
+
+impl TermType {
+    /// States of the term under the combined spin-orbit interaction A L·S and Zeeman interaction μ_B B (L_z + g_s S_z), sorted by energy
+    ///
+    /// Hamiltonian is diagonalized exactly within the term, so that the result covers weak (anomalous Zeeman), intermediate and strong (Paschen–Back) fields. Spin-orbit constant `spin_orbit` is specified in the same `unit` as the resulting energies. Mixing with other terms is neglected
+    pub fn in_field(
+        &self,
+        spin_orbit: f64,
+        field: MagneticField,
+        unit: EnergyUnit,
+    ) -> Vec<FieldState> {
+        let l = self.l_half_int();
+        let s = self.spin;
+        let zeeman = unit.bohr_magneton() * field.as_tesla();
+        // ladder operator coefficient sqrt(j(j+1) - m(m±1)), via doubled values
+        let ladder = |j: HalfInt, m: HalfInt, to: HalfInt| {
+            ((j.casimir() - i64::from(m.doubled()) * i64::from(to.doubled())).max(0) as f64).sqrt()
+                / 2.0
+        };
+        let basis = (-l).up_to(l).cartesian_product((-s).up_to(s)).collect_vec();
+        let mut states = basis
+            .iter()
+            .map(|(ml, ms)| *ml + *ms)
+            .unique()
+            .flat_map(|m| {
+                let block = basis
+                    .iter()
+                    .copied()
+                    .filter(|(ml, ms)| *ml + *ms == m)
+                    .collect_vec();
+                let matrix = block
+                    .iter()
+                    .map(|&(ml1, ms1)| {
+                        block
+                            .iter()
+                            .map(|&(ml2, ms2)| {
+                                if (ml1, ms1) == (ml2, ms2) {
+                                    spin_orbit * ml1.to_f64() * ms1.to_f64()
+                                        + zeeman * (ml1.to_f64() + ELECTRON_G * ms1.to_f64())
+                                } else if (ml1 - ml2).abs() == HalfInt::ONE {
+                                    // (L+ S- + L- S+) / 2, M_L + M_S is the same within the block
+                                    spin_orbit / 2.0 * ladder(l, ml2, ml1) * ladder(s, ms2, ms1)
+                                } else {
+                                    0.0
+                                }
+                            })
+                            .collect_vec()
+                    })
+                    .collect_vec();
+                symmetric_eigen(matrix)
+                    .into_iter()
+                    .map(move |(energy, vector)| FieldState {
+                        m,
+                        energy,
+                        components: block
+                            .iter()
+                            .map(|(ml, ms)| (ml.doubled() / 2, *ms))
+                            .zip(vector)
+                            .collect(),
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        states.sort_by(|a, b| a.energy.total_cmp(&b.energy).then(a.m.cmp(&b.m)));
+        states
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EnergyUnit, HalfInt, Level, MagneticField, TermType};
+
+    #[test]
+    fn sodium_d_lines() {
+        let field: MagneticField = "1 T".parse().unwrap();
+        // ^2P_3/2 has g = 4/3
+        let level: Level = "2P_3/2".parse().unwrap();
+        let sublevels = level.zeeman(field, EnergyUnit::Wavenumber);
+        assert_eq!(sublevels.len(), 4);
+        let expected = 4.0 / 3.0 * 0.466_864_477_83 * 1.5;
+        assert!((sublevels[3].energy - expected).abs() < 1e-12);
+        assert!((sublevels[0].energy + expected).abs() < 1e-12);
+        assert_eq!(
+            "3P_0"
+                .parse::<Level>()
+                .unwrap()
+                .zeeman(field, EnergyUnit::ElectronVolt)[0]
+                .energy,
+            0.0
+        );
+        assert_eq!("5000 G".parse::<MagneticField>().unwrap().as_tesla(), 0.5);
+        assert!("5 Oe".parse::<MagneticField>().is_err());
+    }
+
+    #[test]
+    fn weak_and_strong_fields() {
+        let term: TermType = "2P".parse().unwrap();
+        let spin_orbit = 10.0;
+        // no field: fine structure A/2 [J(J+1) - L(L+1) - S(S+1)]
+        let energies = term
+            .in_field(
+                spin_orbit,
+                MagneticField::tesla(0.0),
+                EnergyUnit::Wavenumber,
+            )
+            .into_iter()
+            .map(|state| state.energy)
+            .collect::<Vec<_>>();
+        for (energy, expected) in energies.iter().zip([-10.0, -10.0, 5.0, 5.0, 5.0, 5.0]) {
+            assert!((energy - expected).abs() < 1e-9);
+        }
+        // weak field: anomalous Zeeman effect with Landé g-factors
+        let field = MagneticField::tesla(1e-3);
+        let states = term.in_field(spin_orbit, field, EnergyUnit::Wavenumber);
+        let level: Level = "2P_3/2".parse().unwrap();
+        let top = level.zeeman(field, EnergyUnit::Wavenumber).pop().unwrap();
+        let shift = states.last().unwrap().energy - 5.0;
+        assert!((shift - top.energy).abs() / top.energy < 1e-3);
+        // strong field: μ_B B (M_L + g_s M_S) + A M_L M_S
+        let field = MagneticField::tesla(1e4);
+        let states = term.in_field(spin_orbit, field, EnergyUnit::Wavenumber);
+        let top = states.last().unwrap();
+        assert_eq!(top.m, HalfInt::from_doubled(3));
+        let zeeman = EnergyUnit::Wavenumber.bohr_magneton() * 1e4;
+        let expected = zeeman * (1.0 + 2.002_319_304_362_56 / 2.0) + spin_orbit / 2.0;
+        assert!((top.energy - expected).abs() < 1e-6);
+    }
+}